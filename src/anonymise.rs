@@ -0,0 +1,275 @@
+//! Scrubbing of identifying information from captured RTP/RTCP traffic.
+//!
+//! *These are included when using the `"anonymise"` feature.*
+
+use crate::{
+	rng::SplitMix64,
+	rtcp::{self, RtcpType},
+};
+use alloc::collections::BTreeMap;
+
+/// Treatment applied to media payloads and application data.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum PayloadScrub {
+	/// Leave payloads untouched.
+	Keep,
+
+	/// Overwrite payload bytes with zeroes.
+	#[default]
+	Zero,
+
+	/// Overwrite payload bytes with (seeded) random data.
+	Randomise,
+}
+
+/// Rewrites RTP and RTCP buffers in place so that captures can be shared safely.
+///
+/// * SSRCs and CSRCs are consistently remapped, so that the same source always
+///   receives the same replacement identifier across RTP and RTCP.
+/// * Media payloads and APP data are zeroed or randomised according to
+///   [`PayloadScrub`], preserving their lengths (and any RTP padding count).
+/// * SDES item text and BYE reasons are overwritten, preserving their lengths.
+///
+/// Packet lengths and header structure are never altered, so anonymised captures
+/// exercise the same parsing paths as the originals.
+///
+/// [`PayloadScrub`]: enum.PayloadScrub.html
+#[derive(Clone, Debug)]
+pub struct Anonymiser {
+	rng: SplitMix64,
+	ssrcs: BTreeMap<u32, u32>,
+	payloads: PayloadScrub,
+}
+
+impl Anonymiser {
+	/// Creates an anonymiser whose replacement SSRCs and random payloads are
+	/// derived from `seed`.
+	#[must_use]
+	pub fn new(seed: u64) -> Self {
+		Self {
+			rng: SplitMix64::new(seed),
+			ssrcs: BTreeMap::new(),
+			payloads: PayloadScrub::default(),
+		}
+	}
+
+	/// Sets how payloads should be treated.
+	#[must_use]
+	pub fn payloads(mut self, payloads: PayloadScrub) -> Self {
+		self.payloads = payloads;
+		self
+	}
+
+	/// Returns the replacement identifier for `ssrc`, allocating a new
+	/// (unique) one if this source has not yet been seen.
+	pub fn map_ssrc(&mut self, ssrc: u32) -> u32 {
+		if let Some(mapped) = self.ssrcs.get(&ssrc) {
+			return *mapped;
+		}
+
+		let mapped = loop {
+			#[allow(clippy::cast_possible_truncation)]
+			let candidate = self.rng.next_u64() as u32;
+			if !self.ssrcs.values().any(|v| *v == candidate) {
+				break candidate;
+			}
+		};
+
+		self.ssrcs.insert(ssrc, mapped);
+		mapped
+	}
+
+	/// Anonymises a single datagram, classifying it as RTP or (compound) RTCP
+	/// in the same manner as the `"demux"` utilities.
+	pub fn anonymise(&mut self, pkt: &mut [u8]) {
+		match RtcpType::from_packet(pkt) {
+			None | Some(RtcpType::Reserved(_) | RtcpType::Unassigned(_)) => self.anonymise_rtp(pkt),
+			Some(_) => self.anonymise_rtcp(pkt),
+		}
+	}
+
+	/// Anonymises an RTP packet in place.
+	///
+	/// Header extensions are left intact; everything following them (up to any
+	/// padding) is treated as payload.
+	pub fn anonymise_rtp(&mut self, pkt: &mut [u8]) {
+		if pkt.len() < 12 {
+			return;
+		}
+
+		self.rewrite_ssrc(pkt, 8);
+
+		let csrc_count = usize::from(pkt[0] & 0x0f);
+		for i in 0..csrc_count {
+			self.rewrite_ssrc(pkt, 12 + 4 * i);
+		}
+
+		let mut start = 12 + 4 * csrc_count;
+		if pkt[0] & 0x10 != 0 {
+			if let Some(words) = pkt.get(start + 2..start + 4) {
+				start += 4 + 4 * usize::from(u16::from_be_bytes([words[0], words[1]]));
+			}
+		}
+
+		let mut end = pkt.len();
+		if pkt[0] & 0x20 != 0 {
+			end = end.saturating_sub(usize::from(pkt[end - 1]));
+		}
+
+		if let Some(payload) = pkt.get_mut(start..end) {
+			self.scrub_payload(payload);
+		}
+	}
+
+	/// Anonymises every packet within a (compound) RTCP datagram in place.
+	pub fn anonymise_rtcp(&mut self, pkt: &mut [u8]) {
+		let mut offset = 0;
+		while let Some(len) = pkt.get(offset..).and_then(rtcp::packet_len) {
+			self.anonymise_rtcp_packet(&mut pkt[offset..offset + len]);
+			offset += len;
+		}
+	}
+
+	fn anonymise_rtcp_packet(&mut self, pkt: &mut [u8]) {
+		let count = usize::from(pkt[0] & 0x1f);
+
+		match RtcpType::new(pkt[1]) {
+			RtcpType::SenderReport | RtcpType::ReceiverReport => {
+				self.rewrite_ssrc(pkt, 4);
+
+				let blocks_start = if pkt[1] == 200 { 28 } else { 8 };
+				for i in 0..count {
+					self.rewrite_ssrc(pkt, blocks_start + 24 * i);
+				}
+			},
+			RtcpType::SourceDescription => self.anonymise_sdes(pkt, count),
+			RtcpType::Goodbye => {
+				for i in 0..count {
+					self.rewrite_ssrc(pkt, 4 + 4 * i);
+				}
+
+				let reason_start = 4 + 4 * count;
+				if let Some(&len) = pkt.get(reason_start) {
+					let text_end = (reason_start + 1 + usize::from(len)).min(pkt.len());
+					if let Some(text) = pkt.get_mut(reason_start + 1..text_end) {
+						text.fill(b'x');
+					}
+				}
+			},
+			RtcpType::ApplicationDefined => {
+				self.rewrite_ssrc(pkt, 4);
+				if let Some(data) = pkt.get_mut(12..) {
+					self.scrub_payload(data);
+				}
+			},
+			RtcpType::TransportFeedback | RtcpType::PayloadFeedback => {
+				self.rewrite_ssrc(pkt, 4);
+				self.rewrite_ssrc(pkt, 8);
+
+				// FIR (PSFB 4) and TMMBR/TMMBN (RTPFB 3, 4) carry SSRCs in each FCI entry.
+				let fci_ssrcs = matches!((pkt[1], count), (206, 4) | (205, 3 | 4));
+				if fci_ssrcs {
+					let mut at = 12;
+					while at + 8 <= pkt.len() {
+						self.rewrite_ssrc(pkt, at);
+						at += 8;
+					}
+				}
+			},
+			RtcpType::ExtendedReport => self.anonymise_xr(pkt),
+			RtcpType::ReceiverSummary => {
+				self.rewrite_ssrc(pkt, 4);
+				self.rewrite_ssrc(pkt, 8);
+			},
+			_ => {
+				self.rewrite_ssrc(pkt, 4);
+			},
+		}
+	}
+
+	fn anonymise_xr(&mut self, pkt: &mut [u8]) {
+		self.rewrite_ssrc(pkt, 4);
+
+		let mut at = 8;
+		while at + 4 <= pkt.len() {
+			let end = (at + 4 * (usize::from(u16::from_be_bytes([pkt[at + 2], pkt[at + 3]])) + 1))
+				.min(pkt.len());
+
+			match pkt[at] {
+				// Loss/duplicate RLE, receipt times, statistics summary and VoIP
+				// metrics each describe a single source.
+				1 | 2 | 3 | 6 | 7 => {
+					self.rewrite_ssrc(pkt, at + 4);
+				},
+				// DLRR holds a list of (SSRC, LRR, DLRR) sub-blocks.
+				5 => {
+					let mut sub = at + 4;
+					while sub + 12 <= end {
+						self.rewrite_ssrc(pkt, sub);
+						sub += 12;
+					}
+				},
+				_ => {},
+			}
+
+			at = end;
+		}
+	}
+
+	fn anonymise_sdes(&mut self, pkt: &mut [u8], chunks: usize) {
+		let mut at = 4;
+
+		for _ in 0..chunks {
+			let Some(mapped) = self.rewrite_ssrc(pkt, at) else {
+				return;
+			};
+			at += 4;
+
+			// Items run until a null "END" item, after which the chunk is padded to
+			// a 32-bit boundary.
+			while let Some(&kind) = pkt.get(at) {
+				if kind == 0 {
+					at = (at + 4) & !3;
+					break;
+				}
+
+				let Some(&len) = pkt.get(at + 1) else {
+					return;
+				};
+				let text_end = (at + 2 + usize::from(len)).min(pkt.len());
+				for (i, b) in pkt[at + 2..text_end].iter_mut().enumerate() {
+					*b = hex_digit(mapped, i);
+				}
+				at = text_end;
+			}
+		}
+	}
+
+	fn rewrite_ssrc(&mut self, pkt: &mut [u8], at: usize) -> Option<u32> {
+		let field = pkt.get_mut(at..at + 4)?;
+		let ssrc = u32::from_be_bytes([field[0], field[1], field[2], field[3]]);
+		let mapped = self.map_ssrc(ssrc);
+		field.copy_from_slice(&mapped.to_be_bytes());
+
+		Some(mapped)
+	}
+
+	fn scrub_payload(&mut self, payload: &mut [u8]) {
+		match self.payloads {
+			PayloadScrub::Keep => {},
+			PayloadScrub::Zero => payload.fill(0),
+			PayloadScrub::Randomise =>
+				for chunk in payload.chunks_mut(8) {
+					let bytes = self.rng.next_u64().to_le_bytes();
+					chunk.copy_from_slice(&bytes[..chunk.len()]);
+				},
+		}
+	}
+}
+
+/// Cycles through the hex digits of `ssrc`, so that rewritten SDES text
+/// remains distinct per (anonymised) source.
+fn hex_digit(ssrc: u32, i: usize) -> u8 {
+	let nibble = (ssrc >> (28 - 4 * (i % 8))) & 0xf;
+	b"0123456789abcdef"[nibble as usize]
+}
@@ -0,0 +1,78 @@
+//! Cursors for parsing packets out of large receive buffers.
+//!
+//! Batched receive paths (*e.g.*, `recvmmsg` or `io_uring`) often place many
+//! datagrams back-to-back in one large ring buffer. [`PacketCursor`] tracks a
+//! position within such a buffer, handing out borrowed packet views and advancing
+//! by the number of bytes each one consumed.
+//!
+//! [`PacketCursor`]: struct.PacketCursor.html
+
+#[cfg(feature = "rtcp")]
+use crate::rtcp::RtcpPacket;
+#[cfg(feature = "rtp")]
+use crate::rtp::RtpPacket;
+
+/// Read position within a larger buffer of packets.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PacketCursor<'a> {
+	buf: &'a [u8],
+	offset: usize,
+}
+
+impl<'a> PacketCursor<'a> {
+	/// Creates a cursor at the start of `buf`.
+	#[must_use]
+	pub fn new(buf: &'a [u8]) -> Self {
+		Self::with_offset(buf, 0)
+	}
+
+	/// Creates a cursor at `offset` bytes into `buf`.
+	#[must_use]
+	pub fn with_offset(buf: &'a [u8], offset: usize) -> Self {
+		Self { buf, offset }
+	}
+
+	/// Current read position, in bytes from the start of the buffer.
+	#[must_use]
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+
+	/// Bytes which have not yet been consumed.
+	#[must_use]
+	pub fn remaining(&self) -> &'a [u8] {
+		self.buf.get(self.offset..).unwrap_or_default()
+	}
+
+	/// Returns `true` if no bytes remain.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.remaining().is_empty()
+	}
+
+	/// Skips `n` bytes, *e.g.*, to move past a datagram which could not be parsed.
+	pub fn advance(&mut self, n: usize) {
+		self.offset = self.offset.saturating_add(n);
+	}
+
+	/// Parses an RTP packet of `len` bytes at the current position, advancing past it.
+	///
+	/// The cursor is left unchanged if parsing fails.
+	#[cfg(feature = "rtp")]
+	pub fn next_rtp(&mut self, len: usize) -> Option<RtpPacket<'a>> {
+		let (pkt, used) = RtpPacket::parse_at(self.buf, self.offset, len)?;
+		self.advance(used);
+		Some(pkt)
+	}
+
+	/// Parses one RTCP packet at the current position, advancing past it
+	/// according to its `pkt_length`.
+	///
+	/// The cursor is left unchanged if parsing fails.
+	#[cfg(feature = "rtcp")]
+	pub fn next_rtcp(&mut self) -> Option<RtcpPacket<'a>> {
+		let (pkt, used) = RtcpPacket::parse_at(self.buf, self.offset)?;
+		self.advance(used);
+		Some(pkt)
+	}
+}
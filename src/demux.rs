@@ -42,7 +42,7 @@ pub enum DemuxedMut<'a> {
 /// [`RtcpPacket`]: ../rtcp/struct.RtcpPacket.html
 /// [RTCP packet type]: ../rtcp/enum.RtcpType.html
 #[must_use]
-pub fn demux(pkt: &[u8]) -> Demuxed {
+pub fn demux(pkt: &[u8]) -> Demuxed<'_> {
 	if pkt.len() < 2 {
 		Demuxed::TooSmall
 	} else {
@@ -60,7 +60,7 @@ pub fn demux(pkt: &[u8]) -> Demuxed {
 /// See [`demux`] for more information.
 ///
 /// [`demux`]: fn.demux.html
-pub fn demux_mut(pkt: &mut [u8]) -> DemuxedMut {
+pub fn demux_mut(pkt: &mut [u8]) -> DemuxedMut<'_> {
 	if pkt.len() < 2 {
 		DemuxedMut::TooSmall
 	} else {
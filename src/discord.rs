@@ -2,6 +2,7 @@
 //!
 //! *These are included when using the `"discord"` feature.*
 
+use crate::error::{impl_new_checked, ParseError};
 use alloc::vec::Vec;
 use pnet_macros::packet;
 use pnet_macros_support::{
@@ -88,6 +89,25 @@ pub struct Keepalive {
 	pub payload: Vec<u8>,
 }
 
+impl_new_checked!(
+	IpDiscoveryPacket,
+	MutableIpDiscoveryPacket,
+	["pkt_type" => 2, "length" => 4, "ssrc" => 8],
+	|pkt: &[u8]| {
+		let length = usize::from(u16::from_be_bytes([pkt[2], pkt[3]]));
+		if length < FIXED_SIZE_COMPONENT {
+			return Err(ParseError {
+				field: "length",
+				needed: 4 + FIXED_SIZE_COMPONENT,
+				provided: 4 + length,
+			});
+		}
+		ParseError::require(pkt, "length", 4 + length)
+	}
+);
+
+impl_new_checked!(KeepalivePacket, MutableKeepalivePacket, ["ssrc" => 4]);
+
 const FIXED_SIZE_COMPONENT: usize = core::mem::size_of::<u16>() + core::mem::size_of::<u32>();
 
 const IP_DISCOVERY_LEN: usize = IpDiscoveryPacket::minimum_packet_size() + 64;
@@ -142,3 +162,41 @@ impl PrimitiveValues for IpDiscoveryType {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn discovery(length: u16, total: usize) -> Vec<u8> {
+		let mut pkt = alloc::vec![0u8; total];
+		pkt[..2].copy_from_slice(&1u16.to_be_bytes());
+		pkt[2..4].copy_from_slice(&length.to_be_bytes());
+		pkt
+	}
+
+	#[test]
+	fn ip_discovery_checks_length_field() {
+		let pkt = discovery(6, 10);
+		let view = IpDiscoveryPacket::new_checked(&pkt).unwrap();
+		assert!(view.get_address().is_empty());
+
+		let pkt = discovery(70, IP_DISCOVERY_LEN);
+		assert!(IpDiscoveryPacket::new_checked(&pkt).is_ok());
+	}
+
+	#[test]
+	fn ip_discovery_rejects_short_length() {
+		let pkt = discovery(5, 74);
+		assert_eq!(
+			IpDiscoveryPacket::new_checked(&pkt).unwrap_err().field,
+			"length"
+		);
+	}
+
+	#[test]
+	fn ip_discovery_rejects_truncated_buffer() {
+		let pkt = discovery(70, 73);
+		let err = IpDiscoveryPacket::new_checked(&pkt).unwrap_err();
+		assert_eq!((err.field, err.needed, err.provided), ("length", 74, 73));
+	}
+}
@@ -0,0 +1,113 @@
+//! Errors reported by checked packet constructors.
+
+use core::fmt;
+
+/// Error returned by `new_checked` constructors when a buffer is too short to
+/// hold a packet.
+///
+/// Each packet view's `new` returns `None` in this case: the checked variants
+/// additionally report which field is cut short, which helps when debugging
+/// truncated packets.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseError {
+	/// Name of the first field which does not fit into the buffer.
+	pub field: &'static str,
+	/// Number of bytes needed to hold `field` and everything before it.
+	pub needed: usize,
+	/// Number of bytes provided.
+	pub provided: usize,
+}
+
+impl ParseError {
+	/// Checks that `field` (ending `needed` bytes into `pkt`) fits.
+	pub(crate) fn require(pkt: &[u8], field: &'static str, needed: usize) -> Result<(), Self> {
+		if needed > pkt.len() {
+			Err(Self {
+				field,
+				needed,
+				provided: pkt.len(),
+			})
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Checks that each of a packet's fixed `fields`, listed in order with their
+	/// end offsets, fit into `pkt` before running any `dynamic` checks.
+	pub(crate) fn check(
+		pkt: &[u8],
+		fields: &[(&'static str, usize)],
+		dynamic: impl Fn(&[u8]) -> Result<(), Self>,
+	) -> Result<(), Self> {
+		for &(field, needed) in fields {
+			Self::require(pkt, field, needed)?;
+		}
+
+		dynamic(pkt)
+	}
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"packet truncated at field `{}`: needed {} bytes, but {} provided",
+			self.field, self.needed, self.provided
+		)
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Implements `new_checked` on an immutable and mutable packet view, given the
+/// names and end offsets of its fixed fields, and an optional check for any
+/// fields whose size depends on the packet contents.
+macro_rules! impl_new_checked {
+	($view:ident, $mut_view:ident, [$($field:literal => $end:expr),* $(,)?]) => {
+		$crate::error::impl_new_checked!($view, $mut_view, [$($field => $end),*], |_| Ok(()));
+	};
+	($view:ident, $mut_view:ident, [$($field:literal => $end:expr),* $(,)?], $dynamic:expr) => {
+		impl<'p> $view<'p> {
+			/// Constructs a new view of a packet, as with [`new`](#method.new).
+			///
+			/// # Errors
+			/// Returns a [`ParseError`] naming the first field (including any lists
+			/// sized by count fields) which does not fit in `packet`.
+			///
+			/// [`ParseError`]: crate::error::ParseError
+			pub fn new_checked(packet: &'p [u8]) -> Result<Self, $crate::error::ParseError> {
+				$crate::error::ParseError::check(packet, &[$(($field, $end)),*], $dynamic)?;
+				let provided = packet.len();
+
+				Self::new(packet).ok_or($crate::error::ParseError {
+					field: "payload",
+					needed: Self::minimum_packet_size(),
+					provided,
+				})
+			}
+		}
+
+		impl<'p> $mut_view<'p> {
+			/// Constructs a new mutable view of a packet, as with [`new`](#method.new).
+			///
+			/// # Errors
+			/// Returns a [`ParseError`] naming the first field (including any lists
+			/// sized by count fields) which does not fit in `packet`.
+			///
+			/// [`ParseError`]: crate::error::ParseError
+			pub fn new_checked(packet: &'p mut [u8]) -> Result<Self, $crate::error::ParseError> {
+				$crate::error::ParseError::check(packet, &[$(($field, $end)),*], $dynamic)?;
+				let provided = packet.len();
+
+				Self::new(packet).ok_or($crate::error::ParseError {
+					field: "payload",
+					needed: Self::minimum_packet_size(),
+					provided,
+				})
+			}
+		}
+	};
+}
+
+pub(crate) use impl_new_checked;
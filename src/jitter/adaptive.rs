@@ -0,0 +1,208 @@
+use super::JitterBuffer;
+use crate::wrap::Wrap32;
+use alloc::{collections::VecDeque, vec::Vec};
+use core::time::Duration;
+
+/// Parameters controlling how a [`DelayEstimator`] chooses a playout delay.
+///
+/// [`DelayEstimator`]: struct.DelayEstimator.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AdaptiveConfig {
+	/// RTP timestamp clock rate of the stream, in Hz.
+	pub clock_rate: u32,
+	/// Smallest target delay which will be chosen.
+	pub min_delay: Duration,
+	/// Largest target delay which will be chosen.
+	pub max_delay: Duration,
+	/// Percentile of observed arrival jitter to be covered by the target delay,
+	/// from `0` to `100`.
+	pub percentile: u8,
+	/// Number of recent packets over which jitter is measured.
+	pub window: usize,
+	/// Extra delay added on top of the measured jitter.
+	pub headroom: Duration,
+}
+
+impl AdaptiveConfig {
+	/// Creates a configuration for a stream with the given timestamp `clock_rate`,
+	/// covering the 95th percentile of jitter over the last 500 packets with a
+	/// delay between 20ms and 500ms.
+	#[must_use]
+	pub fn new(clock_rate: u32) -> Self {
+		Self {
+			clock_rate,
+			min_delay: Duration::from_millis(20),
+			max_delay: Duration::from_millis(500),
+			percentile: 95,
+			window: 500,
+			headroom: Duration::from_millis(10),
+		}
+	}
+}
+
+/// Estimator of the playout delay needed to absorb a stream's arrival jitter.
+///
+/// Each packet's transit delay is measured relative to its RTP timestamp, and the
+/// spread between the fastest packet and the configured percentile of recent
+/// packets is taken as the delay needed for all but the latest packets to arrive
+/// in time.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DelayEstimator {
+	config: AdaptiveConfig,
+	transits: VecDeque<i64>,
+	sorted: Vec<i64>,
+	last_ts: Option<(Wrap32, i64)>,
+}
+
+impl DelayEstimator {
+	/// Creates an estimator which has not yet seen any packets.
+	#[must_use]
+	pub fn new(config: AdaptiveConfig) -> Self {
+		Self {
+			config,
+			transits: VecDeque::new(),
+			sorted: Vec::new(),
+			last_ts: None,
+		}
+	}
+
+	/// Parameters used by this estimator.
+	#[must_use]
+	pub fn config(&self) -> &AdaptiveConfig {
+		&self.config
+	}
+
+	/// Records the arrival at `now` of a packet with RTP timestamp `timestamp`.
+	pub fn update(&mut self, timestamp: Wrap32, now: Duration) {
+		if self.config.clock_rate == 0 {
+			return;
+		}
+
+		let ts = match self.last_ts {
+			Some((last, ext)) => ext + i64::from(last.distance(timestamp)),
+			None => i64::from(u32::from(timestamp)),
+		};
+		if self.last_ts.map_or(true, |(_, ext)| ts > ext) {
+			self.last_ts = Some((timestamp, ts));
+		}
+
+		let media_us = ts.saturating_mul(1_000_000) / i64::from(self.config.clock_rate);
+		let arrival_us = i64::try_from(now.as_micros()).unwrap_or(i64::MAX);
+
+		let transit = arrival_us.saturating_sub(media_us);
+		self.transits.push_back(transit);
+		let idx = self.sorted.partition_point(|&t| t < transit);
+		self.sorted.insert(idx, transit);
+
+		while self.transits.len() > self.config.window.max(1) {
+			if let Some(old) = self.transits.pop_front() {
+				if let Ok(idx) = self.sorted.binary_search(&old) {
+					self.sorted.remove(idx);
+				}
+			}
+		}
+	}
+
+	/// Spread of transit delays over the current window at the configured
+	/// percentile.
+	#[must_use]
+	pub fn jitter(&self) -> Duration {
+		let sorted = &self.sorted;
+		let (Some(&fastest), Some(last)) = (sorted.first(), sorted.len().checked_sub(1)) else {
+			return Duration::ZERO;
+		};
+		let idx = last * usize::from(self.config.percentile.min(100)) / 100;
+		let spread = sorted.get(idx).map_or(0, |v| v.saturating_sub(fastest));
+
+		Duration::from_micros(u64::try_from(spread).unwrap_or(0))
+	}
+
+	/// Playout delay currently recommended for this stream.
+	///
+	/// This is the measured [`jitter`] plus headroom, clamped to the configured
+	/// bounds.
+	///
+	/// [`jitter`]: #method.jitter
+	#[must_use]
+	pub fn estimate(&self) -> Duration {
+		let min = self.config.min_delay;
+		let max = self.config.max_delay.max(min);
+
+		(self.jitter() + self.config.headroom).clamp(min, max)
+	}
+
+	/// Forgets all packets seen, *e.g.*, after the source restarts its timestamps.
+	pub fn reset(&mut self) {
+		self.transits.clear();
+		self.sorted.clear();
+		self.last_ts = None;
+	}
+}
+
+impl JitterBuffer {
+	/// Creates an empty buffer whose target delay adapts to the jitter of arriving
+	/// packets.
+	///
+	/// The target delay starts at `config.min_delay`.
+	#[must_use]
+	pub fn adaptive(config: AdaptiveConfig) -> Self {
+		let mut out = Self::new(config.min_delay);
+		out.adaptive = Some(DelayEstimator::new(config));
+
+		out
+	}
+
+	/// Enables adaptive target delay using `config`, or disables it if `None`.
+	///
+	/// When disabled, the current target delay is kept.
+	pub fn set_adaptive(&mut self, config: Option<AdaptiveConfig>) {
+		self.adaptive = config.map(DelayEstimator::new);
+	}
+
+	/// Estimator used to adapt the target delay, if enabled.
+	#[must_use]
+	pub fn delay_estimator(&self) -> Option<&DelayEstimator> {
+		self.adaptive.as_ref()
+	}
+
+	/// Feeds a packet's arrival to the estimator, and moves the target delay
+	/// towards its estimate.
+	///
+	/// The target grows at once to avoid further late packets, but shrinks
+	/// gradually to avoid audible gaps in playout.
+	pub(crate) fn adapt(&mut self, timestamp: Wrap32, now: Duration) {
+		let Some(estimator) = self.adaptive.as_mut() else {
+			return;
+		};
+
+		estimator.update(timestamp, now);
+		let estimate = estimator.estimate();
+
+		self.target_delay = if estimate >= self.target_delay {
+			estimate
+		} else {
+			let step = self.target_delay.saturating_sub(estimate) / 16;
+			self.target_delay.saturating_sub(step)
+		};
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn jitter_percentile_over_sliding_window() {
+		let mut config = AdaptiveConfig::new(1_000);
+		config.window = 4;
+		config.percentile = 100;
+		let mut estimator = DelayEstimator::new(config);
+
+		for (i, delay) in [0u64, 30, 10, 20, 5, 5].into_iter().enumerate() {
+			let ts = u32::try_from(i * 20).unwrap();
+			estimator.update(ts.into(), Duration::from_millis(u64::from(ts) + delay));
+		}
+
+		assert_eq!(estimator.jitter(), Duration::from_millis(15));
+	}
+}
@@ -0,0 +1,359 @@
+//! Reordering and playout of received RTP packets.
+//!
+//! *These are included when using the `"jitter"` feature.*
+//!
+//! A [`JitterBuffer`] holds packets from a single source for a target delay, so
+//! that packets which arrive out of order can be released to a decoder in
+//! sequence. Times are given as [`Duration`]s since an arbitrary (monotonic)
+//! epoch chosen by the caller.
+//!
+//! The target delay may be fixed, or adapted to the jitter of arriving packets
+//! using an [`AdaptiveConfig`].
+//!
+//! [`JitterBuffer`]: struct.JitterBuffer.html
+//! [`AdaptiveConfig`]: struct.AdaptiveConfig.html
+//! [`Duration`]: https://doc.rust-lang.org/core/time/struct.Duration.html
+
+mod adaptive;
+
+pub use self::adaptive::{AdaptiveConfig, DelayEstimator};
+
+use crate::{rtp::Rtp, wrap::Wrap16};
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+
+/// Default number of packets a [`JitterBuffer`] may hold.
+///
+/// [`JitterBuffer`]: struct.JitterBuffer.html
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Outcome of inserting a packet into a [`JitterBuffer`].
+///
+/// [`JitterBuffer`]: struct.JitterBuffer.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Insertion {
+	/// The packet was buffered for playout.
+	Buffered,
+	/// A packet with the same sequence number is already buffered, so this one
+	/// was discarded.
+	Duplicate,
+	/// Playout has already moved past this packet's sequence number, so it was
+	/// discarded.
+	Late,
+	/// The packet was buffered, but the buffer was full and so the oldest packet
+	/// was discarded.
+	Overflow,
+	/// The buffer was full and this packet was older than every buffered packet,
+	/// so this packet was discarded in place of the oldest.
+	Evicted,
+}
+
+/// An item released from a [`JitterBuffer`] in playout order.
+///
+/// [`JitterBuffer`]: struct.JitterBuffer.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Playout {
+	/// A received packet.
+	Packet(Rtp),
+	/// A packet which did not arrive in time, and for which a decoder may wish to
+	/// conceal loss.
+	Missing(Wrap16),
+}
+
+/// Counters describing the packets seen by a [`JitterBuffer`].
+///
+/// [`JitterBuffer`]: struct.JitterBuffer.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct JitterStats {
+	/// Packets buffered for playout.
+	pub buffered: u64,
+	/// Packets released for playout.
+	pub played: u64,
+	/// Sequence numbers skipped during playout.
+	pub missing: u64,
+	/// Packets discarded as duplicates.
+	pub duplicates: u64,
+	/// Packets discarded for arriving after their playout slot.
+	pub late: u64,
+	/// Packets discarded because the buffer was full.
+	pub overflowed: u64,
+}
+
+impl JitterStats {
+	/// Total packets discarded without being played out.
+	#[must_use]
+	pub fn discarded(&self) -> u64 {
+		self.duplicates + self.late + self.overflowed
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Entry {
+	packet: Rtp,
+	arrival: Duration,
+}
+
+/// Sequence-ordered buffer releasing packets from a single source in playout order.
+///
+/// Each packet is held until `target_delay` after its arrival, and packets are
+/// released strictly in sequence order. If the next packet in sequence has not
+/// arrived by the time a later one is due, the gap is reported as
+/// [`Playout::Missing`] so that a decoder can conceal the loss.
+///
+/// Sequence numbers are extended across wraparound, duplicates of buffered
+/// packets are discarded, and packets arriving after playout has passed them are
+/// counted as late.
+///
+/// [`Playout::Missing`]: enum.Playout.html#variant.Missing
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JitterBuffer {
+	target_delay: Duration,
+	capacity: usize,
+	packets: BTreeMap<i64, Entry>,
+	next_seq: Option<i64>,
+	highest_seq: Option<i64>,
+	stats: JitterStats,
+	adaptive: Option<DelayEstimator>,
+}
+
+impl JitterBuffer {
+	/// Creates an empty buffer which holds packets for `target_delay`.
+	#[must_use]
+	pub fn new(target_delay: Duration) -> Self {
+		Self::with_capacity(target_delay, DEFAULT_CAPACITY)
+	}
+
+	/// Creates an empty buffer which holds packets for `target_delay`, and at most
+	/// `capacity` packets at once.
+	#[must_use]
+	pub fn with_capacity(target_delay: Duration, capacity: usize) -> Self {
+		Self {
+			target_delay,
+			capacity: capacity.max(1),
+			packets: BTreeMap::new(),
+			next_seq: None,
+			highest_seq: None,
+			stats: JitterStats::default(),
+			adaptive: None,
+		}
+	}
+
+	/// Time for which each packet is currently held after arrival.
+	#[must_use]
+	pub fn target_delay(&self) -> Duration {
+		self.target_delay
+	}
+
+	/// Changes the time for which each packet is held after arrival.
+	///
+	/// This applies to packets already buffered. In adaptive mode, the target will
+	/// continue to move from this value as packets arrive.
+	pub fn set_target_delay(&mut self, target_delay: Duration) {
+		self.target_delay = target_delay;
+	}
+
+	/// Number of packets currently buffered.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.packets.len()
+	}
+
+	/// Returns whether no packets are currently buffered.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.packets.is_empty()
+	}
+
+	/// Counters describing the packets seen so far.
+	#[must_use]
+	pub fn stats(&self) -> &JitterStats {
+		&self.stats
+	}
+
+	/// Discards all buffered packets and playout position, *e.g.*, after the
+	/// source changes its SSRC or restarts its sequence numbers.
+	///
+	/// Counters and the current target delay are kept.
+	pub fn reset(&mut self) {
+		self.packets.clear();
+		self.next_seq = None;
+		self.highest_seq = None;
+		if let Some(estimator) = self.adaptive.as_mut() {
+			estimator.reset();
+		}
+	}
+
+	/// Inserts `packet`, which arrived at `now`.
+	///
+	/// In adaptive mode, late packets still count towards the measured jitter.
+	pub fn push(&mut self, packet: Rtp, now: Duration) -> Insertion {
+		let seq = self.extend(packet.sequence);
+
+		if self.packets.contains_key(&seq) {
+			self.stats.duplicates += 1;
+			return Insertion::Duplicate;
+		}
+
+		self.adapt(packet.timestamp, now);
+
+		if self.next_seq.map_or(false, |next| seq < next) {
+			self.stats.late += 1;
+			return Insertion::Late;
+		}
+
+		let full = self.packets.len() >= self.capacity;
+		if full && self.packets.keys().next().map_or(false, |&old| seq < old) {
+			self.next_seq = Some(seq + 1);
+			self.stats.overflowed += 1;
+			return Insertion::Evicted;
+		}
+
+		self.packets.insert(
+			seq,
+			Entry {
+				packet,
+				arrival: now,
+			},
+		);
+		self.stats.buffered += 1;
+
+		if full {
+			if let Some(old) = self.packets.keys().next().copied() {
+				self.packets.remove(&old);
+				self.next_seq = Some(old + 1);
+			}
+			self.stats.overflowed += 1;
+			return Insertion::Overflow;
+		}
+
+		Insertion::Buffered
+	}
+
+	/// Inserts a `received` packet, measuring its arrival from `epoch`.
+	///
+	/// See [`push`] for more information.
+	///
+	/// *This is included when using the `"std"` feature.*
+	///
+	/// [`push`]: #method.push
+	#[cfg(feature = "std")]
+	pub fn push_received(
+		&mut self,
+		received: crate::received::Received<Rtp>,
+		epoch: std::time::Instant,
+	) -> Insertion {
+		let now = received.since(epoch);
+		self.push(received.packet, now)
+	}
+
+	/// Releases the next item in playout order, if due by `now`.
+	///
+	/// Call this repeatedly until it returns `None`. Skipped sequence numbers are
+	/// reported one at a time before the packet which follows them.
+	pub fn pop(&mut self, now: Duration) -> Option<Playout> {
+		let (&seq, entry) = self.packets.iter().next()?;
+		if entry.arrival + self.target_delay > now {
+			return None;
+		}
+
+		let next = self.next_seq.unwrap_or(seq);
+		if next < seq {
+			self.next_seq = Some(next + 1);
+			self.stats.missing += 1;
+			#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+			return Some(Playout::Missing(Wrap16::new(next as u16)));
+		}
+
+		let entry = self.packets.remove(&seq)?;
+		self.next_seq = Some(seq + 1);
+		self.stats.played += 1;
+
+		Some(Playout::Packet(entry.packet))
+	}
+
+	/// Time at which the next item will become due, if any packets are buffered.
+	#[must_use]
+	pub fn next_due(&self) -> Option<Duration> {
+		self.packets
+			.values()
+			.next()
+			.map(|e| e.arrival + self.target_delay)
+	}
+
+	/// Extends a 16-bit sequence number relative to the highest seen so far.
+	fn extend(&mut self, seq: Wrap16) -> i64 {
+		let ext = match self.highest_seq {
+			Some(high) => {
+				#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+				let base = Wrap16::new(high as u16);
+				high + i64::from(base.distance(seq))
+			},
+			None => i64::from(u16::from(seq)),
+		};
+
+		self.highest_seq = Some(self.highest_seq.map_or(ext, |h| h.max(ext)));
+
+		ext
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtp::RtpType;
+	use alloc::vec::Vec;
+
+	fn packet(sequence: u16) -> Rtp {
+		Rtp {
+			version: 2,
+			padding: 0,
+			extension: 0,
+			csrc_count: 0,
+			marker: 0,
+			payload_type: RtpType::Dynamic(96),
+			sequence: sequence.into(),
+			timestamp: (u32::from(sequence) * 960).into(),
+			ssrc: 1,
+			csrc_list: Vec::new(),
+			payload: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn overflow_reports_evicted_packet() {
+		let mut buffer = JitterBuffer::with_capacity(Duration::ZERO, 2);
+		assert_eq!(buffer.push(packet(10), Duration::ZERO), Insertion::Buffered);
+		assert_eq!(buffer.push(packet(12), Duration::ZERO), Insertion::Buffered);
+		assert_eq!(buffer.push(packet(9), Duration::ZERO), Insertion::Evicted);
+		assert_eq!(buffer.push(packet(11), Duration::ZERO), Insertion::Overflow);
+		assert_eq!(buffer.len(), 2);
+		assert_eq!(buffer.stats().overflowed, 2);
+
+		let played: Vec<_> = core::iter::from_fn(|| buffer.pop(Duration::ZERO)).collect();
+		assert_eq!(
+			played,
+			[Playout::Packet(packet(11)), Playout::Packet(packet(12))]
+		);
+	}
+
+	#[cfg(feature = "std")]
+	#[test]
+	fn received_arrival_measured_from_epoch() {
+		use crate::received::Received;
+		use std::time::Instant;
+
+		let epoch = Instant::now();
+		let late = epoch + Duration::from_millis(40);
+		let mut buffer = JitterBuffer::new(Duration::from_millis(20));
+		assert_eq!(
+			buffer.push_received(Received::new(packet(1), late), epoch),
+			Insertion::Buffered
+		);
+
+		assert_eq!(buffer.pop(Duration::from_millis(59)), None);
+		assert_eq!(
+			buffer.pop(Duration::from_millis(60)),
+			Some(Playout::Packet(packet(1)))
+		);
+	}
+}
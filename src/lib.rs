@@ -13,19 +13,36 @@
 //! * `"rtp"` includes copy-free and owned views of RTP packets. *Default*.
 //! * `"rtcp"` includes copy-free and owned views of RTCP packets. *Default*.
 //! * `"pnet"` re-includes traits from [pnet] for packet view manipulation. *Default*.
+//! * `"anonymise"` includes tools for scrubbing identifying information from captures.
 //! * `"demux"` includes utilities for separating multiplexed RTP/RTCP streams.
 //! * `"discord"` includes platform-specific packet formats for Discord.
+//! * `"jitter"` includes a jitter buffer for reordering and playout of received RTP.
+//! * `"metrics"` includes export of stream statistics via the [metrics] facade.
+//! * `"rand"` includes secure random initialisation of RTP stream identifiers.
+//! * `"sdp"` includes parsing of RTP payload and header extension mappings from SDP.
+//! * `"session"` includes utilities for RTP session management, such as RTCP teardown.
+//! * `"testing"` includes tools for simulating network impairments in tests.
+//! * `"std"` includes utilities which rely on the standard library, such as arrival metadata.
 //!
 //! [Real-time Transport Protocol]: https://tools.ietf.org/html/rfc3550
 //! [Discord]: https://discord.gg
 //! [Serenity]: https://github.com/serenity-rs/serenity
 //! [pnet]: https://docs.rs/pnet
+//! [metrics]: https://docs.rs/metrics
 
 #![no_std]
 #![warn(clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "anonymise")]
+pub mod anonymise;
+
+#[cfg(any(feature = "rtp", feature = "rtcp"))]
+pub mod cursor;
 
 #[cfg(feature = "demux")]
 pub mod demux;
@@ -33,15 +50,39 @@ pub mod demux;
 #[cfg(feature = "discord")]
 pub mod discord;
 
+#[cfg(any(feature = "discord", feature = "rtp", feature = "rtcp"))]
+pub mod error;
+
+#[cfg(feature = "std")]
+pub mod received;
+
+#[cfg(feature = "jitter")]
+pub mod jitter;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 #[cfg(feature = "rtcp")]
 pub mod rtcp;
 
 #[cfg(feature = "rtp")]
 pub mod rtp;
 
-#[cfg(feature = "rtp")]
+#[cfg(feature = "sdp")]
+pub mod sdp;
+
+#[cfg(feature = "session")]
+pub mod session;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(any(feature = "rtp", feature = "rtcp"))]
 pub mod wrap;
 
+#[cfg(any(feature = "anonymise", feature = "testing"))]
+mod rng;
+
 #[cfg(feature = "pnet")]
 pub use pnet_macros_support::{
 	self as pnet,
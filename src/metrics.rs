@@ -0,0 +1,88 @@
+//! Export of per-stream statistics to the [`metrics`] facade.
+//!
+//! Any recorder compatible with [`metrics`] (*e.g.*, `metrics-exporter-prometheus`)
+//! will receive the following series, each labelled by `ssrc` and, where known, `mid`:
+//!
+//! | Name | Kind | Unit |
+//! |------|------|------|
+//! | `discortp_packets_total` | counter | packets |
+//! | `discortp_bytes_total` | counter | bytes |
+//! | `discortp_packets_lost` | gauge | packets |
+//! | `discortp_fraction_lost` | gauge | ratio (`0.0`--`1.0`) |
+//! | `discortp_jitter_seconds` | gauge | seconds |
+//! | `discortp_rtt_seconds` | gauge | seconds |
+//!
+//! *These are included when using the `"metrics"` feature.*
+//!
+//! [`metrics`]: https://docs.rs/metrics
+
+use ::metrics::{counter, gauge, Label};
+use alloc::{string::String, vec::Vec};
+use core::time::Duration;
+
+/// Snapshot of the statistics describing one RTP stream.
+///
+/// Statistics trackers in this crate can produce these snapshots, but they may
+/// equally be filled in by hand.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StreamMetrics {
+	/// SSRC of the stream.
+	pub ssrc: u32,
+
+	/// Media ID of the stream, if negotiated.
+	pub mid: Option<String>,
+
+	/// Total packets received.
+	pub packets: u64,
+
+	/// Total payload bytes received.
+	pub bytes: u64,
+
+	/// Cumulative packets lost.
+	pub lost: i64,
+
+	/// Fraction of packets lost over the last reporting interval (`0.0`--`1.0`).
+	pub fraction_lost: f64,
+
+	/// Current interarrival jitter estimate.
+	pub jitter: Duration,
+
+	/// Most recent round-trip time estimate, if known.
+	pub rtt: Option<Duration>,
+}
+
+impl StreamMetrics {
+	/// Labels identifying this stream.
+	#[must_use]
+	pub fn labels(&self) -> Vec<Label> {
+		let mut labels = Vec::with_capacity(2);
+		labels.push(Label::new("ssrc", alloc::format!("{}", self.ssrc)));
+		if let Some(mid) = &self.mid {
+			labels.push(Label::new("mid", mid.clone()));
+		}
+		labels
+	}
+
+	/// Publishes this snapshot to the currently installed `metrics` recorder.
+	#[allow(clippy::cast_precision_loss)]
+	pub fn record(&self) {
+		let labels = self.labels();
+
+		counter!("discortp_packets_total", labels.clone()).absolute(self.packets);
+		counter!("discortp_bytes_total", labels.clone()).absolute(self.bytes);
+		gauge!("discortp_packets_lost", labels.clone()).set(self.lost as f64);
+		gauge!("discortp_fraction_lost", labels.clone()).set(self.fraction_lost);
+		gauge!("discortp_jitter_seconds", labels.clone()).set(self.jitter.as_secs_f64());
+
+		if let Some(rtt) = self.rtt {
+			gauge!("discortp_rtt_seconds", labels.clone()).set(rtt.as_secs_f64());
+		}
+	}
+}
+
+/// Publishes every snapshot in `streams` to the currently installed `metrics` recorder.
+pub fn record_all<'a>(streams: impl IntoIterator<Item = &'a StreamMetrics>) {
+	for stream in streams {
+		stream.record();
+	}
+}
@@ -0,0 +1,149 @@
+//! Arrival metadata for received packets.
+//!
+//! *These are included when using the `"std"` feature.*
+
+use std::{
+	net::SocketAddr,
+	time::{Duration, Instant},
+};
+
+/// A parsed packet (or demux result) alongside the metadata describing its arrival.
+///
+/// Statistics, feedback, and playout components which care about *when* a packet
+/// arrived accept this type, so that receive loops only need to record this
+/// information once:
+///
+/// * [`JitterBuffer::push_received`] for playout,
+/// * [`ReceiverStats::push_received`] for reception statistics, and
+/// * [`TransportCc::from_received`] for transport-wide congestion control feedback.
+///
+/// As these components measure time from an arbitrary epoch, each also takes the
+/// [`Instant`] used as that epoch.
+///
+/// [`JitterBuffer::push_received`]: ../jitter/struct.JitterBuffer.html#method.push_received
+/// [`ReceiverStats::push_received`]: ../session/struct.ReceiverStats.html#method.push_received
+/// [`TransportCc::from_received`]: ../rtcp/feedback/twcc/struct.TransportCc.html#method.from_received
+/// [`Instant`]: https://doc.rust-lang.org/std/time/struct.Instant.html
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Received<T> {
+	/// The received packet.
+	pub packet: T,
+
+	/// Local time at which the datagram was received.
+	pub arrival: Instant,
+
+	/// Remote address which sent the datagram, if known.
+	pub source: Option<SocketAddr>,
+
+	/// ECN codepoint from the IP header, if reported by the socket.
+	pub ecn: Ecn,
+}
+
+impl<T> Received<T> {
+	/// Wraps a packet which arrived at `arrival`, with unknown source and ECN marking.
+	pub fn new(packet: T, arrival: Instant) -> Self {
+		Self {
+			packet,
+			arrival,
+			source: None,
+			ecn: Ecn::NotEct,
+		}
+	}
+
+	/// Wraps a packet which arrived now, with unknown source and ECN marking.
+	pub fn now(packet: T) -> Self {
+		Self::new(packet, Instant::now())
+	}
+
+	/// Time at which this packet arrived, measured from `epoch`.
+	///
+	/// Arrivals before `epoch` are treated as arriving at the epoch.
+	#[must_use]
+	pub fn since(&self, epoch: Instant) -> Duration {
+		self.arrival.saturating_duration_since(epoch)
+	}
+
+	/// Sets the remote address which sent this packet.
+	#[must_use]
+	pub fn with_source(mut self, source: SocketAddr) -> Self {
+		self.source = Some(source);
+		self
+	}
+
+	/// Sets the ECN codepoint this packet was marked with.
+	#[must_use]
+	pub fn with_ecn(mut self, ecn: Ecn) -> Self {
+		self.ecn = ecn;
+		self
+	}
+
+	/// Borrows the inner packet, keeping all arrival metadata.
+	pub fn as_ref(&self) -> Received<&T> {
+		Received {
+			packet: &self.packet,
+			arrival: self.arrival,
+			source: self.source,
+			ecn: self.ecn,
+		}
+	}
+
+	/// Transforms the inner packet (*e.g.*, converting a view into an owned packet),
+	/// keeping all arrival metadata.
+	pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Received<U> {
+		Received {
+			packet: f(self.packet),
+			arrival: self.arrival,
+			source: self.source,
+			ecn: self.ecn,
+		}
+	}
+
+	/// Discards arrival metadata, returning the inner packet.
+	pub fn into_inner(self) -> T {
+		self.packet
+	}
+}
+
+/// Explicit Congestion Notification codepoint, as carried in the IP header.
+///
+/// See [RFC 3168](https://tools.ietf.org/html/rfc3168#section-5).
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Ecn {
+	/// Not ECN-capable transport (`0b00`).
+	#[default]
+	NotEct,
+
+	/// ECN-capable transport, codepoint 1 (`0b01`).
+	Ect1,
+
+	/// ECN-capable transport, codepoint 0 (`0b10`).
+	Ect0,
+
+	/// Congestion experienced (`0b11`).
+	Ce,
+}
+
+impl Ecn {
+	/// Extracts the ECN codepoint from the low two bits of an IPv4 TOS or IPv6
+	/// traffic class byte.
+	#[must_use]
+	pub fn new(tos: u8) -> Self {
+		match tos & 0b11 {
+			0b00 => Self::NotEct,
+			0b01 => Self::Ect1,
+			0b10 => Self::Ect0,
+			_ => Self::Ce,
+		}
+	}
+
+	/// Returns the two-bit value of this codepoint.
+	#[must_use]
+	pub fn bits(self) -> u8 {
+		match self {
+			Self::NotEct => 0b00,
+			Self::Ect1 => 0b01,
+			Self::Ect0 => 0b10,
+			Self::Ce => 0b11,
+		}
+	}
+}
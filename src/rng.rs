@@ -0,0 +1,35 @@
+//! Small deterministic PRNG for seeded, reproducible behaviour.
+//!
+//! This is *not* suitable for anything security-sensitive.
+
+/// `SplitMix64` generator, as described by Steele, Lea & Flood.
+#[derive(Clone, Debug)]
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+	pub(crate) fn new(seed: u64) -> Self {
+		Self(seed)
+	}
+
+	pub(crate) fn next_u64(&mut self) -> u64 {
+		self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+		let mut z = self.0;
+		z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+		z ^ (z >> 31)
+	}
+
+	/// Uniform sample in `[0, 1)`.
+	#[cfg(feature = "testing")]
+	pub(crate) fn next_f64(&mut self) -> f64 {
+		#[allow(clippy::cast_precision_loss)]
+		let mantissa = (self.next_u64() >> 11) as f64;
+		mantissa / 9_007_199_254_740_992.0
+	}
+
+	/// Returns `true` with probability `p`.
+	#[cfg(feature = "testing")]
+	pub(crate) fn chance(&mut self, p: f64) -> bool {
+		p > 0.0 && self.next_f64() < p
+	}
+}
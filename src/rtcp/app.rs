@@ -0,0 +1,193 @@
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, compound::CompoundPackets, RtcpPacket, RtcpType},
+};
+use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+use pnet_macros::packet;
+use pnet_macros_support::types::{u1, u16be, u2, u32be, u5};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Application-defined RTCP message, containing a name and arbitrary data.
+///
+/// See the relevant [RTP RFC section](https://tools.ietf.org/html/rfc3550#section-6.7).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `subtype`
+/// Application-defined subtype, allowing several messages to share one [`name`].
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::ApplicationDefined`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `ssrc`
+/// SSRC/CSRC for the source of this packet.
+///
+/// ## `name`
+/// Four ASCII characters naming this set of APP packets, chosen by the application
+/// designer to be unique.
+///
+/// ## `payload`
+/// Application-dependent data, whose length must be a multiple of 32 bits.
+///
+/// [`length`]: #structfield.length
+/// [`name`]: #structfield.name
+/// [`RtcpType::ApplicationDefined`]: ../enum.RtcpType.html#variant.ApplicationDefined
+pub struct ApplicationDefined {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub subtype: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	pub ssrc: u32be,
+
+	#[length = "4"]
+	pub name: Vec<u8>,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	ApplicationDefinedPacket,
+	MutableApplicationDefinedPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"subtype" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"ssrc" => 8,
+		"name" => 12,
+	],
+	rtcp::check_length
+);
+
+impl ApplicationDefinedPacket<'_> {
+	/// Returns the four-character packet name, if valid ASCII.
+	#[must_use]
+	pub fn name(&self) -> Option<&str> {
+		ascii_name(self.get_name_raw())
+	}
+}
+
+impl MutableApplicationDefinedPacket<'_> {
+	/// Returns the four-character packet name, if valid ASCII.
+	#[must_use]
+	pub fn name(&self) -> Option<&str> {
+		ascii_name(self.get_name_raw())
+	}
+}
+
+pub(crate) fn ascii_name(name: &[u8]) -> Option<&str> {
+	if name.is_ascii() {
+		core::str::from_utf8(name).ok()
+	} else {
+		None
+	}
+}
+
+type AppHandler<'a> = Box<dyn FnMut(&ApplicationDefinedPacket<'_>) + 'a>;
+
+/// Dispatches APP packets to handlers registered by name and subtype.
+///
+/// This allows custom RTCP extensions to be plugged into a receive pipeline:
+/// each APP packet in a (compound) RTCP datagram is handed to the handler
+/// registered for its exact `(name, subtype)` pair, or failing that, to the
+/// handler registered for its name alone.
+#[derive(Default)]
+pub struct AppRegistry<'a> {
+	handlers: BTreeMap<([u8; 4], Option<u8>), AppHandler<'a>>,
+}
+
+impl<'a> AppRegistry<'a> {
+	/// Creates a registry with no handlers.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Registers a handler for APP packets with the given `name` and `subtype`,
+	/// replacing any existing handler.
+	pub fn register(
+		&mut self,
+		name: [u8; 4],
+		subtype: u8,
+		handler: impl FnMut(&ApplicationDefinedPacket<'_>) + 'a,
+	) {
+		self.handlers
+			.insert((name, Some(subtype)), Box::new(handler));
+	}
+
+	/// Registers a handler for APP packets with the given `name` and any subtype
+	/// which lacks a more specific handler, replacing any existing handler.
+	pub fn register_any(
+		&mut self,
+		name: [u8; 4],
+		handler: impl FnMut(&ApplicationDefinedPacket<'_>) + 'a,
+	) {
+		self.handlers.insert((name, None), Box::new(handler));
+	}
+
+	/// Removes the handler for `name` and `subtype` (or all subtypes, if `None`).
+	///
+	/// Returns `true` if a handler was removed.
+	pub fn unregister(&mut self, name: [u8; 4], subtype: Option<u8>) -> bool {
+		self.handlers.remove(&(name, subtype)).is_some()
+	}
+
+	/// Passes an APP packet to its registered handler.
+	///
+	/// Returns `true` if a handler was found.
+	pub fn dispatch(&mut self, pkt: &ApplicationDefinedPacket<'_>) -> bool {
+		let mut name = [0u8; 4];
+		name.copy_from_slice(pkt.get_name_raw());
+
+		let handler = match self.handlers.get_mut(&(name, Some(pkt.get_subtype()))) {
+			Some(h) => Some(h),
+			None => self.handlers.get_mut(&(name, None)),
+		};
+
+		handler.map(|h| h(pkt)).is_some()
+	}
+
+	/// Passes every APP packet within a compound RTCP datagram to its registered
+	/// handler, ignoring other packet types.
+	///
+	/// Returns the number of packets handled.
+	pub fn dispatch_compound(&mut self, buf: &[u8]) -> usize {
+		CompoundPackets::new(buf)
+			.filter_map(|pkt| match pkt {
+				RtcpPacket::ApplicationDefined(app) => Some(self.dispatch(&app)),
+				_ => None,
+			})
+			.filter(|handled| *handled)
+			.count()
+	}
+}
+
+impl core::fmt::Debug for AppRegistry<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("AppRegistry")
+			.field("handlers", &self.handlers.keys())
+			.finish()
+	}
+}
@@ -0,0 +1,129 @@
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, app::ascii_name, RtcpType},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::types::{u1, u16be, u2, u32be, u5, u64be};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// AVB RTCP packet, relating RTP timestamps to the IEEE 802.1AS presentation
+/// time of an AVB stream.
+///
+/// See [IEEE 1733](https://ieeexplore.ieee.org/document/5154142).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `subtype`
+/// Must be set to `0`.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::Avb`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding. Should be `9`.
+///
+/// ## `ssrc`
+/// SSRC/CSRC for the source of this packet.
+///
+/// ## `name`
+/// Four ASCII characters naming this packet.
+///
+/// ## `gm_time_base_indicator`
+/// Changes whenever the grandmaster's time base changes.
+///
+/// ## `gm_identity`
+/// 80-bit identity of the current 802.1AS grandmaster.
+///
+/// ## `stream_id`
+/// 64-bit identifier of the AVB stream carrying this RTP stream.
+///
+/// ## `as_timestamp`
+/// Lower 32 bits of the 802.1AS presentation time, in nanoseconds, corresponding
+/// to `rtp_timestamp`.
+///
+/// ## `rtp_timestamp`
+/// RTP timestamp of the same instant as `as_timestamp`.
+///
+/// ## `payload`
+/// Remainder of the packet. Should be empty, barring padding.
+///
+/// [`length`]: #structfield.length
+/// [`RtcpType::Avb`]: ../enum.RtcpType.html#variant.Avb
+pub struct Avb {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub subtype: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	pub ssrc: u32be,
+
+	#[length = "4"]
+	pub name: Vec<u8>,
+
+	pub gm_time_base_indicator: u16be,
+
+	#[length = "10"]
+	pub gm_identity: Vec<u8>,
+
+	pub stream_id: u64be,
+
+	pub as_timestamp: u32be,
+
+	pub rtp_timestamp: u32be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	AvbPacket,
+	MutableAvbPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"subtype" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"ssrc" => 8,
+		"name" => 12,
+		"gm_time_base_indicator" => 14,
+		"gm_identity" => 24,
+		"stream_id" => 32,
+		"as_timestamp" => 36,
+		"rtp_timestamp" => 40,
+	],
+	rtcp::check_length
+);
+
+impl AvbPacket<'_> {
+	/// Returns the four-character packet name, if valid ASCII.
+	#[must_use]
+	pub fn name(&self) -> Option<&str> {
+		ascii_name(self.get_name_raw())
+	}
+}
+
+impl MutableAvbPacket<'_> {
+	/// Returns the four-character packet name, if valid ASCII.
+	#[must_use]
+	pub fn name(&self) -> Option<&str> {
+		ascii_name(self.get_name_raw())
+	}
+}
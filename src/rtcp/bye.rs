@@ -0,0 +1,211 @@
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, RtcpType},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u16be, u2, u32be, u5},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Source exit message, denoting SSRC/CSRC of exiting hosts and an optional reason string.
+///
+/// See the relevant [RTP RFC section](https://tools.ietf.org/html/rfc3550#section-6.6).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `source_count`
+/// Number of SSRC/CSRC identifiers contained. May be `0`.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::Goodbye`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `ssrcs`
+/// SSRC/CSRC identifiers of each departing source.
+///
+/// ## `payload`
+/// Optional reason for leaving, as a length-prefixed UTF-8 string
+/// (see [`reason`]), padded to a 32-bit boundary.
+///
+/// [`length`]: #structfield.length
+/// [`reason`]: struct.GoodbyePacket.html#method.reason
+/// [`RtcpType::Goodbye`]: ../enum.RtcpType.html#variant.Goodbye
+pub struct Goodbye {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub source_count: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	#[length = "4 * source_count"]
+	pub ssrcs: Vec<u32be>,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	GoodbyePacket,
+	MutableGoodbyePacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"source_count" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+	],
+	|pkt: &[u8]| {
+		rtcp::check_list(pkt, "ssrcs", 4, 4)?;
+		rtcp::check_length(pkt)
+	}
+);
+
+impl Goodbye {
+	/// Creates a goodbye message for up to 31 departing `ssrcs`, with an optional
+	/// `reason` (truncated to the last character boundary within 255 bytes).
+	///
+	/// The reason is padded to a 32-bit boundary, and [`pkt_length`] is filled in.
+	///
+	/// [`pkt_length`]: #structfield.pkt_length
+	#[must_use]
+	pub fn new(ssrcs: &[u32], reason: Option<&str>) -> Self {
+		let ssrcs = &ssrcs[..ssrcs.len().min(31)];
+
+		let mut payload = Vec::new();
+		if let Some(reason) = reason {
+			let mut len = reason.len().min(255);
+			while !reason.is_char_boundary(len) {
+				len -= 1;
+			}
+			let reason = &reason.as_bytes()[..len];
+			#[allow(clippy::cast_possible_truncation)]
+			payload.push(reason.len() as u8);
+			payload.extend_from_slice(reason);
+			while payload.len() % 4 != 0 {
+				payload.push(0);
+			}
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		let mut out = Self {
+			version: 2,
+			padding: 0,
+			source_count: ssrcs.len() as u8,
+			packet_type: RtcpType::Goodbye,
+			pkt_length: 0,
+			ssrcs: ssrcs.to_vec(),
+			payload,
+		};
+		out.pkt_length = rtcp::length_words(out.wire_size());
+
+		out
+	}
+
+	/// Number of bytes needed to serialise this packet.
+	///
+	/// Unlike [`GoodbyePacket::packet_size`], this accounts for each SSRC being 4 bytes wide.
+	///
+	/// [`GoodbyePacket::packet_size`]: struct.GoodbyePacket.html#method.packet_size
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		GoodbyePacket::minimum_packet_size() + 4 * self.ssrcs.len() + self.payload.len()
+	}
+
+	/// Serialises this packet into the start of `buf`, returning the number of bytes written.
+	///
+	/// Returns `None` if `buf` is too small.
+	pub fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let len = self.wire_size();
+		MutableGoodbyePacket::new(buf.get_mut(..len)?)?.populate(self);
+
+		Some(len)
+	}
+}
+
+impl GoodbyePacket<'_> {
+	/// Returns the raw bytes of the reason for leaving, if present.
+	///
+	/// The reason length is clamped to the bytes available.
+	#[must_use]
+	pub fn reason_raw(&self) -> Option<&[u8]> {
+		reason(Packet::payload(self))
+	}
+
+	/// Returns the reason for leaving, if present and valid UTF-8.
+	#[must_use]
+	pub fn reason(&self) -> Option<&str> {
+		self.reason_raw().and_then(|r| core::str::from_utf8(r).ok())
+	}
+}
+
+impl MutableGoodbyePacket<'_> {
+	/// Returns the raw bytes of the reason for leaving, if present.
+	///
+	/// The reason length is clamped to the bytes available.
+	#[must_use]
+	pub fn reason_raw(&self) -> Option<&[u8]> {
+		reason(Packet::payload(self))
+	}
+
+	/// Returns the reason for leaving, if present and valid UTF-8.
+	#[must_use]
+	pub fn reason(&self) -> Option<&str> {
+		self.reason_raw().and_then(|r| core::str::from_utf8(r).ok())
+	}
+}
+
+fn reason(payload: &[u8]) -> Option<&[u8]> {
+	let (&len, rest) = payload.split_first()?;
+	(len != 0).then(|| &rest[..usize::from(len).min(rest.len())])
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn goodbye_round_trip() {
+		let bye = Goodbye::new(&[1, 2], Some("leaving"));
+		let mut buf = [0u8; 64];
+		let len = bye.write(&mut buf).unwrap();
+		assert_eq!(len % 4, 0);
+
+		let pkt = GoodbyePacket::new_checked(&buf[..len]).unwrap();
+		assert_eq!(pkt.get_source_count(), 2);
+		assert_eq!(pkt.get_ssrcs(), [1, 2]);
+		assert_eq!(pkt.reason(), Some("leaving"));
+		assert_eq!(usize::from(pkt.get_pkt_length()), len / 4 - 1);
+	}
+
+	#[test]
+	fn long_reason_truncated_on_char_boundary() {
+		let mut reason = "a".repeat(254);
+		reason.push('é');
+		let bye = Goodbye::new(&[1], Some(&reason));
+		let mut buf = [0u8; 512];
+		let len = bye.write(&mut buf).unwrap();
+
+		let pkt = GoodbyePacket::new_checked(&buf[..len]).unwrap();
+		assert_eq!(pkt.reason(), Some(&*"a".repeat(254)));
+	}
+}
@@ -0,0 +1,250 @@
+use crate::rtcp::{self, Rtcp, RtcpPacket, RtcpType};
+use alloc::vec::Vec;
+
+/// Iterator over the individual packets within a compound RTCP datagram.
+///
+/// In *strict* mode ([`new`]), iteration ends as soon as a packet's `pkt_length`
+/// runs past the end of the datagram or a packet cannot be decoded, leaving the
+/// unparsed bytes available via [`remainder`].
+///
+/// In *lenient* mode ([`lenient`]), a `pkt_length` which overruns the datagram, or
+/// which does not land on another plausible RTCP header, causes the iterator to scan
+/// forward (in 32-bit steps) for the next plausible header: version `2` and a known
+/// packet type. The bytes before that point are treated as one packet, so that a
+/// single malformed packet from a non-compliant peer does not discard the rest.
+///
+/// [`new`]: #method.new
+/// [`lenient`]: #method.lenient
+/// [`remainder`]: #method.remainder
+#[derive(Clone, Debug)]
+pub struct CompoundPackets<'a> {
+	buf: &'a [u8],
+	offset: usize,
+	lenient: bool,
+}
+
+impl<'a> CompoundPackets<'a> {
+	/// Iterates over `buf`, stopping at the first malformed packet.
+	#[must_use]
+	pub fn new(buf: &'a [u8]) -> Self {
+		Self {
+			buf,
+			offset: 0,
+			lenient: false,
+		}
+	}
+
+	/// Iterates over `buf`, resynchronising on the next plausible header after
+	/// a malformed packet.
+	#[must_use]
+	pub fn lenient(buf: &'a [u8]) -> Self {
+		Self {
+			buf,
+			offset: 0,
+			lenient: true,
+		}
+	}
+
+	/// Bytes which have not yet been consumed.
+	///
+	/// In strict mode, this is non-empty after iteration only if a malformed
+	/// packet was found.
+	#[must_use]
+	pub fn remainder(&self) -> &'a [u8] {
+		self.buf.get(self.offset..).unwrap_or_default()
+	}
+
+	fn next_len(&self, rest: &[u8]) -> Option<usize> {
+		let words = u16::from_be_bytes([*rest.get(2)?, *rest.get(3)?]);
+		let declared = (usize::from(words) + 1) * 4;
+
+		if !self.lenient {
+			return (declared <= rest.len()).then_some(declared);
+		}
+
+		if declared == rest.len() || (declared < rest.len() && is_plausible(&rest[declared..])) {
+			Some(declared)
+		} else {
+			let resync = (4..rest.len())
+				.step_by(4)
+				.find(|&i| is_plausible(&rest[i..]))
+				.unwrap_or(rest.len());
+			Some(resync)
+		}
+	}
+}
+
+impl<'a> Iterator for CompoundPackets<'a> {
+	type Item = RtcpPacket<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let rest = self.remainder();
+			let len = self.next_len(rest)?;
+			let pkt = RtcpPacket::new(&rest[..len]);
+
+			if pkt.is_some() || self.lenient {
+				self.offset += len;
+			}
+
+			if pkt.is_some() || !self.lenient {
+				return pkt;
+			}
+		}
+	}
+}
+
+/// Returns `true` if `pkt` begins with a plausible RTCP header: version `2`,
+/// and a known packet type.
+#[must_use]
+pub fn is_plausible(pkt: &[u8]) -> bool {
+	match (pkt.first(), RtcpType::from_packet(pkt)) {
+		(Some(b), Some(rt)) =>
+			b >> 6 == 2 && !matches!(rt, RtcpType::Reserved(_) | RtcpType::Unassigned(_)),
+		_ => false,
+	}
+}
+
+/// Serialises a sequence of owned RTCP packets into one compound datagram.
+///
+/// Each packet's `pkt_length` is filled in to match its serialised size. If requested
+/// via [`pad_to`], padding is appended to the *final* packet only, as required by the
+/// [RFC](https://tools.ietf.org/html/rfc3550#section-6.4.1).
+///
+/// [`pad_to`]: #method.pad_to
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CompoundBuilder {
+	packets: Vec<Rtcp>,
+	block_size: Option<usize>,
+}
+
+impl CompoundBuilder {
+	/// Creates a builder with no packets.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Appends a packet to the compound datagram.
+	///
+	/// [`Rtcp::KnownType`] entries have no body, and are skipped.
+	///
+	/// [`Rtcp::KnownType`]: ../enum.Rtcp.html#variant.KnownType
+	pub fn push(&mut self, packet: Rtcp) -> &mut Self {
+		self.packets.push(packet);
+		self
+	}
+
+	/// Pads the final datagram to a multiple of `block_size` bytes (*e.g.*, for
+	/// cipher alignment).
+	///
+	/// `block_size` must be a multiple of 4, and no more than 256: other sizes
+	/// are ignored, leaving any earlier block size in place.
+	pub fn pad_to(&mut self, block_size: usize) -> &mut Self {
+		if block_size % 4 == 0 && (4..=256).contains(&block_size) {
+			self.block_size = Some(block_size);
+		}
+		self
+	}
+
+	/// Packets which will be written, in order.
+	#[must_use]
+	pub fn packets(&self) -> &[Rtcp] {
+		&self.packets
+	}
+
+	/// Total number of bytes needed to serialise the compound datagram.
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		let body = self.packets.iter().map(Rtcp::wire_size).sum();
+		body + self.padding_for(body)
+	}
+
+	/// Serialises all packets back-to-back into the start of `buf`, returning the
+	/// total number of bytes written.
+	///
+	/// Returns `None` if `buf` is too small.
+	pub fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		if buf.len() < self.wire_size() {
+			return None;
+		}
+
+		let mut offset = 0;
+		let mut last = None;
+		for pkt in &self.packets {
+			let len = pkt.write(&mut buf[offset..])?;
+			if len != 0 {
+				last = Some(offset);
+			}
+			offset += len;
+		}
+
+		let pad = self.padding_for(offset);
+		if let (Some(last), true) = (last, pad != 0) {
+			buf[offset..offset + pad].fill(0);
+			offset += pad;
+
+			buf[offset - 1] = u8::try_from(pad).ok()?;
+			buf[last] |= 0b0010_0000;
+			buf[last + 2..last + 4]
+				.copy_from_slice(&rtcp::length_words(offset - last).to_be_bytes());
+		}
+
+		Some(offset)
+	}
+
+	/// Serialises all packets into a newly allocated buffer.
+	#[must_use]
+	pub fn to_vec(&self) -> Vec<u8> {
+		let mut out = alloc::vec![0u8; self.wire_size()];
+		let len = self.write(&mut out).unwrap_or(0);
+		out.truncate(len);
+		out
+	}
+
+	fn padding_for(&self, len: usize) -> usize {
+		match self.block_size {
+			Some(block) if len != 0 => (block - len % block) % block,
+			_ => 0,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::{bye::Goodbye, RtcpPadding};
+
+	#[test]
+	fn padded_compound_round_trip() {
+		let mut builder = CompoundBuilder::new();
+		builder
+			.push(Rtcp::Goodbye(Goodbye::new(&[1], None)))
+			.push(Rtcp::Goodbye(Goodbye::new(&[2], Some("bye"))))
+			.pad_to(32);
+		let buf = builder.to_vec();
+		assert_eq!(buf.len(), 32);
+
+		let packets: Vec<_> = CompoundPackets::new(&buf).collect();
+		assert_eq!(packets.len(), 2);
+		assert_eq!(packets[0].padding_len(), 0);
+		assert_eq!(packets[1].padding_len(), 12);
+		match &packets[1] {
+			RtcpPacket::Goodbye(bye) => assert_eq!(bye.reason(), Some("bye")),
+			other => panic!("unexpected packet {other:?}"),
+		}
+	}
+
+	#[test]
+	fn invalid_block_sizes_are_ignored() {
+		let mut builder = CompoundBuilder::new();
+		builder.push(Rtcp::Goodbye(Goodbye::new(&[1], None)));
+		let unpadded = builder.wire_size();
+
+		for block in [0, 6, 260, 512] {
+			assert_eq!(builder.pad_to(block).wire_size(), unpadded);
+		}
+		assert_eq!(builder.pad_to(256).wire_size(), 256);
+		assert_eq!(builder.pad_to(300).wire_size(), 256);
+	}
+}
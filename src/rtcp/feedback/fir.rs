@@ -0,0 +1,168 @@
+use super::{
+	MutablePayloadFeedbackPacket,
+	PayloadFeedback,
+	PayloadFeedbackPacket,
+	PayloadFeedbackType,
+};
+use crate::rtcp::{self, RtcpType};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u24be, u32be},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Full Intra Request entry, carried as the FCI of a [`PayloadFeedback`] packet
+/// whose `fmt` is [`PayloadFeedbackType::FullIntraRequest`].
+///
+/// See [RFC 5104](https://tools.ietf.org/html/rfc5104#section-4.3.1).
+///
+/// A description of fields:
+///
+/// ## `ssrc`
+/// SSRC of the media sender which is requested to send a decoder refresh point.
+///
+/// ## `sequence`
+/// Command sequence number, incremented by `1` for each new request to this SSRC.
+/// Repeated requests (*i.e.*, retransmissions) must reuse the same value.
+///
+/// ## `reserved`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `payload`
+/// Remainder of the FCI.
+///
+/// [`PayloadFeedback`]: ../struct.PayloadFeedback.html
+/// [`PayloadFeedbackType::FullIntraRequest`]: ../enum.PayloadFeedbackType.html#variant.FullIntraRequest
+pub struct FirEntry {
+	pub ssrc: u32be,
+
+	pub sequence: u8,
+
+	pub reserved: u24be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+const FIR_LEN: usize = FirEntryPacket::minimum_packet_size();
+
+impl PayloadFeedback {
+	/// Creates an empty Full Intra Request from `sender_ssrc`.
+	///
+	/// Requests for each media sender are added using [`push_fir`]. As required by
+	/// the RFC, the packet's `media_ssrc` is set to `0`.
+	///
+	/// [`push_fir`]: #method.push_fir
+	#[must_use]
+	pub fn fir(sender_ssrc: u32) -> Self {
+		Self {
+			version: 2,
+			padding: 0,
+			fmt: PayloadFeedbackType::FullIntraRequest,
+			packet_type: RtcpType::PayloadFeedback,
+			pkt_length: rtcp::length_words(PayloadFeedbackPacket::minimum_packet_size()),
+			sender_ssrc,
+			media_ssrc: 0,
+			payload: Vec::new(),
+		}
+	}
+
+	/// Appends a request for `ssrc` with command sequence number `sequence` to the
+	/// FCI, updating [`pkt_length`].
+	///
+	/// [`pkt_length`]: #structfield.pkt_length
+	pub fn push_fir(&mut self, ssrc: u32, sequence: u8) -> &mut Self {
+		self.payload.extend_from_slice(&ssrc.to_be_bytes());
+		self.payload.extend_from_slice(&[sequence, 0, 0, 0]);
+		self.pkt_length =
+			rtcp::length_words(PayloadFeedbackPacket::minimum_packet_size() + self.payload.len());
+
+		self
+	}
+}
+
+impl PayloadFeedbackPacket<'_> {
+	/// Iterates over each [`FirEntry`] in the FCI.
+	///
+	/// This assumes that the packet's `fmt` is [`PayloadFeedbackType::FullIntraRequest`].
+	///
+	/// [`FirEntry`]: fir/struct.FirEntry.html
+	/// [`PayloadFeedbackType::FullIntraRequest`]: enum.PayloadFeedbackType.html#variant.FullIntraRequest
+	#[must_use]
+	pub fn fir_entries(&self) -> FirEntries<'_> {
+		FirEntries::new(self.payload())
+	}
+}
+
+impl MutablePayloadFeedbackPacket<'_> {
+	/// Iterates over each [`FirEntry`] in the FCI.
+	///
+	/// This assumes that the packet's `fmt` is [`PayloadFeedbackType::FullIntraRequest`].
+	///
+	/// [`FirEntry`]: fir/struct.FirEntry.html
+	/// [`PayloadFeedbackType::FullIntraRequest`]: enum.PayloadFeedbackType.html#variant.FullIntraRequest
+	#[must_use]
+	pub fn fir_entries(&self) -> FirEntries<'_> {
+		FirEntries::new(self.payload())
+	}
+}
+
+/// Iterator over the [`FirEntry`]s in an FCI.
+///
+/// [`FirEntry`]: struct.FirEntry.html
+#[derive(Clone, Debug)]
+pub struct FirEntries<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> FirEntries<'a> {
+	/// Iterates over each complete 8-byte entry in `fci`.
+	#[must_use]
+	pub fn new(fci: &'a [u8]) -> Self {
+		Self { data: fci }
+	}
+}
+
+impl<'a> Iterator for FirEntries<'a> {
+	type Item = FirEntryPacket<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.data.len() < FIR_LEN {
+			return None;
+		}
+
+		let (entry, rest) = self.data.split_at(FIR_LEN);
+		self.data = rest;
+
+		FirEntryPacket::new(entry)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::Rtcp;
+
+	#[test]
+	fn fir_round_trip() {
+		let mut fir = PayloadFeedback::fir(1);
+		fir.push_fir(0xdead_beef, 7).push_fir(42, 255);
+		let mut buf = [0u8; 64];
+		let len = Rtcp::PayloadFeedback(fir).write(&mut buf).unwrap();
+		assert_eq!(len, 28);
+
+		let pkt = PayloadFeedbackPacket::new_checked(&buf[..len]).unwrap();
+		assert_eq!(pkt.get_fmt(), PayloadFeedbackType::FullIntraRequest);
+		assert_eq!(pkt.get_media_ssrc(), 0);
+		assert_eq!(usize::from(pkt.get_pkt_length()), len / 4 - 1);
+
+		let entries: Vec<_> = pkt
+			.fir_entries()
+			.map(|e| (e.get_ssrc(), e.get_sequence(), e.get_reserved()))
+			.collect();
+		assert_eq!(entries, [(0xdead_beef, 7, 0), (42, 255, 0)]);
+	}
+}
@@ -0,0 +1,456 @@
+pub mod fir;
+pub mod nack;
+pub mod pli;
+pub mod rpsi;
+pub mod sli;
+pub mod twcc;
+
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, RtcpType},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::PrimitiveValues,
+	types::{u1, u16be, u2, u32be, u5},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// RTPFB, feedback on the RTP transport layer.
+///
+/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.1).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `fmt`
+/// Type of feedback message, defining the contents of the payload.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::TransportFeedback`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `sender_ssrc`
+/// SSRC of the originator of this feedback.
+///
+/// ## `media_ssrc`
+/// SSRC of the media source which this feedback concerns.
+///
+/// ## `payload`
+/// Feedback Control Information (FCI), whose format depends on [`fmt`].
+///
+/// [`length`]: #structfield.length
+/// [`fmt`]: #structfield.fmt
+/// [`RtcpType::TransportFeedback`]: ../enum.RtcpType.html#variant.TransportFeedback
+pub struct TransportFeedback {
+	pub version: u2,
+
+	pub padding: u1,
+
+	#[construct_with(u5)]
+	pub fmt: TransportFeedbackType,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	pub sender_ssrc: u32be,
+
+	pub media_ssrc: u32be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	TransportFeedbackPacket,
+	MutableTransportFeedbackPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"fmt" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"sender_ssrc" => 8,
+		"media_ssrc" => 12,
+	],
+	rtcp::check_length
+);
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// PSFB, feedback on the payload.
+///
+/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.1).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `fmt`
+/// Type of feedback message, defining the contents of the payload.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::PayloadFeedback`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `sender_ssrc`
+/// SSRC of the originator of this feedback.
+///
+/// ## `media_ssrc`
+/// SSRC of the media source which this feedback concerns.
+///
+/// ## `payload`
+/// Feedback Control Information (FCI), whose format depends on [`fmt`].
+///
+/// [`length`]: #structfield.length
+/// [`fmt`]: #structfield.fmt
+/// [`RtcpType::PayloadFeedback`]: ../enum.RtcpType.html#variant.PayloadFeedback
+pub struct PayloadFeedback {
+	pub version: u2,
+
+	pub padding: u1,
+
+	#[construct_with(u5)]
+	pub fmt: PayloadFeedbackType,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	pub sender_ssrc: u32be,
+
+	pub media_ssrc: u32be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	PayloadFeedbackPacket,
+	MutablePayloadFeedbackPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"fmt" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"sender_ssrc" => 8,
+		"media_ssrc" => 12,
+	],
+	rtcp::check_length
+);
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+/// RTPFB message types (FMT values), defining the format of the FCI.
+///
+/// See the [IANA page] on the matter for an up-to-date-list.
+///
+/// [IANA page]: https://www.iana.org/assignments/rtp-parameters/rtp-parameters.xhtml#rtp-parameters-8
+pub enum TransportFeedbackType {
+	/// Generic negative acknowledgement.
+	///
+	/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.2.1).
+	///
+	/// Code 1.
+	Nack,
+
+	/// Temporary maximum media stream bit rate request.
+	///
+	/// See [RFC 5104](https://tools.ietf.org/html/rfc5104#section-4.2.1).
+	///
+	/// Code 3.
+	Tmmbr,
+
+	/// Temporary maximum media stream bit rate notification.
+	///
+	/// See [RFC 5104](https://tools.ietf.org/html/rfc5104#section-4.2.2).
+	///
+	/// Code 4.
+	Tmmbn,
+
+	/// Rapid resynchronisation request.
+	///
+	/// See [RFC 6051](https://tools.ietf.org/html/rfc6051).
+	///
+	/// Code 5.
+	RapidResyncRequest,
+
+	/// Rapid acquisition of multicast sessions.
+	///
+	/// See [RFC 6285](https://tools.ietf.org/html/rfc6285).
+	///
+	/// Code 6.
+	Rams,
+
+	/// Transport-layer third-party loss early indication.
+	///
+	/// See [RFC 6642](https://tools.ietf.org/html/rfc6642).
+	///
+	/// Code 7.
+	Tllei,
+
+	/// Explicit congestion notification feedback.
+	///
+	/// See [RFC 6679](https://tools.ietf.org/html/rfc6679).
+	///
+	/// Code 8.
+	EcnFeedback,
+
+	/// Media pause/resume.
+	///
+	/// See [RFC 7728](https://tools.ietf.org/html/rfc7728).
+	///
+	/// Code 9.
+	PauseResume,
+
+	/// Delay budget information.
+	///
+	/// See 3GPP TS 26.114.
+	///
+	/// Code 10.
+	DelayBudget,
+
+	/// RTP congestion control feedback.
+	///
+	/// See [RFC 8888](https://tools.ietf.org/html/rfc8888).
+	///
+	/// Code 11.
+	CongestionControl,
+
+	/// Transport-wide congestion control feedback.
+	///
+	/// See the [draft RFC](https://datatracker.ietf.org/doc/html/draft-holmer-rmcat-transport-wide-cc-extensions-01).
+	///
+	/// Code 15.
+	TransportWideCc,
+
+	/// Reserved for future extensions.
+	///
+	/// Code 31.
+	Extension,
+
+	/// Unknown or unassigned message type.
+	Unassigned(u8),
+}
+
+impl TransportFeedbackType {
+	#[must_use]
+	pub fn new(val: u5) -> Self {
+		match val {
+			1 => Self::Nack,
+			3 => Self::Tmmbr,
+			4 => Self::Tmmbn,
+			5 => Self::RapidResyncRequest,
+			6 => Self::Rams,
+			7 => Self::Tllei,
+			8 => Self::EcnFeedback,
+			9 => Self::PauseResume,
+			10 => Self::DelayBudget,
+			11 => Self::CongestionControl,
+			15 => Self::TransportWideCc,
+			31 => Self::Extension,
+			_ => Self::Unassigned(val),
+		}
+	}
+}
+
+impl PrimitiveValues for TransportFeedbackType {
+	type T = (u5,);
+
+	fn to_primitive_values(&self) -> Self::T {
+		match self {
+			Self::Nack => (1,),
+			Self::Tmmbr => (3,),
+			Self::Tmmbn => (4,),
+			Self::RapidResyncRequest => (5,),
+			Self::Rams => (6,),
+			Self::Tllei => (7,),
+			Self::EcnFeedback => (8,),
+			Self::PauseResume => (9,),
+			Self::DelayBudget => (10,),
+			Self::CongestionControl => (11,),
+			Self::TransportWideCc => (15,),
+			Self::Extension => (31,),
+
+			Self::Unassigned(val) => (*val,),
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+/// PSFB message types (FMT values), defining the format of the FCI.
+///
+/// See the [IANA page] on the matter for an up-to-date-list.
+///
+/// [IANA page]: https://www.iana.org/assignments/rtp-parameters/rtp-parameters.xhtml#rtp-parameters-9
+pub enum PayloadFeedbackType {
+	/// Picture loss indication.
+	///
+	/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.3.1).
+	///
+	/// Code 1.
+	PictureLossIndication,
+
+	/// Slice loss indication.
+	///
+	/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.3.2).
+	///
+	/// Code 2.
+	SliceLossIndication,
+
+	/// Reference picture selection indication.
+	///
+	/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.3.3).
+	///
+	/// Code 3.
+	ReferencePictureSelectionIndication,
+
+	/// Full intra request.
+	///
+	/// See [RFC 5104](https://tools.ietf.org/html/rfc5104#section-4.3.1).
+	///
+	/// Code 4.
+	FullIntraRequest,
+
+	/// Temporal-spatial trade-off request.
+	///
+	/// See [RFC 5104](https://tools.ietf.org/html/rfc5104#section-4.3.2).
+	///
+	/// Code 5.
+	TradeoffRequest,
+
+	/// Temporal-spatial trade-off notification.
+	///
+	/// See [RFC 5104](https://tools.ietf.org/html/rfc5104#section-4.3.3).
+	///
+	/// Code 6.
+	TradeoffNotification,
+
+	/// Video back channel message.
+	///
+	/// See [RFC 5104](https://tools.ietf.org/html/rfc5104#section-4.3.4).
+	///
+	/// Code 7.
+	VideoBackChannel,
+
+	/// Payload-specific third-party loss early indication.
+	///
+	/// See [RFC 6642](https://tools.ietf.org/html/rfc6642).
+	///
+	/// Code 8.
+	Pslei,
+
+	/// Region of interest.
+	///
+	/// See 3GPP TS 26.114.
+	///
+	/// Code 9.
+	RegionOfInterest,
+
+	/// Layer refresh request.
+	///
+	/// See [RFC 9354](https://tools.ietf.org/html/rfc9354).
+	///
+	/// Code 10.
+	LayerRefreshRequest,
+
+	/// Application layer feedback (*e.g.*, REMB).
+	///
+	/// Code 15.
+	ApplicationLayer,
+
+	/// Reserved for future extensions.
+	///
+	/// Code 31.
+	Extension,
+
+	/// Unknown or unassigned message type.
+	Unassigned(u8),
+}
+
+impl PayloadFeedbackType {
+	#[must_use]
+	pub fn new(val: u5) -> Self {
+		match val {
+			1 => Self::PictureLossIndication,
+			2 => Self::SliceLossIndication,
+			3 => Self::ReferencePictureSelectionIndication,
+			4 => Self::FullIntraRequest,
+			5 => Self::TradeoffRequest,
+			6 => Self::TradeoffNotification,
+			7 => Self::VideoBackChannel,
+			8 => Self::Pslei,
+			9 => Self::RegionOfInterest,
+			10 => Self::LayerRefreshRequest,
+			15 => Self::ApplicationLayer,
+			31 => Self::Extension,
+			_ => Self::Unassigned(val),
+		}
+	}
+}
+
+impl PrimitiveValues for PayloadFeedbackType {
+	type T = (u5,);
+
+	fn to_primitive_values(&self) -> Self::T {
+		match self {
+			Self::PictureLossIndication => (1,),
+			Self::SliceLossIndication => (2,),
+			Self::ReferencePictureSelectionIndication => (3,),
+			Self::FullIntraRequest => (4,),
+			Self::TradeoffRequest => (5,),
+			Self::TradeoffNotification => (6,),
+			Self::VideoBackChannel => (7,),
+			Self::Pslei => (8,),
+			Self::RegionOfInterest => (9,),
+			Self::LayerRefreshRequest => (10,),
+			Self::ApplicationLayer => (15,),
+			Self::Extension => (31,),
+
+			Self::Unassigned(val) => (*val,),
+		}
+	}
+}
+
+/// Rotates `items`, sorted by sequence number, so that the item following the
+/// largest (circular) gap comes first.
+///
+/// This places the oldest sequence number first in sets which straddle the
+/// 16-bit wraparound.
+pub(crate) fn rotate_to_oldest<T>(items: &mut [T], seq: impl Fn(&T) -> u16) {
+	let len = items.len();
+	let start = (0..len)
+		.max_by_key(|&i| {
+			let prev = seq(&items[(i + len - 1) % len]);
+			seq(&items[i]).wrapping_sub(prev).wrapping_sub(1)
+		})
+		.unwrap_or(0);
+
+	items.rotate_left(start);
+}
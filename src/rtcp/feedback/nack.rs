@@ -0,0 +1,259 @@
+use super::{
+	MutableTransportFeedbackPacket,
+	TransportFeedback,
+	TransportFeedbackPacket,
+	TransportFeedbackType,
+};
+use crate::{
+	rtcp::{self, RtcpType},
+	wrap::Wrap16,
+};
+use alloc::{vec, vec::Vec};
+use pnet_macros::packet;
+use pnet_macros_support::{packet::Packet, types::u16be};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Generic NACK entry, carried as the FCI of a [`TransportFeedback`] packet
+/// whose `fmt` is [`TransportFeedbackType::Nack`].
+///
+/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.2.1).
+///
+/// A description of fields:
+///
+/// ## `pid`
+/// Sequence number of a lost RTP packet.
+///
+/// ## `blp`
+/// Bitmask of following lost packets: if bit `i` (LSB first) is set, then
+/// the packet with sequence number `pid + i + 1` was also lost.
+///
+/// ## `payload`
+/// Remainder of the FCI.
+///
+/// [`TransportFeedback`]: ../struct.TransportFeedback.html
+/// [`TransportFeedbackType::Nack`]: ../enum.TransportFeedbackType.html#variant.Nack
+pub struct GenericNack {
+	#[construct_with(u16be)]
+	pub pid: Wrap16,
+
+	pub blp: u16be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+const NACK_LEN: usize = GenericNackPacket::minimum_packet_size();
+
+impl GenericNackPacket<'_> {
+	/// Iterates over every sequence number reported lost by this entry, in order.
+	#[must_use]
+	pub fn lost(&self) -> LostSequences {
+		LostSequences::new(self.get_pid(), self.get_blp())
+	}
+}
+
+impl GenericNack {
+	/// Packs a set of lost sequence numbers into the fewest possible entries.
+	///
+	/// Duplicates are ignored, and input order does not matter: sequence numbers
+	/// are ordered according to the largest gap between them, so that sets which
+	/// straddle the 16-bit wraparound are handled correctly.
+	pub fn pack(lost: impl IntoIterator<Item = Wrap16>) -> Vec<GenericNack> {
+		let mut seqs: Vec<u16> = lost.into_iter().map(u16::from).collect();
+		seqs.sort_unstable();
+		seqs.dedup();
+
+		if seqs.is_empty() {
+			return Vec::new();
+		}
+
+		super::rotate_to_oldest(&mut seqs, |&s| s);
+
+		let mut out: Vec<GenericNack> = Vec::new();
+		for seq in seqs {
+			let seq = Wrap16::from(seq);
+			match out.last_mut() {
+				Some(entry) if (1..=16).contains(&u16::from(seq - u16::from(entry.pid))) => {
+					entry.blp |= 1 << (u16::from(seq - u16::from(entry.pid)) - 1);
+				},
+				_ => out.push(GenericNack {
+					pid: seq,
+					blp: 0,
+					payload: Vec::new(),
+				}),
+			}
+		}
+
+		out
+	}
+
+	/// Iterates over every sequence number reported lost by this entry, in order.
+	#[must_use]
+	pub fn lost(&self) -> LostSequences {
+		LostSequences::new(self.pid, self.blp)
+	}
+}
+
+impl TransportFeedback {
+	/// Creates a Generic NACK feedback message from `sender_ssrc` to `media_ssrc`,
+	/// reporting every sequence number in `lost`.
+	///
+	/// Lost packets are packed into as few FCI entries as possible (see
+	/// [`GenericNack::pack`]), and [`pkt_length`] is filled in.
+	///
+	/// [`GenericNack::pack`]: nack/struct.GenericNack.html#method.pack
+	/// [`pkt_length`]: #structfield.pkt_length
+	#[must_use]
+	pub fn nack(sender_ssrc: u32, media_ssrc: u32, lost: impl IntoIterator<Item = Wrap16>) -> Self {
+		let entries = GenericNack::pack(lost);
+		let mut payload = vec![0u8; entries.len() * NACK_LEN];
+		for (entry, space) in entries.iter().zip(payload.chunks_mut(NACK_LEN)) {
+			if let Some(mut pkt) = MutableGenericNackPacket::new(space) {
+				pkt.populate(entry);
+			}
+		}
+
+		Self {
+			version: 2,
+			padding: 0,
+			fmt: TransportFeedbackType::Nack,
+			packet_type: RtcpType::TransportFeedback,
+			pkt_length: rtcp::length_words(
+				TransportFeedbackPacket::minimum_packet_size() + payload.len(),
+			),
+			sender_ssrc,
+			media_ssrc,
+			payload,
+		}
+	}
+}
+
+impl TransportFeedbackPacket<'_> {
+	/// Iterates over each [`GenericNack`] entry in the FCI.
+	///
+	/// This assumes that the packet's `fmt` is [`TransportFeedbackType::Nack`].
+	///
+	/// [`GenericNack`]: nack/struct.GenericNack.html
+	/// [`TransportFeedbackType::Nack`]: enum.TransportFeedbackType.html#variant.Nack
+	#[must_use]
+	pub fn nacks(&self) -> GenericNacks<'_> {
+		GenericNacks::new(self.payload())
+	}
+
+	/// Iterates over every sequence number reported lost across all
+	/// [`GenericNack`] entries in the FCI.
+	///
+	/// [`GenericNack`]: nack/struct.GenericNack.html
+	pub fn lost_sequences(&self) -> impl Iterator<Item = Wrap16> + '_ {
+		self.nacks().flat_map(|n| n.lost())
+	}
+}
+
+impl MutableTransportFeedbackPacket<'_> {
+	/// Iterates over each [`GenericNack`] entry in the FCI.
+	///
+	/// This assumes that the packet's `fmt` is [`TransportFeedbackType::Nack`].
+	///
+	/// [`GenericNack`]: nack/struct.GenericNack.html
+	/// [`TransportFeedbackType::Nack`]: enum.TransportFeedbackType.html#variant.Nack
+	#[must_use]
+	pub fn nacks(&self) -> GenericNacks<'_> {
+		GenericNacks::new(self.payload())
+	}
+}
+
+/// Iterator over the [`GenericNack`] entries in an FCI.
+///
+/// [`GenericNack`]: struct.GenericNack.html
+#[derive(Clone, Debug)]
+pub struct GenericNacks<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> GenericNacks<'a> {
+	/// Iterates over each complete 4-byte entry in `fci`.
+	#[must_use]
+	pub fn new(fci: &'a [u8]) -> Self {
+		Self { data: fci }
+	}
+}
+
+impl<'a> Iterator for GenericNacks<'a> {
+	type Item = GenericNackPacket<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.data.len() < NACK_LEN {
+			return None;
+		}
+
+		let (entry, rest) = self.data.split_at(NACK_LEN);
+		self.data = rest;
+
+		GenericNackPacket::new(entry)
+	}
+}
+
+/// Iterator over the sequence numbers described by a PID and BLP pair.
+#[derive(Clone, Debug)]
+pub struct LostSequences {
+	pid: Wrap16,
+	mask: u32,
+}
+
+impl LostSequences {
+	/// Expands a PID and its bitmask of following losses.
+	#[must_use]
+	pub fn new(pid: Wrap16, blp: u16) -> Self {
+		Self {
+			pid,
+			mask: 1 | (u32::from(blp) << 1),
+		}
+	}
+}
+
+impl Iterator for LostSequences {
+	type Item = Wrap16;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.mask == 0 {
+			return None;
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		let offset = self.mask.trailing_zeros() as u16;
+		self.mask &= self.mask - 1;
+
+		Some(self.pid + offset)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let n = self.mask.count_ones() as usize;
+		(n, Some(n))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::Rtcp;
+
+	#[test]
+	fn nack_round_trip_across_wraparound() {
+		let lost = [20u16, 3, 65535, 5, 65534, 3].map(Wrap16::from);
+		let nack = TransportFeedback::nack(1, 2, lost);
+		let mut buf = [0u8; 64];
+		let len = Rtcp::TransportFeedback(nack).write(&mut buf).unwrap();
+		assert_eq!(len, 20);
+
+		let pkt = TransportFeedbackPacket::new_checked(&buf[..len]).unwrap();
+		assert_eq!(pkt.get_fmt(), TransportFeedbackType::Nack);
+		assert_eq!(pkt.get_sender_ssrc(), 1);
+		assert_eq!(pkt.get_media_ssrc(), 2);
+		assert_eq!(usize::from(pkt.get_pkt_length()), len / 4 - 1);
+		assert_eq!(pkt.nacks().count(), 2);
+
+		let seqs: Vec<u16> = pkt.lost_sequences().map(u16::from).collect();
+		assert_eq!(seqs, [65534, 65535, 3, 5, 20]);
+	}
+}
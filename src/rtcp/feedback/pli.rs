@@ -0,0 +1,79 @@
+use super::{
+	MutablePayloadFeedbackPacket,
+	PayloadFeedback,
+	PayloadFeedbackPacket,
+	PayloadFeedbackType,
+};
+use crate::rtcp::{self, RtcpType};
+use alloc::vec::Vec;
+
+impl PayloadFeedback {
+	/// Creates a Picture Loss Indication from `sender_ssrc`, asking `media_ssrc`
+	/// to send a decoder refresh point.
+	///
+	/// PLI carries no FCI, and so is exactly 12 bytes long.
+	///
+	/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.3.1).
+	#[must_use]
+	pub fn pli(sender_ssrc: u32, media_ssrc: u32) -> Self {
+		Self {
+			version: 2,
+			padding: 0,
+			fmt: PayloadFeedbackType::PictureLossIndication,
+			packet_type: RtcpType::PayloadFeedback,
+			pkt_length: rtcp::length_words(PayloadFeedbackPacket::minimum_packet_size()),
+			sender_ssrc,
+			media_ssrc,
+			payload: Vec::new(),
+		}
+	}
+}
+
+impl PayloadFeedbackPacket<'_> {
+	/// Returns `true` if this is a Picture Loss Indication.
+	#[must_use]
+	pub fn is_pli(&self) -> bool {
+		self.get_fmt() == PayloadFeedbackType::PictureLossIndication
+	}
+}
+
+impl MutablePayloadFeedbackPacket<'_> {
+	/// Returns `true` if this is a Picture Loss Indication.
+	#[must_use]
+	pub fn is_pli(&self) -> bool {
+		self.get_fmt() == PayloadFeedbackType::PictureLossIndication
+	}
+
+	/// Rewrites this packet's header as a Picture Loss Indication from `sender_ssrc`
+	/// to `media_ssrc`, returning the packet length (12 bytes).
+	pub fn make_pli(&mut self, sender_ssrc: u32, media_ssrc: u32) -> usize {
+		self.populate(&PayloadFeedback::pli(sender_ssrc, media_ssrc));
+		PayloadFeedbackPacket::minimum_packet_size()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::Rtcp;
+
+	#[test]
+	fn pli_round_trip() {
+		let mut buf = [0u8; 32];
+		let len = Rtcp::PayloadFeedback(PayloadFeedback::pli(1, 2))
+			.write(&mut buf)
+			.unwrap();
+		assert_eq!(len, 12);
+
+		let pkt = PayloadFeedbackPacket::new_checked(&buf[..len]).unwrap();
+		assert!(pkt.is_pli());
+		assert_eq!(pkt.get_sender_ssrc(), 1);
+		assert_eq!(pkt.get_media_ssrc(), 2);
+		assert_eq!(pkt.get_pkt_length(), 2);
+
+		let mut rewrite = [0xffu8; 12];
+		let mut pkt = MutablePayloadFeedbackPacket::new(&mut rewrite).unwrap();
+		assert_eq!(pkt.make_pli(1, 2), 12);
+		assert_eq!(rewrite, buf[..len]);
+	}
+}
@@ -0,0 +1,174 @@
+use super::{
+	MutablePayloadFeedbackPacket,
+	PayloadFeedback,
+	PayloadFeedbackPacket,
+	PayloadFeedbackType,
+};
+use crate::rtcp::{self, RtcpType};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u7},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Reference Picture Selection Indication, carried as the FCI of a [`PayloadFeedback`]
+/// packet whose `fmt` is [`PayloadFeedbackType::ReferencePictureSelectionIndication`].
+///
+/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.3.3).
+///
+/// A description of fields:
+///
+/// ## `padding_bits`
+/// Number of unused bits at the end of the FCI, covering both the tail of the
+/// native bit string and any padding to a 32-bit boundary.
+///
+/// ## `zero`
+/// Must be set to `0`.
+///
+/// ## `payload_type`
+/// RTP payload type for which the native RPSI bit string must be interpreted.
+///
+/// ## `payload`
+/// Native RPSI bit string, followed by padding.
+/// Use [`native_bits`] to remove padding.
+///
+/// [`PayloadFeedback`]: ../struct.PayloadFeedback.html
+/// [`PayloadFeedbackType::ReferencePictureSelectionIndication`]: ../enum.PayloadFeedbackType.html#variant.ReferencePictureSelectionIndication
+/// [`native_bits`]: struct.RpsiPacket.html#method.native_bits
+pub struct Rpsi {
+	pub padding_bits: u8,
+
+	pub zero: u1,
+
+	pub payload_type: u7,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl RpsiPacket<'_> {
+	/// Returns the length of the native RPSI bit string, in bits.
+	#[must_use]
+	pub fn native_bit_len(&self) -> usize {
+		(self.payload().len() * 8).saturating_sub(self.get_padding_bits().into())
+	}
+
+	/// Returns the bytes holding the native RPSI bit string.
+	///
+	/// Any bits beyond [`native_bit_len`] in the final byte are padding.
+	///
+	/// [`native_bit_len`]: #method.native_bit_len
+	#[must_use]
+	pub fn native_bits(&self) -> &[u8] {
+		let len = (self.native_bit_len() + 7) / 8;
+		&self.payload()[..len]
+	}
+}
+
+impl PayloadFeedback {
+	/// Creates a Reference Picture Selection Indication from `sender_ssrc` to
+	/// `media_ssrc`.
+	///
+	/// The FCI holds the first `bit_len` bits of `bits` (clamped to its length),
+	/// to be interpreted according to `payload_type`. Padding bits are zeroed, and
+	/// the FCI is padded to a 32-bit boundary.
+	#[must_use]
+	pub fn rpsi(
+		sender_ssrc: u32,
+		media_ssrc: u32,
+		payload_type: u7,
+		bits: &[u8],
+		bit_len: usize,
+	) -> Self {
+		let bit_len = bit_len.min(bits.len() * 8);
+		let byte_len = (bit_len + 7) / 8;
+		let fci_len = (RpsiPacket::minimum_packet_size() + byte_len + 3) & !3;
+
+		let mut payload = Vec::with_capacity(fci_len);
+		// At most 31 bits: 7 in the final byte of the bit string, and 3 padding bytes.
+		#[allow(clippy::cast_possible_truncation)]
+		payload.push(((fci_len - RpsiPacket::minimum_packet_size()) * 8 - bit_len) as u8);
+		payload.push(payload_type & 0x7f);
+		payload.extend_from_slice(&bits[..byte_len]);
+
+		if bit_len % 8 != 0 {
+			if let Some(last) = payload.last_mut() {
+				*last &= 0xff << (8 - bit_len % 8);
+			}
+		}
+
+		payload.resize(fci_len, 0);
+
+		Self {
+			version: 2,
+			padding: 0,
+			fmt: PayloadFeedbackType::ReferencePictureSelectionIndication,
+			packet_type: RtcpType::PayloadFeedback,
+			pkt_length: rtcp::length_words(
+				PayloadFeedbackPacket::minimum_packet_size() + payload.len(),
+			),
+			sender_ssrc,
+			media_ssrc,
+			payload,
+		}
+	}
+}
+
+impl PayloadFeedbackPacket<'_> {
+	/// Returns a view over the FCI as a Reference Picture Selection Indication.
+	///
+	/// This returns `None` if the packet's `fmt` is not
+	/// [`PayloadFeedbackType::ReferencePictureSelectionIndication`], or if the FCI is too short.
+	///
+	/// [`PayloadFeedbackType::ReferencePictureSelectionIndication`]: enum.PayloadFeedbackType.html#variant.ReferencePictureSelectionIndication
+	#[must_use]
+	pub fn rpsi(&self) -> Option<RpsiPacket<'_>> {
+		if self.get_fmt() == PayloadFeedbackType::ReferencePictureSelectionIndication {
+			RpsiPacket::new(self.payload())
+		} else {
+			None
+		}
+	}
+}
+
+impl MutablePayloadFeedbackPacket<'_> {
+	/// Returns a view over the FCI as a Reference Picture Selection Indication.
+	///
+	/// This returns `None` if the packet's `fmt` is not
+	/// [`PayloadFeedbackType::ReferencePictureSelectionIndication`], or if the FCI is too short.
+	///
+	/// [`PayloadFeedbackType::ReferencePictureSelectionIndication`]: enum.PayloadFeedbackType.html#variant.ReferencePictureSelectionIndication
+	#[must_use]
+	pub fn rpsi(&self) -> Option<RpsiPacket<'_>> {
+		if self.get_fmt() == PayloadFeedbackType::ReferencePictureSelectionIndication {
+			RpsiPacket::new(self.payload())
+		} else {
+			None
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::Rtcp;
+
+	#[test]
+	fn rpsi_round_trip() {
+		let rpsi = PayloadFeedback::rpsi(1, 2, 96, &[0xab, 0xcd, 0xff], 20);
+		let mut buf = [0u8; 64];
+		let len = Rtcp::PayloadFeedback(rpsi).write(&mut buf).unwrap();
+		assert_eq!(len, 20);
+
+		let pkt = PayloadFeedbackPacket::new_checked(&buf[..len]).unwrap();
+		assert_eq!(usize::from(pkt.get_pkt_length()), len / 4 - 1);
+
+		let rpsi = pkt.rpsi().unwrap();
+		assert_eq!(rpsi.get_payload_type(), 96);
+		assert_eq!(rpsi.native_bit_len(), 20);
+		assert_eq!(rpsi.native_bits(), [0xab, 0xcd, 0xf0]);
+	}
+}
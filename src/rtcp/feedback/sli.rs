@@ -0,0 +1,175 @@
+use super::{
+	MutablePayloadFeedbackPacket,
+	PayloadFeedback,
+	PayloadFeedbackPacket,
+	PayloadFeedbackType,
+};
+use crate::rtcp::{self, RtcpType};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u13be, u6},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Slice Loss Indication entry, carried as the FCI of a [`PayloadFeedback`] packet
+/// whose `fmt` is [`PayloadFeedbackType::SliceLossIndication`].
+///
+/// See [RFC 4585](https://tools.ietf.org/html/rfc4585#section-6.3.2).
+///
+/// A description of fields:
+///
+/// ## `first`
+/// Macroblock address of the first lost macroblock.
+///
+/// ## `number`
+/// Number of lost macroblocks, in scan order.
+///
+/// ## `picture_id`
+/// The six least significant bits of the codec-specific identifier of the
+/// picture in which the loss occurred.
+///
+/// ## `payload`
+/// Remainder of the FCI.
+///
+/// [`PayloadFeedback`]: ../struct.PayloadFeedback.html
+/// [`PayloadFeedbackType::SliceLossIndication`]: ../enum.PayloadFeedbackType.html#variant.SliceLossIndication
+pub struct SliEntry {
+	pub first: u13be,
+
+	pub number: u13be,
+
+	pub picture_id: u6,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+const SLI_LEN: usize = SliEntryPacket::minimum_packet_size();
+
+impl PayloadFeedback {
+	/// Creates an empty Slice Loss Indication from `sender_ssrc` about `media_ssrc`.
+	///
+	/// Lost slices are added using [`push_sli`].
+	///
+	/// [`push_sli`]: #method.push_sli
+	#[must_use]
+	pub fn sli(sender_ssrc: u32, media_ssrc: u32) -> Self {
+		Self {
+			version: 2,
+			padding: 0,
+			fmt: PayloadFeedbackType::SliceLossIndication,
+			packet_type: RtcpType::PayloadFeedback,
+			pkt_length: rtcp::length_words(PayloadFeedbackPacket::minimum_packet_size()),
+			sender_ssrc,
+			media_ssrc,
+			payload: Vec::new(),
+		}
+	}
+
+	/// Appends an entry reporting `number` macroblocks lost from `first` in
+	/// picture `picture_id`, updating [`pkt_length`].
+	///
+	/// `first` and `number` are truncated to 13 bits, and `picture_id` to 6 bits.
+	///
+	/// [`pkt_length`]: #structfield.pkt_length
+	pub fn push_sli(&mut self, first: u13be, number: u13be, picture_id: u6) -> &mut Self {
+		let mut entry = [0u8; SLI_LEN];
+		if let Some(mut pkt) = MutableSliEntryPacket::new(&mut entry[..]) {
+			pkt.set_first(first & 0x1fff);
+			pkt.set_number(number & 0x1fff);
+			pkt.set_picture_id(picture_id & 0x3f);
+		}
+
+		self.payload.extend_from_slice(&entry);
+		self.pkt_length =
+			rtcp::length_words(PayloadFeedbackPacket::minimum_packet_size() + self.payload.len());
+
+		self
+	}
+}
+
+impl PayloadFeedbackPacket<'_> {
+	/// Iterates over each [`SliEntry`] in the FCI.
+	///
+	/// This assumes that the packet's `fmt` is [`PayloadFeedbackType::SliceLossIndication`].
+	///
+	/// [`SliEntry`]: sli/struct.SliEntry.html
+	/// [`PayloadFeedbackType::SliceLossIndication`]: enum.PayloadFeedbackType.html#variant.SliceLossIndication
+	#[must_use]
+	pub fn sli_entries(&self) -> SliEntries<'_> {
+		SliEntries::new(self.payload())
+	}
+}
+
+impl MutablePayloadFeedbackPacket<'_> {
+	/// Iterates over each [`SliEntry`] in the FCI.
+	///
+	/// This assumes that the packet's `fmt` is [`PayloadFeedbackType::SliceLossIndication`].
+	///
+	/// [`SliEntry`]: sli/struct.SliEntry.html
+	/// [`PayloadFeedbackType::SliceLossIndication`]: enum.PayloadFeedbackType.html#variant.SliceLossIndication
+	#[must_use]
+	pub fn sli_entries(&self) -> SliEntries<'_> {
+		SliEntries::new(self.payload())
+	}
+}
+
+/// Iterator over the [`SliEntry`]s in an FCI.
+///
+/// [`SliEntry`]: struct.SliEntry.html
+#[derive(Clone, Debug)]
+pub struct SliEntries<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> SliEntries<'a> {
+	/// Iterates over each complete 4-byte entry in `fci`.
+	#[must_use]
+	pub fn new(fci: &'a [u8]) -> Self {
+		Self { data: fci }
+	}
+}
+
+impl<'a> Iterator for SliEntries<'a> {
+	type Item = SliEntryPacket<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.data.len() < SLI_LEN {
+			return None;
+		}
+
+		let (entry, rest) = self.data.split_at(SLI_LEN);
+		self.data = rest;
+
+		SliEntryPacket::new(entry)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::Rtcp;
+
+	#[test]
+	fn sli_round_trip() {
+		let mut sli = PayloadFeedback::sli(1, 2);
+		sli.push_sli(0x1abc, 0x0123, 0x2a)
+			.push_sli(0xffff, 0xffff, 0xff);
+		let mut buf = [0u8; 64];
+		let len = Rtcp::PayloadFeedback(sli).write(&mut buf).unwrap();
+		assert_eq!(len, 20);
+
+		let pkt = PayloadFeedbackPacket::new_checked(&buf[..len]).unwrap();
+		assert_eq!(pkt.get_fmt(), PayloadFeedbackType::SliceLossIndication);
+		assert_eq!(usize::from(pkt.get_pkt_length()), len / 4 - 1);
+
+		let entries: Vec<_> = pkt
+			.sli_entries()
+			.map(|e| (e.get_first(), e.get_number(), e.get_picture_id()))
+			.collect();
+		assert_eq!(entries, [(0x1abc, 0x0123, 0x2a), (0x1fff, 0x1fff, 0x3f)]);
+	}
+}
@@ -0,0 +1,558 @@
+use super::{
+	MutableTransportFeedbackPacket,
+	TransportFeedback,
+	TransportFeedbackPacket,
+	TransportFeedbackType,
+};
+use crate::{
+	rtcp::{self, RtcpType},
+	wrap::Wrap16,
+};
+use alloc::{vec, vec::Vec};
+use core::time::Duration;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u16be, u24be},
+};
+
+/// Duration of one receive delta tick, in microseconds.
+pub const DELTA_TICK_US: i32 = 250;
+
+/// Duration of one reference time tick, in microseconds.
+pub const REFERENCE_TICK_US: i64 = 64_000;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Transport-wide congestion control feedback, carried as the FCI of a
+/// [`TransportFeedback`] packet whose `fmt` is [`TransportFeedbackType::TransportWideCc`].
+///
+/// See [draft-holmer-rmcat-transport-wide-cc-extensions](https://tools.ietf.org/html/draft-holmer-rmcat-transport-wide-cc-extensions-01#section-3.1).
+///
+/// A description of fields:
+///
+/// ## `base_sequence`
+/// Transport-wide sequence number of the first packet described by this message.
+///
+/// ## `status_count`
+/// Number of packets whose status is described, starting at `base_sequence`.
+///
+/// ## `reference_time`
+/// Signed 24-bit absolute time in multiples of 64ms, which the first receive delta
+/// is relative to. Use [`reference_time_us`] to sign-extend this value.
+///
+/// ## `feedback_count`
+/// Counter incremented for each feedback message sent, to detect feedback loss.
+///
+/// ## `payload`
+/// Packet status chunks, followed by receive deltas and padding.
+/// Use [`arrivals`] to decode these.
+///
+/// [`TransportFeedback`]: ../struct.TransportFeedback.html
+/// [`TransportFeedbackType::TransportWideCc`]: ../enum.TransportFeedbackType.html#variant.TransportWideCc
+/// [`reference_time_us`]: struct.TransportCcPacket.html#method.reference_time_us
+/// [`arrivals`]: struct.TransportCcPacket.html#method.arrivals
+pub struct TransportCc {
+	#[construct_with(u16be)]
+	pub base_sequence: Wrap16,
+
+	pub status_count: u16be,
+
+	pub reference_time: u24be,
+
+	pub feedback_count: u8,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+/// Reception status of a single packet in a TWCC feedback message.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PacketStatus {
+	/// Packet was not received.
+	NotReceived,
+	/// Packet was received, with a one-byte unsigned receive delta.
+	SmallDelta,
+	/// Packet was received, with a two-byte signed receive delta.
+	LargeOrNegativeDelta,
+	/// Reserved symbol.
+	Reserved,
+}
+
+impl PacketStatus {
+	/// Converts a 2-bit status symbol.
+	#[must_use]
+	pub fn new(symbol: u8) -> Self {
+		match symbol & 0b11 {
+			0 => Self::NotReceived,
+			1 => Self::SmallDelta,
+			2 => Self::LargeOrNegativeDelta,
+			_ => Self::Reserved,
+		}
+	}
+
+	/// Returns the 2-bit symbol used to encode this status.
+	#[must_use]
+	pub fn symbol(self) -> u8 {
+		match self {
+			Self::NotReceived => 0,
+			Self::SmallDelta => 1,
+			Self::LargeOrNegativeDelta => 2,
+			Self::Reserved => 3,
+		}
+	}
+
+	/// Returns the size, in bytes, of the receive delta accompanying this status.
+	#[must_use]
+	pub fn delta_size(self) -> usize {
+		match self {
+			Self::SmallDelta => 1,
+			Self::LargeOrNegativeDelta => 2,
+			_ => 0,
+		}
+	}
+}
+
+/// Returns the number of packet statuses described by a status chunk.
+fn chunk_symbols(chunk: u16) -> u16 {
+	if chunk & 0x8000 == 0 {
+		chunk & 0x1fff
+	} else if chunk & 0x4000 == 0 {
+		14
+	} else {
+		7
+	}
+}
+
+/// Returns the packet status at `index` within a status chunk.
+fn chunk_status(chunk: u16, index: u16) -> PacketStatus {
+	#[allow(clippy::cast_possible_truncation)]
+	let symbol = if chunk & 0x8000 == 0 {
+		(chunk >> 13) as u8
+	} else if chunk & 0x4000 == 0 {
+		((chunk >> (13 - index)) & 0b1) as u8
+	} else {
+		((chunk >> (12 - 2 * index)) & 0b11) as u8
+	};
+
+	PacketStatus::new(symbol)
+}
+
+/// Appends the shortest status chunk encoding of `statuses` to `out`.
+fn encode_chunks(statuses: &[PacketStatus], out: &mut Vec<u8>) {
+	let mut rest = statuses;
+	while let Some(&first) = rest.first() {
+		let run = rest
+			.iter()
+			.take(0x1fff)
+			.take_while(|&&s| s == first)
+			.count();
+		let one_bit = rest
+			.iter()
+			.take(14)
+			.all(|s| matches!(s, PacketStatus::NotReceived | PacketStatus::SmallDelta));
+
+		let (chunk, used) = if run >= 14 || run == rest.len() || (run >= 7 && !one_bit) {
+			let len = u16::try_from(run).unwrap_or(0x1fff);
+			((u16::from(first.symbol()) << 13) | len, run)
+		} else if one_bit {
+			let used = rest.len().min(14);
+			let chunk = rest[..used].iter().enumerate().fold(0x8000, |acc, (i, s)| {
+				acc | (u16::from(s.symbol()) << (13 - i))
+			});
+			(chunk, used)
+		} else {
+			let used = rest.len().min(7);
+			let chunk = rest[..used].iter().enumerate().fold(0xc000, |acc, (i, s)| {
+				acc | (u16::from(s.symbol()) << (12 - 2 * i))
+			});
+			(chunk, used)
+		};
+
+		out.extend_from_slice(&chunk.to_be_bytes());
+		rest = &rest[used..];
+	}
+}
+
+/// Feedback message under construction by [`TransportCc::from_arrivals`].
+struct PendingCc {
+	base_sequence: u16,
+	reference: i64,
+	last_ticks: i64,
+	statuses: Vec<PacketStatus>,
+	deltas: Vec<u8>,
+}
+
+impl PendingCc {
+	fn new(base_sequence: u16, reference_us: i64) -> Self {
+		let reference = reference_us.div_euclid(REFERENCE_TICK_US);
+
+		Self {
+			base_sequence,
+			reference,
+			last_ticks: reference * (REFERENCE_TICK_US / i64::from(DELTA_TICK_US)),
+			statuses: Vec::new(),
+			deltas: Vec::new(),
+		}
+	}
+
+	/// Returns the number of lost packets between the last status and `seq`.
+	fn gap_to(&self, seq: u16) -> usize {
+		let next = u16::try_from(self.statuses.len()).unwrap_or(u16::MAX);
+		usize::from(seq.wrapping_sub(self.base_sequence).wrapping_sub(next))
+	}
+
+	fn finish(self, feedback_count: u8) -> TransportCc {
+		let mut payload = Vec::new();
+		encode_chunks(&self.statuses, &mut payload);
+		payload.extend_from_slice(&self.deltas);
+
+		let padded = (TransportCcPacket::minimum_packet_size() + payload.len() + 3) & !3;
+		payload.resize(padded - TransportCcPacket::minimum_packet_size(), 0);
+
+		TransportCc {
+			base_sequence: self.base_sequence.into(),
+			status_count: u16::try_from(self.statuses.len()).unwrap_or(u16::MAX),
+			reference_time: u32::try_from(self.reference.rem_euclid(1 << 24)).unwrap_or(0),
+			feedback_count,
+			payload,
+		}
+	}
+}
+
+impl TransportCc {
+	/// Builds feedback messages describing a set of received packets, given as
+	/// transport sequence numbers and arrival times.
+	///
+	/// Arrival times and `reference` share an arbitrary epoch. The first message's
+	/// reference time is `reference` rounded down to a multiple of 64ms. Packets
+	/// missing between the oldest and newest sequence numbers are marked as lost.
+	///
+	/// Samples may be given in any order, and duplicates are ignored. They are
+	/// ordered according to the largest gap between sequence numbers, so that
+	/// sets which straddle the 16-bit wraparound are handled correctly. Receive
+	/// deltas follow this order, and so may be negative if packets were reordered.
+	///
+	/// If a receive delta cannot be represented in a signed 16-bit field, a new
+	/// message is started at that packet with its own reference time, and the
+	/// feedback count (starting at `feedback_count`) is incremented.
+	pub fn from_arrivals(
+		reference: Duration,
+		feedback_count: u8,
+		samples: impl IntoIterator<Item = (Wrap16, Duration)>,
+	) -> Vec<TransportCc> {
+		let to_us = |t: Duration| i64::try_from(t.as_micros()).unwrap_or(i64::MAX);
+
+		let mut samples: Vec<(u16, i64)> = samples
+			.into_iter()
+			.map(|(seq, t)| (u16::from(seq), to_us(t)))
+			.collect();
+		samples.sort_by_key(|&(seq, _)| seq);
+		samples.dedup_by_key(|&mut (seq, _)| seq);
+
+		if samples.is_empty() {
+			return Vec::new();
+		}
+
+		super::rotate_to_oldest(&mut samples, |&(seq, _)| seq);
+
+		let mut out = Vec::new();
+		let mut feedback_count = feedback_count;
+		let mut pending = PendingCc::new(samples[0].0, to_us(reference));
+
+		for (seq, arrival_us) in samples {
+			let ticks = arrival_us.div_euclid(i64::from(DELTA_TICK_US));
+			let gap = pending.gap_to(seq);
+
+			let fits = i16::try_from(ticks - pending.last_ticks).is_ok()
+				&& pending.statuses.len() + gap < usize::from(u16::MAX);
+
+			if !fits {
+				let next = PendingCc::new(seq, arrival_us);
+				if pending.statuses.is_empty() {
+					pending = next;
+				} else {
+					out.push(core::mem::replace(&mut pending, next).finish(feedback_count));
+					feedback_count = feedback_count.wrapping_add(1);
+				}
+			}
+
+			let gap = pending.gap_to(seq);
+			pending
+				.statuses
+				.extend(core::iter::repeat(PacketStatus::NotReceived).take(gap));
+
+			let delta = ticks - pending.last_ticks;
+			pending.last_ticks = ticks;
+
+			if let Ok(small) = u8::try_from(delta) {
+				pending.statuses.push(PacketStatus::SmallDelta);
+				pending.deltas.push(small);
+			} else if let Ok(large) = i16::try_from(delta) {
+				pending.statuses.push(PacketStatus::LargeOrNegativeDelta);
+				pending.deltas.extend_from_slice(&large.to_be_bytes());
+			}
+		}
+
+		out.push(pending.finish(feedback_count));
+
+		out
+	}
+
+	/// Builds feedback messages describing a set of `received` transport sequence
+	/// numbers, measuring their arrival times and `reference` from `epoch`.
+	///
+	/// See [`from_arrivals`] for more information.
+	///
+	/// *This is included when using the `"std"` feature.*
+	///
+	/// [`from_arrivals`]: #method.from_arrivals
+	#[cfg(feature = "std")]
+	pub fn from_received(
+		epoch: std::time::Instant,
+		reference: std::time::Instant,
+		feedback_count: u8,
+		received: impl IntoIterator<Item = crate::received::Received<Wrap16>>,
+	) -> Vec<TransportCc> {
+		Self::from_arrivals(
+			reference.saturating_duration_since(epoch),
+			feedback_count,
+			received.into_iter().map(|r| (r.packet, r.since(epoch))),
+		)
+	}
+}
+
+impl TransportFeedback {
+	/// Creates a transport-wide congestion control feedback message from
+	/// `sender_ssrc` to `media_ssrc`, carrying `cc` as its FCI.
+	///
+	/// Messages can be built from packet arrival times using [`TransportCc::from_arrivals`].
+	///
+	/// [`TransportCc::from_arrivals`]: twcc/struct.TransportCc.html#method.from_arrivals
+	#[must_use]
+	pub fn twcc(sender_ssrc: u32, media_ssrc: u32, cc: &TransportCc) -> Self {
+		let mut payload = vec![0u8; TransportCcPacket::packet_size(cc)];
+		if let Some(mut pkt) = MutableTransportCcPacket::new(&mut payload[..]) {
+			pkt.populate(cc);
+		}
+
+		Self {
+			version: 2,
+			padding: 0,
+			fmt: TransportFeedbackType::TransportWideCc,
+			packet_type: RtcpType::TransportFeedback,
+			pkt_length: rtcp::length_words(
+				TransportFeedbackPacket::minimum_packet_size() + payload.len(),
+			),
+			sender_ssrc,
+			media_ssrc,
+			payload,
+		}
+	}
+}
+
+impl TransportCcPacket<'_> {
+	/// Returns the sign-extended reference time, in microseconds.
+	#[must_use]
+	pub fn reference_time_us(&self) -> i64 {
+		let raw = self.get_reference_time();
+		let signed = i32::from_be_bytes((raw << 8).to_be_bytes()) >> 8;
+
+		i64::from(signed) * REFERENCE_TICK_US
+	}
+
+	/// Iterates over the transport sequence number and receive delta of each
+	/// packet described by this message.
+	#[must_use]
+	pub fn arrivals(&self) -> TwccArrivals<'_> {
+		TwccArrivals::new(
+			self.get_base_sequence(),
+			self.get_status_count(),
+			self.payload(),
+		)
+	}
+}
+
+impl TransportFeedbackPacket<'_> {
+	/// Returns a view over the FCI as transport-wide congestion control feedback.
+	///
+	/// This returns `None` if the packet's `fmt` is not
+	/// [`TransportFeedbackType::TransportWideCc`], or if the FCI is too short.
+	///
+	/// [`TransportFeedbackType::TransportWideCc`]: enum.TransportFeedbackType.html#variant.TransportWideCc
+	#[must_use]
+	pub fn transport_cc(&self) -> Option<TransportCcPacket<'_>> {
+		if self.get_fmt() == TransportFeedbackType::TransportWideCc {
+			TransportCcPacket::new(self.payload())
+		} else {
+			None
+		}
+	}
+}
+
+impl MutableTransportFeedbackPacket<'_> {
+	/// Returns a view over the FCI as transport-wide congestion control feedback.
+	///
+	/// This returns `None` if the packet's `fmt` is not
+	/// [`TransportFeedbackType::TransportWideCc`], or if the FCI is too short.
+	///
+	/// [`TransportFeedbackType::TransportWideCc`]: enum.TransportFeedbackType.html#variant.TransportWideCc
+	#[must_use]
+	pub fn transport_cc(&self) -> Option<TransportCcPacket<'_>> {
+		if self.get_fmt() == TransportFeedbackType::TransportWideCc {
+			TransportCcPacket::new(self.payload())
+		} else {
+			None
+		}
+	}
+}
+
+/// Iterator over the packets described by a TWCC feedback message.
+///
+/// Each item is a transport sequence number, and its receive delta in microseconds
+/// if the packet was received. The first delta is relative to the reference time,
+/// while each later delta is relative to the previous received packet.
+///
+/// Iteration ends early if the status chunks or receive deltas are truncated.
+#[derive(Clone, Debug)]
+pub struct TwccArrivals<'a> {
+	chunks: &'a [u8],
+	deltas: &'a [u8],
+	chunk: u16,
+	index: u16,
+	symbols: u16,
+	sequence: Wrap16,
+	remaining: u16,
+}
+
+impl<'a> TwccArrivals<'a> {
+	/// Decodes `status_count` packet statuses from `body`, which holds the status
+	/// chunks and receive deltas following the TWCC header.
+	#[must_use]
+	pub fn new(base_sequence: Wrap16, status_count: u16, body: &'a [u8]) -> Self {
+		let mut described = 0u32;
+		let mut chunk_len = 0;
+		for chunk in body.chunks_exact(2) {
+			if described >= u32::from(status_count) {
+				break;
+			}
+
+			described += u32::from(chunk_symbols(u16::from_be_bytes([chunk[0], chunk[1]])));
+			chunk_len += 2;
+		}
+
+		let (chunks, deltas) = body.split_at(chunk_len);
+
+		Self {
+			chunks,
+			deltas,
+			chunk: 0,
+			index: 0,
+			symbols: 0,
+			sequence: base_sequence,
+			remaining: status_count,
+		}
+	}
+
+	fn next_status(&mut self) -> Option<PacketStatus> {
+		while self.index >= self.symbols {
+			if self.chunks.len() < 2 {
+				return None;
+			}
+
+			let (chunk, rest) = self.chunks.split_at(2);
+			self.chunks = rest;
+			self.chunk = u16::from_be_bytes([chunk[0], chunk[1]]);
+			self.symbols = chunk_symbols(self.chunk);
+			self.index = 0;
+		}
+
+		let status = chunk_status(self.chunk, self.index);
+		self.index += 1;
+
+		Some(status)
+	}
+}
+
+impl Iterator for TwccArrivals<'_> {
+	type Item = (Wrap16, Option<i32>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.remaining == 0 {
+			return None;
+		}
+
+		let status = self.next_status()?;
+		let size = status.delta_size();
+		if self.deltas.len() < size {
+			return None;
+		}
+
+		let (delta, rest) = self.deltas.split_at(size);
+		self.deltas = rest;
+
+		let delta = match *delta {
+			[small] => Some(i32::from(small) * DELTA_TICK_US),
+			[hi, lo] => Some(i32::from(i16::from_be_bytes([hi, lo])) * DELTA_TICK_US),
+			_ => None,
+		};
+
+		let sequence = self.sequence;
+		self.sequence += 1;
+		self.remaining -= 1;
+
+		Some((sequence, delta))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::Rtcp;
+
+	#[test]
+	fn twcc_round_trip_across_wraparound() {
+		let ms = Duration::from_millis;
+		let samples = [
+			(2, ms(135)),
+			(65535, ms(131)),
+			(1, ms(140)),
+			(65534, ms(130)),
+		];
+		let cc = TransportCc::from_arrivals(
+			ms(130),
+			3,
+			samples.into_iter().map(|(seq, t)| (Wrap16::from(seq), t)),
+		);
+		assert_eq!(cc.len(), 1);
+
+		let mut buf = [0u8; 64];
+		let len = Rtcp::TransportFeedback(TransportFeedback::twcc(1, 2, &cc[0]))
+			.write(&mut buf)
+			.unwrap();
+		assert_eq!(len % 4, 0);
+
+		let pkt = TransportFeedbackPacket::new_checked(&buf[..len]).unwrap();
+		assert_eq!(usize::from(pkt.get_pkt_length()), len / 4 - 1);
+
+		let twcc = pkt.transport_cc().unwrap();
+		assert_eq!(twcc.get_feedback_count(), 3);
+		assert_eq!(twcc.get_status_count(), 5);
+		assert_eq!(twcc.reference_time_us(), 128_000);
+
+		let arrivals: Vec<_> = twcc
+			.arrivals()
+			.map(|(seq, d)| (u16::from(seq), d))
+			.collect();
+		assert_eq!(
+			arrivals,
+			[
+				(65534, Some(2000)),
+				(65535, Some(1000)),
+				(0, None),
+				(1, Some(9000)),
+				(2, Some(-5000)),
+			]
+		);
+	}
+}
@@ -0,0 +1,125 @@
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, RtcpType},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::types::{u1, u16be, u2, u32be, u5};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Extended inter-arrival jitter report, giving jitter estimates computed over
+/// RTP transmission times.
+///
+/// The IJ report carries no SSRCs: each jitter value corresponds to the report
+/// block at the same index in the preceding SR or RR of the compound packet.
+///
+/// See [RFC 5450](https://tools.ietf.org/html/rfc5450#section-4).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `report_count`
+/// Number of jitter values contained. May be `0`.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::JitterReport`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `jitters`
+/// Inter-arrival jitter estimates, in RTP timestamp units.
+///
+/// ## `payload`
+/// Remainder of the packet. Should be empty, barring padding.
+///
+/// [`length`]: #structfield.length
+/// [`RtcpType::JitterReport`]: ../enum.RtcpType.html#variant.JitterReport
+pub struct JitterReport {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub report_count: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	#[length = "4 * report_count"]
+	pub jitters: Vec<u32be>,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	JitterReportPacket,
+	MutableJitterReportPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"report_count" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+	],
+	|pkt: &[u8]| {
+		rtcp::check_list(pkt, "jitters", 4, 4)?;
+		rtcp::check_length(pkt)
+	}
+);
+
+impl JitterReport {
+	/// Creates a jitter report for up to 31 `jitters`, filling in [`pkt_length`].
+	///
+	/// [`pkt_length`]: #structfield.pkt_length
+	#[must_use]
+	pub fn new(jitters: &[u32]) -> Self {
+		let jitters = &jitters[..jitters.len().min(31)];
+
+		#[allow(clippy::cast_possible_truncation)]
+		let mut out = Self {
+			version: 2,
+			padding: 0,
+			report_count: jitters.len() as u8,
+			packet_type: RtcpType::JitterReport,
+			pkt_length: 0,
+			jitters: jitters.to_vec(),
+			payload: Vec::new(),
+		};
+		out.pkt_length = rtcp::length_words(out.wire_size());
+
+		out
+	}
+
+	/// Number of bytes needed to serialise this packet.
+	///
+	/// Unlike [`JitterReportPacket::packet_size`], this accounts for each jitter value
+	/// being 4 bytes wide.
+	///
+	/// [`JitterReportPacket::packet_size`]: struct.JitterReportPacket.html#method.packet_size
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		JitterReportPacket::minimum_packet_size() + 4 * self.jitters.len() + self.payload.len()
+	}
+
+	/// Serialises this packet into the start of `buf`, returning the number of bytes written.
+	///
+	/// Returns `None` if `buf` is too small.
+	pub fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let len = self.wire_size();
+		MutableJitterReportPacket::new(buf.get_mut(..len)?)?.populate(self);
+
+		Some(len)
+	}
+}
@@ -2,8 +2,31 @@
 //!
 //! *These are included when using the `"rtcp"` feature.*
 
+pub mod app;
+pub mod avb;
+pub mod bye;
+pub mod compound;
+pub mod feedback;
+pub mod jitter;
 pub mod report;
-use crate::{FromPacket, MutablePacket, Packet, PacketSize};
+pub mod rsi;
+pub mod sdes;
+pub mod splicing;
+pub mod validate;
+pub mod xr;
+use crate::{error::ParseError, FromPacket, MutablePacket, Packet, PacketSize};
+use app::{ApplicationDefined, ApplicationDefinedPacket, MutableApplicationDefinedPacket};
+use avb::{Avb, AvbPacket, MutableAvbPacket};
+use bye::{Goodbye, GoodbyePacket, MutableGoodbyePacket};
+use feedback::{
+	MutablePayloadFeedbackPacket,
+	MutableTransportFeedbackPacket,
+	PayloadFeedback,
+	PayloadFeedbackPacket,
+	TransportFeedback,
+	TransportFeedbackPacket,
+};
+use jitter::{JitterReport, JitterReportPacket, MutableJitterReportPacket};
 use pnet_macros_support::packet::PrimitiveValues;
 use report::{
 	MutableReceiverReportPacket,
@@ -13,22 +36,115 @@ use report::{
 	SenderReport,
 	SenderReportPacket,
 };
+use rsi::{MutableReceiverSummaryPacket, ReceiverSummary, ReceiverSummaryPacket};
+use sdes::{MutableSourceDescriptionPacket, SourceDescription, SourceDescriptionPacket};
+use splicing::{
+	MutableSplicingNotificationPacket,
+	SplicingNotification,
+	SplicingNotificationPacket,
+};
+use xr::{ExtendedReport, ExtendedReportPacket, MutableExtendedReportPacket};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[non_exhaustive]
 pub enum Rtcp {
 	SenderReport(SenderReport),
 	ReceiverReport(ReceiverReport),
+	SplicingNotification(SplicingNotification),
+	ReceiverSummary(ReceiverSummary),
+	Avb(Avb),
+	JitterReport(JitterReport),
+	ExtendedReport(ExtendedReport),
+	PayloadFeedback(PayloadFeedback),
+	TransportFeedback(TransportFeedback),
+	ApplicationDefined(ApplicationDefined),
+	Goodbye(Goodbye),
+	SourceDescription(SourceDescription),
 
 	KnownType(RtcpType),
 }
 
+impl Rtcp {
+	/// Number of bytes needed to serialise this packet, rounded up to a 32-bit boundary.
+	///
+	/// [`KnownType`] packets carry no body, and so require `0` bytes.
+	///
+	/// [`KnownType`]: #variant.KnownType
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		let raw = match self {
+			Self::SenderReport(p) => SenderReportPacket::packet_size(p),
+			Self::ReceiverReport(p) => ReceiverReportPacket::packet_size(p),
+			Self::SourceDescription(p) => SourceDescriptionPacket::packet_size(p),
+			Self::Goodbye(p) => p.wire_size(),
+			Self::ApplicationDefined(p) => ApplicationDefinedPacket::packet_size(p),
+			Self::TransportFeedback(p) => TransportFeedbackPacket::packet_size(p),
+			Self::PayloadFeedback(p) => PayloadFeedbackPacket::packet_size(p),
+			Self::ExtendedReport(p) => ExtendedReportPacket::packet_size(p),
+			Self::JitterReport(p) => p.wire_size(),
+			Self::Avb(p) => AvbPacket::packet_size(p),
+			Self::ReceiverSummary(p) => ReceiverSummaryPacket::packet_size(p),
+			Self::SplicingNotification(p) => SplicingNotificationPacket::packet_size(p),
+			Self::KnownType(_) => 0,
+		};
+
+		(raw + 3) & !3
+	}
+
+	/// Serialises this packet into the start of `buf`, returning the number of bytes written.
+	///
+	/// Any bytes needed to reach a 32-bit boundary are zeroed, and `pkt_length` is set to
+	/// match the serialised size regardless of its value in `self`.
+	///
+	/// Returns `None` if `buf` is too small.
+	pub fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let len = self.wire_size();
+		let buf = buf.get_mut(..len)?;
+		buf.fill(0);
+
+		match self {
+			Self::SenderReport(p) => MutableSenderReportPacket::new(buf)?.populate(p),
+			Self::ReceiverReport(p) => MutableReceiverReportPacket::new(buf)?.populate(p),
+			Self::SourceDescription(p) => MutableSourceDescriptionPacket::new(buf)?.populate(p),
+			Self::Goodbye(p) => {
+				p.write(buf)?;
+			},
+			Self::ApplicationDefined(p) => MutableApplicationDefinedPacket::new(buf)?.populate(p),
+			Self::TransportFeedback(p) => MutableTransportFeedbackPacket::new(buf)?.populate(p),
+			Self::PayloadFeedback(p) => MutablePayloadFeedbackPacket::new(buf)?.populate(p),
+			Self::ExtendedReport(p) => MutableExtendedReportPacket::new(buf)?.populate(p),
+			Self::JitterReport(p) => {
+				p.write(buf)?;
+			},
+			Self::Avb(p) => MutableAvbPacket::new(buf)?.populate(p),
+			Self::ReceiverSummary(p) => MutableReceiverSummaryPacket::new(buf)?.populate(p),
+			Self::SplicingNotification(p) =>
+				MutableSplicingNotificationPacket::new(buf)?.populate(p),
+			Self::KnownType(_) => return Some(0),
+		}
+
+		buf[2..4].copy_from_slice(&length_words(len).to_be_bytes());
+
+		Some(len)
+	}
+}
+
 /// RTCP packet variants separated from the same stream.
 #[derive(Debug, PartialEq)]
 #[non_exhaustive]
 pub enum RtcpPacket<'a> {
 	SenderReport(SenderReportPacket<'a>),
 	ReceiverReport(ReceiverReportPacket<'a>),
+	SplicingNotification(SplicingNotificationPacket<'a>),
+	ReceiverSummary(ReceiverSummaryPacket<'a>),
+	Avb(AvbPacket<'a>),
+	JitterReport(JitterReportPacket<'a>),
+	ExtendedReport(ExtendedReportPacket<'a>),
+	PayloadFeedback(PayloadFeedbackPacket<'a>),
+	TransportFeedback(TransportFeedbackPacket<'a>),
+	ApplicationDefined(ApplicationDefinedPacket<'a>),
+	Goodbye(GoodbyePacket<'a>),
+	SourceDescription(SourceDescriptionPacket<'a>),
 
 	KnownType(RtcpType),
 }
@@ -38,13 +154,74 @@ impl RtcpPacket<'_> {
 	pub fn new(pkt: &[u8]) -> Option<RtcpPacket<'_>> {
 		RtcpType::from_packet(pkt).and_then(|rtcp_id| rtcp_id.decode(pkt))
 	}
+
+	/// Parses an RTCP packet, as with [`new`](#method.new).
+	///
+	/// # Errors
+	/// Returns a [`ParseError`] naming the first field (including any lists sized
+	/// by count fields) which does not fit in `pkt`.
+	///
+	/// [`ParseError`]: ../error/struct.ParseError.html
+	pub fn new_checked(pkt: &[u8]) -> Result<RtcpPacket<'_>, ParseError> {
+		ParseError::require(pkt, "packet_type", 2)?;
+
+		Ok(match RtcpType::new(pkt[1]) {
+			RtcpType::SenderReport =>
+				RtcpPacket::SenderReport(SenderReportPacket::new_checked(pkt)?),
+			RtcpType::ReceiverReport =>
+				RtcpPacket::ReceiverReport(ReceiverReportPacket::new_checked(pkt)?),
+			RtcpType::SplicingNotification =>
+				RtcpPacket::SplicingNotification(SplicingNotificationPacket::new_checked(pkt)?),
+			RtcpType::ReceiverSummary =>
+				RtcpPacket::ReceiverSummary(ReceiverSummaryPacket::new_checked(pkt)?),
+			RtcpType::Avb => RtcpPacket::Avb(AvbPacket::new_checked(pkt)?),
+			RtcpType::JitterReport =>
+				RtcpPacket::JitterReport(JitterReportPacket::new_checked(pkt)?),
+			RtcpType::ExtendedReport =>
+				RtcpPacket::ExtendedReport(ExtendedReportPacket::new_checked(pkt)?),
+			RtcpType::PayloadFeedback =>
+				RtcpPacket::PayloadFeedback(PayloadFeedbackPacket::new_checked(pkt)?),
+			RtcpType::TransportFeedback =>
+				RtcpPacket::TransportFeedback(TransportFeedbackPacket::new_checked(pkt)?),
+			RtcpType::ApplicationDefined =>
+				RtcpPacket::ApplicationDefined(ApplicationDefinedPacket::new_checked(pkt)?),
+			RtcpType::Goodbye => RtcpPacket::Goodbye(GoodbyePacket::new_checked(pkt)?),
+			RtcpType::SourceDescription =>
+				RtcpPacket::SourceDescription(SourceDescriptionPacket::new_checked(pkt)?),
+			a => RtcpPacket::KnownType(a),
+		})
+	}
+
+	/// Parses a single RTCP packet starting at `offset` bytes into `buf`.
+	///
+	/// The returned view is bounded by the packet's `pkt_length` field, which is
+	/// clamped to the bytes remaining in `buf`. The second element is the number of
+	/// bytes consumed, allowing a cursor into a larger receive buffer to be advanced.
+	///
+	/// Returns `None` if `offset` lies outside `buf`, or too few bytes remain.
+	#[must_use]
+	pub fn parse_at(buf: &[u8], offset: usize) -> Option<(RtcpPacket<'_>, usize)> {
+		let pkt = buf.get(offset..)?;
+		let len = packet_len(pkt)?;
+		Self::new(&pkt[..len]).map(|p| (p, len))
+	}
 }
 
-impl<'a> Packet for RtcpPacket<'a> {
+impl Packet for RtcpPacket<'_> {
 	fn packet(&self) -> &[u8] {
 		match self {
 			Self::SenderReport(s) => s.packet(),
 			Self::ReceiverReport(s) => s.packet(),
+			Self::SplicingNotification(s) => s.packet(),
+			Self::ReceiverSummary(s) => s.packet(),
+			Self::Avb(s) => s.packet(),
+			Self::JitterReport(s) => s.packet(),
+			Self::ExtendedReport(s) => s.packet(),
+			Self::PayloadFeedback(s) => s.packet(),
+			Self::TransportFeedback(s) => s.packet(),
+			Self::ApplicationDefined(s) => s.packet(),
+			Self::Goodbye(s) => s.packet(),
+			Self::SourceDescription(s) => s.packet(),
 			Self::KnownType(_) => &[],
 		}
 	}
@@ -53,28 +230,58 @@ impl<'a> Packet for RtcpPacket<'a> {
 		match self {
 			Self::SenderReport(s) => s.payload(),
 			Self::ReceiverReport(s) => s.payload(),
+			Self::SplicingNotification(s) => s.payload(),
+			Self::ReceiverSummary(s) => s.payload(),
+			Self::Avb(s) => s.payload(),
+			Self::JitterReport(s) => s.payload(),
+			Self::ExtendedReport(s) => s.payload(),
+			Self::PayloadFeedback(s) => s.payload(),
+			Self::TransportFeedback(s) => s.payload(),
+			Self::ApplicationDefined(s) => s.payload(),
+			Self::Goodbye(s) => s.payload(),
+			Self::SourceDescription(s) => s.payload(),
 			Self::KnownType(_) => &[],
 		}
 	}
 }
 
-impl<'a> FromPacket for RtcpPacket<'a> {
+impl FromPacket for RtcpPacket<'_> {
 	type T = Rtcp;
 
 	fn from_packet(&self) -> Self::T {
 		match self {
 			Self::SenderReport(s) => Rtcp::SenderReport(s.from_packet()),
 			Self::ReceiverReport(s) => Rtcp::ReceiverReport(s.from_packet()),
+			Self::SplicingNotification(s) => Rtcp::SplicingNotification(s.from_packet()),
+			Self::ReceiverSummary(s) => Rtcp::ReceiverSummary(s.from_packet()),
+			Self::Avb(s) => Rtcp::Avb(s.from_packet()),
+			Self::JitterReport(s) => Rtcp::JitterReport(s.from_packet()),
+			Self::ExtendedReport(s) => Rtcp::ExtendedReport(s.from_packet()),
+			Self::PayloadFeedback(s) => Rtcp::PayloadFeedback(s.from_packet()),
+			Self::TransportFeedback(s) => Rtcp::TransportFeedback(s.from_packet()),
+			Self::ApplicationDefined(s) => Rtcp::ApplicationDefined(s.from_packet()),
+			Self::Goodbye(s) => Rtcp::Goodbye(s.from_packet()),
+			Self::SourceDescription(s) => Rtcp::SourceDescription(s.from_packet()),
 			Self::KnownType(t) => Rtcp::KnownType(*t),
 		}
 	}
 }
 
-impl<'a> PacketSize for RtcpPacket<'a> {
+impl PacketSize for RtcpPacket<'_> {
 	fn packet_size(&self) -> usize {
 		match self {
 			Self::SenderReport(s) => s.packet_size(),
 			Self::ReceiverReport(s) => s.packet_size(),
+			Self::SplicingNotification(s) => s.packet_size(),
+			Self::ReceiverSummary(s) => s.packet_size(),
+			Self::Avb(s) => s.packet_size(),
+			Self::JitterReport(s) => s.packet_size(),
+			Self::ExtendedReport(s) => s.packet_size(),
+			Self::PayloadFeedback(s) => s.packet_size(),
+			Self::TransportFeedback(s) => s.packet_size(),
+			Self::ApplicationDefined(s) => s.packet_size(),
+			Self::Goodbye(s) => s.packet_size(),
+			Self::SourceDescription(s) => s.packet_size(),
 			Self::KnownType(_) => 0,
 		}
 	}
@@ -86,6 +293,16 @@ impl<'a> PacketSize for RtcpPacket<'a> {
 pub enum MutableRtcpPacket<'a> {
 	SenderReport(MutableSenderReportPacket<'a>),
 	ReceiverReport(MutableReceiverReportPacket<'a>),
+	SplicingNotification(MutableSplicingNotificationPacket<'a>),
+	ReceiverSummary(MutableReceiverSummaryPacket<'a>),
+	Avb(MutableAvbPacket<'a>),
+	JitterReport(MutableJitterReportPacket<'a>),
+	ExtendedReport(MutableExtendedReportPacket<'a>),
+	PayloadFeedback(MutablePayloadFeedbackPacket<'a>),
+	TransportFeedback(MutableTransportFeedbackPacket<'a>),
+	ApplicationDefined(MutableApplicationDefinedPacket<'a>),
+	Goodbye(MutableGoodbyePacket<'a>),
+	SourceDescription(MutableSourceDescriptionPacket<'a>),
 
 	KnownType(RtcpType),
 }
@@ -94,13 +311,76 @@ impl MutableRtcpPacket<'_> {
 	pub fn new(pkt: &mut [u8]) -> Option<MutableRtcpPacket<'_>> {
 		RtcpType::from_packet(pkt).and_then(move |rtcp_id| rtcp_id.decode_mut(pkt))
 	}
+
+	/// Parses a mutable RTCP packet, as with [`new`](#method.new).
+	///
+	/// # Errors
+	/// Returns a [`ParseError`] naming the first field (including any lists sized
+	/// by count fields) which does not fit in `pkt`.
+	///
+	/// [`ParseError`]: ../error/struct.ParseError.html
+	pub fn new_checked(pkt: &mut [u8]) -> Result<MutableRtcpPacket<'_>, ParseError> {
+		ParseError::require(pkt, "packet_type", 2)?;
+
+		Ok(match RtcpType::new(pkt[1]) {
+			RtcpType::SenderReport =>
+				MutableRtcpPacket::SenderReport(MutableSenderReportPacket::new_checked(pkt)?),
+			RtcpType::ReceiverReport =>
+				MutableRtcpPacket::ReceiverReport(MutableReceiverReportPacket::new_checked(pkt)?),
+			RtcpType::SplicingNotification => MutableRtcpPacket::SplicingNotification(
+				MutableSplicingNotificationPacket::new_checked(pkt)?,
+			),
+			RtcpType::ReceiverSummary =>
+				MutableRtcpPacket::ReceiverSummary(MutableReceiverSummaryPacket::new_checked(pkt)?),
+			RtcpType::Avb => MutableRtcpPacket::Avb(MutableAvbPacket::new_checked(pkt)?),
+			RtcpType::JitterReport =>
+				MutableRtcpPacket::JitterReport(MutableJitterReportPacket::new_checked(pkt)?),
+			RtcpType::ExtendedReport =>
+				MutableRtcpPacket::ExtendedReport(MutableExtendedReportPacket::new_checked(pkt)?),
+			RtcpType::PayloadFeedback =>
+				MutableRtcpPacket::PayloadFeedback(MutablePayloadFeedbackPacket::new_checked(pkt)?),
+			RtcpType::TransportFeedback => MutableRtcpPacket::TransportFeedback(
+				MutableTransportFeedbackPacket::new_checked(pkt)?,
+			),
+			RtcpType::ApplicationDefined => MutableRtcpPacket::ApplicationDefined(
+				MutableApplicationDefinedPacket::new_checked(pkt)?,
+			),
+			RtcpType::Goodbye =>
+				MutableRtcpPacket::Goodbye(MutableGoodbyePacket::new_checked(pkt)?),
+			RtcpType::SourceDescription => MutableRtcpPacket::SourceDescription(
+				MutableSourceDescriptionPacket::new_checked(pkt)?,
+			),
+			a => MutableRtcpPacket::KnownType(a),
+		})
+	}
+
+	/// Parses a single mutable RTCP packet starting at `offset` bytes into `buf`.
+	///
+	/// See [`RtcpPacket::parse_at`] for more information.
+	///
+	/// [`RtcpPacket::parse_at`]: enum.RtcpPacket.html#method.parse_at
+	pub fn parse_at(buf: &mut [u8], offset: usize) -> Option<(MutableRtcpPacket<'_>, usize)> {
+		let pkt = buf.get_mut(offset..)?;
+		let len = packet_len(pkt)?;
+		Self::new(&mut pkt[..len]).map(|p| (p, len))
+	}
 }
 
-impl<'a> Packet for MutableRtcpPacket<'a> {
+impl Packet for MutableRtcpPacket<'_> {
 	fn packet(&self) -> &[u8] {
 		match self {
 			Self::SenderReport(s) => s.packet(),
 			Self::ReceiverReport(s) => s.packet(),
+			Self::SplicingNotification(s) => s.packet(),
+			Self::ReceiverSummary(s) => s.packet(),
+			Self::Avb(s) => s.packet(),
+			Self::JitterReport(s) => s.packet(),
+			Self::ExtendedReport(s) => s.packet(),
+			Self::PayloadFeedback(s) => s.packet(),
+			Self::TransportFeedback(s) => s.packet(),
+			Self::ApplicationDefined(s) => s.packet(),
+			Self::Goodbye(s) => s.packet(),
+			Self::SourceDescription(s) => s.packet(),
 			Self::KnownType(_) => &[],
 		}
 	}
@@ -109,16 +389,36 @@ impl<'a> Packet for MutableRtcpPacket<'a> {
 		match self {
 			Self::SenderReport(s) => s.payload(),
 			Self::ReceiverReport(s) => s.payload(),
+			Self::SplicingNotification(s) => s.payload(),
+			Self::ReceiverSummary(s) => s.payload(),
+			Self::Avb(s) => s.payload(),
+			Self::JitterReport(s) => s.payload(),
+			Self::ExtendedReport(s) => s.payload(),
+			Self::PayloadFeedback(s) => s.payload(),
+			Self::TransportFeedback(s) => s.payload(),
+			Self::ApplicationDefined(s) => s.payload(),
+			Self::Goodbye(s) => s.payload(),
+			Self::SourceDescription(s) => s.payload(),
 			Self::KnownType(_) => &[],
 		}
 	}
 }
 
-impl<'a> MutablePacket for MutableRtcpPacket<'a> {
+impl MutablePacket for MutableRtcpPacket<'_> {
 	fn packet_mut(&mut self) -> &mut [u8] {
 		match self {
 			Self::SenderReport(s) => s.packet_mut(),
 			Self::ReceiverReport(s) => s.packet_mut(),
+			Self::SplicingNotification(s) => s.packet_mut(),
+			Self::ReceiverSummary(s) => s.packet_mut(),
+			Self::Avb(s) => s.packet_mut(),
+			Self::JitterReport(s) => s.packet_mut(),
+			Self::ExtendedReport(s) => s.packet_mut(),
+			Self::PayloadFeedback(s) => s.packet_mut(),
+			Self::TransportFeedback(s) => s.packet_mut(),
+			Self::ApplicationDefined(s) => s.packet_mut(),
+			Self::Goodbye(s) => s.packet_mut(),
+			Self::SourceDescription(s) => s.packet_mut(),
 			Self::KnownType(_) => &mut [],
 		}
 	}
@@ -127,33 +427,283 @@ impl<'a> MutablePacket for MutableRtcpPacket<'a> {
 		match self {
 			Self::SenderReport(s) => s.payload_mut(),
 			Self::ReceiverReport(s) => s.payload_mut(),
+			Self::SplicingNotification(s) => s.payload_mut(),
+			Self::ReceiverSummary(s) => s.payload_mut(),
+			Self::Avb(s) => s.payload_mut(),
+			Self::JitterReport(s) => s.payload_mut(),
+			Self::ExtendedReport(s) => s.payload_mut(),
+			Self::PayloadFeedback(s) => s.payload_mut(),
+			Self::TransportFeedback(s) => s.payload_mut(),
+			Self::ApplicationDefined(s) => s.payload_mut(),
+			Self::Goodbye(s) => s.payload_mut(),
+			Self::SourceDescription(s) => s.payload_mut(),
 			Self::KnownType(_) => &mut [],
 		}
 	}
 }
 
-impl<'a> FromPacket for MutableRtcpPacket<'a> {
+impl FromPacket for MutableRtcpPacket<'_> {
 	type T = Rtcp;
 
 	fn from_packet(&self) -> Self::T {
 		match self {
 			Self::SenderReport(s) => Rtcp::SenderReport(s.from_packet()),
 			Self::ReceiverReport(s) => Rtcp::ReceiverReport(s.from_packet()),
+			Self::SplicingNotification(s) => Rtcp::SplicingNotification(s.from_packet()),
+			Self::ReceiverSummary(s) => Rtcp::ReceiverSummary(s.from_packet()),
+			Self::Avb(s) => Rtcp::Avb(s.from_packet()),
+			Self::JitterReport(s) => Rtcp::JitterReport(s.from_packet()),
+			Self::ExtendedReport(s) => Rtcp::ExtendedReport(s.from_packet()),
+			Self::PayloadFeedback(s) => Rtcp::PayloadFeedback(s.from_packet()),
+			Self::TransportFeedback(s) => Rtcp::TransportFeedback(s.from_packet()),
+			Self::ApplicationDefined(s) => Rtcp::ApplicationDefined(s.from_packet()),
+			Self::Goodbye(s) => Rtcp::Goodbye(s.from_packet()),
+			Self::SourceDescription(s) => Rtcp::SourceDescription(s.from_packet()),
 			Self::KnownType(t) => Rtcp::KnownType(*t),
 		}
 	}
 }
 
-impl<'a> PacketSize for MutableRtcpPacket<'a> {
+impl PacketSize for MutableRtcpPacket<'_> {
 	fn packet_size(&self) -> usize {
 		match self {
 			Self::SenderReport(s) => s.packet_size(),
 			Self::ReceiverReport(s) => s.packet_size(),
+			Self::SplicingNotification(s) => s.packet_size(),
+			Self::ReceiverSummary(s) => s.packet_size(),
+			Self::Avb(s) => s.packet_size(),
+			Self::JitterReport(s) => s.packet_size(),
+			Self::ExtendedReport(s) => s.packet_size(),
+			Self::PayloadFeedback(s) => s.packet_size(),
+			Self::TransportFeedback(s) => s.packet_size(),
+			Self::ApplicationDefined(s) => s.packet_size(),
+			Self::Goodbye(s) => s.packet_size(),
+			Self::SourceDescription(s) => s.packet_size(),
 			Self::KnownType(_) => 0,
 		}
 	}
 }
 
+/// Returns the length in bytes of the RTCP packet at the start of `pkt`, as
+/// described by its `pkt_length` field.
+///
+/// This is clamped to `pkt.len()`, and is `None` if no full header is present.
+pub(crate) fn packet_len(pkt: &[u8]) -> Option<usize> {
+	let words = u16::from_be_bytes([*pkt.get(2)?, *pkt.get(3)?]);
+	let len = (usize::from(words) + 1) * 4;
+
+	Some(len.min(pkt.len()))
+}
+
+/// Maintenance of the `pkt_length` field on mutable RTCP packets.
+///
+/// `pkt_length` counts 32-bit words *minus one*, including the header and any
+/// padding: getting this encoding wrong is a common source of malformed RTCP.
+pub trait RtcpLengthMut: MutablePacket {
+	/// Sets `pkt_length` to describe the entirety of this view's buffer, returning
+	/// the value written.
+	///
+	/// The view should span exactly one packet, whose length is a multiple of 4 bytes.
+	fn recalculate_length(&mut self) -> u16 {
+		let words = length_words(self.packet().len());
+		if let Some(field) = self.packet_mut().get_mut(2..4) {
+			field.copy_from_slice(&words.to_be_bytes());
+		}
+
+		words
+	}
+
+	/// Writes `payload` after this packet's header, zero-fills up to the next 32-bit
+	/// boundary, and updates `pkt_length` to match.
+	///
+	/// Returns the total length of the packet in bytes, or `None` if the underlying
+	/// buffer is too small.
+	fn set_payload_with_length(&mut self, payload: &[u8]) -> Option<usize> {
+		let header = self.packet().len() - self.payload().len();
+		let padded = (payload.len() + 3) & !3;
+		let total = header + padded;
+
+		let buf = self.packet_mut().get_mut(..total)?;
+		if buf.len() < 4 {
+			return None;
+		}
+		buf[header..header + payload.len()].copy_from_slice(payload);
+		buf[header + payload.len()..].fill(0);
+		buf[2..4].copy_from_slice(&length_words(total).to_be_bytes());
+
+		Some(total)
+	}
+
+	/// Removes any padding from this packet in place, clearing the padding bit and
+	/// updating `pkt_length`.
+	///
+	/// Padding bytes are zeroed. Returns the number of bytes removed, by which the
+	/// caller may shorten the datagram (this is `0` if the packet is unpadded).
+	fn strip_padding(&mut self) -> usize {
+		let pad = padding_len(self.packet());
+		if pad == 0 {
+			return 0;
+		}
+
+		let Some(len) = packet_len(self.packet()) else {
+			return 0;
+		};
+		let buf = self.packet_mut();
+		buf[len - pad..len].fill(0);
+		buf[0] &= !0b0010_0000;
+		buf[2..4].copy_from_slice(&length_words(len - pad).to_be_bytes());
+
+		pad
+	}
+
+	/// Replaces any padding at the end of this packet with `pad` bytes, setting the
+	/// padding bit and updating `pkt_length`.
+	///
+	/// Pad bytes are zeroed, save for the final count octet. A `pad` of `0` removes
+	/// any existing padding. Returns the new length of the packet in bytes.
+	///
+	/// Returns `None` if `pad` is not a multiple of 4, or the underlying buffer is
+	/// too small.
+	fn write_padding(&mut self, pad: u8) -> Option<usize> {
+		if pad % 4 != 0 {
+			return None;
+		}
+
+		let len = packet_len(self.packet())?;
+		let unpadded = len - padding_len(self.packet());
+		let new_len = unpadded + usize::from(pad);
+
+		let buf = self.packet_mut();
+		buf.get_mut(unpadded..new_len)?.fill(0);
+		if pad == 0 {
+			buf[0] &= !0b0010_0000;
+		} else {
+			buf[new_len - 1] = pad;
+			buf[0] |= 0b0010_0000;
+		}
+		buf[2..4].copy_from_slice(&length_words(new_len).to_be_bytes());
+
+		Some(new_len)
+	}
+
+	/// Pads this packet to a multiple of `block` bytes, setting the padding bit and
+	/// updating `pkt_length`.
+	///
+	/// This is useful for cipher alignment or disguising packet sizes. Any existing
+	/// padding is replaced, and no padding is added if the unpadded packet is already
+	/// aligned. Returns the new length of the packet in bytes.
+	///
+	/// Returns `None` if `block` is not a multiple of 4 between 4 and 256, or the
+	/// underlying buffer is too small.
+	fn pad_to_block(&mut self, block: usize) -> Option<usize> {
+		if block % 4 != 0 || !(4..=256).contains(&block) {
+			return None;
+		}
+
+		let unpadded = packet_len(self.packet())? - padding_len(self.packet());
+		let pad = u8::try_from((block - unpadded % block) % block).ok()?;
+
+		self.write_padding(pad)
+	}
+}
+
+/// Access to RTCP packet contents with any trailing padding removed.
+///
+/// When the padding bit is set, the final byte of the packet (as described by
+/// `pkt_length`) counts the padding bytes to be ignored, including itself.
+pub trait RtcpPadding: Packet {
+	/// Returns the number of padding bytes at the end of this packet.
+	///
+	/// This is `0` if the padding bit is unset, and is clamped so that padding
+	/// never extends into the header.
+	fn padding_len(&self) -> usize {
+		padding_len(self.packet())
+	}
+
+	/// Returns this packet's payload, excluding any padding.
+	fn payload_without_padding(&self) -> &[u8] {
+		let pkt = self.packet();
+		let start = pkt.len() - self.payload().len();
+		let end = packet_len(pkt).unwrap_or(0) - padding_len(pkt);
+
+		pkt.get(start..end).unwrap_or_default()
+	}
+}
+
+impl RtcpPadding for RtcpPacket<'_> {}
+impl RtcpPadding for SenderReportPacket<'_> {}
+impl RtcpPadding for ReceiverReportPacket<'_> {}
+impl RtcpPadding for SourceDescriptionPacket<'_> {}
+impl RtcpPadding for GoodbyePacket<'_> {}
+impl RtcpPadding for ApplicationDefinedPacket<'_> {}
+impl RtcpPadding for SplicingNotificationPacket<'_> {}
+impl RtcpPadding for ReceiverSummaryPacket<'_> {}
+impl RtcpPadding for AvbPacket<'_> {}
+impl RtcpPadding for JitterReportPacket<'_> {}
+impl RtcpPadding for ExtendedReportPacket<'_> {}
+impl RtcpPadding for PayloadFeedbackPacket<'_> {}
+impl RtcpPadding for TransportFeedbackPacket<'_> {}
+impl RtcpPadding for MutableRtcpPacket<'_> {}
+impl RtcpPadding for MutableSenderReportPacket<'_> {}
+impl RtcpPadding for MutableReceiverReportPacket<'_> {}
+impl RtcpPadding for MutableSourceDescriptionPacket<'_> {}
+impl RtcpPadding for MutableGoodbyePacket<'_> {}
+impl RtcpPadding for MutableApplicationDefinedPacket<'_> {}
+impl RtcpPadding for MutableSplicingNotificationPacket<'_> {}
+impl RtcpPadding for MutableReceiverSummaryPacket<'_> {}
+impl RtcpPadding for MutableAvbPacket<'_> {}
+impl RtcpPadding for MutableJitterReportPacket<'_> {}
+impl RtcpPadding for MutableExtendedReportPacket<'_> {}
+impl RtcpPadding for MutablePayloadFeedbackPacket<'_> {}
+impl RtcpPadding for MutableTransportFeedbackPacket<'_> {}
+
+/// Returns the number of padding bytes at the end of the RTCP packet at the start of `pkt`.
+pub(crate) fn padding_len(pkt: &[u8]) -> usize {
+	match (pkt.first(), packet_len(pkt)) {
+		(Some(b), Some(len)) if b & 0b0010_0000 != 0 && len > 4 =>
+			usize::from(pkt[len - 1]).min(len - 4),
+		_ => 0,
+	}
+}
+
+impl RtcpLengthMut for MutableRtcpPacket<'_> {}
+impl RtcpLengthMut for MutableSenderReportPacket<'_> {}
+impl RtcpLengthMut for MutableReceiverReportPacket<'_> {}
+impl RtcpLengthMut for MutableSourceDescriptionPacket<'_> {}
+impl RtcpLengthMut for MutableGoodbyePacket<'_> {}
+impl RtcpLengthMut for MutableApplicationDefinedPacket<'_> {}
+impl RtcpLengthMut for MutableSplicingNotificationPacket<'_> {}
+impl RtcpLengthMut for MutableReceiverSummaryPacket<'_> {}
+impl RtcpLengthMut for MutableAvbPacket<'_> {}
+impl RtcpLengthMut for MutableJitterReportPacket<'_> {}
+impl RtcpLengthMut for MutableExtendedReportPacket<'_> {}
+impl RtcpLengthMut for MutablePayloadFeedbackPacket<'_> {}
+impl RtcpLengthMut for MutableTransportFeedbackPacket<'_> {}
+
+/// Checks that the body described by `pkt_length` fits into `pkt`.
+pub(crate) fn check_length(pkt: &[u8]) -> Result<(), ParseError> {
+	let words = u16::from_be_bytes([pkt[2], pkt[3]]);
+	ParseError::require(pkt, "payload", 4 * (usize::from(words) + 1))
+}
+
+/// Checks that the list `field`, with as many `size`-byte entries as the packet's
+/// 5-bit count and starting `offset` bytes in, fits into `pkt`.
+pub(crate) fn check_list(
+	pkt: &[u8],
+	field: &'static str,
+	offset: usize,
+	size: usize,
+) -> Result<(), ParseError> {
+	ParseError::require(pkt, field, offset + size * usize::from(pkt[0] & 0x1f))
+}
+
+/// Converts a packet length in bytes into the "32-bit words minus one" encoding
+/// used by `pkt_length`, rounding up any partial word.
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn length_words(bytes: usize) -> u16 {
+	((bytes + 3) / 4).saturating_sub(1) as u16
+}
+
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 /// RTCP message types. These define the packet format used for both the header and payload.
@@ -312,6 +862,22 @@ impl<'a> RtcpType {
 		match self {
 			Self::SenderReport => SenderReportPacket::new(pkt).map(RtcpPacket::SenderReport),
 			Self::ReceiverReport => ReceiverReportPacket::new(pkt).map(RtcpPacket::ReceiverReport),
+			Self::SplicingNotification =>
+				SplicingNotificationPacket::new(pkt).map(RtcpPacket::SplicingNotification),
+			Self::ReceiverSummary =>
+				ReceiverSummaryPacket::new(pkt).map(RtcpPacket::ReceiverSummary),
+			Self::Avb => AvbPacket::new(pkt).map(RtcpPacket::Avb),
+			Self::JitterReport => JitterReportPacket::new(pkt).map(RtcpPacket::JitterReport),
+			Self::ExtendedReport => ExtendedReportPacket::new(pkt).map(RtcpPacket::ExtendedReport),
+			Self::PayloadFeedback =>
+				PayloadFeedbackPacket::new(pkt).map(RtcpPacket::PayloadFeedback),
+			Self::TransportFeedback =>
+				TransportFeedbackPacket::new(pkt).map(RtcpPacket::TransportFeedback),
+			Self::ApplicationDefined =>
+				ApplicationDefinedPacket::new(pkt).map(RtcpPacket::ApplicationDefined),
+			Self::Goodbye => GoodbyePacket::new(pkt).map(RtcpPacket::Goodbye),
+			Self::SourceDescription =>
+				SourceDescriptionPacket::new(pkt).map(RtcpPacket::SourceDescription),
 			a => Some(RtcpPacket::KnownType(*a)),
 		}
 	}
@@ -322,6 +888,24 @@ impl<'a> RtcpType {
 				MutableSenderReportPacket::new(pkt).map(MutableRtcpPacket::SenderReport),
 			Self::ReceiverReport =>
 				MutableReceiverReportPacket::new(pkt).map(MutableRtcpPacket::ReceiverReport),
+			Self::SplicingNotification => MutableSplicingNotificationPacket::new(pkt)
+				.map(MutableRtcpPacket::SplicingNotification),
+			Self::ReceiverSummary =>
+				MutableReceiverSummaryPacket::new(pkt).map(MutableRtcpPacket::ReceiverSummary),
+			Self::Avb => MutableAvbPacket::new(pkt).map(MutableRtcpPacket::Avb),
+			Self::JitterReport =>
+				MutableJitterReportPacket::new(pkt).map(MutableRtcpPacket::JitterReport),
+			Self::ExtendedReport =>
+				MutableExtendedReportPacket::new(pkt).map(MutableRtcpPacket::ExtendedReport),
+			Self::PayloadFeedback =>
+				MutablePayloadFeedbackPacket::new(pkt).map(MutableRtcpPacket::PayloadFeedback),
+			Self::TransportFeedback =>
+				MutableTransportFeedbackPacket::new(pkt).map(MutableRtcpPacket::TransportFeedback),
+			Self::ApplicationDefined =>
+				MutableApplicationDefinedPacket::new(pkt).map(MutableRtcpPacket::ApplicationDefined),
+			Self::Goodbye => MutableGoodbyePacket::new(pkt).map(MutableRtcpPacket::Goodbye),
+			Self::SourceDescription =>
+				MutableSourceDescriptionPacket::new(pkt).map(MutableRtcpPacket::SourceDescription),
 			a => Some(MutableRtcpPacket::KnownType(*a)),
 		}
 	}
@@ -1,7 +1,14 @@
-use crate::rtcp::RtcpType;
+use crate::{
+	error::{impl_new_checked, ParseError},
+	rtcp::{self, RtcpType},
+};
 use alloc::vec::Vec;
+use core::time::Duration;
 use pnet_macros::packet;
-use pnet_macros_support::types::{u1, u16be, u2, u24be, u32be, u5};
+use pnet_macros_support::{
+	packet::{MutablePacket, Packet},
+	types::{u1, u16be, u2, u24be, u32be, u5},
+};
 
 #[packet]
 #[derive(Eq, PartialEq)]
@@ -59,6 +66,24 @@ pub struct SenderReport {
 	pub payload: Vec<u8>,
 }
 
+impl_new_checked!(
+	SenderReportPacket,
+	MutableSenderReportPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"rx_report_count" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"ssrc" => 8,
+	],
+	|pkt: &[u8]| {
+		ParseError::require(pkt, "sender_info", 28)?;
+		rtcp::check_list(pkt, "report_blocks", 28, 24)?;
+		rtcp::check_length(pkt)
+	}
+);
+
 #[packet]
 #[derive(Eq, PartialEq)]
 /// Receiver report, containing jitter and reception information.
@@ -115,6 +140,23 @@ pub struct ReceiverReport {
 	pub payload: Vec<u8>,
 }
 
+impl_new_checked!(
+	ReceiverReportPacket,
+	MutableReceiverReportPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"rx_report_count" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"ssrc" => 8,
+	],
+	|pkt: &[u8]| {
+		rtcp::check_list(pkt, "report_blocks", 8, 24)?;
+		rtcp::check_length(pkt)
+	}
+);
+
 #[packet]
 #[derive(Eq, PartialEq)]
 /// Sender Info block in a [`SenderReport`].
@@ -235,3 +277,285 @@ pub struct ReportBlock {
 	#[payload]
 	pub payload: Vec<u8>,
 }
+
+impl SenderReportPacket<'_> {
+	/// Returns a view over the [`SenderInfo`] block at the start of the payload.
+	///
+	/// Returns `None` if the payload is too short to hold one.
+	///
+	/// [`SenderInfo`]: struct.SenderInfo.html
+	#[must_use]
+	pub fn sender_info(&self) -> Option<SenderInfoPacket<'_>> {
+		SenderInfoPacket::new(self.payload().get(..SENDER_INFO_LEN)?)
+	}
+
+	/// Iterates over the [`ReportBlock`]s following the [`SenderInfo`] block.
+	///
+	/// At most [`rx_report_count`] blocks are returned, stopping early if the
+	/// payload is exhausted.
+	///
+	/// [`ReportBlock`]: struct.ReportBlock.html
+	/// [`SenderInfo`]: struct.SenderInfo.html
+	/// [`rx_report_count`]: struct.SenderReport.html#structfield.rx_report_count
+	#[must_use]
+	pub fn report_blocks(&self) -> ReportBlocks<'_> {
+		ReportBlocks::new(
+			self.payload().get(SENDER_INFO_LEN..).unwrap_or_default(),
+			self.get_rx_report_count(),
+		)
+	}
+}
+
+impl MutableSenderReportPacket<'_> {
+	/// Returns a view over the [`SenderInfo`] block at the start of the payload.
+	///
+	/// Returns `None` if the payload is too short to hold one.
+	///
+	/// [`SenderInfo`]: struct.SenderInfo.html
+	#[must_use]
+	pub fn sender_info(&self) -> Option<SenderInfoPacket<'_>> {
+		SenderInfoPacket::new(self.payload().get(..SENDER_INFO_LEN)?)
+	}
+
+	/// Returns a mutable view over the [`SenderInfo`] block at the start of the payload.
+	///
+	/// Returns `None` if the payload is too short to hold one.
+	///
+	/// [`SenderInfo`]: struct.SenderInfo.html
+	pub fn sender_info_mut(&mut self) -> Option<MutableSenderInfoPacket<'_>> {
+		MutableSenderInfoPacket::new(self.payload_mut().get_mut(..SENDER_INFO_LEN)?)
+	}
+
+	/// Iterates over the [`ReportBlock`]s following the [`SenderInfo`] block.
+	///
+	/// See [`SenderReportPacket::report_blocks`] for more information.
+	///
+	/// [`ReportBlock`]: struct.ReportBlock.html
+	/// [`SenderInfo`]: struct.SenderInfo.html
+	/// [`SenderReportPacket::report_blocks`]: struct.SenderReportPacket.html#method.report_blocks
+	#[must_use]
+	pub fn report_blocks(&self) -> ReportBlocks<'_> {
+		ReportBlocks::new(
+			self.payload().get(SENDER_INFO_LEN..).unwrap_or_default(),
+			self.get_rx_report_count(),
+		)
+	}
+}
+
+impl ReceiverReportPacket<'_> {
+	/// Iterates over the [`ReportBlock`]s in this packet.
+	///
+	/// At most [`rx_report_count`] blocks are returned, stopping early if the
+	/// payload is exhausted.
+	///
+	/// [`ReportBlock`]: struct.ReportBlock.html
+	/// [`rx_report_count`]: struct.ReceiverReport.html#structfield.rx_report_count
+	#[must_use]
+	pub fn report_blocks(&self) -> ReportBlocks<'_> {
+		ReportBlocks::new(self.payload(), self.get_rx_report_count())
+	}
+}
+
+impl MutableReceiverReportPacket<'_> {
+	/// Iterates over the [`ReportBlock`]s in this packet.
+	///
+	/// See [`ReceiverReportPacket::report_blocks`] for more information.
+	///
+	/// [`ReportBlock`]: struct.ReportBlock.html
+	/// [`ReceiverReportPacket::report_blocks`]: struct.ReceiverReportPacket.html#method.report_blocks
+	#[must_use]
+	pub fn report_blocks(&self) -> ReportBlocks<'_> {
+		ReportBlocks::new(self.payload(), self.get_rx_report_count())
+	}
+}
+
+/// Iterator over the [`ReportBlock`]s held in a [`SenderReport`] or [`ReceiverReport`].
+///
+/// [`ReportBlock`]: struct.ReportBlock.html
+/// [`SenderReport`]: struct.SenderReport.html
+/// [`ReceiverReport`]: struct.ReceiverReport.html
+#[derive(Clone, Debug)]
+pub struct ReportBlocks<'a> {
+	data: &'a [u8],
+	remaining: u8,
+}
+
+impl<'a> ReportBlocks<'a> {
+	/// Iterates over up to `count` report blocks packed into `data`.
+	#[must_use]
+	pub fn new(data: &'a [u8], count: u8) -> Self {
+		Self {
+			data,
+			remaining: count,
+		}
+	}
+}
+
+impl<'a> Iterator for ReportBlocks<'a> {
+	type Item = ReportBlockPacket<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.remaining == 0 || self.data.len() < REPORT_BLOCK_LEN {
+			return None;
+		}
+		self.remaining -= 1;
+
+		let (block, rest) = self.data.split_at(REPORT_BLOCK_LEN);
+		self.data = rest;
+
+		ReportBlockPacket::new(block)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let n = usize::from(self.remaining).min(self.data.len() / REPORT_BLOCK_LEN);
+		(n, Some(n))
+	}
+}
+
+const SENDER_INFO_LEN: usize = SenderInfoPacket::minimum_packet_size();
+
+const REPORT_BLOCK_LEN: usize = ReportBlockPacket::minimum_packet_size();
+
+impl ReportBlock {
+	/// Extended highest sequence number received, combining [`cycles`] and [`sequence`].
+	///
+	/// [`cycles`]: #structfield.cycles
+	/// [`sequence`]: #structfield.sequence
+	#[must_use]
+	pub fn extended_sequence(&self) -> u32 {
+		(u32::from(self.cycles) << 16) | u32::from(self.sequence)
+	}
+
+	/// Cumulative number of packets lost, interpreting [`cumulative_pkts_lost`] as
+	/// the signed 24-bit value described by the RFC (duplicates may make this negative).
+	///
+	/// [`cumulative_pkts_lost`]: #structfield.cumulative_pkts_lost
+	#[must_use]
+	pub fn cumulative_lost(&self) -> i32 {
+		// Shift the 24-bit value into the top of an i32 to sign-extend it.
+		#[allow(clippy::cast_possible_wrap)]
+		let shifted = (self.cumulative_pkts_lost << 8) as i32;
+		shifted >> 8
+	}
+
+	/// Computes reception statistics over the interval between an earlier report
+	/// block `prev` and this one, following [Appendix A.3] of the RFC.
+	///
+	/// Returns `None` if the two blocks describe different SSRCs.
+	///
+	/// [Appendix A.3]: https://tools.ietf.org/html/rfc3550#appendix-A.3
+	#[must_use]
+	pub fn delta_since(&self, prev: &ReportBlock) -> Option<ReportBlockDelta> {
+		(self.ssrc == prev.ssrc).then(|| {
+			let expected = self
+				.extended_sequence()
+				.wrapping_sub(prev.extended_sequence());
+			let lost = self.cumulative_lost().wrapping_sub(prev.cumulative_lost());
+			let received = i64::from(expected) - i64::from(lost);
+
+			let fraction_lost = if expected == 0 || lost <= 0 {
+				0
+			} else {
+				#[allow(clippy::cast_sign_loss)]
+				let lost = lost as u64;
+				((lost << 8) / u64::from(expected)).min(255)
+			};
+
+			ReportBlockDelta {
+				ssrc: self.ssrc,
+				expected,
+				lost,
+				received,
+				#[allow(clippy::cast_possible_truncation)]
+				fraction_lost: fraction_lost as u8,
+				jitter_change: i64::from(self.interarrival_jitter)
+					- i64::from(prev.interarrival_jitter),
+			}
+		})
+	}
+
+	/// Computes the round-trip time to the source of this block, given the local
+	/// wallclock time `now_ntp` (as a 64-bit NTP timestamp) at which it arrived.
+	///
+	/// See [`round_trip_time`] for more information.
+	///
+	/// [`round_trip_time`]: fn.round_trip_time.html
+	#[must_use]
+	pub fn round_trip_time(&self, now_ntp: u64) -> Option<Duration> {
+		round_trip_time(self.last_sr_timestamp, self.last_sr_delay, now_ntp)
+	}
+}
+
+impl ReportBlockPacket<'_> {
+	/// Computes the round-trip time to the source of this block, given the local
+	/// wallclock time `now_ntp` (as a 64-bit NTP timestamp) at which it arrived.
+	///
+	/// See [`round_trip_time`] for more information.
+	///
+	/// [`round_trip_time`]: fn.round_trip_time.html
+	#[must_use]
+	pub fn round_trip_time(&self, now_ntp: u64) -> Option<Duration> {
+		round_trip_time(
+			self.get_last_sr_timestamp(),
+			self.get_last_sr_delay(),
+			now_ntp,
+		)
+	}
+}
+
+/// Computes round-trip time from the `last_sr_timestamp` (LSR) and `last_sr_delay`
+/// (DLSR) fields of a report block, and the local wallclock time `now_ntp` (as a
+/// 64-bit NTP timestamp) at which the block arrived.
+///
+/// This follows [RFC 3550](https://tools.ietf.org/html/rfc3550#section-6.4.1):
+/// the RTT is the middle 32 bits of `now_ntp`, minus LSR and DLSR. Results are
+/// accurate to 1/65536 seconds.
+///
+/// Returns `None` if no SR has been reported on (LSR is `0`), or if the result
+/// would be negative (*e.g.*, due to clock adjustments).
+#[must_use]
+pub fn round_trip_time(lsr: u32, dlsr: u32, now_ntp: u64) -> Option<Duration> {
+	if lsr == 0 {
+		return None;
+	}
+
+	#[allow(clippy::cast_possible_truncation)]
+	let now = (now_ntp >> 16) as u32;
+	let elapsed = now.wrapping_sub(lsr);
+
+	// A "negative" elapsed time wraps to a value larger than any sane RTT.
+	if elapsed >= 1 << 31 || elapsed < dlsr {
+		return None;
+	}
+
+	let units = u64::from(elapsed - dlsr);
+	Some(
+		Duration::from_secs(units >> 16)
+			+ Duration::from_nanos(((units & 0xffff) * 1_000_000_000) >> 16),
+	)
+}
+
+/// Reception statistics over the interval between two consecutive [`ReportBlock`]s
+/// for the same SSRC.
+///
+/// [`ReportBlock`]: struct.ReportBlock.html
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ReportBlockDelta {
+	/// SSRC of the stream which both reports concern.
+	pub ssrc: u32,
+
+	/// Packets expected during the interval, from the change in extended highest sequence.
+	pub expected: u32,
+
+	/// Packets lost during the interval. May be negative if duplicates were received.
+	pub lost: i32,
+
+	/// Packets received during the interval (`expected - lost`).
+	pub received: i64,
+
+	/// Interval loss as a fixed-point number (*i.e.*, n => n/256).
+	pub fraction_lost: u8,
+
+	/// Change in interarrival jitter, in RTP timestamp units.
+	pub jitter_change: i64,
+}
@@ -0,0 +1,242 @@
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, RtcpType},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u16be, u2, u32be, u5},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Receiver summary information, sent by a distribution source to summarise
+/// feedback from the receivers of a source-specific multicast session.
+///
+/// See [RFC 5760](https://tools.ietf.org/html/rfc5760#section-7.1).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `reserved`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::ReceiverSummary`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `ssrc`
+/// SSRC of the distribution source.
+///
+/// ## `summarized_ssrc`
+/// SSRC of the media sender whose receivers are being summarised.
+///
+/// ## `ntp_timestamp_{second,fraction}`
+/// Wallclock time at which this summary was generated.
+///
+/// ## `payload`
+/// Zero or more sub-report blocks. Use [`sub_reports`] to iterate over these.
+///
+/// [`length`]: #structfield.length
+/// [`sub_reports`]: struct.ReceiverSummaryPacket.html#method.sub_reports
+/// [`RtcpType::ReceiverSummary`]: ../enum.RtcpType.html#variant.ReceiverSummary
+pub struct ReceiverSummary {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub reserved: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	pub ssrc: u32be,
+
+	pub summarized_ssrc: u32be,
+
+	pub ntp_timestamp_second: u32be,
+
+	pub ntp_timestamp_fraction: u32be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	ReceiverSummaryPacket,
+	MutableReceiverSummaryPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"reserved" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"ssrc" => 8,
+		"summarized_ssrc" => 12,
+		"ntp_timestamp_second" => 16,
+		"ntp_timestamp_fraction" => 20,
+	],
+	rtcp::check_length
+);
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// A single sub-report block within a [`ReceiverSummary`].
+///
+/// See [RFC 5760](https://tools.ietf.org/html/rfc5760#section-7.1.1).
+///
+/// A description of fields:
+///
+/// ## `srbt`
+/// Sub-report block type, identifying the format of the block body.
+///
+/// ## `block_length`
+/// Length of this block in 32-bit words, including the header.
+///
+/// ## `type_specific`
+/// Meaning depends on `srbt`.
+///
+/// ## `payload`
+/// Block body, whose format is defined by `srbt`.
+///
+/// [`ReceiverSummary`]: struct.ReceiverSummary.html
+pub struct SubReport {
+	pub srbt: u8,
+
+	pub block_length: u8,
+
+	pub type_specific: u16be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl ReceiverSummary {
+	/// Creates a receiver summary from distribution source `ssrc` about
+	/// `summarized_ssrc`, with no sub-report blocks.
+	///
+	/// `ntp_timestamp` is given as a 64-bit NTP timestamp.
+	#[must_use]
+	pub fn new(ssrc: u32, summarized_ssrc: u32, ntp_timestamp: u64) -> Self {
+		let [s0, s1, s2, s3, f0, f1, f2, f3] = ntp_timestamp.to_be_bytes();
+
+		Self {
+			version: 2,
+			padding: 0,
+			reserved: 0,
+			packet_type: RtcpType::ReceiverSummary,
+			pkt_length: rtcp::length_words(ReceiverSummaryPacket::minimum_packet_size()),
+			ssrc,
+			summarized_ssrc,
+			ntp_timestamp_second: u32::from_be_bytes([s0, s1, s2, s3]),
+			ntp_timestamp_fraction: u32::from_be_bytes([f0, f1, f2, f3]),
+			payload: Vec::new(),
+		}
+	}
+
+	/// Appends a sub-report block of type `srbt` carrying `data`, updating
+	/// [`pkt_length`].
+	///
+	/// `data` is zero-padded to a 32-bit boundary. Blocks longer than 1020 bytes
+	/// cannot be represented, and are truncated.
+	///
+	/// [`pkt_length`]: #structfield.pkt_length
+	pub fn push_sub_report(&mut self, srbt: u8, type_specific: u16, data: &[u8]) -> &mut Self {
+		let header = SubReportPacket::minimum_packet_size();
+		let data = &data[..data.len().min(255 * 4 - header)];
+		let padded = (data.len() + 3) & !3;
+
+		#[allow(clippy::cast_possible_truncation)]
+		let words = ((header + padded) / 4) as u8;
+
+		self.payload.extend_from_slice(&[srbt, words]);
+		self.payload.extend_from_slice(&type_specific.to_be_bytes());
+		self.payload.extend_from_slice(data);
+		self.payload
+			.resize(self.payload.len() + padded - data.len(), 0);
+
+		self.pkt_length =
+			rtcp::length_words(ReceiverSummaryPacket::minimum_packet_size() + self.payload.len());
+
+		self
+	}
+}
+
+impl ReceiverSummaryPacket<'_> {
+	/// Returns the 64-bit NTP timestamp at which this summary was generated.
+	#[must_use]
+	pub fn ntp_timestamp(&self) -> u64 {
+		(u64::from(self.get_ntp_timestamp_second()) << 32)
+			| u64::from(self.get_ntp_timestamp_fraction())
+	}
+
+	/// Iterates over each sub-report block in this packet.
+	#[must_use]
+	pub fn sub_reports(&self) -> SubReports<'_> {
+		SubReports::new(self.payload())
+	}
+}
+
+impl MutableReceiverSummaryPacket<'_> {
+	/// Returns the 64-bit NTP timestamp at which this summary was generated.
+	#[must_use]
+	pub fn ntp_timestamp(&self) -> u64 {
+		(u64::from(self.get_ntp_timestamp_second()) << 32)
+			| u64::from(self.get_ntp_timestamp_fraction())
+	}
+
+	/// Iterates over each sub-report block in this packet.
+	#[must_use]
+	pub fn sub_reports(&self) -> SubReports<'_> {
+		SubReports::new(self.payload())
+	}
+}
+
+/// Iterator over the sub-report blocks in a [`ReceiverSummary`].
+///
+/// Each block is limited to its own `block_length`. Iteration ends at the first
+/// truncated or zero-length block.
+///
+/// [`ReceiverSummary`]: struct.ReceiverSummary.html
+#[derive(Clone, Debug)]
+pub struct SubReports<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> SubReports<'a> {
+	/// Iterates over each complete sub-report block in `body`.
+	#[must_use]
+	pub fn new(body: &'a [u8]) -> Self {
+		Self { data: body }
+	}
+}
+
+impl<'a> Iterator for SubReports<'a> {
+	type Item = SubReportPacket<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let header = SubReportPacket::new(self.data)?;
+		let len = usize::from(header.get_block_length()) * 4;
+		if len < SubReportPacket::minimum_packet_size() || self.data.len() < len {
+			return None;
+		}
+
+		let (block, rest) = self.data.split_at(len);
+		self.data = rest;
+
+		SubReportPacket::new(block)
+	}
+}
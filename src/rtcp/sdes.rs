@@ -0,0 +1,514 @@
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, RtcpType},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::{Packet, PrimitiveValues},
+	types::{u1, u16be, u2, u5},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Source description, mapping SSRC/CSRC values to information about each host.
+///
+/// See the relevant [RTP RFC section](https://tools.ietf.org/html/rfc3550#section-6.5).
+/// Main payload body is [`source_count`] x chunks, each containing an SSRC/CSRC and a
+/// list of SDES items: these can be read using [`chunks`].
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `source_count`
+/// Number of SSRC/CSRC chunks contained. May be `0`.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::SourceDescription`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `payload`
+/// Bytes of the RTCP body.
+///
+/// [`source_count`]: #structfield.source_count
+/// [`chunks`]: struct.SourceDescriptionPacket.html#method.chunks
+/// [`length`]: #structfield.length
+/// [`RtcpType::SourceDescription`]: ../enum.RtcpType.html#variant.SourceDescription
+pub struct SourceDescription {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub source_count: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	SourceDescriptionPacket,
+	MutableSourceDescriptionPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"source_count" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+	],
+	rtcp::check_length
+);
+
+impl<'a> SourceDescriptionPacket<'a> {
+	/// Iterates over each SSRC/CSRC chunk in this packet.
+	///
+	/// At most [`source_count`] chunks are returned, stopping early if the payload
+	/// is exhausted.
+	///
+	/// [`source_count`]: struct.SourceDescription.html#structfield.source_count
+	#[must_use]
+	pub fn chunks(&'a self) -> SdesChunks<'a> {
+		SdesChunks::new(Packet::payload(self), self.get_source_count())
+	}
+}
+
+impl<'a> MutableSourceDescriptionPacket<'a> {
+	/// Iterates over each SSRC/CSRC chunk in this packet.
+	///
+	/// See [`SourceDescriptionPacket::chunks`] for more information.
+	///
+	/// [`SourceDescriptionPacket::chunks`]: struct.SourceDescriptionPacket.html#method.chunks
+	#[must_use]
+	pub fn chunks(&'a self) -> SdesChunks<'a> {
+		SdesChunks::new(Packet::payload(self), self.get_source_count())
+	}
+}
+
+impl SourceDescription {
+	/// Creates an empty source description, containing no chunks.
+	///
+	/// Chunks and items can then be appended, while the [`source_count`],
+	/// [`pkt_length`], and chunk padding are kept consistent.
+	///
+	/// [`source_count`]: #structfield.source_count
+	/// [`pkt_length`]: #structfield.pkt_length
+	#[must_use]
+	pub fn new() -> Self {
+		Self {
+			version: 2,
+			padding: 0,
+			source_count: 0,
+			packet_type: RtcpType::SourceDescription,
+			pkt_length: 0,
+			payload: Vec::new(),
+		}
+	}
+
+	/// Appends a new (empty) chunk describing `ssrc`.
+	///
+	/// Subsequent items are added to this chunk. At most 31 chunks may be held;
+	/// further calls are ignored.
+	pub fn push_chunk(&mut self, ssrc: u32) -> &mut Self {
+		if self.source_count < 31 {
+			self.payload.extend_from_slice(&ssrc.to_be_bytes());
+			terminate_chunk(&mut self.payload);
+			self.source_count += 1;
+			self.update_length();
+		}
+
+		self
+	}
+
+	/// Appends an item to the most recently added chunk.
+	///
+	/// Values longer than 255 bytes are truncated. Does nothing if no chunk
+	/// has been added, or if `kind` is the END item type (`0`), as this would
+	/// terminate the chunk early.
+	pub fn push_item(&mut self, kind: SdesItemType, value: &[u8]) -> &mut Self {
+		if kind == SdesItemType::Unassigned(0) {
+			return self;
+		}
+		let Some(end) = self.last_items_end() else {
+			return self;
+		};
+		let value = &value[..value.len().min(255)];
+
+		self.payload.truncate(end);
+		self.payload.push(kind.to_primitive_values().0);
+		#[allow(clippy::cast_possible_truncation)]
+		self.payload.push(value.len() as u8);
+		self.payload.extend_from_slice(value);
+		terminate_chunk(&mut self.payload);
+		self.update_length();
+
+		self
+	}
+
+	/// Appends a canonical name (CNAME) item to the most recently added chunk.
+	pub fn push_cname(&mut self, cname: &str) -> &mut Self {
+		self.push_item(SdesItemType::CName, cname.as_bytes())
+	}
+
+	/// Appends a tool name item to the most recently added chunk.
+	pub fn push_tool(&mut self, tool: &str) -> &mut Self {
+		self.push_item(SdesItemType::Tool, tool.as_bytes())
+	}
+
+	/// Appends a private extension item to the most recently added chunk.
+	///
+	/// The combined prefix and value are truncated to fit within 255 bytes.
+	pub fn push_private(&mut self, prefix: &[u8], value: &[u8]) -> &mut Self {
+		let prefix = &prefix[..prefix.len().min(254)];
+		let mut item = Vec::with_capacity(1 + prefix.len() + value.len());
+		#[allow(clippy::cast_possible_truncation)]
+		item.push(prefix.len() as u8);
+		item.extend_from_slice(prefix);
+		item.extend_from_slice(value);
+
+		self.push_item(SdesItemType::Private, &item)
+	}
+
+	/// Iterates over each SSRC/CSRC chunk in this packet.
+	#[must_use]
+	pub fn chunks(&self) -> SdesChunks<'_> {
+		SdesChunks::new(&self.payload, self.source_count)
+	}
+
+	/// Byte offset in the payload where the last chunk's END item begins.
+	fn last_items_end(&self) -> Option<usize> {
+		let mut at = 0;
+		let mut out = None;
+		for _ in 0..self.source_count {
+			let Some(chunk) = self.payload.get(at..).filter(|c| c.len() >= 4) else {
+				break;
+			};
+			let (items_end, chunk_end) = chunk_bounds(chunk);
+			out = Some(at + items_end);
+			at += chunk_end;
+		}
+
+		out
+	}
+
+	fn update_length(&mut self) {
+		self.pkt_length = rtcp::length_words(4 + self.payload.len());
+	}
+}
+
+impl Default for SourceDescription {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Appends a null END item, then pads the chunk to a 32-bit boundary.
+fn terminate_chunk(payload: &mut Vec<u8>) {
+	payload.push(0);
+	while payload.len() % 4 != 0 {
+		payload.push(0);
+	}
+}
+
+/// Locates the END item of the chunk at the start of `data`, and the start of the
+/// next chunk.
+///
+/// Both offsets are clamped to the length of `data`.
+fn chunk_bounds(data: &[u8]) -> (usize, usize) {
+	// Walk the item list to find the END item, then skip to the next word boundary.
+	let mut at = 4;
+	let mut items_end = None;
+	while let Some(&kind) = data.get(at) {
+		if kind == 0 {
+			items_end = Some(at);
+			at = (at + 4) & !3;
+			break;
+		}
+
+		at += data
+			.get(at + 1)
+			.map_or(data.len(), |len| 2 + usize::from(*len));
+	}
+	let at = at.min(data.len());
+
+	(items_end.unwrap_or(at), at)
+}
+
+/// Iterator over the SSRC/CSRC chunks of a [`SourceDescription`] packet.
+///
+/// [`SourceDescription`]: struct.SourceDescription.html
+#[derive(Clone, Debug)]
+pub struct SdesChunks<'a> {
+	data: &'a [u8],
+	remaining: u8,
+}
+
+impl<'a> SdesChunks<'a> {
+	/// Iterates over up to `count` chunks in an SDES packet body.
+	#[must_use]
+	pub fn new(data: &'a [u8], count: u8) -> Self {
+		Self {
+			data,
+			remaining: count,
+		}
+	}
+}
+
+impl<'a> Iterator for SdesChunks<'a> {
+	type Item = SdesChunk<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.remaining == 0 || self.data.len() < 4 {
+			return None;
+		}
+		self.remaining -= 1;
+
+		let ssrc = u32::from_be_bytes([self.data[0], self.data[1], self.data[2], self.data[3]]);
+
+		let (items_end, at) = chunk_bounds(self.data);
+
+		let items = &self.data[4..items_end];
+		self.data = &self.data[at..];
+
+		Some(SdesChunk { ssrc, items })
+	}
+}
+
+/// A single chunk of SDES items, describing one SSRC/CSRC.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SdesChunk<'a> {
+	/// Source described by this chunk.
+	pub ssrc: u32,
+
+	items: &'a [u8],
+}
+
+impl<'a> SdesChunk<'a> {
+	/// Iterates over each SDES item in this chunk, stopping at the END item.
+	#[must_use]
+	pub fn items(&self) -> SdesItems<'a> {
+		SdesItems { data: self.items }
+	}
+
+	/// Returns the value of the first item of the given kind, if present.
+	#[must_use]
+	pub fn find(&self, kind: SdesItemType) -> Option<&'a [u8]> {
+		self.items().find(|i| i.kind == kind).map(|i| i.value)
+	}
+
+	/// Returns the canonical name (CNAME) of this source, if present and valid UTF-8.
+	#[must_use]
+	pub fn cname(&self) -> Option<&'a str> {
+		self.find(SdesItemType::CName)
+			.and_then(|v| core::str::from_utf8(v).ok())
+	}
+}
+
+/// Iterator over the items within an [`SdesChunk`].
+///
+/// [`SdesChunk`]: struct.SdesChunk.html
+#[derive(Clone, Debug)]
+pub struct SdesItems<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> Iterator for SdesItems<'a> {
+	type Item = SdesItem<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (&kind, rest) = self.data.split_first()?;
+		if kind == 0 {
+			self.data = &[];
+			return None;
+		}
+
+		let (&len, rest) = rest.split_first()?;
+		let len = usize::from(len).min(rest.len());
+		let (value, rest) = rest.split_at(len);
+		self.data = rest;
+
+		Some(SdesItem {
+			kind: SdesItemType::new(kind),
+			value,
+		})
+	}
+}
+
+/// A single SDES item (type-length-value).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SdesItem<'a> {
+	/// Type of information held in this item.
+	pub kind: SdesItemType,
+
+	/// Raw item contents. Standard item types contain UTF-8 text.
+	pub value: &'a [u8],
+}
+
+impl<'a> SdesItem<'a> {
+	/// Returns the value of this item as text, if valid UTF-8.
+	#[must_use]
+	pub fn text(&self) -> Option<&'a str> {
+		core::str::from_utf8(self.value).ok()
+	}
+
+	/// For [`SdesItemType::Private`] items, splits the value into its prefix
+	/// string and the remaining value.
+	///
+	/// [`SdesItemType::Private`]: enum.SdesItemType.html#variant.Private
+	#[must_use]
+	pub fn private_parts(&self) -> Option<(&'a [u8], &'a [u8])> {
+		if self.kind != SdesItemType::Private {
+			return None;
+		}
+
+		let (&len, rest) = self.value.split_first()?;
+		let len = usize::from(len);
+		(len <= rest.len()).then(|| rest.split_at(len))
+	}
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+/// SDES item types.
+///
+/// See the [IANA page] on the matter for an up-to-date-list.
+///
+/// [IANA page]: https://www.iana.org/assignments/rtp-parameters/rtp-parameters.xhtml#rtp-parameters-5
+pub enum SdesItemType {
+	/// Canonical end-point identifier. Code 1.
+	CName,
+
+	/// User name. Code 2.
+	Name,
+
+	/// Electronic mail address. Code 3.
+	Email,
+
+	/// Phone number. Code 4.
+	Phone,
+
+	/// Geographic user location. Code 5.
+	Location,
+
+	/// Application or tool name. Code 6.
+	Tool,
+
+	/// Notice/status. Code 7.
+	Note,
+
+	/// Private extensions, prefixed by a length-delimited name. Code 8.
+	Private,
+
+	/// H.323 callable address. Code 9.
+	H323Caddr,
+
+	/// Application-layer media ID. Code 10.
+	Mid,
+
+	/// RTP stream identifier. Code 11.
+	RtpStreamId,
+
+	/// Repaired RTP stream identifier. Code 12.
+	RepairedRtpStreamId,
+
+	/// Unknown item type.
+	Unassigned(u8),
+}
+
+impl SdesItemType {
+	#[must_use]
+	pub fn new(val: u8) -> Self {
+		match val {
+			1 => Self::CName,
+			2 => Self::Name,
+			3 => Self::Email,
+			4 => Self::Phone,
+			5 => Self::Location,
+			6 => Self::Tool,
+			7 => Self::Note,
+			8 => Self::Private,
+			9 => Self::H323Caddr,
+			10 => Self::Mid,
+			11 => Self::RtpStreamId,
+			12 => Self::RepairedRtpStreamId,
+			_ => Self::Unassigned(val),
+		}
+	}
+}
+
+impl PrimitiveValues for SdesItemType {
+	type T = (u8,);
+
+	fn to_primitive_values(&self) -> Self::T {
+		match self {
+			Self::CName => (1,),
+			Self::Name => (2,),
+			Self::Email => (3,),
+			Self::Phone => (4,),
+			Self::Location => (5,),
+			Self::Tool => (6,),
+			Self::Note => (7,),
+			Self::Private => (8,),
+			Self::H323Caddr => (9,),
+			Self::Mid => (10,),
+			Self::RtpStreamId => (11,),
+			Self::RepairedRtpStreamId => (12,),
+
+			Self::Unassigned(val) => (*val,),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn items_round_trip_across_chunks() {
+		let mut sdes = SourceDescription::new();
+		sdes.push_chunk(1)
+			.push_cname("alice@example.com")
+			.push_tool("discortp")
+			.push_chunk(2)
+			.push_private(b"x", b"\0\0")
+			.push_cname("bob");
+		assert_eq!(sdes.payload.len() % 4, 0);
+
+		let chunks: Vec<_> = sdes.chunks().collect();
+		assert_eq!(chunks.len(), 2);
+		assert_eq!(chunks[0].ssrc, 1);
+		assert_eq!(chunks[0].cname(), Some("alice@example.com"));
+		assert_eq!(chunks[0].find(SdesItemType::Tool), Some(&b"discortp"[..]));
+		assert_eq!(chunks[1].ssrc, 2);
+		assert_eq!(chunks[1].cname(), Some("bob"));
+		let private = chunks[1].items().next().unwrap();
+		assert_eq!(private.private_parts(), Some((&b"x"[..], &b"\0\0"[..])));
+	}
+
+	#[test]
+	fn end_item_type_is_rejected() {
+		let mut sdes = SourceDescription::new();
+		sdes.push_chunk(1).push_cname("a");
+		let before = sdes.payload.clone();
+
+		sdes.push_item(SdesItemType::Unassigned(0), &[1, 2, 3, 4]);
+		assert_eq!(sdes.payload, before);
+
+		sdes.push_chunk(2);
+		let ssrcs: Vec<_> = sdes.chunks().map(|c| c.ssrc).collect();
+		assert_eq!(ssrcs, [1, 2]);
+	}
+}
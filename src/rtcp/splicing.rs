@@ -0,0 +1,140 @@
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, RtcpType},
+	wrap::Wrap16,
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::types::{u1, u16be, u2, u32be, u5};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Splicing notification, announcing when substitutive content will be spliced
+/// into (and out of) the main RTP stream.
+///
+/// See [RFC 8286](https://tools.ietf.org/html/rfc8286#section-4.3).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `reserved`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::SplicingNotification`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding. Should be `4`.
+///
+/// ## `ssrc`
+/// SSRC of the splicer sending this notification.
+///
+/// ## `sequence`
+/// Incremented for each new splicing event, so that repeated notifications
+/// of the same event can be recognised.
+///
+/// ## `reserved_2`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `splice_out_time`
+/// RTP timestamp of the main content at which substitutive content begins.
+///
+/// ## `splice_in_time`
+/// RTP timestamp of the main content at which it resumes.
+///
+/// ## `payload`
+/// Remainder of the packet. Should be empty, barring padding.
+///
+/// [`length`]: #structfield.length
+/// [`RtcpType::SplicingNotification`]: ../enum.RtcpType.html#variant.SplicingNotification
+pub struct SplicingNotification {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub reserved: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	pub ssrc: u32be,
+
+	#[construct_with(u16be)]
+	pub sequence: Wrap16,
+
+	pub reserved_2: u16be,
+
+	pub splice_out_time: u32be,
+
+	pub splice_in_time: u32be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	SplicingNotificationPacket,
+	MutableSplicingNotificationPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"reserved" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"ssrc" => 8,
+		"sequence" => 10,
+		"reserved_2" => 12,
+		"splice_out_time" => 16,
+		"splice_in_time" => 20,
+	],
+	rtcp::check_length
+);
+
+impl SplicingNotification {
+	/// Creates a notification from `ssrc` for splicing event `sequence`, during
+	/// which the main content is replaced between the given RTP timestamps.
+	#[must_use]
+	pub fn new(ssrc: u32, sequence: Wrap16, splice_out_time: u32, splice_in_time: u32) -> Self {
+		Self {
+			version: 2,
+			padding: 0,
+			reserved: 0,
+			packet_type: RtcpType::SplicingNotification,
+			pkt_length: rtcp::length_words(SplicingNotificationPacket::minimum_packet_size()),
+			ssrc,
+			sequence,
+			reserved_2: 0,
+			splice_out_time,
+			splice_in_time,
+			payload: Vec::new(),
+		}
+	}
+}
+
+impl SplicingNotificationPacket<'_> {
+	/// Returns the duration of the splice, in RTP timestamp units.
+	#[must_use]
+	pub fn splice_duration(&self) -> u32 {
+		self.get_splice_in_time()
+			.wrapping_sub(self.get_splice_out_time())
+	}
+}
+
+impl MutableSplicingNotificationPacket<'_> {
+	/// Returns the duration of the splice, in RTP timestamp units.
+	#[must_use]
+	pub fn splice_duration(&self) -> u32 {
+		self.get_splice_in_time()
+			.wrapping_sub(self.get_splice_out_time())
+	}
+}
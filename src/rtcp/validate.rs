@@ -0,0 +1,203 @@
+//! Validation of RTCP datagrams, covering both compound and
+//! [reduced-size](https://tools.ietf.org/html/rfc5506) RTCP.
+
+use crate::rtcp::{sdes::SourceDescriptionPacket, RtcpType};
+use alloc::vec::Vec;
+
+/// How an RTCP datagram is structured.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RtcpMode {
+	/// A compound packet, beginning with a sender or receiver report.
+	///
+	/// See [RFC 3550](https://tools.ietf.org/html/rfc3550#section-6.1).
+	Compound,
+	/// A reduced-size (non-compound) packet, such as standalone feedback.
+	///
+	/// See [RFC 5506](https://tools.ietf.org/html/rfc5506#section-3).
+	ReducedSize,
+}
+
+/// A rule broken by an RTCP datagram.
+///
+/// Offsets give the start of the offending packet within the datagram.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Violation {
+	/// The datagram contains no RTCP packets.
+	Empty,
+	/// A packet's version field is not `2`.
+	BadVersion { offset: usize },
+	/// A packet's header is cut short, or its `pkt_length` runs past the end of the datagram.
+	Truncated { offset: usize },
+	/// A packet other than the last has its padding bit set.
+	PaddingNotLast { offset: usize },
+	/// The padding count of the last packet is zero, or exceeds its length.
+	BadPadding { offset: usize },
+	/// The datagram does not begin with a sender or receiver report, and
+	/// reduced-size RTCP has not been negotiated.
+	NotCompound,
+	/// A compound datagram contains no source description.
+	MissingSdes,
+	/// A compound datagram contains no CNAME for the SSRC of its first report.
+	MissingCname { ssrc: u32 },
+	/// A goodbye packet appears before the end of a compound datagram.
+	ByeNotLast { offset: usize },
+}
+
+/// Classifies a datagram as compound or reduced-size, based on the type of its
+/// first packet.
+///
+/// Returns `None` if `buf` does not begin with an RTCP header.
+#[must_use]
+pub fn classify(buf: &[u8]) -> Option<RtcpMode> {
+	if buf.len() < 4 {
+		return None;
+	}
+
+	match RtcpType::from_packet(buf)? {
+		RtcpType::SenderReport | RtcpType::ReceiverReport => Some(RtcpMode::Compound),
+		_ => Some(RtcpMode::ReducedSize),
+	}
+}
+
+/// Checks the structural rules shared by compound and reduced-size datagrams,
+/// and determines which mode `buf` uses.
+///
+/// Every packet must be version `2`, only the last packet may be padded, and
+/// packet lengths must exactly cover the datagram. Non-compound datagrams are
+/// accepted only if `allow_reduced_size` is set (*i.e.*, `rtcp-rsize` has been
+/// negotiated).
+///
+/// # Errors
+/// Returns the first [`Violation`] found.
+///
+/// [`Violation`]: enum.Violation.html
+pub fn validate(buf: &[u8], allow_reduced_size: bool) -> Result<RtcpMode, Violation> {
+	let mode = classify(buf).ok_or(Violation::Empty)?;
+	if mode == RtcpMode::ReducedSize && !allow_reduced_size {
+		return Err(Violation::NotCompound);
+	}
+
+	let mut offset = 0;
+	while offset < buf.len() {
+		let len = packet_extent(buf, offset)?;
+		let last = offset + len == buf.len();
+
+		if buf[offset] & 0b0010_0000 != 0 {
+			if !last {
+				return Err(Violation::PaddingNotLast { offset });
+			}
+
+			let pad = usize::from(buf[buf.len() - 1]);
+			if pad == 0 || pad > len - 4 {
+				return Err(Violation::BadPadding { offset });
+			}
+		}
+
+		offset += len;
+	}
+
+	Ok(mode)
+}
+
+/// Returns the length of the packet at `offset`, checking its version and that it
+/// fits within `buf`.
+pub(crate) fn packet_extent(buf: &[u8], offset: usize) -> Result<usize, Violation> {
+	let header = buf
+		.get(offset..offset + 4)
+		.ok_or(Violation::Truncated { offset })?;
+
+	if header[0] >> 6 != 2 {
+		return Err(Violation::BadVersion { offset });
+	}
+
+	let len = (usize::from(u16::from_be_bytes([header[2], header[3]])) + 1) * 4;
+	if offset + len > buf.len() {
+		return Err(Violation::Truncated { offset });
+	}
+
+	Ok(len)
+}
+
+/// Checks every rule which a compound datagram must follow, returning each
+/// [`Violation`] found (or an empty list if `buf` is valid).
+///
+/// This covers the [RFC 3550](https://tools.ietf.org/html/rfc3550#section-6.1) rules
+/// as well as the structural checks of [`validate`]: the first packet must be a
+/// sender or receiver report, a source description must carry a CNAME for the
+/// reporting SSRC, any goodbye must come last, and packet lengths must exactly
+/// cover the datagram.
+///
+/// Checking stops at the first truncated packet or bad version, as later packet
+/// boundaries cannot be trusted.
+///
+/// [`Violation`]: enum.Violation.html
+/// [`validate`]: fn.validate.html
+#[must_use]
+pub fn validate_compound(buf: &[u8]) -> Vec<Violation> {
+	let mut out = Vec::new();
+
+	match classify(buf) {
+		None => {
+			out.push(Violation::Empty);
+			return out;
+		},
+		Some(RtcpMode::ReducedSize) => out.push(Violation::NotCompound),
+		Some(RtcpMode::Compound) => {},
+	}
+
+	let reporter = buf
+		.get(4..8)
+		.map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+	let mut saw_sdes = false;
+	let mut saw_cname = false;
+	let mut bye_at = None;
+
+	let mut offset = 0;
+	while offset < buf.len() {
+		let len = match packet_extent(buf, offset) {
+			Ok(len) => len,
+			Err(e) => {
+				out.push(e);
+				break;
+			},
+		};
+		let pkt = &buf[offset..offset + len];
+		let last = offset + len == buf.len();
+
+		if let Some(at) = bye_at.take() {
+			out.push(Violation::ByeNotLast { offset: at });
+		}
+
+		if pkt[0] & 0b0010_0000 != 0 {
+			if !last {
+				out.push(Violation::PaddingNotLast { offset });
+			} else if !(1..=len - 4).contains(&usize::from(pkt[len - 1])) {
+				out.push(Violation::BadPadding { offset });
+			}
+		}
+
+		match RtcpType::new(pkt[1]) {
+			RtcpType::SourceDescription => {
+				saw_sdes = true;
+				if let Some(sdes) = SourceDescriptionPacket::new(pkt) {
+					saw_cname |= sdes
+						.chunks()
+						.any(|c| Some(c.ssrc) == reporter && c.cname().is_some());
+				}
+			},
+			RtcpType::Goodbye => bye_at = Some(offset),
+			_ => {},
+		}
+
+		offset += len;
+	}
+
+	if !saw_sdes {
+		out.push(Violation::MissingSdes);
+	} else if let (false, Some(ssrc)) = (saw_cname, reporter) {
+		out.push(Violation::MissingCname { ssrc });
+	}
+
+	out
+}
@@ -0,0 +1,261 @@
+use super::{
+	rle::{MutableSequenceBlockPacket, SequenceBlock, SequenceBlockPacket},
+	summary::{MutableStatisticsSummaryPacket, StatisticsSummary, StatisticsSummaryPacket},
+	voip::{MutableVoipMetricsPacket, VoipMetrics, VoipMetricsPacket},
+	ExtendedReport,
+	ExtendedReportPacket,
+	MutableExtendedReportPacket,
+	MutableXrBlockPacket,
+	XrBlock,
+	XrBlockPacket,
+};
+use crate::rtcp::{self, RtcpType};
+use alloc::vec::Vec;
+use pnet_macros_support::packet::MutablePacket;
+
+/// A typed report block, to be written into an [`ExtendedReport`].
+///
+/// [`ExtendedReport`]: struct.ExtendedReport.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum XrReportBlock {
+	/// Loss RLE, duplicate RLE, or packet receipt times.
+	Sequence(SequenceBlock),
+	/// Statistics summary.
+	StatisticsSummary(StatisticsSummary),
+	/// Voice over IP metrics.
+	VoipMetrics(VoipMetrics),
+	/// Any other block, whose payload is written as-is.
+	Other(XrBlock),
+}
+
+impl XrReportBlock {
+	/// Number of bytes needed to serialise this block, rounded up to a 32-bit boundary.
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		let raw = match self {
+			Self::Sequence(b) => SequenceBlockPacket::packet_size(b),
+			Self::StatisticsSummary(b) => StatisticsSummaryPacket::packet_size(b),
+			Self::VoipMetrics(b) => VoipMetricsPacket::packet_size(b),
+			Self::Other(b) => XrBlockPacket::packet_size(b),
+		};
+
+		(raw + 3) & !3
+	}
+
+	/// Serialises this block into the start of `buf`, zero-padding it to a
+	/// 32-bit boundary and filling in its `block_length`.
+	///
+	/// Returns the number of bytes written, or `None` if `buf` is too small.
+	pub fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let len = self.wire_size();
+		let buf = buf.get_mut(..len)?;
+		buf.fill(0);
+
+		match self {
+			Self::Sequence(b) => MutableSequenceBlockPacket::new(buf)?.populate(b),
+			Self::StatisticsSummary(b) => MutableStatisticsSummaryPacket::new(buf)?.populate(b),
+			Self::VoipMetrics(b) => MutableVoipMetricsPacket::new(buf)?.populate(b),
+			Self::Other(b) => MutableXrBlockPacket::new(buf)?.populate(b),
+		}
+
+		MutableXrBlockPacket::new(buf)?.set_block_length(rtcp::length_words(len));
+
+		Some(len)
+	}
+}
+
+impl From<SequenceBlock> for XrReportBlock {
+	fn from(block: SequenceBlock) -> Self {
+		Self::Sequence(block)
+	}
+}
+
+impl From<StatisticsSummary> for XrReportBlock {
+	fn from(block: StatisticsSummary) -> Self {
+		Self::StatisticsSummary(block)
+	}
+}
+
+impl From<VoipMetrics> for XrReportBlock {
+	fn from(block: VoipMetrics) -> Self {
+		Self::VoipMetrics(block)
+	}
+}
+
+impl From<XrBlock> for XrReportBlock {
+	fn from(block: XrBlock) -> Self {
+		Self::Other(block)
+	}
+}
+
+/// Assembles an [`ExtendedReport`] from a list of typed report blocks.
+///
+/// Each block's `block_length` and the packet's `pkt_length` are computed
+/// automatically.
+///
+/// [`ExtendedReport`]: struct.ExtendedReport.html
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExtendedReportBuilder {
+	ssrc: u32,
+	blocks: Vec<XrReportBlock>,
+}
+
+impl ExtendedReportBuilder {
+	/// Creates a builder for an extended report from `ssrc`, with no blocks.
+	#[must_use]
+	pub fn new(ssrc: u32) -> Self {
+		Self {
+			ssrc,
+			blocks: Vec::new(),
+		}
+	}
+
+	/// Appends a report block.
+	pub fn push(&mut self, block: impl Into<XrReportBlock>) -> &mut Self {
+		self.blocks.push(block.into());
+		self
+	}
+
+	/// Blocks which will be written, in order.
+	#[must_use]
+	pub fn blocks(&self) -> &[XrReportBlock] {
+		&self.blocks
+	}
+
+	/// Total number of bytes needed to serialise the extended report.
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		ExtendedReportPacket::minimum_packet_size()
+			+ self
+				.blocks
+				.iter()
+				.map(XrReportBlock::wire_size)
+				.sum::<usize>()
+	}
+
+	/// Serialises the extended report into the start of `buf`, returning the
+	/// total number of bytes written.
+	///
+	/// Returns `None` if `buf` is too small.
+	pub fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let len = self.wire_size();
+		let mut pkt = MutableExtendedReportPacket::new(buf.get_mut(..len)?)?;
+
+		pkt.set_version(2);
+		pkt.set_padding(0);
+		pkt.set_reserved(0);
+		pkt.set_packet_type(RtcpType::ExtendedReport);
+		pkt.set_pkt_length(rtcp::length_words(len));
+		pkt.set_ssrc(self.ssrc);
+
+		let body = pkt.payload_mut();
+		let mut offset = 0;
+		for block in &self.blocks {
+			offset += block.write(&mut body[offset..])?;
+		}
+
+		Some(len)
+	}
+
+	/// Serialises the extended report into a newly allocated buffer.
+	#[must_use]
+	pub fn to_vec(&self) -> Vec<u8> {
+		let mut out = alloc::vec![0u8; self.wire_size()];
+		let len = self.write(&mut out).unwrap_or(0);
+		out.truncate(len);
+		out
+	}
+
+	/// Builds an owned [`ExtendedReport`], *e.g.* for use with
+	/// [`CompoundBuilder`].
+	///
+	/// [`ExtendedReport`]: struct.ExtendedReport.html
+	/// [`CompoundBuilder`]: ../compound/struct.CompoundBuilder.html
+	#[must_use]
+	pub fn build(&self) -> ExtendedReport {
+		let bytes = self.to_vec();
+		let min = ExtendedReportPacket::minimum_packet_size();
+
+		ExtendedReport {
+			version: 2,
+			padding: 0,
+			reserved: 0,
+			packet_type: RtcpType::ExtendedReport,
+			pkt_length: rtcp::length_words(bytes.len()),
+			ssrc: self.ssrc,
+			payload: bytes.get(min..).map(<[u8]>::to_vec).unwrap_or_default(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtcp::{xr::XrBlockType, Rtcp};
+	use pnet_macros_support::packet::Packet;
+
+	#[test]
+	fn extended_report_round_trip() {
+		let loss = SequenceBlock {
+			block_type: XrBlockType::LossRle,
+			reserved: 0,
+			thinning: 0,
+			block_length: 0,
+			ssrc: 2,
+			begin_seq: 10.into(),
+			end_seq: 20.into(),
+			payload: alloc::vec![0x40, 0x0a, 0, 0],
+		};
+		let mut voip = VoipMetrics::new(2);
+		voip.r_factor = 90;
+		let other = XrBlock {
+			block_type: XrBlockType::Unassigned(200),
+			type_specific: 7,
+			block_length: 0,
+			payload: alloc::vec![1, 2, 3],
+		};
+
+		let mut builder = ExtendedReportBuilder::new(1);
+		builder
+			.push(loss)
+			.push(StatisticsSummary::new(2, 10.into(), 20.into()).with_loss(3))
+			.push(voip)
+			.push(other);
+		let buf = builder.to_vec();
+		assert_eq!(buf.len(), builder.wire_size());
+		assert_eq!(buf.len() % 4, 0);
+
+		let pkt = ExtendedReportPacket::new_checked(&buf).unwrap();
+		assert_eq!(pkt.get_ssrc(), 1);
+		assert_eq!(usize::from(pkt.get_pkt_length()), buf.len() / 4 - 1);
+
+		let blocks: Vec<_> = pkt.blocks().collect();
+		assert_eq!(blocks.len(), 4);
+
+		let loss = blocks[0].sequence_block().unwrap();
+		assert_eq!(loss.get_ssrc(), 2);
+		assert!(loss.rle().all(|(_, received)| received));
+		assert_eq!(loss.rle().count(), 10);
+
+		let summary = blocks[1].statistics_summary().unwrap();
+		assert_eq!(summary.sequence_span(), 10);
+		assert_eq!(summary.lost(), Some(3));
+		assert_eq!(summary.jitter(), None);
+
+		let voip = blocks[2].voip_metrics().unwrap();
+		assert_eq!(voip.r_factor_value(), Some(90));
+		assert_eq!(voip.mos_lq_value(), None);
+
+		assert_eq!(blocks[3].get_block_type(), XrBlockType::Unassigned(200));
+		assert_eq!(blocks[3].get_type_specific(), 7);
+		assert_eq!(blocks[3].get_block_length(), 1);
+		assert_eq!(blocks[3].payload(), [1, 2, 3, 0]);
+
+		let mut written = alloc::vec![0u8; buf.len()];
+		let len = Rtcp::ExtendedReport(builder.build())
+			.write(&mut written)
+			.unwrap();
+		assert_eq!(written[..len], buf[..]);
+	}
+}
@@ -0,0 +1,411 @@
+//! Extended report (XR) packets, and the report blocks they carry.
+//!
+//! See [RFC 3611](https://tools.ietf.org/html/rfc3611).
+
+mod builder;
+pub mod quality;
+pub mod rle;
+pub mod summary;
+pub mod voip;
+
+pub use builder::{ExtendedReportBuilder, XrReportBlock};
+
+use crate::{
+	error::impl_new_checked,
+	rtcp::{self, RtcpType},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::{Packet, PrimitiveValues},
+	types::{u1, u16be, u2, u32be, u5},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Extended report, carrying a sequence of report blocks from a single sender.
+///
+/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-2).
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload, but
+/// who are counted in [`length`]. The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `reserved`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `packet_type`
+/// Must be [`RtcpType::ExtendedReport`].
+///
+/// ## `pkt_length`
+/// Length of this RTCP packet in 32-bit words, minus one.
+/// Includes header and padding.
+///
+/// ## `ssrc`
+/// SSRC of the originator of this packet.
+///
+/// ## `payload`
+/// Zero or more report blocks. Use [`blocks`] to iterate over these.
+///
+/// [`length`]: #structfield.length
+/// [`blocks`]: struct.ExtendedReportPacket.html#method.blocks
+/// [`RtcpType::ExtendedReport`]: ../enum.RtcpType.html#variant.ExtendedReport
+pub struct ExtendedReport {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub reserved: u5,
+
+	#[construct_with(u8)]
+	pub packet_type: RtcpType,
+
+	pub pkt_length: u16be,
+
+	pub ssrc: u32be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	ExtendedReportPacket,
+	MutableExtendedReportPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"reserved" => 1,
+		"packet_type" => 2,
+		"pkt_length" => 4,
+		"ssrc" => 8,
+	],
+	rtcp::check_length
+);
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// A single report block within an [`ExtendedReport`].
+///
+/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-3).
+///
+/// A description of fields:
+///
+/// ## `block_type`
+/// Identifies the format of the block body.
+///
+/// ## `type_specific`
+/// Meaning depends on `block_type`.
+///
+/// ## `block_length`
+/// Length of this block in 32-bit words, minus one.
+/// Includes the header.
+///
+/// ## `payload`
+/// Block body, whose format is defined by `block_type`.
+///
+/// [`ExtendedReport`]: struct.ExtendedReport.html
+pub struct XrBlock {
+	#[construct_with(u8)]
+	pub block_type: XrBlockType,
+
+	pub type_specific: u8,
+
+	pub block_length: u16be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl ExtendedReportPacket<'_> {
+	/// Iterates over each report block in this packet.
+	#[must_use]
+	pub fn blocks(&self) -> XrBlocks<'_> {
+		XrBlocks::new(self.payload())
+	}
+}
+
+impl MutableExtendedReportPacket<'_> {
+	/// Iterates over each report block in this packet.
+	#[must_use]
+	pub fn blocks(&self) -> XrBlocks<'_> {
+		XrBlocks::new(self.payload())
+	}
+}
+
+/// Iterator over the report blocks in an [`ExtendedReport`].
+///
+/// Each block is limited to its own `block_length`, so that its payload holds
+/// exactly the block body. Iteration ends at the first truncated block.
+///
+/// [`ExtendedReport`]: struct.ExtendedReport.html
+#[derive(Clone, Debug)]
+pub struct XrBlocks<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> XrBlocks<'a> {
+	/// Iterates over each complete report block in `body`.
+	#[must_use]
+	pub fn new(body: &'a [u8]) -> Self {
+		Self { data: body }
+	}
+
+	/// Returns any bytes remaining after the last complete block.
+	#[must_use]
+	pub fn remainder(&self) -> &'a [u8] {
+		self.data
+	}
+}
+
+impl<'a> Iterator for XrBlocks<'a> {
+	type Item = XrBlockPacket<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let header = XrBlockPacket::new(self.data)?;
+		let len = (usize::from(header.get_block_length()) + 1) * 4;
+		if self.data.len() < len {
+			return None;
+		}
+
+		let (block, rest) = self.data.split_at(len);
+		self.data = rest;
+
+		XrBlockPacket::new(block)
+	}
+}
+
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+/// XR report block types, defining the format of each block body.
+///
+/// See the [IANA page] on the matter for an up-to-date-list.
+///
+/// [IANA page]: https://www.iana.org/assignments/rtcp-xr-block-types/rtcp-xr-block-types.xhtml
+pub enum XrBlockType {
+	/// Run-length encoded packet loss.
+	///
+	/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.1).
+	///
+	/// Code 1.
+	LossRle,
+
+	/// Run-length encoded packet duplication.
+	///
+	/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.2).
+	///
+	/// Code 2.
+	DuplicateRle,
+
+	/// Per-packet receipt times.
+	///
+	/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.3).
+	///
+	/// Code 3.
+	PacketReceiptTimes,
+
+	/// NTP timestamp from a non-sending receiver.
+	///
+	/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.4).
+	///
+	/// Code 4.
+	ReceiverReferenceTime,
+
+	/// Delay since last receiver reference time.
+	///
+	/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.5).
+	///
+	/// Code 5.
+	Dlrr,
+
+	/// Summary statistics over a sequence number range.
+	///
+	/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.6).
+	///
+	/// Code 6.
+	StatisticsSummary,
+
+	/// Call quality metrics for voice over IP.
+	///
+	/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.7).
+	///
+	/// Code 7.
+	VoipMetrics,
+
+	/// Run-length encoded loss after repair.
+	///
+	/// See [RFC 5725](https://tools.ietf.org/html/rfc5725).
+	///
+	/// Code 8.
+	PostRepairLossRle,
+
+	/// Multicast acquisition reporting.
+	///
+	/// See [RFC 6332](https://tools.ietf.org/html/rfc6332).
+	///
+	/// Code 9.
+	MulticastAcquisition,
+
+	/// Inter-destination media synchronisation.
+	///
+	/// See [RFC 7272](https://tools.ietf.org/html/rfc7272).
+	///
+	/// Code 10.
+	Idms,
+
+	/// Explicit congestion notification summary.
+	///
+	/// See [RFC 6679](https://tools.ietf.org/html/rfc6679#section-5.2).
+	///
+	/// Code 11.
+	EcnSummary,
+
+	/// Measurement information.
+	///
+	/// See [RFC 6776](https://tools.ietf.org/html/rfc6776).
+	///
+	/// Code 12.
+	MeasurementInformation,
+
+	/// Packet delay variation metrics.
+	///
+	/// See [RFC 6798](https://tools.ietf.org/html/rfc6798).
+	///
+	/// Code 13.
+	PacketDelayVariation,
+
+	/// Delay metrics.
+	///
+	/// See [RFC 6843](https://tools.ietf.org/html/rfc6843).
+	///
+	/// Code 14.
+	Delay,
+
+	/// Burst/gap loss summary statistics.
+	///
+	/// See [RFC 7004](https://tools.ietf.org/html/rfc7004#section-3).
+	///
+	/// Code 15.
+	BurstGapLossSummary,
+
+	/// Burst/gap discard summary statistics.
+	///
+	/// See [RFC 7004](https://tools.ietf.org/html/rfc7004#section-4).
+	///
+	/// Code 16.
+	BurstGapDiscardSummary,
+
+	/// Frame impairment statistics summary.
+	///
+	/// See [RFC 7004](https://tools.ietf.org/html/rfc7004#section-5).
+	///
+	/// Code 17.
+	FrameImpairmentSummary,
+
+	/// Burst/gap loss metrics.
+	///
+	/// See [RFC 6958](https://tools.ietf.org/html/rfc6958).
+	///
+	/// Code 18.
+	BurstGapLoss,
+
+	/// Burst/gap discard metrics.
+	///
+	/// See [RFC 7003](https://tools.ietf.org/html/rfc7003).
+	///
+	/// Code 19.
+	BurstGapDiscard,
+
+	/// MPEG2 transport stream PSI-independent decodability statistics.
+	///
+	/// See [RFC 6990](https://tools.ietf.org/html/rfc6990).
+	///
+	/// Code 20.
+	Mpeg2TsDecodability,
+
+	/// De-jitter buffer metrics.
+	///
+	/// See [RFC 7005](https://tools.ietf.org/html/rfc7005).
+	///
+	/// Code 21.
+	DeJitterBuffer,
+
+	/// Discard count metrics.
+	///
+	/// See [RFC 7002](https://tools.ietf.org/html/rfc7002).
+	///
+	/// Code 22.
+	DiscardCount,
+
+	/// Unknown or unassigned block type.
+	Unassigned(u8),
+}
+
+impl XrBlockType {
+	#[must_use]
+	pub fn new(val: u8) -> Self {
+		match val {
+			1 => Self::LossRle,
+			2 => Self::DuplicateRle,
+			3 => Self::PacketReceiptTimes,
+			4 => Self::ReceiverReferenceTime,
+			5 => Self::Dlrr,
+			6 => Self::StatisticsSummary,
+			7 => Self::VoipMetrics,
+			8 => Self::PostRepairLossRle,
+			9 => Self::MulticastAcquisition,
+			10 => Self::Idms,
+			11 => Self::EcnSummary,
+			12 => Self::MeasurementInformation,
+			13 => Self::PacketDelayVariation,
+			14 => Self::Delay,
+			15 => Self::BurstGapLossSummary,
+			16 => Self::BurstGapDiscardSummary,
+			17 => Self::FrameImpairmentSummary,
+			18 => Self::BurstGapLoss,
+			19 => Self::BurstGapDiscard,
+			20 => Self::Mpeg2TsDecodability,
+			21 => Self::DeJitterBuffer,
+			22 => Self::DiscardCount,
+			_ => Self::Unassigned(val),
+		}
+	}
+}
+
+impl PrimitiveValues for XrBlockType {
+	type T = (u8,);
+
+	fn to_primitive_values(&self) -> Self::T {
+		match self {
+			Self::LossRle => (1,),
+			Self::DuplicateRle => (2,),
+			Self::PacketReceiptTimes => (3,),
+			Self::ReceiverReferenceTime => (4,),
+			Self::Dlrr => (5,),
+			Self::StatisticsSummary => (6,),
+			Self::VoipMetrics => (7,),
+			Self::PostRepairLossRle => (8,),
+			Self::MulticastAcquisition => (9,),
+			Self::Idms => (10,),
+			Self::EcnSummary => (11,),
+			Self::MeasurementInformation => (12,),
+			Self::PacketDelayVariation => (13,),
+			Self::Delay => (14,),
+			Self::BurstGapLossSummary => (15,),
+			Self::BurstGapDiscardSummary => (16,),
+			Self::FrameImpairmentSummary => (17,),
+			Self::BurstGapLoss => (18,),
+			Self::BurstGapDiscard => (19,),
+			Self::Mpeg2TsDecodability => (20,),
+			Self::DeJitterBuffer => (21,),
+			Self::DiscardCount => (22,),
+
+			Self::Unassigned(val) => (*val,),
+		}
+	}
+}
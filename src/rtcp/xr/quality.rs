@@ -0,0 +1,148 @@
+//! Call quality estimation using a simplified [ITU-T G.107] E-model.
+//!
+//! This produces R factor and MOS estimates from loss and delay measurements,
+//! either taken from a [`VoipMetricsPacket`] or from a receiver's own statistics.
+//!
+//! [ITU-T G.107]: https://www.itu.int/rec/T-REC-G.107
+//! [`VoipMetricsPacket`]: ../voip/struct.VoipMetricsPacket.html
+
+use super::voip::{VoipMetrics, VoipMetricsPacket};
+use core::time::Duration;
+
+/// Codec-dependent parameters of the E-model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EModel {
+	/// Basic signal-to-noise ratio, before any impairments.
+	pub r0: f64,
+	/// Equipment impairment factor of the codec, with no packet loss.
+	pub ie: f64,
+	/// Packet loss robustness factor of the codec.
+	pub bpl: f64,
+}
+
+impl EModel {
+	/// G.711 without packet loss concealment.
+	pub const G711: Self = Self::new(0.0, 4.3);
+
+	/// G.711 with packet loss concealment (G.711 Appendix I).
+	pub const G711_PLC: Self = Self::new(0.0, 25.1);
+
+	/// G.729A with voice activity detection.
+	pub const G729A: Self = Self::new(11.0, 19.0);
+
+	/// Creates a model for a codec with equipment impairment `ie` and packet
+	/// loss robustness `bpl`, using the default basic signal-to-noise ratio.
+	#[must_use]
+	pub const fn new(ie: f64, bpl: f64) -> Self {
+		Self { r0: 93.2, ie, bpl }
+	}
+
+	/// Computes the effective equipment impairment under packet loss.
+	///
+	/// `loss` is the fraction of packets lost or discarded, and `burst_ratio`
+	/// is `1.0` for random loss, or higher for bursty loss.
+	#[must_use]
+	pub fn effective_impairment(&self, loss: f64, burst_ratio: f64) -> f64 {
+		let ppl = (loss * 100.0).clamp(0.0, 100.0);
+		let burst_ratio = burst_ratio.max(1.0);
+
+		self.ie + (95.0 - self.ie) * ppl / (ppl / burst_ratio + self.bpl)
+	}
+
+	/// Computes the R factor (`0`–`100`) given packet loss and one-way
+	/// mouth-to-ear delay.
+	#[must_use]
+	pub fn r_factor(&self, loss: f64, burst_ratio: f64, one_way_delay: Duration) -> f64 {
+		let r = self.r0
+			- delay_impairment(one_way_delay)
+			- self.effective_impairment(loss, burst_ratio);
+
+		r.clamp(0.0, 100.0)
+	}
+
+	/// Estimates listening and conversational quality given packet loss and
+	/// one-way mouth-to-ear delay.
+	///
+	/// Listening quality ignores delay, while conversational quality accounts for it.
+	#[must_use]
+	pub fn estimate(
+		&self,
+		loss: f64,
+		burst_ratio: f64,
+		one_way_delay: Duration,
+	) -> QualityEstimate {
+		let r_factor = self.r_factor(loss, burst_ratio, one_way_delay);
+		let r_listening = self.r_factor(loss, burst_ratio, Duration::ZERO);
+
+		QualityEstimate {
+			r_factor,
+			mos_lq: mos_from_r(r_listening),
+			mos_cq: mos_from_r(r_factor),
+		}
+	}
+}
+
+/// R factor and mean opinion score estimates for a call.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityEstimate {
+	/// Conversational R factor, from `0` to `100`.
+	pub r_factor: f64,
+	/// Listening quality MOS, from `1.0` to `4.5`.
+	pub mos_lq: f64,
+	/// Conversational quality MOS, from `1.0` to `4.5`.
+	pub mos_cq: f64,
+}
+
+/// Computes the delay impairment factor for a one-way mouth-to-ear delay.
+#[must_use]
+pub fn delay_impairment(one_way_delay: Duration) -> f64 {
+	let d = one_way_delay.as_secs_f64() * 1000.0;
+	let excess = (d - 177.3).max(0.0);
+
+	0.024 * d + 0.11 * excess
+}
+
+/// Converts an R factor into an estimated mean opinion score.
+#[must_use]
+pub fn mos_from_r(r: f64) -> f64 {
+	if r <= 0.0 {
+		1.0
+	} else if r >= 100.0 {
+		4.5
+	} else {
+		1.0 + 0.035 * r + 7.0e-6 * r * (r - 60.0) * (100.0 - r)
+	}
+}
+
+impl VoipMetricsPacket<'_> {
+	/// Estimates call quality from the loss, discard and delay measurements in
+	/// this block.
+	///
+	/// Loss is assumed to be random, and one-way delay is taken to be half the
+	/// round trip delay plus end system delay.
+	#[must_use]
+	pub fn estimate_quality(&self, model: &EModel) -> QualityEstimate {
+		let loss = self.loss_fraction() + self.discard_fraction();
+		let delay = Duration::from_millis(
+			u64::from(self.get_round_trip_delay()) / 2 + u64::from(self.get_end_system_delay()),
+		);
+
+		model.estimate(loss, 1.0, delay)
+	}
+}
+
+impl VoipMetrics {
+	/// Fills in the R factor and MOS fields from a quality estimate.
+	#[must_use]
+	pub fn with_quality(mut self, estimate: &QualityEstimate) -> Self {
+		// Each value is clamped into range before conversion.
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		{
+			self.r_factor = estimate.r_factor.clamp(0.0, 100.0) as u8;
+			self.mos_lq = (estimate.mos_lq.clamp(1.0, 5.0) * 10.0) as u8;
+			self.mos_cq = (estimate.mos_cq.clamp(1.0, 5.0) * 10.0) as u8;
+		}
+
+		self
+	}
+}
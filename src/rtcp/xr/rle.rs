@@ -0,0 +1,352 @@
+use super::{XrBlockPacket, XrBlockType};
+use crate::wrap::Wrap16;
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u16be, u32be, u4},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Report block describing individual packets within a range of sequence numbers.
+///
+/// This is shared by loss RLE, duplicate RLE and packet receipt times blocks.
+///
+/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.1).
+///
+/// A description of fields:
+///
+/// ## `block_type`
+/// One of [`XrBlockType::LossRle`], [`XrBlockType::DuplicateRle`] or
+/// [`XrBlockType::PacketReceiptTimes`].
+///
+/// ## `reserved`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `thinning`
+/// Only sequence numbers which are multiples of `2^thinning` are reported on.
+///
+/// ## `block_length`
+/// Length of this block in 32-bit words, minus one.
+///
+/// ## `ssrc`
+/// SSRC of the source being reported on.
+///
+/// ## `begin_seq`, `end_seq`
+/// First sequence number covered by this block, and one past the last.
+///
+/// ## `payload`
+/// Run-length encoded chunks (see [`rle`]), or receipt times (see [`receipt_times`]).
+///
+/// [`XrBlockType::LossRle`]: ../enum.XrBlockType.html#variant.LossRle
+/// [`XrBlockType::DuplicateRle`]: ../enum.XrBlockType.html#variant.DuplicateRle
+/// [`XrBlockType::PacketReceiptTimes`]: ../enum.XrBlockType.html#variant.PacketReceiptTimes
+/// [`rle`]: struct.SequenceBlockPacket.html#method.rle
+/// [`receipt_times`]: struct.SequenceBlockPacket.html#method.receipt_times
+pub struct SequenceBlock {
+	#[construct_with(u8)]
+	pub block_type: XrBlockType,
+
+	pub reserved: u4,
+
+	pub thinning: u4,
+
+	pub block_length: u16be,
+
+	pub ssrc: u32be,
+
+	#[construct_with(u16be)]
+	pub begin_seq: Wrap16,
+
+	#[construct_with(u16be)]
+	pub end_seq: Wrap16,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+/// A single 16-bit chunk of a loss or duplicate RLE block.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RleChunk {
+	/// `length` consecutive sequence numbers, each with the same state.
+	Run {
+		/// Whether each packet was received (loss RLE) or duplicated (duplicate RLE).
+		value: bool,
+		/// Number of sequence numbers covered.
+		length: u16,
+	},
+	/// The state of the next 15 sequence numbers, most significant bit first.
+	Vector(u16),
+	/// Null chunk, used as padding at the end of the block.
+	Terminator,
+}
+
+impl RleChunk {
+	/// Decodes a raw chunk.
+	#[must_use]
+	pub fn new(chunk: u16) -> Self {
+		if chunk == 0 {
+			Self::Terminator
+		} else if chunk & 0x8000 == 0 {
+			Self::Run {
+				value: chunk & 0x4000 != 0,
+				length: chunk & 0x3fff,
+			}
+		} else {
+			Self::Vector(chunk & 0x7fff)
+		}
+	}
+
+	/// Returns the number of sequence numbers described by this chunk.
+	#[must_use]
+	pub fn len(self) -> u16 {
+		match self {
+			Self::Run { length, .. } => length,
+			Self::Vector(_) => 15,
+			Self::Terminator => 0,
+		}
+	}
+
+	/// Returns `true` if this chunk describes no sequence numbers.
+	#[must_use]
+	pub fn is_empty(self) -> bool {
+		self.len() == 0
+	}
+
+	/// Returns the state of the sequence number at `index` within this chunk.
+	#[must_use]
+	pub fn get(self, index: u16) -> bool {
+		match self {
+			Self::Run { value, .. } => value,
+			Self::Vector(bits) => (bits >> (14 - index)) & 1 != 0,
+			Self::Terminator => false,
+		}
+	}
+}
+
+impl SequenceBlockPacket<'_> {
+	/// Returns the number of sequence numbers reported on, after thinning.
+	#[must_use]
+	pub fn reported_count(&self) -> u16 {
+		let step = self.step();
+		let begin = u16::from(self.get_begin_seq());
+		let span = u32::from(u16::from(self.get_end_seq()).wrapping_sub(begin));
+		let skip = u32::from(begin.wrapping_neg() % step);
+		let step = u32::from(step);
+
+		u16::try_from((span.saturating_sub(skip) + step - 1) / step).unwrap_or(u16::MAX)
+	}
+
+	/// Returns the first sequence number reported on, after thinning.
+	fn first_reported(&self) -> Wrap16 {
+		let begin = u16::from(self.get_begin_seq());
+		self.get_begin_seq() + begin.wrapping_neg() % self.step()
+	}
+
+	fn step(&self) -> u16 {
+		1 << self.get_thinning()
+	}
+
+	/// Iterates over the raw chunks of a loss or duplicate RLE block.
+	#[must_use]
+	pub fn rle_chunks(&self) -> RleChunks<'_> {
+		RleChunks::new(self.payload())
+	}
+
+	/// Iterates over each sequence number reported on by a loss or duplicate
+	/// RLE block, alongside whether it was received (or duplicated).
+	#[must_use]
+	pub fn rle(&self) -> RleSequences<'_> {
+		RleSequences {
+			chunks: self.rle_chunks(),
+			chunk: RleChunk::Terminator,
+			index: 0,
+			sequence: self.first_reported(),
+			step: self.step(),
+			remaining: self.reported_count(),
+		}
+	}
+
+	/// Iterates over each sequence number reported on by a packet receipt times
+	/// block, alongside its receipt time.
+	///
+	/// Receipt times are in RTP timestamp units.
+	#[must_use]
+	pub fn receipt_times(&self) -> ReceiptTimes<'_> {
+		ReceiptTimes {
+			data: self.payload(),
+			sequence: self.first_reported(),
+			step: self.step(),
+			remaining: self.reported_count(),
+		}
+	}
+}
+
+impl XrBlockPacket<'_> {
+	/// Returns a view over this block as a loss RLE, duplicate RLE, or packet receipt
+	/// times block.
+	///
+	/// This returns `None` if the block type is not one of these, or if the block
+	/// is too short.
+	#[must_use]
+	pub fn sequence_block(&self) -> Option<SequenceBlockPacket<'_>> {
+		if matches!(
+			self.get_block_type(),
+			XrBlockType::LossRle | XrBlockType::DuplicateRle | XrBlockType::PacketReceiptTimes
+		) {
+			SequenceBlockPacket::new(self.packet())
+		} else {
+			None
+		}
+	}
+}
+
+/// Iterator over the chunks of a loss or duplicate RLE block.
+///
+/// Iteration ends at the first terminating null chunk.
+#[derive(Clone, Debug)]
+pub struct RleChunks<'a> {
+	data: &'a [u8],
+}
+
+impl<'a> RleChunks<'a> {
+	/// Iterates over each chunk in `body`.
+	#[must_use]
+	pub fn new(body: &'a [u8]) -> Self {
+		Self { data: body }
+	}
+}
+
+impl Iterator for RleChunks<'_> {
+	type Item = RleChunk;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.data.len() < 2 {
+			return None;
+		}
+
+		let (chunk, rest) = self.data.split_at(2);
+		self.data = rest;
+
+		match RleChunk::new(u16::from_be_bytes([chunk[0], chunk[1]])) {
+			RleChunk::Terminator => {
+				self.data = &[];
+				None
+			},
+			chunk => Some(chunk),
+		}
+	}
+}
+
+/// Iterator over the state of each sequence number in a loss or duplicate RLE block.
+#[derive(Clone, Debug)]
+pub struct RleSequences<'a> {
+	chunks: RleChunks<'a>,
+	chunk: RleChunk,
+	index: u16,
+	sequence: Wrap16,
+	step: u16,
+	remaining: u16,
+}
+
+impl Iterator for RleSequences<'_> {
+	type Item = (Wrap16, bool);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.remaining == 0 {
+			return None;
+		}
+
+		while self.index >= self.chunk.len() {
+			self.chunk = self.chunks.next()?;
+			self.index = 0;
+		}
+
+		let out = (self.sequence, self.chunk.get(self.index));
+		self.index += 1;
+		self.sequence += self.step;
+		self.remaining -= 1;
+
+		Some(out)
+	}
+}
+
+/// Iterator over the receipt time of each sequence number in a packet receipt
+/// times block.
+#[derive(Clone, Debug)]
+pub struct ReceiptTimes<'a> {
+	data: &'a [u8],
+	sequence: Wrap16,
+	step: u16,
+	remaining: u16,
+}
+
+impl Iterator for ReceiptTimes<'_> {
+	type Item = (Wrap16, u32);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.remaining == 0 || self.data.len() < 4 {
+			return None;
+		}
+
+		let (time, rest) = self.data.split_at(4);
+		self.data = rest;
+
+		let out = (
+			self.sequence,
+			u32::from_be_bytes([time[0], time[1], time[2], time[3]]),
+		);
+		self.sequence += self.step;
+		self.remaining -= 1;
+
+		Some(out)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn loss_rle(thinning: u8, begin: u16, end: u16, chunks: &[u16]) -> Vec<u8> {
+		let mut out = alloc::vec![1, thinning, 0, 0, 0, 0, 0, 1];
+		out.extend_from_slice(&begin.to_be_bytes());
+		out.extend_from_slice(&end.to_be_bytes());
+		for chunk in chunks {
+			out.extend_from_slice(&chunk.to_be_bytes());
+		}
+		out
+	}
+
+	#[test]
+	fn reported_count_includes_partial_step() {
+		let block = loss_rle(1, 0, 5, &[0x4003, 0]);
+		let pkt = SequenceBlockPacket::new(&block).unwrap();
+		assert_eq!(pkt.reported_count(), 3);
+		assert_eq!(
+			pkt.rle().map(|(seq, _)| u16::from(seq)).collect::<Vec<_>>(),
+			[0, 2, 4]
+		);
+
+		let block = loss_rle(1, 1, 6, &[0x4002, 0]);
+		let pkt = SequenceBlockPacket::new(&block).unwrap();
+		assert_eq!(pkt.reported_count(), 2);
+		assert_eq!(
+			pkt.rle().map(|(seq, _)| u16::from(seq)).collect::<Vec<_>>(),
+			[2, 4]
+		);
+	}
+
+	#[test]
+	fn reported_count_wraps_and_handles_exact_multiples() {
+		let block = loss_rle(2, 65_534, 8, &[]);
+		let pkt = SequenceBlockPacket::new(&block).unwrap();
+		assert_eq!(pkt.reported_count(), 2);
+
+		let block = loss_rle(0, 10, 10, &[]);
+		assert_eq!(
+			SequenceBlockPacket::new(&block).unwrap().reported_count(),
+			0
+		);
+	}
+}
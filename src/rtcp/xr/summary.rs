@@ -0,0 +1,237 @@
+use super::{XrBlockPacket, XrBlockType};
+use crate::wrap::Wrap16;
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u16be, u2, u3, u32be},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Statistics summary report block, describing packets received from a single
+/// source over a range of sequence numbers.
+///
+/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.6).
+///
+/// A description of fields:
+///
+/// ## `block_type`
+/// Must be [`XrBlockType::StatisticsSummary`].
+///
+/// ## `has_loss`, `has_duplicates`, `has_jitter`
+/// Set to `1` if the loss, duplicate and jitter fields respectively are valid.
+///
+/// ## `ttl_or_hop_limit`
+/// Meaning of the TTL fields: `0` if unused, `1` for IPv4 TTL, `2` for IPv6 hop limit.
+///
+/// ## `reserved`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `block_length`
+/// Must be `9`.
+///
+/// ## `ssrc`
+/// SSRC of the source being reported on.
+///
+/// ## `begin_seq`, `end_seq`
+/// First sequence number covered by this block, and one past the last.
+///
+/// ## `lost_packets`, `dup_packets`
+/// Number of packets lost and duplicated within the sequence range.
+///
+/// ## `min_jitter`, `max_jitter`, `mean_jitter`, `dev_jitter`
+/// Relative transit time statistics in timestamp units, as defined by
+/// [RFC 3550](https://tools.ietf.org/html/rfc3550#section-6.4.1).
+///
+/// ## `min_ttl`, `max_ttl`, `mean_ttl`, `dev_ttl`
+/// TTL or hop limit statistics of received packets.
+///
+/// ## `payload`
+/// Remainder of the block. Should be empty.
+///
+/// [`XrBlockType::StatisticsSummary`]: ../enum.XrBlockType.html#variant.StatisticsSummary
+pub struct StatisticsSummary {
+	#[construct_with(u8)]
+	pub block_type: XrBlockType,
+
+	pub has_loss: u1,
+
+	pub has_duplicates: u1,
+
+	pub has_jitter: u1,
+
+	pub ttl_or_hop_limit: u2,
+
+	pub reserved: u3,
+
+	pub block_length: u16be,
+
+	pub ssrc: u32be,
+
+	#[construct_with(u16be)]
+	pub begin_seq: Wrap16,
+
+	#[construct_with(u16be)]
+	pub end_seq: Wrap16,
+
+	pub lost_packets: u32be,
+
+	pub dup_packets: u32be,
+
+	pub min_jitter: u32be,
+
+	pub max_jitter: u32be,
+
+	pub mean_jitter: u32be,
+
+	pub dev_jitter: u32be,
+
+	pub min_ttl: u8,
+
+	pub max_ttl: u8,
+
+	pub mean_ttl: u8,
+
+	pub dev_ttl: u8,
+
+	#[payload]
+	#[length = "0"]
+	pub payload: Vec<u8>,
+}
+
+impl StatisticsSummary {
+	/// Creates a statistics summary about `ssrc` for sequence numbers from `begin_seq`
+	/// up to (but excluding) `end_seq`, with no valid measurements.
+	#[must_use]
+	pub fn new(ssrc: u32, begin_seq: Wrap16, end_seq: Wrap16) -> Self {
+		Self {
+			block_type: XrBlockType::StatisticsSummary,
+			has_loss: 0,
+			has_duplicates: 0,
+			has_jitter: 0,
+			ttl_or_hop_limit: 0,
+			reserved: 0,
+			block_length: 9,
+			ssrc,
+			begin_seq,
+			end_seq,
+			lost_packets: 0,
+			dup_packets: 0,
+			min_jitter: 0,
+			max_jitter: 0,
+			mean_jitter: 0,
+			dev_jitter: 0,
+			min_ttl: 0,
+			max_ttl: 0,
+			mean_ttl: 0,
+			dev_ttl: 0,
+			payload: Vec::new(),
+		}
+	}
+
+	/// Sets the number of lost packets, marking it as valid.
+	#[must_use]
+	pub fn with_loss(mut self, lost_packets: u32) -> Self {
+		self.has_loss = 1;
+		self.lost_packets = lost_packets;
+		self
+	}
+
+	/// Sets the number of duplicate packets, marking it as valid.
+	#[must_use]
+	pub fn with_duplicates(mut self, dup_packets: u32) -> Self {
+		self.has_duplicates = 1;
+		self.dup_packets = dup_packets;
+		self
+	}
+
+	/// Sets the minimum, maximum, mean and standard deviation of jitter,
+	/// marking them as valid.
+	#[must_use]
+	pub fn with_jitter(mut self, min: u32, max: u32, mean: u32, dev: u32) -> Self {
+		self.has_jitter = 1;
+		self.min_jitter = min;
+		self.max_jitter = max;
+		self.mean_jitter = mean;
+		self.dev_jitter = dev;
+		self
+	}
+
+	/// Sets the minimum, maximum, mean and standard deviation of IPv4 TTL
+	/// (or IPv6 hop limit if `ipv6` is set), marking them as valid.
+	#[must_use]
+	pub fn with_ttl(mut self, ipv6: bool, min: u8, max: u8, mean: u8, dev: u8) -> Self {
+		self.ttl_or_hop_limit = if ipv6 { 2 } else { 1 };
+		self.min_ttl = min;
+		self.max_ttl = max;
+		self.mean_ttl = mean;
+		self.dev_ttl = dev;
+		self
+	}
+}
+
+impl StatisticsSummaryPacket<'_> {
+	/// Returns the number of sequence numbers covered by this block.
+	#[must_use]
+	pub fn sequence_span(&self) -> u16 {
+		u16::from(self.get_end_seq() - u16::from(self.get_begin_seq()))
+	}
+
+	/// Returns the number of lost packets, if reported.
+	#[must_use]
+	pub fn lost(&self) -> Option<u32> {
+		(self.get_has_loss() != 0).then(|| self.get_lost_packets())
+	}
+
+	/// Returns the number of duplicate packets, if reported.
+	#[must_use]
+	pub fn duplicates(&self) -> Option<u32> {
+		(self.get_has_duplicates() != 0).then(|| self.get_dup_packets())
+	}
+
+	/// Returns the minimum, maximum, mean and standard deviation of jitter,
+	/// if reported.
+	#[must_use]
+	pub fn jitter(&self) -> Option<[u32; 4]> {
+		(self.get_has_jitter() != 0).then(|| {
+			[
+				self.get_min_jitter(),
+				self.get_max_jitter(),
+				self.get_mean_jitter(),
+				self.get_dev_jitter(),
+			]
+		})
+	}
+
+	/// Returns the minimum, maximum, mean and standard deviation of TTL or
+	/// hop limit, if reported.
+	#[must_use]
+	pub fn ttl(&self) -> Option<[u8; 4]> {
+		matches!(self.get_ttl_or_hop_limit(), 1 | 2).then(|| {
+			[
+				self.get_min_ttl(),
+				self.get_max_ttl(),
+				self.get_mean_ttl(),
+				self.get_dev_ttl(),
+			]
+		})
+	}
+}
+
+impl XrBlockPacket<'_> {
+	/// Returns a view over this block as a statistics summary.
+	///
+	/// This returns `None` if the block type is not [`XrBlockType::StatisticsSummary`],
+	/// or if the block is too short.
+	///
+	/// [`XrBlockType::StatisticsSummary`]: enum.XrBlockType.html#variant.StatisticsSummary
+	#[must_use]
+	pub fn statistics_summary(&self) -> Option<StatisticsSummaryPacket<'_>> {
+		if self.get_block_type() == XrBlockType::StatisticsSummary {
+			StatisticsSummaryPacket::new(self.packet())
+		} else {
+			None
+		}
+	}
+}
@@ -0,0 +1,251 @@
+use super::{XrBlockPacket, XrBlockType};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u16be, u2, u32be, u4},
+};
+
+/// Value used by 8-bit voice over IP metrics to indicate that a measurement is unavailable.
+pub const UNAVAILABLE: u8 = 127;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Voice over IP metrics report block, describing call quality for a single source.
+///
+/// See [RFC 3611](https://tools.ietf.org/html/rfc3611#section-4.7).
+///
+/// A description of fields:
+///
+/// ## `block_type`
+/// Must be [`XrBlockType::VoipMetrics`].
+///
+/// ## `reserved`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `block_length`
+/// Must be `8`.
+///
+/// ## `ssrc`
+/// SSRC of the source being reported on.
+///
+/// ## `loss_rate`, `discard_rate`
+/// Fraction of packets lost within the network, or discarded due to late or
+/// early arrival, as a fixed point number with the binary point at the left edge.
+///
+/// ## `burst_density`, `gap_density`
+/// Fraction of packets lost or discarded within bursts (and gaps), as a fixed
+/// point number with the binary point at the left edge.
+///
+/// ## `burst_duration`, `gap_duration`
+/// Mean duration of bursts (and gaps) in milliseconds.
+///
+/// ## `round_trip_delay`
+/// Most recent round trip time between RTP interfaces in milliseconds.
+///
+/// ## `end_system_delay`
+/// Most recent estimate of internal delay (encoding, jitter buffering, *etc.*)
+/// in milliseconds.
+///
+/// ## `signal_level`, `noise_level`
+/// Signed voice and silent period levels in dBm0, or [`UNAVAILABLE`].
+///
+/// ## `rerl`
+/// Residual echo return loss in dB, or [`UNAVAILABLE`].
+///
+/// ## `gmin`
+/// Gap threshold, in packets, used to determine burst and gap metrics.
+///
+/// ## `r_factor`, `ext_r_factor`
+/// Call quality R factor on a scale of `0`–`100`, or [`UNAVAILABLE`].
+///
+/// ## `mos_lq`, `mos_cq`
+/// Listening and conversational quality mean opinion scores multiplied by
+/// `10` (*i.e.*, `10`–`50`), or [`UNAVAILABLE`].
+///
+/// ## `plc`
+/// Packet loss concealment: `0` unspecified, `1` disabled, `2` enhanced, `3` standard.
+///
+/// ## `jb_adaptive`
+/// Jitter buffer mode: `0` unknown, `2` non-adaptive, `3` adaptive.
+///
+/// ## `jb_rate`
+/// Adjustment rate of an adaptive jitter buffer.
+///
+/// ## `reserved_2`
+/// Must be set to `0`, and ignored by receivers.
+///
+/// ## `jb_nominal`, `jb_maximum`, `jb_abs_max`
+/// Current nominal, maximum and absolute maximum jitter buffer delay in milliseconds.
+///
+/// ## `payload`
+/// Remainder of the block. Should be empty.
+///
+/// [`XrBlockType::VoipMetrics`]: ../enum.XrBlockType.html#variant.VoipMetrics
+/// [`UNAVAILABLE`]: constant.UNAVAILABLE.html
+pub struct VoipMetrics {
+	#[construct_with(u8)]
+	pub block_type: XrBlockType,
+
+	pub reserved: u8,
+
+	pub block_length: u16be,
+
+	pub ssrc: u32be,
+
+	pub loss_rate: u8,
+
+	pub discard_rate: u8,
+
+	pub burst_density: u8,
+
+	pub gap_density: u8,
+
+	pub burst_duration: u16be,
+
+	pub gap_duration: u16be,
+
+	pub round_trip_delay: u16be,
+
+	pub end_system_delay: u16be,
+
+	pub signal_level: u8,
+
+	pub noise_level: u8,
+
+	pub rerl: u8,
+
+	pub gmin: u8,
+
+	pub r_factor: u8,
+
+	pub ext_r_factor: u8,
+
+	pub mos_lq: u8,
+
+	pub mos_cq: u8,
+
+	pub plc: u2,
+
+	pub jb_adaptive: u2,
+
+	pub jb_rate: u4,
+
+	pub reserved_2: u8,
+
+	pub jb_nominal: u16be,
+
+	pub jb_maximum: u16be,
+
+	pub jb_abs_max: u16be,
+
+	#[payload]
+	#[length = "0"]
+	pub payload: Vec<u8>,
+}
+
+/// Converts an 8-bit measurement, mapping [`UNAVAILABLE`] to `None`.
+///
+/// [`UNAVAILABLE`]: constant.UNAVAILABLE.html
+fn available(val: u8) -> Option<u8> {
+	(val != UNAVAILABLE).then_some(val)
+}
+
+impl VoipMetrics {
+	/// Creates a voice over IP metrics block about `ssrc`, with every measurement
+	/// either zeroed or marked as unavailable.
+	#[must_use]
+	pub fn new(ssrc: u32) -> Self {
+		Self {
+			block_type: XrBlockType::VoipMetrics,
+			reserved: 0,
+			block_length: 8,
+			ssrc,
+			loss_rate: 0,
+			discard_rate: 0,
+			burst_density: 0,
+			gap_density: 0,
+			burst_duration: 0,
+			gap_duration: 0,
+			round_trip_delay: 0,
+			end_system_delay: 0,
+			signal_level: UNAVAILABLE,
+			noise_level: UNAVAILABLE,
+			rerl: UNAVAILABLE,
+			gmin: 16,
+			r_factor: UNAVAILABLE,
+			ext_r_factor: UNAVAILABLE,
+			mos_lq: UNAVAILABLE,
+			mos_cq: UNAVAILABLE,
+			plc: 0,
+			jb_adaptive: 0,
+			jb_rate: 0,
+			reserved_2: 0,
+			jb_nominal: 0,
+			jb_maximum: 0,
+			jb_abs_max: 0,
+			payload: Vec::new(),
+		}
+	}
+}
+
+impl VoipMetricsPacket<'_> {
+	/// Returns the fraction of packets lost within the network, in `[0, 1)`.
+	#[must_use]
+	pub fn loss_fraction(&self) -> f64 {
+		f64::from(self.get_loss_rate()) / 256.0
+	}
+
+	/// Returns the fraction of packets discarded by the jitter buffer, in `[0, 1)`.
+	#[must_use]
+	pub fn discard_fraction(&self) -> f64 {
+		f64::from(self.get_discard_rate()) / 256.0
+	}
+
+	/// Returns the voice signal level in dBm0, if measured.
+	#[must_use]
+	pub fn signal_level_dbm(&self) -> Option<i8> {
+		available(self.get_signal_level()).map(|v| i8::from_be_bytes([v]))
+	}
+
+	/// Returns the noise level in dBm0, if measured.
+	#[must_use]
+	pub fn noise_level_dbm(&self) -> Option<i8> {
+		available(self.get_noise_level()).map(|v| i8::from_be_bytes([v]))
+	}
+
+	/// Returns the call quality R factor, if measured.
+	#[must_use]
+	pub fn r_factor_value(&self) -> Option<u8> {
+		available(self.get_r_factor())
+	}
+
+	/// Returns the listening quality MOS (`1.0`–`5.0`), if measured.
+	#[must_use]
+	pub fn mos_lq_value(&self) -> Option<f64> {
+		available(self.get_mos_lq()).map(|v| f64::from(v) / 10.0)
+	}
+
+	/// Returns the conversational quality MOS (`1.0`–`5.0`), if measured.
+	#[must_use]
+	pub fn mos_cq_value(&self) -> Option<f64> {
+		available(self.get_mos_cq()).map(|v| f64::from(v) / 10.0)
+	}
+}
+
+impl XrBlockPacket<'_> {
+	/// Returns a view over this block as voice over IP metrics.
+	///
+	/// This returns `None` if the block type is not [`XrBlockType::VoipMetrics`],
+	/// or if the block is too short.
+	///
+	/// [`XrBlockType::VoipMetrics`]: enum.XrBlockType.html#variant.VoipMetrics
+	#[must_use]
+	pub fn voip_metrics(&self) -> Option<VoipMetricsPacket<'_>> {
+		if self.get_block_type() == XrBlockType::VoipMetrics {
+			VoipMetricsPacket::new(self.packet())
+		} else {
+			None
+		}
+	}
+}
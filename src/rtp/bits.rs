@@ -0,0 +1,151 @@
+//! Bit-level reading and writing of packed payload fields.
+
+use alloc::vec::Vec;
+
+/// MSB-first reader over a bit-packed buffer.
+pub(crate) struct BitReader<'a> {
+	data: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+	pub(crate) fn new(data: &'a [u8]) -> Self {
+		Self { data, pos: 0 }
+	}
+
+	pub(crate) fn read(&mut self, bits: u8) -> Option<u32> {
+		let mut out = 0;
+		for _ in 0..bits {
+			let byte = *self.data.get(self.pos / 8)?;
+			out = (out << 1) | u32::from((byte >> (7 - self.pos % 8)) & 1);
+			self.pos += 1;
+		}
+
+		Some(out)
+	}
+
+	pub(crate) fn read_u8(&mut self, bits: u8) -> Option<u8> {
+		#[allow(clippy::cast_possible_truncation)]
+		self.read(bits.min(8)).map(|v| v as u8)
+	}
+
+	pub(crate) fn flag(&mut self) -> Option<bool> {
+		self.read(1).map(|v| v != 0)
+	}
+
+	/// Advances past `bits` bits, failing if fewer remain.
+	pub(crate) fn skip(&mut self, bits: usize) -> Option<()> {
+		(bits <= self.remaining()).then(|| self.pos += bits)
+	}
+
+	/// Returns the number of bits read so far.
+	pub(crate) fn position(&self) -> usize {
+		self.pos
+	}
+
+	/// Returns the number of bits left unread.
+	pub(crate) fn remaining(&self) -> usize {
+		(self.data.len() * 8).saturating_sub(self.pos)
+	}
+
+	/// Reads `bits` bits into bytes, most significant bit first, with the last
+	/// byte padded with zero bits.
+	pub(crate) fn read_bytes(&mut self, bits: usize) -> Option<Vec<u8>> {
+		let mut out = Vec::with_capacity((bits + 7) / 8);
+		for _ in 0..bits / 8 {
+			out.push(self.read_u8(8)?);
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		let tail = (bits % 8) as u8;
+		if tail != 0 {
+			out.push(self.read_u8(tail)? << (8 - tail));
+		}
+
+		Some(out)
+	}
+
+	/// Reads a non-symmetric unsigned value in `0..n`.
+	pub(crate) fn ns(&mut self, n: u32) -> Option<u32> {
+		let (w, m) = ns_params(n)?;
+		let v = self.read(w - 1)?;
+
+		if v < m {
+			Some(v)
+		} else {
+			Some((v << 1) - m + self.read(1)?)
+		}
+	}
+}
+
+/// MSB-first writer into a growable bit-packed buffer.
+#[derive(Default)]
+pub(crate) struct BitWriter {
+	data: Vec<u8>,
+	pos: usize,
+}
+
+impl BitWriter {
+	pub(crate) fn write(&mut self, val: u32, bits: u8) {
+		for i in (0..bits).rev() {
+			if self.pos % 8 == 0 {
+				self.data.push(0);
+			}
+			#[allow(clippy::cast_possible_truncation)]
+			let bit = ((val >> i) & 1) as u8;
+			if let Some(last) = self.data.last_mut() {
+				*last |= bit << (7 - self.pos % 8);
+			}
+			self.pos += 1;
+		}
+	}
+
+	pub(crate) fn flag(&mut self, val: bool) {
+		self.write(u32::from(val), 1);
+	}
+
+	/// Writes the first `bits` bits of `data`, most significant bit first.
+	pub(crate) fn write_bytes(&mut self, data: &[u8], bits: usize) {
+		for &byte in &data[..bits / 8] {
+			self.write(u32::from(byte), 8);
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		let tail = (bits % 8) as u8;
+		if let (true, Some(&byte)) = (tail != 0, data.get(bits / 8)) {
+			self.write(u32::from(byte >> (8 - tail)), tail);
+		}
+	}
+
+	/// Writes a non-symmetric unsigned value `v` in `0..n`.
+	pub(crate) fn ns(&mut self, n: u32, v: u32) {
+		if let Some((w, m)) = ns_params(n) {
+			if v < m {
+				self.write(v, w - 1);
+			} else {
+				self.write(v + m, w);
+			}
+		}
+	}
+
+	/// Returns the number of bits written so far.
+	pub(crate) fn len(&self) -> usize {
+		self.pos
+	}
+
+	pub(crate) fn finish(self) -> Vec<u8> {
+		self.data
+	}
+}
+
+/// Returns the bit width `w` and threshold `m` of a non-symmetric value in `0..n`.
+fn ns_params(n: u32) -> Option<(u8, u32)> {
+	if n == 0 {
+		return None;
+	}
+
+	#[allow(clippy::cast_possible_truncation)]
+	let w = (32 - n.leading_zeros()) as u8;
+
+	Some((w, (1 << w) - n))
+}
@@ -0,0 +1,175 @@
+use super::{csrc::MAX_CSRCS, ext, MutableRtpPacket, RtpType};
+use crate::wrap::{Wrap16, Wrap32};
+use alloc::{vec, vec::Vec};
+use pnet_macros_support::packet::MutablePacket;
+
+/// Builder for RTP packets, filling in the version, CSRC count and
+/// extension fields automatically.
+///
+/// Unless otherwise set, packets have sequence number and timestamp `0`, no marker,
+/// CSRCs, extension, or payload.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RtpPacketBuilder {
+	payload_type: RtpType,
+	ssrc: u32,
+	sequence: Wrap16,
+	timestamp: Wrap32,
+	marker: bool,
+	csrcs: Vec<u32>,
+	extension: Option<(u16, Vec<u8>)>,
+	payload: Vec<u8>,
+}
+
+impl RtpPacketBuilder {
+	/// Creates a builder for packets of `payload_type` sent by `ssrc`.
+	#[must_use]
+	pub fn new(payload_type: RtpType, ssrc: u32) -> Self {
+		Self {
+			payload_type,
+			ssrc,
+			sequence: Wrap16::new(0),
+			timestamp: Wrap32::new(0),
+			marker: false,
+			csrcs: Vec::new(),
+			extension: None,
+			payload: Vec::new(),
+		}
+	}
+
+	/// Sets the sequence number.
+	pub fn sequence(&mut self, sequence: Wrap16) -> &mut Self {
+		self.sequence = sequence;
+		self
+	}
+
+	/// Sets the timestamp.
+	pub fn timestamp(&mut self, timestamp: Wrap32) -> &mut Self {
+		self.timestamp = timestamp;
+		self
+	}
+
+	/// Sets the sequence number and timestamp from the bits of `random`, which
+	/// should be drawn uniformly at random.
+	///
+	/// RFC 3550 recommends random initial values to frustrate known-plaintext
+	/// attacks on encrypted streams.
+	pub fn random_start(&mut self, random: u64) -> &mut Self {
+		#[allow(clippy::cast_possible_truncation)]
+		{
+			self.sequence = Wrap16::new((random >> 32) as u16);
+			self.timestamp = Wrap32::new(random as u32);
+		}
+		self
+	}
+
+	/// Sets the marker bit.
+	pub fn marker(&mut self, marker: bool) -> &mut Self {
+		self.marker = marker;
+		self
+	}
+
+	/// Sets the list of contributing sources.
+	///
+	/// Only the first [`MAX_CSRCS`] entries are kept.
+	///
+	/// [`MAX_CSRCS`]: constant.MAX_CSRCS.html
+	pub fn csrcs(&mut self, csrcs: &[u32]) -> &mut Self {
+		self.csrcs = csrcs[..csrcs.len().min(MAX_CSRCS)].to_vec();
+		self
+	}
+
+	/// Sets the header extension, given its profile identifier and data.
+	///
+	/// `data` is zero-padded to a 32-bit boundary, and truncated to the largest
+	/// length describable by the extension header. See [`encode_elements`] for
+	/// building RFC 8285 extension data.
+	///
+	/// [`encode_elements`]: ext/fn.encode_elements.html
+	pub fn extension(&mut self, profile: u16, data: &[u8]) -> &mut Self {
+		let mut data = data[..data.len().min(4 * usize::from(u16::MAX))].to_vec();
+		data.resize((data.len() + 3) & !3, 0);
+		self.extension = Some((profile, data));
+		self
+	}
+
+	/// Sets the payload.
+	pub fn payload(&mut self, payload: &[u8]) -> &mut Self {
+		self.payload = payload.to_vec();
+		self
+	}
+
+	/// Total number of bytes needed to serialise the packet.
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		ext::FIXED_HEADER_LEN
+			+ 4 * self.csrcs.len()
+			+ self.extension.as_ref().map_or(0, |(_, d)| 4 + d.len())
+			+ self.payload.len()
+	}
+
+	/// Serialises the packet into the start of `buf`, returning a view over
+	/// exactly the bytes written.
+	///
+	/// Returns `None` if `buf` is too small.
+	pub fn build_into<'a>(&self, buf: &'a mut [u8]) -> Option<MutableRtpPacket<'a>> {
+		let len = self.wire_size();
+		let mut pkt = MutableRtpPacket::new(buf.get_mut(..len)?)?;
+		self.fill(&mut pkt);
+
+		Some(pkt)
+	}
+
+	/// Serialises the packet into a newly allocated buffer.
+	#[must_use]
+	pub fn to_vec(&self) -> Vec<u8> {
+		let mut out = vec![0u8; self.wire_size()];
+		if let Some(mut pkt) = MutableRtpPacket::new(&mut out) {
+			self.fill(&mut pkt);
+		}
+
+		out
+	}
+
+	/// Serialises the packet into a newly allocated buffer, returning an owned view.
+	///
+	/// As with [`MutableRtpPacket::owned`], this returns an `Option`, though the
+	/// buffer is always large enough to hold the packet.
+	///
+	/// [`MutableRtpPacket::owned`]: struct.MutableRtpPacket.html#method.owned
+	#[must_use]
+	pub fn build(&self) -> Option<MutableRtpPacket<'static>> {
+		MutableRtpPacket::owned(self.to_vec())
+	}
+
+	fn fill(&self, pkt: &mut MutableRtpPacket<'_>) {
+		pkt.set_version(2);
+		pkt.set_padding(0);
+		pkt.set_extension(u8::from(self.extension.is_some()));
+		#[allow(clippy::cast_possible_truncation)]
+		pkt.set_csrc_count(self.csrcs.len() as u8);
+		pkt.set_marker(u8::from(self.marker));
+		pkt.set_payload_type(self.payload_type);
+		pkt.set_sequence(self.sequence);
+		pkt.set_timestamp(self.timestamp);
+		pkt.set_ssrc(self.ssrc);
+
+		let buf = pkt.packet_mut();
+		let mut offset = ext::FIXED_HEADER_LEN;
+
+		for csrc in &self.csrcs {
+			buf[offset..offset + 4].copy_from_slice(&csrc.to_be_bytes());
+			offset += 4;
+		}
+
+		if let Some((profile, data)) = &self.extension {
+			#[allow(clippy::cast_possible_truncation)]
+			let words = (data.len() / 4) as u16;
+			buf[offset..offset + 2].copy_from_slice(&profile.to_be_bytes());
+			buf[offset + 2..offset + 4].copy_from_slice(&words.to_be_bytes());
+			buf[offset + 4..offset + 4 + data.len()].copy_from_slice(data);
+			offset += 4 + data.len();
+		}
+
+		buf[offset..].copy_from_slice(&self.payload);
+	}
+}
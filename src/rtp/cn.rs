@@ -0,0 +1,92 @@
+//! Comfort noise, describing background noise to be synthesised by a receiver
+//! during silence.
+//!
+//! See [RFC 3389](https://tools.ietf.org/html/rfc3389).
+
+use super::RtpType;
+use crate::error::impl_new_checked;
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u7},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Payload of a comfort noise RTP packet.
+///
+/// A description of fields:
+///
+/// ## `reserved`
+/// Must be `0`.
+///
+/// ## `noise_level`
+/// Magnitude of the noise level in -dBov, from `0` to `127`.
+///
+/// ## `payload`
+/// Quantised reflection coefficients describing the spectrum of the noise, one
+/// byte per coefficient. The model order is the number of bytes, and may be `0`.
+pub struct ComfortNoise {
+	pub reserved: u1,
+
+	pub noise_level: u7,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	ComfortNoisePacket,
+	MutableComfortNoisePacket,
+	["reserved" => 1, "noise_level" => 1]
+);
+
+impl ComfortNoisePacket<'_> {
+	/// Returns the noise level in dBov, from `0` down to `-127`.
+	#[must_use]
+	pub fn dbov(&self) -> i8 {
+		#[allow(clippy::cast_possible_wrap)]
+		{
+			-(self.get_noise_level() as i8)
+		}
+	}
+
+	/// Returns the quantised reflection coefficients of the noise spectrum.
+	#[must_use]
+	pub fn coefficients(&self) -> &[u8] {
+		self.payload()
+	}
+}
+
+impl ComfortNoise {
+	/// Creates a comfort noise payload at `-noise_level` dBov, with spectral
+	/// information given by the quantised reflection `coefficients`.
+	///
+	/// `noise_level` is clamped to `127`.
+	#[must_use]
+	pub fn new(noise_level: u8, coefficients: &[u8]) -> Self {
+		Self {
+			reserved: 0,
+			noise_level: noise_level.min(127),
+			payload: coefficients.to_vec(),
+		}
+	}
+
+	/// Serialises this payload into a newly allocated buffer.
+	#[must_use]
+	pub fn to_vec(&self) -> Vec<u8> {
+		let mut out = Vec::with_capacity(1 + self.payload.len());
+		out.push(self.noise_level & 0x7f);
+		out.extend_from_slice(&self.payload);
+
+		out
+	}
+}
+
+/// Returns whether packets of `payload_type` carry comfort noise, either as the
+/// static type `13` or one of the `dynamic` types negotiated for `CN`.
+#[must_use]
+pub fn is_comfort_noise(payload_type: RtpType, dynamic: &[RtpType]) -> bool {
+	payload_type == RtpType::Cn || dynamic.contains(&payload_type)
+}
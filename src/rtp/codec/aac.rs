@@ -0,0 +1,401 @@
+//! MPEG-4 audio (such as AAC), carried in the `mpeg4-generic` payload format.
+//!
+//! Each payload begins with an AU header section describing the size of each
+//! access unit (AU) it carries, followed by the AUs themselves. AUs too large for
+//! one packet are fragmented, with every fragment's header giving the size of the
+//! whole AU.
+//!
+//! See [RFC 3640](https://tools.ietf.org/html/rfc3640).
+
+use super::fmtp_params;
+use crate::rtp::{
+	bits::{BitReader, BitWriter},
+	depacketizer::Depacketizer,
+	packetizer::Packetizer,
+};
+use alloc::vec::Vec;
+
+/// Layout of the AU headers in a stream, as negotiated by the `sizeLength`,
+/// `indexLength` and `indexDeltaLength` format parameters.
+///
+/// Field lengths are in bits, up to `32`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AuHeaderConfig {
+	/// Length of the `AU-size` field of each header.
+	pub size_length: u8,
+	/// Length of the `AU-Index` field of the first header in a packet.
+	pub index_length: u8,
+	/// Length of the `AU-Index-delta` field of later headers in a packet.
+	pub index_delta_length: u8,
+}
+
+impl AuHeaderConfig {
+	/// Header layout of the high bit-rate AAC mode, `AAC-hbr`.
+	pub const HBR: Self = Self {
+		size_length: 13,
+		index_length: 3,
+		index_delta_length: 3,
+	};
+
+	/// Header layout of the low bit-rate AAC mode, `AAC-lbr`.
+	pub const LBR: Self = Self {
+		size_length: 6,
+		index_length: 2,
+		index_delta_length: 2,
+	};
+
+	/// Reads the header layout from the parameters of an `a=fmtp` line.
+	///
+	/// Lengths which are not given explicitly are taken from the `mode` parameter
+	/// (for the AAC modes), or are otherwise `0`. Returns `None` if a length is
+	/// malformed or longer than 32 bits.
+	#[must_use]
+	pub fn from_fmtp(params: &str) -> Option<Self> {
+		let mut out = Self::default();
+		let mut explicit = Self::default();
+
+		for (key, value) in fmtp_params(params) {
+			let field = if key.eq_ignore_ascii_case("mode") {
+				if value.eq_ignore_ascii_case("AAC-hbr") {
+					out = Self::HBR;
+				} else if value.eq_ignore_ascii_case("AAC-lbr") {
+					out = Self::LBR;
+				}
+				continue;
+			} else if key.eq_ignore_ascii_case("sizeLength") {
+				&mut explicit.size_length
+			} else if key.eq_ignore_ascii_case("indexLength") {
+				&mut explicit.index_length
+			} else if key.eq_ignore_ascii_case("indexDeltaLength") {
+				&mut explicit.index_delta_length
+			} else {
+				continue;
+			};
+
+			let len: u8 = value.parse().ok()?;
+			if len == 0 || len > 32 {
+				return None;
+			}
+			*field = len;
+		}
+
+		for (dst, src) in [
+			(&mut out.size_length, explicit.size_length),
+			(&mut out.index_length, explicit.index_length),
+			(&mut out.index_delta_length, explicit.index_delta_length),
+		] {
+			if src != 0 {
+				*dst = src;
+			}
+		}
+
+		Some(out)
+	}
+
+	/// Returns whether packets begin with an AU header section.
+	#[must_use]
+	pub fn has_headers(self) -> bool {
+		self.size_length != 0 || self.index_length != 0 || self.index_delta_length != 0
+	}
+}
+
+/// Header describing one access unit of a packet.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AuHeader {
+	/// Size of the AU in bytes. For a fragment, this is the size of the whole AU.
+	pub size: u32,
+	/// `AU-Index` of the first AU in a packet, or the `AU-Index-delta` of a later
+	/// AU (which is one less than its distance from the previous AU).
+	pub index: u32,
+}
+
+/// Parsed AU header section of a payload, alongside the AUs it describes.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AuSection<'a> {
+	headers: Vec<AuHeader>,
+	data: &'a [u8],
+}
+
+impl<'a> AuSection<'a> {
+	/// Parses the AU headers at the start of `payload`, which are laid out
+	/// according to `config`.
+	///
+	/// If `config` calls for no headers, the whole payload is a single AU. Returns
+	/// `None` if the header section is truncated.
+	#[must_use]
+	pub fn parse(payload: &'a [u8], config: AuHeaderConfig) -> Option<Self> {
+		if !config.has_headers() {
+			#[allow(clippy::cast_possible_truncation)]
+			return Some(Self {
+				headers: alloc::vec![AuHeader {
+					size: payload.len() as u32,
+					index: 0,
+				}],
+				data: payload,
+			});
+		}
+
+		let total_bits = usize::from(u16::from_be_bytes([*payload.first()?, *payload.get(1)?]));
+		let header_bytes = (total_bits + 7) / 8;
+		let section = payload.get(2..2 + header_bytes)?;
+		let data = &payload[2 + header_bytes..];
+
+		let mut reader = BitReader::new(section);
+		let mut headers = Vec::new();
+		let mut used = 0;
+		loop {
+			let index_length = if headers.is_empty() {
+				config.index_length
+			} else {
+				config.index_delta_length
+			};
+			let len = usize::from(config.size_length) + usize::from(index_length);
+			if len == 0 || used + len > total_bits {
+				break;
+			}
+
+			headers.push(AuHeader {
+				size: reader.read(config.size_length)?,
+				index: reader.read(index_length)?,
+			});
+			used += len;
+		}
+
+		Some(Self { headers, data })
+	}
+
+	/// Writes a payload carrying each of `units` in full, using the header layout
+	/// of `config`.
+	///
+	/// Returns `None` if an AU is too large for the `AU-size` field, or if the
+	/// header section would exceed 65535 bits.
+	#[must_use]
+	pub fn build(units: &[&[u8]], config: AuHeaderConfig) -> Option<Vec<u8>> {
+		let sizes = units.iter().map(|u| u.len());
+		let mut out = write_headers(config, sizes)?;
+		for unit in units {
+			out.extend_from_slice(unit);
+		}
+
+		Some(out)
+	}
+
+	/// Returns the AU headers of this payload.
+	#[must_use]
+	pub fn headers(&self) -> &[AuHeader] {
+		&self.headers
+	}
+
+	/// Returns the AU data of this payload, following the header section.
+	#[must_use]
+	pub fn data(&self) -> &'a [u8] {
+		self.data
+	}
+
+	/// Returns whether this payload holds a fragment of a single AU, rather than
+	/// whole AUs.
+	#[must_use]
+	pub fn is_fragment(&self) -> bool {
+		match self.headers[..] {
+			[header] => header.size as usize != self.data.len(),
+			_ => false,
+		}
+	}
+
+	/// Iterates over the (possibly fragmentary) AUs of this payload.
+	///
+	/// Iteration ends early if the data is shorter than the headers describe, so a
+	/// fragment is returned as its available bytes.
+	pub fn access_units(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+		let mut rest = self.data;
+		self.headers.iter().map_while(move |h| {
+			if rest.is_empty() {
+				return None;
+			}
+			let len = (h.size as usize).min(rest.len());
+			let (unit, tail) = rest.split_at(len);
+			rest = tail;
+			Some(unit)
+		})
+	}
+}
+
+/// Writes the AU-headers-length field and AU headers describing AUs of `sizes`,
+/// with all indices `0`.
+fn write_headers(config: AuHeaderConfig, sizes: impl Iterator<Item = usize>) -> Option<Vec<u8>> {
+	if !config.has_headers() {
+		return Some(Vec::new());
+	}
+
+	let size_limit = 1u64 << config.size_length;
+	let mut writer = BitWriter::default();
+	let mut bits = 0usize;
+	for (i, size) in sizes.enumerate() {
+		if size as u64 >= size_limit {
+			return None;
+		}
+		let index_length = if i == 0 {
+			config.index_length
+		} else {
+			config.index_delta_length
+		};
+
+		#[allow(clippy::cast_possible_truncation)]
+		writer.write(size as u32, config.size_length);
+		writer.write(0, index_length);
+		bits += usize::from(config.size_length) + usize::from(index_length);
+	}
+
+	let bits = u16::try_from(bits).ok()?;
+	let mut out = bits.to_be_bytes().to_vec();
+	out.extend_from_slice(&writer.finish());
+
+	Some(out)
+}
+
+/// Packetizer of MPEG-4 access units into `mpeg4-generic` RTP payloads.
+///
+/// Each frame passed in is a single AU, which is sent alone in one packet or
+/// fragmented across several.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AacPacketizer {
+	/// Layout of the AU headers written.
+	pub config: AuHeaderConfig,
+}
+
+impl AacPacketizer {
+	/// Creates a packetizer writing AU headers laid out as in `config`.
+	#[must_use]
+	pub fn new(config: AuHeaderConfig) -> Self {
+		Self { config }
+	}
+}
+
+impl Packetizer for AacPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		let Some(header) = write_headers(self.config, core::iter::once(frame.len())) else {
+			return Vec::new();
+		};
+		let Some(chunk_len) = max_payload.checked_sub(header.len()).filter(|&l| l > 0) else {
+			return Vec::new();
+		};
+		if !self.config.has_headers() && frame.len() > chunk_len {
+			return Vec::new();
+		}
+
+		frame
+			.chunks(chunk_len)
+			.map(|chunk| {
+				let mut pkt = Vec::with_capacity(header.len() + chunk.len());
+				pkt.extend_from_slice(&header);
+				pkt.extend_from_slice(chunk);
+				pkt
+			})
+			.collect()
+	}
+}
+
+/// Depacketizer of `mpeg4-generic` RTP payloads into MPEG-4 access units.
+///
+/// The AUs of a frame are concatenated: where a packet carries several AUs, their
+/// boundaries may instead be found by parsing its [`AuSection`]. Fragmented AUs
+/// with missing pieces are dropped.
+///
+/// [`AuSection`]: struct.AuSection.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AacDepacketizer {
+	/// Layout of the AU headers expected.
+	pub config: AuHeaderConfig,
+}
+
+impl AacDepacketizer {
+	/// Creates a depacketizer reading AU headers laid out as in `config`.
+	#[must_use]
+	pub fn new(config: AuHeaderConfig) -> Self {
+		Self { config }
+	}
+}
+
+impl Depacketizer for AacDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		let mut fragment: Option<(usize, Vec<u8>)> = None;
+
+		for payload in payloads {
+			let Some(section) = AuSection::parse(payload, self.config) else {
+				fragment = None;
+				continue;
+			};
+
+			if !section.is_fragment() {
+				fragment = None;
+				for unit in section.access_units() {
+					out.extend_from_slice(unit);
+				}
+				continue;
+			}
+
+			let size = section.headers[0].size as usize;
+			if fragment
+				.as_ref()
+				.map_or(true, |(expected, _)| *expected != size)
+			{
+				fragment = Some((size, Vec::with_capacity(size)));
+			}
+			let Some((expected, buf)) = fragment.as_mut() else {
+				continue;
+			};
+			buf.extend_from_slice(section.data);
+
+			match buf.len().cmp(expected) {
+				core::cmp::Ordering::Less => {},
+				core::cmp::Ordering::Equal => {
+					out.append(buf);
+					fragment = None;
+				},
+				core::cmp::Ordering::Greater => fragment = None,
+			}
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn fragmented_au_round_trip() {
+		let config = AuHeaderConfig::from_fmtp("mode=AAC-hbr; sizeLength=13").unwrap();
+		assert_eq!(config, AuHeaderConfig::HBR);
+
+		let frame: Vec<u8> = (0..250u8).collect();
+		let payloads = AacPacketizer::new(config).packetize(&frame, 104);
+		assert_eq!(payloads.len(), 3);
+		for payload in &payloads {
+			let section = AuSection::parse(payload, config).unwrap();
+			assert!(section.is_fragment());
+			assert_eq!(section.headers()[0].size, 250);
+		}
+
+		let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+		let mut depacketizer = AacDepacketizer::new(config);
+		assert_eq!(depacketizer.depacketize(&payloads), Some(frame));
+		assert_eq!(depacketizer.depacketize(&[payloads[0], payloads[2]]), None);
+	}
+
+	#[test]
+	fn aggregated_aus_round_trip() {
+		let units: [&[u8]; 3] = [&[1, 2, 3], &[4], &[5, 6]];
+		let payload = AuSection::build(&units, AuHeaderConfig::LBR).unwrap();
+
+		let section = AuSection::parse(&payload, AuHeaderConfig::LBR).unwrap();
+		assert!(!section.is_fragment());
+		assert_eq!(section.access_units().collect::<Vec<_>>(), units);
+		assert_eq!(
+			AacDepacketizer::new(AuHeaderConfig::LBR).depacketize(&[&payload]),
+			Some(alloc::vec![1, 2, 3, 4, 5, 6])
+		);
+	}
+}
@@ -0,0 +1,438 @@
+//! AMR and AMR-WB speech, as used by mobile networks (including voice over LTE).
+//!
+//! Each payload carries a codec mode request (CMR) for the far end, a table of
+//! contents (TOC) describing each 20 ms frame, and the frames' speech bits. Both
+//! the bandwidth-efficient format (where these fields are packed bitwise) and the
+//! octet-aligned format are supported, without interleaving or frame CRCs.
+//!
+//! Frames are exchanged with callers in the AMR storage format: a header byte
+//! holding the frame type and quality bit, followed by the frame's speech bits,
+//! padded to a whole number of bytes.
+//!
+//! See [RFC 4867](https://tools.ietf.org/html/rfc4867).
+
+use super::fmtp_params;
+use crate::rtp::{
+	bits::{BitReader, BitWriter},
+	depacketizer::Depacketizer,
+	packetizer::Packetizer,
+};
+use alloc::vec::Vec;
+
+/// Frame type indicating that no speech data is present.
+pub const NO_DATA: u8 = 15;
+
+/// Codec mode request indicating that no particular mode is requested.
+pub const CMR_NONE: u8 = 15;
+
+/// Duration of every AMR frame, in milliseconds.
+pub const FRAME_MS: u32 = 20;
+
+/// Variant of the AMR codec in use.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum AmrVariant {
+	/// Narrowband AMR, at 8 kHz.
+	Narrowband,
+	/// Wideband AMR-WB, at 16 kHz.
+	Wideband,
+}
+
+impl AmrVariant {
+	/// Returns the variant named by an SDP encoding name (`AMR` or `AMR-WB`),
+	/// matched case-insensitively.
+	#[must_use]
+	pub fn from_encoding(name: &str) -> Option<Self> {
+		if name.eq_ignore_ascii_case("AMR") {
+			Some(Self::Narrowband)
+		} else if name.eq_ignore_ascii_case("AMR-WB") {
+			Some(Self::Wideband)
+		} else {
+			None
+		}
+	}
+
+	/// Returns the RTP clock rate of this variant, in Hz.
+	#[must_use]
+	pub fn clock_rate(self) -> u32 {
+		match self {
+			Self::Narrowband => 8_000,
+			Self::Wideband => 16_000,
+		}
+	}
+
+	/// Returns the amount by which the timestamp advances for each frame.
+	#[must_use]
+	pub fn frame_samples(self) -> u32 {
+		self.clock_rate() / 1000 * FRAME_MS
+	}
+
+	/// Returns the number of speech bits in a frame of type `frame_type`.
+	///
+	/// Returns `None` for reserved or unused frame types.
+	#[must_use]
+	pub fn frame_bits(self, frame_type: u8) -> Option<usize> {
+		const NARROWBAND: [usize; 9] = [95, 103, 118, 134, 148, 159, 204, 244, 39];
+		const WIDEBAND: [usize; 10] = [132, 177, 253, 285, 317, 365, 397, 461, 477, 40];
+
+		match (self, frame_type) {
+			(_, NO_DATA) | (Self::Wideband, 14) => Some(0),
+			(Self::Narrowband, _) => NARROWBAND.get(usize::from(frame_type)).copied(),
+			(Self::Wideband, _) => WIDEBAND.get(usize::from(frame_type)).copied(),
+		}
+	}
+
+	/// Returns the number of bytes taken by the speech bits of a frame of type
+	/// `frame_type`, once padded.
+	#[must_use]
+	pub fn frame_len(self, frame_type: u8) -> Option<usize> {
+		self.frame_bits(frame_type).map(|bits| (bits + 7) / 8)
+	}
+}
+
+/// Payload format parameters of an AMR stream.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AmrConfig {
+	/// Codec variant in use.
+	pub variant: AmrVariant,
+	/// Whether the octet-aligned format is used, rather than the
+	/// bandwidth-efficient format.
+	pub octet_aligned: bool,
+}
+
+impl AmrConfig {
+	/// Creates a configuration for `variant`, in the given format.
+	#[must_use]
+	pub fn new(variant: AmrVariant, octet_aligned: bool) -> Self {
+		Self {
+			variant,
+			octet_aligned,
+		}
+	}
+
+	/// Reads the payload format from the parameters of an `a=fmtp` line for
+	/// `variant`.
+	///
+	/// Returns `None` if interleaving, frame CRCs or robust sorting are requested,
+	/// as these are unsupported.
+	#[must_use]
+	pub fn from_fmtp(variant: AmrVariant, params: &str) -> Option<Self> {
+		let mut out = Self::new(variant, false);
+
+		for (key, value) in fmtp_params(params) {
+			if key.eq_ignore_ascii_case("octet-align") {
+				out.octet_aligned = value == "1";
+			} else if key.eq_ignore_ascii_case("interleaving")
+				|| ((key.eq_ignore_ascii_case("crc") || key.eq_ignore_ascii_case("robust-sorting"))
+					&& value == "1")
+			{
+				return None;
+			}
+		}
+
+		Some(out)
+	}
+}
+
+/// Single speech frame of an AMR payload.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AmrFrame {
+	/// Frame type, which determines the codec mode (and size) of the frame.
+	pub frame_type: u8,
+	/// Whether the frame is undamaged. Unset on frames with detected errors.
+	pub quality: bool,
+	/// Speech bits of the frame, packed most significant bit first and padded to
+	/// a whole number of bytes.
+	pub data: Vec<u8>,
+}
+
+impl AmrFrame {
+	/// Returns the storage format header byte of this frame.
+	#[must_use]
+	pub fn header(&self) -> u8 {
+		((self.frame_type & 0x0f) << 3) | (u8::from(self.quality) << 2)
+	}
+}
+
+/// Parsed contents of an AMR payload.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AmrPayload {
+	/// Codec mode requested of the far end, or [`CMR_NONE`].
+	///
+	/// [`CMR_NONE`]: constant.CMR_NONE.html
+	pub cmr: u8,
+	/// Speech frames, in order.
+	pub frames: Vec<AmrFrame>,
+}
+
+impl AmrPayload {
+	/// Parses an RTP payload in the format given by `config`.
+	///
+	/// Returns `None` if the payload is truncated or uses a reserved frame type.
+	#[must_use]
+	pub fn parse(payload: &[u8], config: AmrConfig) -> Option<Self> {
+		let variant = config.variant;
+		let mut reader = BitReader::new(payload);
+		let mut out = Self {
+			cmr: reader.read_u8(4)?,
+			frames: Vec::new(),
+		};
+
+		if config.octet_aligned {
+			reader.read(4)?;
+		}
+
+		loop {
+			let follows = reader.flag()?;
+			let frame_type = reader.read_u8(4)?;
+			let quality = reader.flag()?;
+			if config.octet_aligned {
+				reader.read(2)?;
+			}
+
+			variant.frame_bits(frame_type)?;
+			out.frames.push(AmrFrame {
+				frame_type,
+				quality,
+				data: Vec::new(),
+			});
+
+			if !follows {
+				break;
+			}
+		}
+
+		for frame in &mut out.frames {
+			let bits = variant.frame_bits(frame.frame_type)?;
+			let bits = if config.octet_aligned {
+				(bits + 7) / 8 * 8
+			} else {
+				bits
+			};
+			frame.data = reader.read_bytes(bits)?;
+		}
+
+		Some(out)
+	}
+
+	/// Serialises this payload in the format given by `config`.
+	///
+	/// Returns `None` if there are no frames, or if a frame's data is shorter than
+	/// its frame type requires. Excess data is ignored.
+	#[must_use]
+	pub fn to_vec(&self, config: AmrConfig) -> Option<Vec<u8>> {
+		let variant = config.variant;
+		let last = self.frames.len().checked_sub(1)?;
+		let mut writer = BitWriter::default();
+
+		writer.write(u32::from(self.cmr), 4);
+		if config.octet_aligned {
+			writer.write(0, 4);
+		}
+
+		for (i, frame) in self.frames.iter().enumerate() {
+			writer.flag(i != last);
+			writer.write(u32::from(frame.frame_type), 4);
+			writer.flag(frame.quality);
+			if config.octet_aligned {
+				writer.write(0, 2);
+			}
+		}
+
+		for frame in &self.frames {
+			let bits = variant.frame_bits(frame.frame_type)?;
+			let bits = if config.octet_aligned {
+				(bits + 7) / 8 * 8
+			} else {
+				bits
+			};
+			writer.write_bytes(frame.data.get(..(bits + 7) / 8)?, bits);
+		}
+
+		Some(writer.finish())
+	}
+
+	/// Reads frames in the AMR storage format, each a header byte followed by its
+	/// padded speech bits.
+	///
+	/// Returns `None` if a frame is truncated or of a reserved type.
+	#[must_use]
+	pub fn from_storage(mut data: &[u8], variant: AmrVariant, cmr: u8) -> Option<Self> {
+		let mut frames = Vec::new();
+
+		while let Some((&header, rest)) = data.split_first() {
+			let frame_type = (header >> 3) & 0x0f;
+			let len = variant.frame_len(frame_type)?;
+			frames.push(AmrFrame {
+				frame_type,
+				quality: header & 0x04 != 0,
+				data: rest.get(..len)?.to_vec(),
+			});
+			data = &rest[len..];
+		}
+
+		Some(Self { cmr, frames })
+	}
+
+	/// Writes the frames of this payload in the AMR storage format.
+	#[must_use]
+	pub fn to_storage(&self) -> Vec<u8> {
+		let mut out = Vec::new();
+		for frame in &self.frames {
+			out.push(frame.header());
+			out.extend_from_slice(&frame.data);
+		}
+
+		out
+	}
+
+	/// Returns the amount by which the timestamp advances after this payload.
+	#[must_use]
+	pub fn duration(&self, variant: AmrVariant) -> u32 {
+		#[allow(clippy::cast_possible_truncation)]
+		{
+			self.frames.len() as u32 * variant.frame_samples()
+		}
+	}
+}
+
+/// Packetizer of AMR storage format frames into RTP payloads.
+///
+/// Each frame passed in may hold several storage format frames, which are all
+/// sent in a single payload: the timestamp should then advance by
+/// [`AmrVariant::frame_samples`] for each.
+///
+/// [`AmrVariant::frame_samples`]: enum.AmrVariant.html#method.frame_samples
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AmrPacketizer {
+	/// Payload format to send.
+	pub config: AmrConfig,
+	/// Codec mode request sent to the far end.
+	pub cmr: u8,
+}
+
+impl AmrPacketizer {
+	/// Creates a packetizer in the payload format of `config`, which requests no
+	/// particular codec mode.
+	#[must_use]
+	pub fn new(config: AmrConfig) -> Self {
+		Self {
+			config,
+			cmr: CMR_NONE,
+		}
+	}
+}
+
+impl Packetizer for AmrPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		AmrPayload::from_storage(frame, self.config.variant, self.cmr)
+			.and_then(|p| p.to_vec(self.config))
+			.filter(|p| p.len() <= max_payload)
+			.map(|p| alloc::vec![p])
+			.unwrap_or_default()
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		false
+	}
+}
+
+/// Depacketizer of AMR RTP payloads into storage format frames.
+///
+/// The codec mode request of the most recent payload is kept, to be honoured by
+/// the local encoder.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AmrDepacketizer {
+	/// Payload format expected.
+	pub config: AmrConfig,
+	/// Codec mode most recently requested by the far end.
+	pub cmr: u8,
+}
+
+impl AmrDepacketizer {
+	/// Creates a depacketizer for the payload format of `config`.
+	#[must_use]
+	pub fn new(config: AmrConfig) -> Self {
+		Self {
+			config,
+			cmr: CMR_NONE,
+		}
+	}
+}
+
+impl Depacketizer for AmrDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		for payload in payloads {
+			if let Some(parsed) = AmrPayload::parse(payload, self.config) {
+				self.cmr = parsed.cmr;
+				out.extend_from_slice(&parsed.to_storage());
+			}
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn storage(variant: AmrVariant, frame_types: &[u8]) -> Vec<u8> {
+		let mut out = Vec::new();
+		for &frame_type in frame_types {
+			let bits = variant.frame_bits(frame_type).unwrap();
+			let mut data: Vec<u8> = (1..=255).take((bits + 7) / 8).collect();
+			if let Some(last) = data.last_mut() {
+				*last &= 0xff << ((8 - bits % 8) % 8);
+			}
+
+			let frame = AmrFrame {
+				frame_type,
+				quality: true,
+				data,
+			};
+			out.push(frame.header());
+			out.extend_from_slice(&frame.data);
+		}
+
+		out
+	}
+
+	#[test]
+	fn frames_round_trip_in_both_formats() {
+		for variant in [AmrVariant::Narrowband, AmrVariant::Wideband] {
+			let frames = storage(variant, &[7, 8, NO_DATA, 0]);
+
+			for octet_aligned in [false, true] {
+				let config = AmrConfig::new(variant, octet_aligned);
+				let mut packetizer = AmrPacketizer::new(config);
+				packetizer.cmr = 2;
+
+				let payloads = packetizer.packetize(&frames, 1200);
+				assert_eq!(payloads.len(), 1);
+
+				let parsed = AmrPayload::parse(&payloads[0], config).unwrap();
+				assert_eq!(parsed.frames.len(), 4);
+				assert_eq!(parsed.duration(variant), 4 * variant.frame_samples());
+				assert_eq!(parsed.to_vec(config).unwrap(), payloads[0]);
+
+				let mut depacketizer = AmrDepacketizer::new(config);
+				assert_eq!(depacketizer.depacketize(&[&payloads[0]]).unwrap(), frames);
+				assert_eq!(depacketizer.cmr, 2);
+			}
+		}
+	}
+
+	#[test]
+	fn oversized_payloads_are_dropped() {
+		let config = AmrConfig::new(AmrVariant::Narrowband, true);
+		let frames = storage(AmrVariant::Narrowband, &[7]);
+
+		assert!(AmrPacketizer::new(config).packetize(&frames, 32).is_empty());
+		assert_eq!(AmrPacketizer::new(config).packetize(&frames, 33).len(), 1);
+	}
+}
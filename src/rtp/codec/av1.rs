@@ -0,0 +1,14 @@
+//! AV1 video.
+//!
+//! Each payload begins with a one-byte aggregation header, followed by one or
+//! more (possibly fragmented) OBU elements. Scalability information is carried
+//! separately, in the dependency descriptor header extension.
+//!
+//! See the [AV1 RTP payload specification](https://aomediacodec.github.io/av1-rtp-spec/).
+
+/// Returns whether an RTP payload is the first packet of a new coded video
+/// sequence, which begins with a keyframe.
+#[must_use]
+pub fn is_keyframe(payload: &[u8]) -> bool {
+	payload.first().map_or(false, |header| header & 0x08 != 0)
+}
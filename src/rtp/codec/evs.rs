@@ -0,0 +1,579 @@
+//! 3GPP EVS speech, as used by mobile networks alongside (or in place of) AMR-WB.
+//!
+//! Each 20 ms frame is coded either in one of the EVS primary modes, or in the
+//! AMR-WB interoperable (IO) mode. Payloads come in two formats:
+//!
+//! * The compact format carries a single frame with no header, its frame type
+//!   given by the payload's length. AMR-WB IO frames are preceded by a 3-bit
+//!   codec mode request (CMR).
+//! * The header-full format carries an optional CMR byte, a table of contents
+//!   (TOC) byte for each frame, and then the frames, each padded to a whole byte.
+//!
+//! A receiver treats any payload whose length matches a compact frame size as
+//! compact, unless only the header-full format was negotiated (`hf-only=1`).
+//! Senders therefore add a CMR byte (or padding) to header-full payloads which
+//! would otherwise be mistaken for compact ones.
+//!
+//! Frames are exchanged with callers as a TOC byte followed by the frame's
+//! padded speech bits, as in the EVS storage format.
+//!
+//! See [3GPP TS 26.445, Annex A](https://www.3gpp.org/ftp/Specs/archive/26_series/26.445/).
+
+use super::fmtp_params;
+use crate::rtp::{
+	bits::{BitReader, BitWriter},
+	depacketizer::Depacketizer,
+	packetizer::Packetizer,
+};
+use alloc::vec::Vec;
+
+/// RTP clock rate of EVS, regardless of the audio bandwidth in use.
+pub const CLOCK_RATE: u32 = 16_000;
+
+/// Amount by which the timestamp advances for each frame.
+pub const FRAME_SAMPLES: u32 = 320;
+
+/// Frame type index marking a frame lost or damaged before transmission.
+pub const SPEECH_LOST: u8 = 14;
+
+/// Frame type index indicating that no speech data is present.
+pub const NO_DATA: u8 = 15;
+
+/// Header-full CMR byte requesting no particular codec mode.
+pub const CMR_NONE: u8 = 0xff;
+
+/// Compact CMR field of an AMR-WB IO frame requesting no particular mode.
+pub const COMPACT_CMR_NONE: u8 = 0b111;
+
+/// Size in bits of an EVS primary frame of each bit rate index.
+const PRIMARY_BITS: [Option<usize>; 16] = [
+	Some(56),
+	Some(144),
+	Some(160),
+	Some(192),
+	Some(264),
+	Some(328),
+	Some(488),
+	Some(640),
+	Some(960),
+	Some(1280),
+	Some(1920),
+	Some(2560),
+	Some(48),
+	None,
+	Some(0),
+	Some(0),
+];
+
+/// Size in bits of an AMR-WB IO frame of each mode index.
+const IO_BITS: [Option<usize>; 16] = [
+	Some(132),
+	Some(177),
+	Some(253),
+	Some(285),
+	Some(317),
+	Some(365),
+	Some(397),
+	Some(461),
+	Some(477),
+	Some(35),
+	None,
+	None,
+	None,
+	None,
+	Some(0),
+	Some(0),
+];
+
+/// Coding mode and bit rate of a single EVS frame.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum EvsFrameType {
+	/// EVS primary mode, with the given bit rate index (where `12` is a SID frame).
+	Primary(u8),
+	/// AMR-WB interoperable mode, with the given AMR-WB mode index (where `9` is a
+	/// SID frame).
+	AmrWbIo(u8),
+}
+
+impl EvsFrameType {
+	/// Reads the frame type from the low six bits of a TOC byte.
+	#[must_use]
+	pub fn from_toc(toc: u8) -> Self {
+		if toc & 0x20 == 0 {
+			Self::Primary(toc & 0x0f)
+		} else {
+			Self::AmrWbIo(toc & 0x0f)
+		}
+	}
+
+	/// Returns the six frame type bits of a TOC byte, with the quality bit of
+	/// AMR-WB IO frames set.
+	#[must_use]
+	pub fn toc_bits(self) -> u8 {
+		match self {
+			Self::Primary(index) => index & 0x0f,
+			Self::AmrWbIo(index) => 0x30 | (index & 0x0f),
+		}
+	}
+
+	/// Returns the number of speech bits in a frame of this type.
+	///
+	/// Returns `None` for reserved frame types.
+	#[must_use]
+	pub fn frame_bits(self) -> Option<usize> {
+		match self {
+			Self::Primary(index) => *PRIMARY_BITS.get(usize::from(index))?,
+			Self::AmrWbIo(index) => *IO_BITS.get(usize::from(index))?,
+		}
+	}
+
+	/// Returns the number of bytes taken by the speech bits of a frame of this
+	/// type, once padded.
+	#[must_use]
+	pub fn frame_len(self) -> Option<usize> {
+		self.frame_bits().map(|bits| (bits + 7) / 8)
+	}
+
+	/// Returns the size of a compact format payload holding a frame of this type.
+	///
+	/// Returns `None` if this type cannot be sent in the compact format.
+	#[must_use]
+	pub fn compact_len(self) -> Option<usize> {
+		match self {
+			Self::Primary(0..=12) => self.frame_len(),
+			Self::AmrWbIo(0..=9) => self.frame_bits().map(|bits| (bits + 3 + 7) / 8),
+			_ => None,
+		}
+	}
+
+	/// Returns the type of frame held by a compact format payload of `len` bytes.
+	///
+	/// Returns `None` if no compact frame has this size.
+	#[must_use]
+	pub fn from_compact_len(len: usize) -> Option<Self> {
+		(0..=12)
+			.map(Self::Primary)
+			.chain((0..=9).map(Self::AmrWbIo))
+			.find(|ft| ft.compact_len() == Some(len))
+	}
+
+	/// Returns whether this is a silence descriptor (SID) frame.
+	#[must_use]
+	pub fn is_sid(self) -> bool {
+		matches!(self, Self::Primary(12) | Self::AmrWbIo(9))
+	}
+
+	/// Returns whether this frame type carries no speech data.
+	#[must_use]
+	pub fn is_empty(self) -> bool {
+		matches!(
+			self,
+			Self::Primary(SPEECH_LOST | NO_DATA) | Self::AmrWbIo(SPEECH_LOST | NO_DATA)
+		)
+	}
+}
+
+/// Payload format parameters of an EVS stream.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EvsConfig {
+	/// Whether only the header-full format is used, so that payloads are never
+	/// read or written in the compact format.
+	pub header_full_only: bool,
+}
+
+impl EvsConfig {
+	/// Reads the payload format from the parameters of an `a=fmtp` line.
+	#[must_use]
+	pub fn from_fmtp(params: &str) -> Self {
+		let header_full_only =
+			fmtp_params(params).any(|(k, v)| k.eq_ignore_ascii_case("hf-only") && v == "1");
+
+		Self { header_full_only }
+	}
+}
+
+/// Single speech frame of an EVS payload.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct EvsFrame {
+	/// Frame type, which determines the mode (and size) of the frame.
+	pub frame_type: EvsFrameType,
+	/// Whether the frame is undamaged. Only AMR-WB IO frames may be marked as
+	/// damaged.
+	pub quality: bool,
+	/// Speech bits of the frame, packed most significant bit first and padded to
+	/// a whole number of bytes.
+	pub data: Vec<u8>,
+}
+
+impl EvsFrame {
+	/// Returns the TOC byte of this frame, with its follow bit unset.
+	#[must_use]
+	pub fn toc(&self) -> u8 {
+		match self.frame_type {
+			EvsFrameType::AmrWbIo(_) if !self.quality => self.frame_type.toc_bits() & !0x10,
+			ft => ft.toc_bits(),
+		}
+	}
+}
+
+/// Parsed contents of an EVS payload.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EvsPayload {
+	/// Whether the payload is (or is to be sent) in the compact format.
+	pub compact: bool,
+	/// Codec mode requested of the far end, if any.
+	///
+	/// This is the whole CMR byte of a header-full payload, or the 3-bit CMR field
+	/// of a compact AMR-WB IO payload.
+	pub cmr: Option<u8>,
+	/// Speech frames, in order.
+	pub frames: Vec<EvsFrame>,
+}
+
+impl EvsPayload {
+	/// Parses an RTP payload in either format, as allowed by `config`.
+	///
+	/// Returns `None` if the payload is truncated or uses a reserved frame type.
+	#[must_use]
+	pub fn parse(payload: &[u8], config: EvsConfig) -> Option<Self> {
+		if !config.header_full_only {
+			if let Some(frame_type) = EvsFrameType::from_compact_len(payload.len()) {
+				return Some(Self::parse_compact(payload, frame_type));
+			}
+		}
+
+		Self::parse_header_full(payload)
+	}
+
+	fn parse_compact(payload: &[u8], frame_type: EvsFrameType) -> Self {
+		let mut reader = BitReader::new(payload);
+		let cmr = match frame_type {
+			EvsFrameType::AmrWbIo(_) => reader.read_u8(3),
+			EvsFrameType::Primary(_) => None,
+		};
+		let data = frame_type
+			.frame_bits()
+			.and_then(|bits| reader.read_bytes(bits))
+			.unwrap_or_default();
+
+		Self {
+			compact: true,
+			cmr,
+			frames: alloc::vec![EvsFrame {
+				frame_type,
+				quality: true,
+				data,
+			}],
+		}
+	}
+
+	fn parse_header_full(payload: &[u8]) -> Option<Self> {
+		let mut rest = payload;
+		let mut out = Self::default();
+
+		if let Some((&cmr, tail)) = rest.split_first().filter(|(&b, _)| b & 0x80 != 0) {
+			out.cmr = Some(cmr);
+			rest = tail;
+		}
+
+		loop {
+			let (&toc, tail) = rest.split_first()?;
+			rest = tail;
+			if toc & 0x80 != 0 {
+				return None;
+			}
+
+			let frame_type = EvsFrameType::from_toc(toc);
+			frame_type.frame_bits()?;
+			out.frames.push(EvsFrame {
+				frame_type,
+				quality: matches!(frame_type, EvsFrameType::Primary(_)) || toc & 0x10 != 0,
+				data: Vec::new(),
+			});
+
+			if toc & 0x40 == 0 {
+				break;
+			}
+		}
+
+		for frame in &mut out.frames {
+			let len = frame.frame_type.frame_len()?;
+			frame.data = rest.get(..len)?.to_vec();
+			rest = &rest[len..];
+		}
+
+		Some(out)
+	}
+
+	/// Serialises this payload in the format given by its `compact` field.
+	///
+	/// Header-full payloads which could be mistaken for compact ones are given a
+	/// [`CMR_NONE`] byte (and zero padding if still needed), unless `config` allows
+	/// only the header-full format.
+	///
+	/// Returns `None` if there are no frames, if a frame's data is shorter than its
+	/// frame type requires, or if a compact payload would not hold exactly one
+	/// frame (with no CMR, unless in AMR-WB IO mode). Excess data is ignored.
+	///
+	/// [`CMR_NONE`]: constant.CMR_NONE.html
+	#[must_use]
+	pub fn to_vec(&self, config: EvsConfig) -> Option<Vec<u8>> {
+		if self.compact {
+			return self.to_compact();
+		}
+
+		let last = self.frames.len().checked_sub(1)?;
+		let mut out = Vec::new();
+
+		if let Some(cmr) = self.cmr {
+			out.push(cmr | 0x80);
+		}
+
+		for (i, frame) in self.frames.iter().enumerate() {
+			out.push(frame.toc() | if i == last { 0 } else { 0x40 });
+		}
+
+		for frame in &self.frames {
+			let len = frame.frame_type.frame_len()?;
+			out.extend_from_slice(frame.data.get(..len)?);
+		}
+
+		if !config.header_full_only {
+			if self.cmr.is_none() && EvsFrameType::from_compact_len(out.len()).is_some() {
+				out.insert(0, CMR_NONE);
+			}
+			while EvsFrameType::from_compact_len(out.len()).is_some() {
+				out.push(0);
+			}
+		}
+
+		Some(out)
+	}
+
+	fn to_compact(&self) -> Option<Vec<u8>> {
+		let [frame] = &self.frames[..] else {
+			return None;
+		};
+		let bits = frame.frame_type.frame_bits()?;
+		frame.frame_type.compact_len()?;
+
+		let mut writer = BitWriter::default();
+		match (frame.frame_type, self.cmr) {
+			(EvsFrameType::Primary(_), None) => {},
+			(EvsFrameType::AmrWbIo(_), cmr) => {
+				writer.write(u32::from(cmr.unwrap_or(COMPACT_CMR_NONE)), 3);
+			},
+			_ => return None,
+		}
+		writer.write_bytes(frame.data.get(..(bits + 7) / 8)?, bits);
+
+		Some(writer.finish())
+	}
+
+	/// Reads frames in the EVS storage format, each a TOC byte followed by its
+	/// padded speech bits.
+	///
+	/// Returns `None` if a frame is truncated or of a reserved type.
+	#[must_use]
+	pub fn from_storage(mut data: &[u8], cmr: Option<u8>) -> Option<Self> {
+		let mut frames = Vec::new();
+
+		while let Some((&toc, rest)) = data.split_first() {
+			let frame_type = EvsFrameType::from_toc(toc);
+			let len = frame_type.frame_len()?;
+			frames.push(EvsFrame {
+				frame_type,
+				quality: matches!(frame_type, EvsFrameType::Primary(_)) || toc & 0x10 != 0,
+				data: rest.get(..len)?.to_vec(),
+			});
+			data = &rest[len..];
+		}
+
+		Some(Self {
+			compact: false,
+			cmr,
+			frames,
+		})
+	}
+
+	/// Writes the frames of this payload in the EVS storage format.
+	#[must_use]
+	pub fn to_storage(&self) -> Vec<u8> {
+		let mut out = Vec::new();
+		for frame in &self.frames {
+			out.push(frame.toc());
+			out.extend_from_slice(&frame.data);
+		}
+
+		out
+	}
+
+	/// Returns the amount by which the timestamp advances after this payload.
+	#[must_use]
+	pub fn duration(&self) -> u32 {
+		#[allow(clippy::cast_possible_truncation)]
+		{
+			self.frames.len() as u32 * FRAME_SAMPLES
+		}
+	}
+}
+
+/// Packetizer of EVS storage format frames into RTP payloads.
+///
+/// Each frame passed in may hold several storage format frames, which are all
+/// sent in a single payload: the timestamp should then advance by
+/// [`FRAME_SAMPLES`] for each. A lone frame is sent in the compact format where
+/// possible, unless a header-full CMR must be sent.
+///
+/// [`FRAME_SAMPLES`]: constant.FRAME_SAMPLES.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EvsPacketizer {
+	/// Payload format to send.
+	pub config: EvsConfig,
+	/// Header-full CMR byte sent to the far end, if any.
+	pub cmr: Option<u8>,
+}
+
+impl EvsPacketizer {
+	/// Creates a packetizer in the payload format of `config`, which requests no
+	/// particular codec mode.
+	#[must_use]
+	pub fn new(config: EvsConfig) -> Self {
+		Self { config, cmr: None }
+	}
+}
+
+impl Packetizer for EvsPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		let Some(mut payload) = EvsPayload::from_storage(frame, self.cmr) else {
+			return Vec::new();
+		};
+
+		payload.compact = !self.config.header_full_only
+			&& self.cmr.is_none()
+			&& payload.frames.len() == 1
+			&& payload.frames[0].quality
+			&& payload.frames[0].frame_type.compact_len().is_some();
+
+		payload
+			.to_vec(self.config)
+			.filter(|p| p.len() <= max_payload)
+			.map(|p| alloc::vec![p])
+			.unwrap_or_default()
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		false
+	}
+}
+
+/// Depacketizer of EVS RTP payloads into storage format frames.
+///
+/// The header-full CMR byte of the most recent payload to carry one is kept, to
+/// be honoured by the local encoder.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct EvsDepacketizer {
+	/// Payload format expected.
+	pub config: EvsConfig,
+	/// Header-full CMR byte most recently sent by the far end, if any.
+	pub cmr: Option<u8>,
+}
+
+impl EvsDepacketizer {
+	/// Creates a depacketizer for the payload format of `config`.
+	#[must_use]
+	pub fn new(config: EvsConfig) -> Self {
+		Self { config, cmr: None }
+	}
+}
+
+impl Depacketizer for EvsDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		for payload in payloads {
+			if let Some(parsed) = EvsPayload::parse(payload, self.config) {
+				if !parsed.compact && parsed.cmr.is_some() {
+					self.cmr = parsed.cmr;
+				}
+				out.extend_from_slice(&parsed.to_storage());
+			}
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn storage(frames: &[(EvsFrameType, bool)]) -> Vec<u8> {
+		let mut out = Vec::new();
+		for &(frame_type, quality) in frames {
+			let bits = frame_type.frame_bits().unwrap();
+			let mut data: Vec<u8> = (1..=255).take((bits + 7) / 8).collect();
+			if let Some(last) = data.last_mut() {
+				*last &= 0xff << ((8 - bits % 8) % 8);
+			}
+
+			let frame = EvsFrame {
+				frame_type,
+				quality,
+				data,
+			};
+			out.push(frame.toc());
+			out.extend_from_slice(&frame.data);
+		}
+
+		out
+	}
+
+	fn round_trip(config: EvsConfig, frames: &[u8]) -> Vec<u8> {
+		let payloads = EvsPacketizer::new(config).packetize(frames, 1200);
+		assert_eq!(payloads.len(), 1);
+
+		let mut depack = EvsDepacketizer::new(config);
+		assert_eq!(depack.depacketize(&[&payloads[0]]).unwrap(), frames);
+
+		payloads.into_iter().next().unwrap()
+	}
+
+	#[test]
+	fn compact_frames_round_trip() {
+		let primary = storage(&[(EvsFrameType::Primary(2), true)]);
+		assert_eq!(round_trip(EvsConfig::default(), &primary).len(), 20);
+
+		let io = storage(&[(EvsFrameType::AmrWbIo(2), true)]);
+		let payload = round_trip(EvsConfig::default(), &io);
+		assert_eq!(payload.len(), 32);
+		assert_eq!(payload[0] >> 5, COMPACT_CMR_NONE);
+	}
+
+	#[test]
+	fn header_full_frames_round_trip() {
+		let config = EvsConfig::default();
+
+		let frames = storage(&[
+			(EvsFrameType::Primary(2), true),
+			(EvsFrameType::Primary(NO_DATA), true),
+			(EvsFrameType::Primary(12), true),
+		]);
+		let payload = round_trip(config, &frames);
+		assert_eq!(EvsPayload::parse(&payload, config).unwrap().duration(), 960);
+
+		// A damaged IO frame cannot be compact, and its header-full payload would
+		// otherwise have the length of a compact primary frame.
+		let damaged = storage(&[(EvsFrameType::AmrWbIo(2), false)]);
+		let payload = round_trip(config, &damaged);
+		assert_eq!(payload.len(), 34);
+		assert_eq!(payload[0], CMR_NONE);
+
+		let config = EvsConfig::from_fmtp("hf-only=1");
+		let primary = storage(&[(EvsFrameType::Primary(2), true)]);
+		assert_eq!(round_trip(config, &primary).len(), 21);
+	}
+}
@@ -0,0 +1,181 @@
+//! G.711 audio, as µ-law (`PCMU`, type `0`) or A-law (`PCMA`, type `8`) samples.
+//!
+//! Both variants carry one byte per sample at 8 kHz, so that a payload's size
+//! is also its duration in timestamp units. Payloads typically last 20 ms, but
+//! any multiple of a sample may be sent.
+//!
+//! See [RFC 3551](https://tools.ietf.org/html/rfc3551#section-4.5.14).
+
+use crate::rtp::{
+	depacketizer::Depacketizer,
+	packetizer::{duration_samples, Packetizer},
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// Sampling rate (and RTP clock rate) of G.711 audio.
+pub const CLOCK_RATE: u32 = 8000;
+
+/// Conventional duration of a G.711 payload.
+pub const DEFAULT_PTIME: Duration = Duration::from_millis(20);
+
+/// Returns the number of samples (and bytes) lasting `ptime`, which is also the
+/// amount by which the timestamp advances between packets of this duration.
+#[must_use]
+pub fn samples(ptime: Duration) -> usize {
+	let samples = duration_samples(CLOCK_RATE, ptime);
+
+	usize::try_from(samples).unwrap_or(usize::MAX)
+}
+
+/// Splits a received payload into the frames of `frame_time` it is made up of,
+/// such as the 10 ms frames expected by some decoders.
+///
+/// The last frame may be shorter, if the payload is not a multiple of
+/// `frame_time`.
+pub fn split_frames(payload: &[u8], frame_time: Duration) -> core::slice::Chunks<'_, u8> {
+	payload.chunks(samples(frame_time).max(1))
+}
+
+/// Packetizer of G.711 samples into RTP payloads of a fixed duration.
+///
+/// Samples may be fed in as they are produced by [`push`], which returns every
+/// payload completed. Each payload should then be sent by an [`RtpPacketizer`],
+/// advancing the timestamp by its length.
+///
+/// Payloads passed directly to [`Packetizer::packetize`] are sent unchanged.
+///
+/// [`push`]: #method.push
+/// [`RtpPacketizer`]: ../../packetizer/struct.RtpPacketizer.html
+/// [`Packetizer::packetize`]: ../../packetizer/trait.Packetizer.html#tymethod.packetize
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct G711Packetizer {
+	ptime: Duration,
+	pending: Vec<u8>,
+}
+
+impl Default for G711Packetizer {
+	fn default() -> Self {
+		Self::new(DEFAULT_PTIME)
+	}
+}
+
+impl G711Packetizer {
+	/// Creates a packetizer producing payloads lasting `ptime`, such as 10, 20 or
+	/// 30 ms.
+	#[must_use]
+	pub fn new(ptime: Duration) -> Self {
+		Self {
+			ptime,
+			pending: Vec::new(),
+		}
+	}
+
+	/// Returns the duration of each payload.
+	#[must_use]
+	pub fn ptime(&self) -> Duration {
+		self.ptime
+	}
+
+	/// Changes the duration of each payload.
+	pub fn set_ptime(&mut self, ptime: Duration) {
+		self.ptime = ptime;
+	}
+
+	/// Returns the number of samples (and bytes) in each payload.
+	#[must_use]
+	pub fn payload_len(&self) -> usize {
+		samples(self.ptime).max(1)
+	}
+
+	/// Adds `samples` to those awaiting packetization, returning all newly
+	/// completed payloads.
+	pub fn push(&mut self, samples: &[u8]) -> Vec<Vec<u8>> {
+		self.pending.extend_from_slice(samples);
+
+		let len = self.payload_len();
+		let whole = self.pending.len() - self.pending.len() % len;
+		let out = self.pending[..whole]
+			.chunks_exact(len)
+			.map(<[u8]>::to_vec)
+			.collect();
+		self.pending.drain(..whole);
+
+		out
+	}
+
+	/// Returns the samples which do not yet fill a payload.
+	#[must_use]
+	pub fn pending(&self) -> &[u8] {
+		&self.pending
+	}
+
+	/// Removes and returns the samples which do not yet fill a payload, such as at
+	/// the end of a talkspurt.
+	pub fn flush(&mut self) -> Option<Vec<u8>> {
+		(!self.pending.is_empty()).then(|| core::mem::take(&mut self.pending))
+	}
+}
+
+impl Packetizer for G711Packetizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		if frame.is_empty() || frame.len() > max_payload {
+			return Vec::new();
+		}
+
+		alloc::vec![frame.to_vec()]
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		false
+	}
+}
+
+/// Depacketizer of G.711 RTP payloads, which returns each payload's samples.
+///
+/// See [`split_frames`] to divide these into fixed-size decoder frames.
+///
+/// [`split_frames`]: fn.split_frames.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct G711Depacketizer;
+
+impl Depacketizer for G711Depacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let out = payloads.concat();
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn ptime_framing_round_trip() {
+		let mut packetizer = G711Packetizer::new(Duration::from_millis(10));
+		assert_eq!(packetizer.payload_len(), 80);
+
+		let samples: Vec<u8> = (0..=255).cycle().take(200).collect();
+		let mut payloads = packetizer.push(&samples[..50]);
+		assert!(payloads.is_empty());
+		payloads.extend(packetizer.push(&samples[50..]));
+		assert_eq!(payloads.len(), 2);
+		assert_eq!(packetizer.pending().len(), 40);
+		payloads.extend(packetizer.flush());
+		assert_eq!(packetizer.flush(), None);
+
+		let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+		let out = G711Depacketizer.depacketize(&payloads).unwrap();
+		assert_eq!(out, samples);
+
+		let frames: Vec<usize> = split_frames(&out, Duration::from_millis(10))
+			.map(<[u8]>::len)
+			.collect();
+		assert_eq!(frames, [80, 80, 40]);
+	}
+}
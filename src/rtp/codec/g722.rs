@@ -0,0 +1,70 @@
+//! G.722 wideband audio (type `9`).
+//!
+//! G.722 is sampled at 16 kHz, but RTP timestamps advance at only 8 kHz: an
+//! error in RFC 1890 which was kept for compatibility. Timestamp increments are
+//! therefore half the number of samples sent, and durations must be computed
+//! from the 8 kHz [`CLOCK_RATE`]. At 64 kbit/s, this makes each payload byte
+//! worth one timestamp unit.
+//!
+//! See [RFC 3551](https://tools.ietf.org/html/rfc3551#section-4.5.2).
+//!
+//! [`CLOCK_RATE`]: constant.CLOCK_RATE.html
+
+use crate::rtp::packetizer::duration_samples;
+use core::time::Duration;
+
+/// Rate at which RTP timestamps of G.722 audio advance.
+pub const CLOCK_RATE: u32 = 8_000;
+
+/// Rate at which G.722 audio is sampled.
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// Returns the amount by which the timestamp advances after sending `samples`
+/// samples (at 16 kHz).
+#[must_use]
+pub fn timestamp_advance(samples: u32) -> u32 {
+	samples / (SAMPLE_RATE / CLOCK_RATE)
+}
+
+/// Returns the number of samples (at 16 kHz) spanned by a timestamp difference
+/// of `timestamp_delta`.
+#[must_use]
+pub fn samples(timestamp_delta: u32) -> u32 {
+	timestamp_delta.saturating_mul(SAMPLE_RATE / CLOCK_RATE)
+}
+
+/// Returns the amount by which the timestamp advances between packets lasting
+/// `ptime`.
+#[must_use]
+pub fn ptime_advance(ptime: Duration) -> u32 {
+	let advance = duration_samples(CLOCK_RATE, ptime);
+
+	u32::try_from(advance).unwrap_or(u32::MAX)
+}
+
+/// Returns the duration spanned by a timestamp difference of `timestamp_delta`.
+#[must_use]
+pub fn duration(timestamp_delta: u32) -> Duration {
+	Duration::from_nanos(u64::from(timestamp_delta) * 1_000_000_000 / u64::from(CLOCK_RATE))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtp::RtpType;
+
+	#[test]
+	fn timestamps_use_legacy_clock() {
+		assert_eq!(RtpType::G722.clock_rate(), Some(CLOCK_RATE));
+		assert_eq!(RtpType::G722.sample_rate(), Some(SAMPLE_RATE));
+		assert!(RtpType::G722.has_legacy_clock_rate());
+		assert!(!RtpType::Pcmu.has_legacy_clock_rate());
+
+		let ptime = Duration::from_millis(20);
+		assert_eq!(ptime_advance(ptime), 160);
+		assert_eq!(timestamp_advance(320), 160);
+		assert_eq!(samples(160), 320);
+		assert_eq!(duration(ptime_advance(ptime)), ptime);
+		assert_eq!(timestamp_advance(samples(u32::MAX / 2)), u32::MAX / 2);
+	}
+}
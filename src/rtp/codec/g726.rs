@@ -0,0 +1,252 @@
+//! G.726 ADPCM speech, at 16, 24, 32 or 40 kbit/s.
+//!
+//! Each 8 kHz sample is encoded as a 2- to 5-bit codeword, and codewords are
+//! packed back to back into octets in one of two orders:
+//!
+//! * [RFC 3551] packing (encoding names such as `G726-32`) places the first
+//!   codeword in the least significant bits of the first octet.
+//! * AAL2 packing (encoding names such as `AAL2-G726-32`), from ITU-T I.366.2,
+//!   places the first codeword in the most significant bits of the first octet.
+//!
+//! Software is inconsistent as to which of these is called "little-endian", so
+//! the packing in use is best read from the negotiated encoding name.
+//!
+//! [RFC 3551]: https://tools.ietf.org/html/rfc3551#section-4.5.4
+
+use alloc::vec::Vec;
+
+/// Sampling rate (and RTP clock rate) of G.726 audio.
+pub const CLOCK_RATE: u32 = 8_000;
+
+/// Bit rate of a G.726 stream, which fixes its codeword size.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum G726Rate {
+	/// 16 kbit/s, with 2-bit codewords.
+	Kbps16,
+	/// 24 kbit/s, with 3-bit codewords.
+	Kbps24,
+	/// 32 kbit/s, with 4-bit codewords.
+	Kbps32,
+	/// 40 kbit/s, with 5-bit codewords.
+	Kbps40,
+}
+
+impl G726Rate {
+	/// Returns the size of each codeword, in bits.
+	#[must_use]
+	pub fn codeword_bits(self) -> u8 {
+		match self {
+			Self::Kbps16 => 2,
+			Self::Kbps24 => 3,
+			Self::Kbps32 => 4,
+			Self::Kbps40 => 5,
+		}
+	}
+
+	/// Returns the bit rate of the stream, in bits per second.
+	#[must_use]
+	pub fn bit_rate(self) -> u32 {
+		CLOCK_RATE * u32::from(self.codeword_bits())
+	}
+
+	/// Returns the number of samples held in a payload of `len` bytes, which is
+	/// also the amount by which its timestamp advances.
+	#[must_use]
+	pub fn samples(self, len: usize) -> usize {
+		len * 8 / usize::from(self.codeword_bits())
+	}
+
+	/// Returns the size of a payload holding `samples` codewords, in bytes.
+	#[must_use]
+	pub fn payload_len(self, samples: usize) -> usize {
+		(samples * usize::from(self.codeword_bits()) + 7) / 8
+	}
+}
+
+/// Order in which codewords are packed into octets.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum G726Packing {
+	/// Packing of RFC 3551, starting from the least significant bit of each octet.
+	#[default]
+	Rfc3551,
+	/// Packing of ITU-T I.366.2 Annex E, starting from the most significant bit of
+	/// each octet.
+	Aal2,
+}
+
+/// Returns the rate and packing named by an SDP encoding name, such as
+/// `G726-32` or `AAL2-G726-32`, matched case-insensitively.
+///
+/// The older name `G721` is read as 32 kbit/s with RFC 3551 packing.
+#[must_use]
+pub fn from_encoding(name: &str) -> Option<(G726Rate, G726Packing)> {
+	if name.eq_ignore_ascii_case("G721") {
+		return Some((G726Rate::Kbps32, G726Packing::Rfc3551));
+	}
+
+	let (packing, name) = match name.get(..5) {
+		Some(prefix) if prefix.eq_ignore_ascii_case("AAL2-") => (G726Packing::Aal2, &name[5..]),
+		_ => (G726Packing::Rfc3551, name),
+	};
+
+	let (codec, rate) = name.split_once('-')?;
+	if !codec.eq_ignore_ascii_case("G726") {
+		return None;
+	}
+
+	let rate = match rate {
+		"16" => G726Rate::Kbps16,
+		"24" => G726Rate::Kbps24,
+		"32" => G726Rate::Kbps32,
+		"40" => G726Rate::Kbps40,
+		_ => return None,
+	};
+
+	Some((rate, packing))
+}
+
+/// Packs `codewords`, one per byte, into a payload.
+///
+/// Only the low bits of each codeword are used. If the codewords do not fill a
+/// whole number of octets, the last is padded with zero bits.
+#[must_use]
+pub fn pack(codewords: &[u8], rate: G726Rate, packing: G726Packing) -> Vec<u8> {
+	let bits = u32::from(rate.codeword_bits());
+	let mask = (1 << bits) - 1;
+	let mut out = Vec::with_capacity(rate.payload_len(codewords.len()));
+
+	let mut acc = 0u32;
+	let mut held = 0;
+	for &codeword in codewords {
+		let codeword = u32::from(codeword) & mask;
+		match packing {
+			G726Packing::Rfc3551 => acc |= codeword << held,
+			G726Packing::Aal2 => acc = (acc << bits) | codeword,
+		}
+		held += bits;
+
+		while held >= 8 {
+			held -= 8;
+			#[allow(clippy::cast_possible_truncation)]
+			match packing {
+				G726Packing::Rfc3551 => {
+					out.push(acc as u8);
+					acc >>= 8;
+				},
+				G726Packing::Aal2 => {
+					out.push((acc >> held) as u8);
+					acc &= (1 << held) - 1;
+				},
+			}
+		}
+	}
+
+	if held != 0 {
+		#[allow(clippy::cast_possible_truncation)]
+		out.push(match packing {
+			G726Packing::Rfc3551 => acc as u8,
+			G726Packing::Aal2 => (acc << (8 - held)) as u8,
+		});
+	}
+
+	out
+}
+
+/// Unpacks the codewords of `payload`, one per byte.
+///
+/// Any trailing bits too few to form a codeword are ignored.
+#[must_use]
+pub fn unpack(payload: &[u8], rate: G726Rate, packing: G726Packing) -> Vec<u8> {
+	let bits = u32::from(rate.codeword_bits());
+	let mask = (1 << bits) - 1;
+	let mut out = Vec::with_capacity(rate.samples(payload.len()));
+
+	let mut acc = 0u32;
+	let mut held = 0;
+	for &byte in payload {
+		match packing {
+			G726Packing::Rfc3551 => acc |= u32::from(byte) << held,
+			G726Packing::Aal2 => acc = (acc << 8) | u32::from(byte),
+		}
+		held += 8;
+
+		while held >= bits {
+			held -= bits;
+			#[allow(clippy::cast_possible_truncation)]
+			match packing {
+				G726Packing::Rfc3551 => {
+					out.push((acc & mask) as u8);
+					acc >>= bits;
+				},
+				G726Packing::Aal2 => {
+					out.push(((acc >> held) & mask) as u8);
+					acc &= (1 << held) - 1;
+				},
+			}
+		}
+	}
+
+	out
+}
+
+/// Converts `payload` from one packing order to another.
+///
+/// Any trailing bits too few to form a codeword are dropped.
+#[must_use]
+pub fn repack(payload: &[u8], rate: G726Rate, from: G726Packing, to: G726Packing) -> Vec<u8> {
+	if from == to {
+		return payload.to_vec();
+	}
+
+	pack(&unpack(payload, rate, from), rate, to)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const RATES: [G726Rate; 4] = [
+		G726Rate::Kbps16,
+		G726Rate::Kbps24,
+		G726Rate::Kbps32,
+		G726Rate::Kbps40,
+	];
+
+	#[test]
+	fn codewords_round_trip_in_both_packings() {
+		for rate in RATES {
+			let mask = (1 << rate.codeword_bits()) - 1;
+			let codewords: Vec<u8> = (0..160u8).map(|i| i.wrapping_mul(7) & mask).collect();
+
+			for packing in [G726Packing::Rfc3551, G726Packing::Aal2] {
+				let payload = pack(&codewords, rate, packing);
+				assert_eq!(payload.len(), rate.payload_len(160));
+				assert_eq!(rate.samples(payload.len()), 160);
+				assert_eq!(unpack(&payload, rate, packing), codewords);
+			}
+
+			let rfc = pack(&codewords, rate, G726Packing::Rfc3551);
+			let aal2 = repack(&rfc, rate, G726Packing::Rfc3551, G726Packing::Aal2);
+			assert_eq!(aal2, pack(&codewords, rate, G726Packing::Aal2));
+			assert_eq!(
+				repack(&aal2, rate, G726Packing::Aal2, G726Packing::Rfc3551),
+				rfc
+			);
+		}
+	}
+
+	#[test]
+	fn packing_orders_differ() {
+		let rate = G726Rate::Kbps32;
+		assert_eq!(pack(&[1, 2], rate, G726Packing::Rfc3551), [0x21]);
+		assert_eq!(pack(&[1, 2], rate, G726Packing::Aal2), [0x12]);
+		assert_eq!(pack(&[1, 2, 3], rate, G726Packing::Aal2), [0x12, 0x30]);
+
+		assert_eq!(
+			from_encoding("aal2-g726-32"),
+			Some((rate, G726Packing::Aal2))
+		);
+		assert_eq!(from_encoding("G721"), Some((rate, G726Packing::Rfc3551)));
+		assert_eq!(from_encoding("G726-33"), None);
+	}
+}
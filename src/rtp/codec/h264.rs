@@ -0,0 +1,289 @@
+//! H.264 video, carried as NAL units.
+//!
+//! Frames are exchanged as Annex B byte streams. Single NAL unit packets,
+//! STAP-A aggregation packets, and FU-A fragments are supported.
+//!
+//! See [RFC 6184](https://tools.ietf.org/html/rfc6184).
+
+use super::{fu_flags, push_annex_b, AnnexBUnits};
+use crate::{
+	rtp::{
+		depacketizer::Depacketizer,
+		fragment::{self, Defragmenter},
+		packetizer::Packetizer,
+	},
+	wrap::Wrap16,
+};
+use alloc::{vec, vec::Vec};
+
+/// NAL unit type of a coded slice of a non-IDR picture.
+pub const NAL_SLICE: u8 = 1;
+
+/// NAL unit type of a coded slice of an IDR (instantaneous decoder refresh)
+/// picture, which begins a keyframe.
+pub const NAL_IDR: u8 = 5;
+
+/// NAL unit type of supplemental enhancement information.
+pub const NAL_SEI: u8 = 6;
+
+/// NAL unit type of a sequence parameter set.
+pub const NAL_SPS: u8 = 7;
+
+/// NAL unit type of a picture parameter set.
+pub const NAL_PPS: u8 = 8;
+
+/// NAL unit type of an access unit delimiter.
+pub const NAL_AUD: u8 = 9;
+
+/// Payload type of a single-time aggregation packet.
+pub const STAP_A: u8 = 24;
+
+/// Payload type of a fragmentation unit without decoding order number.
+pub const FU_A: u8 = 28;
+
+/// Returns the NAL unit type of a NAL unit header byte.
+#[must_use]
+pub fn nal_type(header: u8) -> u8 {
+	header & 0x1f
+}
+
+/// Returns the types of the NAL units (or unit fragments) carried in an RTP
+/// payload, looking inside STAP-A packets and FU-A fragments.
+#[must_use]
+pub fn payload_nal_types(payload: &[u8]) -> Vec<u8> {
+	let Some(&header) = payload.first() else {
+		return Vec::new();
+	};
+
+	match nal_type(header) {
+		STAP_A => aggregated(&payload[1..])
+			.filter_map(|unit| unit.first().map(|&h| nal_type(h)))
+			.collect(),
+		FU_A => payload
+			.get(1)
+			.map(|&h| vec![nal_type(h)])
+			.unwrap_or_default(),
+		t => vec![t],
+	}
+}
+
+/// Returns whether an RTP payload carries (the start of) an IDR slice or a
+/// sequence parameter set, either of which begins a keyframe.
+#[must_use]
+pub fn is_keyframe(payload: &[u8]) -> bool {
+	payload_nal_types(payload)
+		.into_iter()
+		.any(|t| t == NAL_IDR || t == NAL_SPS)
+}
+
+/// Iterates over the NAL units of a STAP-A body (after its header byte), each of
+/// which is prefixed by a 16-bit length.
+///
+/// Iteration ends early if a length is truncated.
+fn aggregated(mut body: &[u8]) -> impl Iterator<Item = &[u8]> {
+	core::iter::from_fn(move || {
+		let len = usize::from(u16::from_be_bytes([*body.first()?, *body.get(1)?]));
+		let unit = body.get(2..2 + len)?;
+		body = &body[2 + len..];
+
+		Some(unit)
+	})
+}
+
+/// Packetizer of Annex B access units into H.264 RTP payloads.
+///
+/// NAL units which fit are sent whole, or aggregated into STAP-A packets if
+/// enabled and no longer than the 16-bit STAP-A size field allows. Larger NAL
+/// units are split into FU-A fragments.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct H264Packetizer {
+	/// Whether small NAL units (*e.g.*, parameter sets) are combined into
+	/// STAP-A packets.
+	pub aggregate: bool,
+}
+
+impl H264Packetizer {
+	/// Creates a packetizer, which aggregates small NAL units if `aggregate` is set.
+	#[must_use]
+	pub fn new(aggregate: bool) -> Self {
+		Self { aggregate }
+	}
+}
+
+impl Packetizer for H264Packetizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		let mut out = Vec::new();
+		let mut stap: Vec<(u16, &[u8])> = Vec::new();
+
+		for unit in AnnexBUnits::new(frame) {
+			if unit.len() <= max_payload {
+				let len = u16::try_from(unit.len()).ok().filter(|_| self.aggregate);
+				let Some(len) = len else {
+					flush_stap(&mut stap, &mut out);
+					out.push(unit.to_vec());
+					continue;
+				};
+
+				let stap_len = 1 + stap.iter().map(|(_, u)| 2 + u.len()).sum::<usize>();
+				if !stap.is_empty() && stap_len + 2 + unit.len() > max_payload {
+					flush_stap(&mut stap, &mut out);
+				}
+				stap.push((len, unit));
+			} else {
+				flush_stap(&mut stap, &mut out);
+				if !fragment(unit, max_payload, &mut out) {
+					return Vec::new();
+				}
+			}
+		}
+		flush_stap(&mut stap, &mut out);
+
+		out
+	}
+}
+
+/// Emits the pending NAL units as a single NAL unit packet or STAP-A packet.
+fn flush_stap(stap: &mut Vec<(u16, &[u8])>, out: &mut Vec<Vec<u8>>) {
+	match stap.len() {
+		0 => {},
+		1 => out.push(stap[0].1.to_vec()),
+		_ => {
+			let nri = stap.iter().map(|(_, u)| u[0] & 0x60).max().unwrap_or(0);
+			let forbidden = stap.iter().fold(0, |acc, (_, u)| acc | (u[0] & 0x80));
+
+			let mut pkt = vec![forbidden | nri | STAP_A];
+			for (len, unit) in stap.iter() {
+				pkt.extend_from_slice(&len.to_be_bytes());
+				pkt.extend_from_slice(unit);
+			}
+			out.push(pkt);
+		},
+	}
+	stap.clear();
+}
+
+/// Splits `unit` into FU-A fragments of at most `max_payload` bytes.
+///
+/// Returns `false` if `max_payload` is too small to make progress.
+fn fragment(unit: &[u8], max_payload: usize, out: &mut Vec<Vec<u8>>) -> bool {
+	let header = unit[0];
+	let indicator = (header & 0xe0) | FU_A;
+
+	fragment::fragment(&unit[1..], max_payload, 2, out, |flags, pkt| {
+		let fu_header =
+			(u8::from(flags.start) << 7) | (u8::from(flags.end) << 6) | nal_type(header);
+		pkt.extend_from_slice(&[indicator, fu_header]);
+	})
+}
+
+/// Reassembles the NAL units of `packets`, dropping any FU-A whose fragments
+/// are not sequential if sequence numbers are known.
+fn unpack<'a>(packets: impl Iterator<Item = (Option<Wrap16>, &'a [u8])>) -> Option<Vec<u8>> {
+	let mut out = Vec::new();
+	let mut fu = Defragmenter::new();
+
+	for (sequence, payload) in packets {
+		let Some(&header) = payload.first() else {
+			continue;
+		};
+
+		match nal_type(header) {
+			STAP_A =>
+				for unit in aggregated(&payload[1..]) {
+					push_annex_b(&mut out, unit);
+				},
+			FU_A => {
+				let Some(&fu_header) = payload.get(1) else {
+					continue;
+				};
+				let flags = fu_flags(fu_header);
+				let write_header = |unit: &mut Vec<u8>| {
+					unit.push((header & 0xe0) | nal_type(fu_header));
+				};
+				let unit = match sequence {
+					Some(seq) => fu.push_sequenced(seq, flags, &payload[2..], write_header),
+					None => fu.push(flags, &payload[2..], write_header),
+				};
+				if let Some(unit) = unit {
+					push_annex_b(&mut out, &unit);
+				}
+			},
+			0 | 25..=31 => {},
+			_ => push_annex_b(&mut out, payload),
+		}
+	}
+
+	(!out.is_empty()).then_some(out)
+}
+
+/// Depacketizer of H.264 RTP payloads into Annex B access units.
+///
+/// FU-A fragments whose start is missing, or which are never finished, are
+/// dropped. Missing middle fragments are only detected when sequence numbers
+/// are given, such as by an [`RtpDepacketizer`].
+///
+/// [`RtpDepacketizer`]: ../../depacketizer/struct.RtpDepacketizer.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct H264Depacketizer;
+
+impl Depacketizer for H264Depacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		unpack(payloads.iter().map(|&p| (None, p)))
+	}
+
+	fn depacketize_sequenced(&mut self, packets: &[(Wrap16, &[u8])]) -> Option<Vec<u8>> {
+		unpack(packets.iter().map(|&(seq, p)| (Some(seq), p)))
+	}
+
+	fn is_keyframe(&self, payloads: &[&[u8]]) -> bool {
+		payloads.iter().any(|p| is_keyframe(p))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lost_fragment_drops_unit() {
+		let mut unit = vec![0x65];
+		unit.extend((0..250).map(|i: u16| i.to_be_bytes()[1]));
+		let mut payloads = Vec::new();
+		assert!(fragment(&unit, 100, &mut payloads));
+		assert_eq!(payloads.len(), 3);
+
+		let packets: Vec<(Wrap16, &[u8])> = payloads
+			.iter()
+			.zip(0u16..)
+			.map(|(p, seq)| (seq.into(), &p[..]))
+			.collect();
+
+		let out = H264Depacketizer.depacketize_sequenced(&packets).unwrap();
+		assert_eq!(out[4..], unit[..]);
+
+		let lost = [packets[0], packets[2]];
+		assert_eq!(H264Depacketizer.depacketize_sequenced(&lost), None);
+	}
+
+	#[test]
+	fn oversized_units_are_not_aggregated() {
+		let sps = [0x67, 1, 2, 3];
+		let pps = [0x68, 4, 5];
+		let mut idr = vec![0x65];
+		idr.resize(70_000, 0x55);
+
+		let mut frame = Vec::new();
+		for unit in [&sps[..], &pps[..], &idr[..], &sps[..]] {
+			push_annex_b(&mut frame, unit);
+		}
+
+		let payloads = H264Packetizer::new(true).packetize(&frame, 100_000);
+		assert_eq!(payloads.len(), 3);
+		assert_eq!(nal_type(payloads[0][0]), STAP_A);
+		assert_eq!(payloads[1], idr);
+		assert_eq!(payloads[2], sps);
+
+		let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+		assert_eq!(H264Depacketizer.depacketize(&payloads), Some(frame));
+	}
+}
@@ -0,0 +1,315 @@
+//! H.265 (HEVC) video, carried as NAL units.
+//!
+//! Frames are exchanged as Annex B byte streams. Single NAL unit packets,
+//! aggregation packets (APs), and fragmentation units (FUs) are supported,
+//! without decoding order numbers.
+//!
+//! See [RFC 7798](https://tools.ietf.org/html/rfc7798).
+
+use super::{fu_flags, push_annex_b, AnnexBUnits};
+use crate::{
+	rtp::{
+		depacketizer::Depacketizer,
+		fragment::{self, Defragmenter},
+		packetizer::Packetizer,
+	},
+	wrap::Wrap16,
+};
+use alloc::{vec, vec::Vec};
+
+/// Length of an H.265 NAL unit header, and of the RTP payload header.
+pub const HEADER_LEN: usize = 2;
+
+/// NAL unit type of an IDR picture with leading RADL pictures.
+pub const NAL_IDR_W_RADL: u8 = 19;
+
+/// NAL unit type of an IDR picture without leading pictures.
+pub const NAL_IDR_N_LP: u8 = 20;
+
+/// NAL unit type of a clean random access picture.
+pub const NAL_CRA: u8 = 21;
+
+/// NAL unit type of a video parameter set.
+pub const NAL_VPS: u8 = 32;
+
+/// NAL unit type of a sequence parameter set.
+pub const NAL_SPS: u8 = 33;
+
+/// NAL unit type of a picture parameter set.
+pub const NAL_PPS: u8 = 34;
+
+/// Payload type of an aggregation packet.
+pub const AP: u8 = 48;
+
+/// Payload type of a fragmentation unit.
+pub const FU: u8 = 49;
+
+/// Payload type of a payload content information packet.
+pub const PACI: u8 = 50;
+
+/// Returns the NAL unit type of the first byte of a NAL unit (or payload) header.
+#[must_use]
+pub fn nal_type(header: u8) -> u8 {
+	(header >> 1) & 0x3f
+}
+
+/// Returns whether a NAL unit type is an intra random access point (IRAP)
+/// picture, such as an IDR or CRA picture.
+#[must_use]
+pub fn is_irap(nal_type: u8) -> bool {
+	(16..=23).contains(&nal_type)
+}
+
+/// Returns the types of the NAL units (or unit fragments) carried in an RTP
+/// payload, looking inside aggregation packets and fragmentation units.
+#[must_use]
+pub fn payload_nal_types(payload: &[u8]) -> Vec<u8> {
+	if payload.len() < HEADER_LEN {
+		return Vec::new();
+	}
+
+	match nal_type(payload[0]) {
+		AP => aggregated(&payload[HEADER_LEN..])
+			.filter_map(|unit| unit.first().map(|&h| nal_type(h)))
+			.collect(),
+		FU => payload
+			.get(HEADER_LEN)
+			.map(|&h| vec![h & 0x3f])
+			.unwrap_or_default(),
+		t => vec![t],
+	}
+}
+
+/// Returns whether an RTP payload carries (the start of) an IRAP picture or a
+/// parameter set, any of which begins a keyframe.
+#[must_use]
+pub fn is_keyframe(payload: &[u8]) -> bool {
+	payload_nal_types(payload)
+		.into_iter()
+		.any(|t| is_irap(t) || t == NAL_VPS || t == NAL_SPS)
+}
+
+/// Iterates over the NAL units of an AP body (after its payload header), each of
+/// which is prefixed by a 16-bit length.
+///
+/// Iteration ends early if a length is truncated.
+fn aggregated(mut body: &[u8]) -> impl Iterator<Item = &[u8]> {
+	core::iter::from_fn(move || {
+		let len = usize::from(u16::from_be_bytes([*body.first()?, *body.get(1)?]));
+		let unit = body.get(2..2 + len)?;
+		body = &body[2 + len..];
+
+		Some(unit)
+	})
+}
+
+/// Packetizer of Annex B access units into H.265 RTP payloads.
+///
+/// NAL units which fit are sent whole, or aggregated into APs if enabled and
+/// no longer than the 16-bit AP size field allows. Larger NAL units are split
+/// into FUs.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct H265Packetizer {
+	/// Whether small NAL units (*e.g.*, parameter sets) are combined into APs.
+	pub aggregate: bool,
+}
+
+impl H265Packetizer {
+	/// Creates a packetizer, which aggregates small NAL units if `aggregate` is set.
+	#[must_use]
+	pub fn new(aggregate: bool) -> Self {
+		Self { aggregate }
+	}
+}
+
+impl Packetizer for H265Packetizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		let mut out = Vec::new();
+		let mut ap: Vec<(u16, &[u8])> = Vec::new();
+
+		for unit in AnnexBUnits::new(frame).filter(|u| u.len() >= HEADER_LEN) {
+			if unit.len() <= max_payload {
+				let len = u16::try_from(unit.len()).ok().filter(|_| self.aggregate);
+				let Some(len) = len else {
+					flush_ap(&mut ap, &mut out);
+					out.push(unit.to_vec());
+					continue;
+				};
+
+				let ap_len = HEADER_LEN + ap.iter().map(|(_, u)| 2 + u.len()).sum::<usize>();
+				if !ap.is_empty() && ap_len + 2 + unit.len() > max_payload {
+					flush_ap(&mut ap, &mut out);
+				}
+				ap.push((len, unit));
+			} else {
+				flush_ap(&mut ap, &mut out);
+				if !fragment(unit, max_payload, &mut out) {
+					return Vec::new();
+				}
+			}
+		}
+		flush_ap(&mut ap, &mut out);
+
+		out
+	}
+}
+
+/// Emits the pending NAL units as a single NAL unit packet or AP.
+fn flush_ap(ap: &mut Vec<(u16, &[u8])>, out: &mut Vec<Vec<u8>>) {
+	match ap.len() {
+		0 => {},
+		1 => out.push(ap[0].1.to_vec()),
+		_ => {
+			let forbidden = ap.iter().fold(0, |acc, (_, u)| acc | (u[0] & 0x80));
+			let layer_id = ap
+				.iter()
+				.map(|(_, u)| (u16::from(u[0] & 1) << 5) | u16::from(u[1] >> 3))
+				.min()
+				.unwrap_or(0);
+			let tid = ap.iter().map(|(_, u)| u[1] & 0x07).min().unwrap_or(1);
+
+			#[allow(clippy::cast_possible_truncation)]
+			let mut pkt = vec![
+				forbidden | (AP << 1) | (layer_id >> 5) as u8,
+				((layer_id as u8) << 3) | tid,
+			];
+			for (len, unit) in ap.iter() {
+				pkt.extend_from_slice(&len.to_be_bytes());
+				pkt.extend_from_slice(unit);
+			}
+			out.push(pkt);
+		},
+	}
+	ap.clear();
+}
+
+/// Splits `unit` into FUs of at most `max_payload` bytes.
+///
+/// Returns `false` if `max_payload` is too small to make progress.
+fn fragment(unit: &[u8], max_payload: usize, out: &mut Vec<Vec<u8>>) -> bool {
+	let payload_header = [(unit[0] & 0x81) | (FU << 1), unit[1]];
+	let fu_type = nal_type(unit[0]);
+
+	fragment::fragment(
+		&unit[HEADER_LEN..],
+		max_payload,
+		HEADER_LEN + 1,
+		out,
+		|flags, pkt| {
+			pkt.extend_from_slice(&payload_header);
+			pkt.push((u8::from(flags.start) << 7) | (u8::from(flags.end) << 6) | fu_type);
+		},
+	)
+}
+
+/// Reassembles the NAL units of `packets`, dropping any FU whose fragments are
+/// not sequential if sequence numbers are known.
+fn unpack<'a>(packets: impl Iterator<Item = (Option<Wrap16>, &'a [u8])>) -> Option<Vec<u8>> {
+	let mut out = Vec::new();
+	let mut fu = Defragmenter::new();
+
+	for (sequence, payload) in packets.filter(|(_, p)| p.len() >= HEADER_LEN) {
+		match nal_type(payload[0]) {
+			AP =>
+				for unit in aggregated(&payload[HEADER_LEN..]) {
+					push_annex_b(&mut out, unit);
+				},
+			FU => {
+				let Some(&fu_header) = payload.get(HEADER_LEN) else {
+					continue;
+				};
+				let flags = fu_flags(fu_header);
+				let body = &payload[HEADER_LEN + 1..];
+				let write_header = |unit: &mut Vec<u8>| {
+					unit.extend_from_slice(&[
+						(payload[0] & 0x81) | ((fu_header & 0x3f) << 1),
+						payload[1],
+					]);
+				};
+				let unit = match sequence {
+					Some(seq) => fu.push_sequenced(seq, flags, body, write_header),
+					None => fu.push(flags, body, write_header),
+				};
+				if let Some(unit) = unit {
+					push_annex_b(&mut out, &unit);
+				}
+			},
+			PACI | 51..=63 => {},
+			_ => push_annex_b(&mut out, payload),
+		}
+	}
+
+	(!out.is_empty()).then_some(out)
+}
+
+/// Depacketizer of H.265 RTP payloads into Annex B access units.
+///
+/// FUs whose start is missing, or which are never finished, are dropped, as are
+/// PACI packets. Missing middle fragments are only detected when sequence
+/// numbers are given, such as by an [`RtpDepacketizer`].
+///
+/// [`RtpDepacketizer`]: ../../depacketizer/struct.RtpDepacketizer.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct H265Depacketizer;
+
+impl Depacketizer for H265Depacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		unpack(payloads.iter().map(|&p| (None, p)))
+	}
+
+	fn depacketize_sequenced(&mut self, packets: &[(Wrap16, &[u8])]) -> Option<Vec<u8>> {
+		unpack(packets.iter().map(|&(seq, p)| (Some(seq), p)))
+	}
+
+	fn is_keyframe(&self, payloads: &[&[u8]]) -> bool {
+		payloads.iter().any(|p| is_keyframe(p))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn lost_fragment_drops_unit() {
+		let mut unit = vec![NAL_IDR_W_RADL << 1, 1];
+		unit.extend((0..250).map(|i: u16| i.to_be_bytes()[1]));
+		let mut payloads = Vec::new();
+		assert!(fragment(&unit, 100, &mut payloads));
+		assert_eq!(payloads.len(), 3);
+
+		let packets: Vec<(Wrap16, &[u8])> = payloads
+			.iter()
+			.zip(0u16..)
+			.map(|(p, seq)| (seq.into(), &p[..]))
+			.collect();
+
+		let out = H265Depacketizer.depacketize_sequenced(&packets).unwrap();
+		assert_eq!(out[4..], unit[..]);
+
+		let lost = [packets[0], packets[2]];
+		assert_eq!(H265Depacketizer.depacketize_sequenced(&lost), None);
+	}
+
+	#[test]
+	fn oversized_units_are_not_aggregated() {
+		let vps = [NAL_VPS << 1, 1, 2, 3];
+		let sps = [NAL_SPS << 1, 1, 4, 5];
+		let mut idr = vec![NAL_IDR_N_LP << 1, 1];
+		idr.resize(70_000, 0x55);
+
+		let mut frame = Vec::new();
+		for unit in [&vps[..], &sps[..], &idr[..], &sps[..]] {
+			push_annex_b(&mut frame, unit);
+		}
+
+		let payloads = H265Packetizer::new(true).packetize(&frame, 100_000);
+		assert_eq!(payloads.len(), 3);
+		assert_eq!(nal_type(payloads[0][0]), AP);
+		assert_eq!(payloads[1], idr);
+		assert_eq!(payloads[2], sps);
+
+		let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+		assert_eq!(H265Depacketizer.depacketize(&payloads), Some(frame));
+	}
+}
@@ -0,0 +1,209 @@
+//! iLBC speech, in either its 20 ms or 30 ms frame mode.
+//!
+//! A payload holds one or more frames of the same mode back to back. The mode
+//! is negotiated by the `mode` format parameter (defaulting to 30 ms), but may
+//! also be inferred from a payload's length where this is unambiguous.
+//!
+//! See [RFC 3952](https://tools.ietf.org/html/rfc3952).
+
+use super::fmtp_params;
+use crate::rtp::{depacketizer::Depacketizer, packetizer::Packetizer};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// Sampling rate (and RTP clock rate) of iLBC audio.
+pub const CLOCK_RATE: u32 = 8_000;
+
+/// Frame mode of an iLBC stream.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum IlbcMode {
+	/// 20 ms frames of 38 bytes, at 15.2 kbit/s.
+	Ms20,
+	/// 30 ms frames of 50 bytes, at 13.33 kbit/s.
+	#[default]
+	Ms30,
+}
+
+impl IlbcMode {
+	/// Reads the frame mode from the parameters of an `a=fmtp` line, defaulting to
+	/// 30 ms if none is given.
+	///
+	/// Returns `None` if the mode is neither `20` nor `30`.
+	#[must_use]
+	pub fn from_fmtp(params: &str) -> Option<Self> {
+		let mode = fmtp_params(params)
+			.find(|(k, _)| k.eq_ignore_ascii_case("mode"))
+			.map(|(_, v)| v);
+
+		match mode {
+			Some("20") => Some(Self::Ms20),
+			Some("30") | None => Some(Self::Ms30),
+			_ => None,
+		}
+	}
+
+	/// Returns the size of each frame, in bytes.
+	#[must_use]
+	pub fn frame_len(self) -> usize {
+		match self {
+			Self::Ms20 => 38,
+			Self::Ms30 => 50,
+		}
+	}
+
+	/// Returns the duration of each frame.
+	#[must_use]
+	pub fn frame_duration(self) -> Duration {
+		match self {
+			Self::Ms20 => Duration::from_millis(20),
+			Self::Ms30 => Duration::from_millis(30),
+		}
+	}
+
+	/// Returns the amount by which the timestamp advances for each frame.
+	#[must_use]
+	pub fn frame_samples(self) -> u32 {
+		match self {
+			Self::Ms20 => 160,
+			Self::Ms30 => 240,
+		}
+	}
+
+	/// Returns the mode of a payload of `len` bytes, preferring `self` where the
+	/// length is a whole number of frames in either mode.
+	///
+	/// Returns `None` if the payload is empty, or holds a partial frame in both
+	/// modes.
+	#[must_use]
+	pub fn detect(self, len: usize) -> Option<Self> {
+		let other = match self {
+			Self::Ms20 => Self::Ms30,
+			Self::Ms30 => Self::Ms20,
+		};
+
+		[self, other]
+			.into_iter()
+			.find(|mode| len != 0 && len % mode.frame_len() == 0)
+	}
+
+	/// Returns the amount by which the timestamp advances after a payload of `len`
+	/// bytes in this mode.
+	#[must_use]
+	pub fn duration(self, len: usize) -> u32 {
+		let frames = u32::try_from(len / self.frame_len()).unwrap_or(u32::MAX);
+
+		frames.saturating_mul(self.frame_samples())
+	}
+
+	/// Splits `payload` into its frames in this mode.
+	///
+	/// Returns `None` if the payload does not hold a whole number of frames.
+	#[must_use]
+	pub fn frames(self, payload: &[u8]) -> Option<core::slice::ChunksExact<'_, u8>> {
+		(payload.len() % self.frame_len() == 0).then(|| payload.chunks_exact(self.frame_len()))
+	}
+}
+
+/// Packetizer of iLBC frames into RTP payloads.
+///
+/// Each frame passed in may hold several encoded frames, which are sent together
+/// in one payload: the timestamp should then advance by [`IlbcMode::duration`]
+/// of its length.
+///
+/// [`IlbcMode::duration`]: enum.IlbcMode.html#method.duration
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IlbcPacketizer {
+	/// Frame mode of the stream sent.
+	pub mode: IlbcMode,
+}
+
+impl IlbcPacketizer {
+	/// Creates a packetizer of frames in `mode`.
+	#[must_use]
+	pub fn new(mode: IlbcMode) -> Self {
+		Self { mode }
+	}
+}
+
+impl Packetizer for IlbcPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		if frame.is_empty() || frame.len() > max_payload || self.mode.frames(frame).is_none() {
+			return Vec::new();
+		}
+
+		alloc::vec![frame.to_vec()]
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		false
+	}
+}
+
+/// Depacketizer of iLBC RTP payloads, which returns each payload's frames.
+///
+/// The frame mode of each payload is inferred from its length, preferring the
+/// last mode seen, and is exposed for configuring the decoder. Payloads holding
+/// partial frames are dropped.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct IlbcDepacketizer {
+	/// Frame mode of the most recent payload, or the negotiated mode if none has
+	/// yet been received.
+	pub mode: IlbcMode,
+}
+
+impl IlbcDepacketizer {
+	/// Creates a depacketizer expecting frames in `mode`.
+	#[must_use]
+	pub fn new(mode: IlbcMode) -> Self {
+		Self { mode }
+	}
+}
+
+impl Depacketizer for IlbcDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		for payload in payloads {
+			if let Some(mode) = self.mode.detect(payload.len()) {
+				self.mode = mode;
+				out.extend_from_slice(payload);
+			}
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn frames_round_trip_with_mode_detection() {
+		let frames: Vec<u8> = (0..=255).cycle().take(3 * 38).collect();
+		let mut packetizer = IlbcPacketizer::new(IlbcMode::Ms20);
+		assert!(packetizer.packetize(&frames[1..], 1200).is_empty());
+		assert!(packetizer.packetize(&frames, 100).is_empty());
+
+		let payloads = packetizer.packetize(&frames, 1200);
+		assert_eq!(payloads, [frames.as_slice()]);
+		assert_eq!(IlbcMode::Ms20.duration(frames.len()), 480);
+		assert_eq!(IlbcMode::Ms20.frames(&payloads[0]).unwrap().count(), 3);
+
+		let mut depacketizer = IlbcDepacketizer::default();
+		assert_eq!(depacketizer.depacketize(&[&payloads[0]]).unwrap(), frames);
+		assert_eq!(depacketizer.mode, IlbcMode::Ms20);
+
+		// A length valid in both modes keeps the mode last seen.
+		let ambiguous = [0; 950];
+		assert!(depacketizer.depacketize(&[&ambiguous]).is_some());
+		assert_eq!(depacketizer.mode, IlbcMode::Ms20);
+
+		assert!(depacketizer.depacketize(&[&[0; 50], &[0; 7]]).is_some());
+		assert_eq!(depacketizer.mode, IlbcMode::Ms30);
+		assert_eq!(depacketizer.depacketize(&[&[0; 7]]), None);
+	}
+}
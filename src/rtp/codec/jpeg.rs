@@ -0,0 +1,532 @@
+//! Motion JPEG video, carried as fragments of baseline JPEG scans.
+//!
+//! Each RTP packet carries a main JPEG header, followed by a restart marker
+//! header (for types `64` to `127`) and, in the first fragment of a frame, a
+//! quantisation table header (for `Q` values of `128` and above). Frames are
+//! reassembled into complete JFIF images, with the tables and markers omitted by
+//! the sender regenerated from these headers.
+//!
+//! See [RFC 2435](https://tools.ietf.org/html/rfc2435).
+
+use crate::{
+	error::{impl_new_checked, ParseError},
+	rtp::depacketizer::Depacketizer,
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u14be, u16be, u24be},
+};
+
+/// Size of the main JPEG header, in bytes.
+pub const HEADER_LEN: usize = 8;
+
+/// Size of the restart marker header, in bytes.
+pub const RESTART_HEADER_LEN: usize = 4;
+
+/// Lowest `Q` value whose quantisation tables are sent in-band, rather than being
+/// derived from the standard tables.
+pub const Q_IN_BAND: u8 = 128;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Main JPEG header, present at the start of every RTP payload.
+///
+/// A description of fields:
+///
+/// ## `type_specific`
+/// Interpretation depends on `jpeg_type`. Progressive (interlaced) video uses
+/// this to identify the field carried.
+///
+/// ## `fragment_offset`
+/// Offset in bytes of this fragment's data within the frame's scan.
+///
+/// ## `jpeg_type`
+/// Chroma subsampling of the image: `0` for 4:2:2, or `1` for 4:2:0. Adding
+/// `64` indicates that a restart marker header follows.
+///
+/// ## `q`
+/// Quality factor, from `1` to `99`, of the standard quantisation tables in use.
+/// Values from `128` indicate that the tables are sent in a quantisation table
+/// header.
+///
+/// ## `width`, `height`
+/// Dimensions of the image, in multiples of 8 pixels.
+///
+/// ## `payload`
+/// Any further headers, followed by the fragment's scan data.
+pub struct Jpeg {
+	pub type_specific: u8,
+
+	pub fragment_offset: u24be,
+
+	pub jpeg_type: u8,
+
+	pub q: u8,
+
+	pub width: u8,
+
+	pub height: u8,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	JpegPacket,
+	MutableJpegPacket,
+	[
+		"type_specific" => 1,
+		"fragment_offset" => 4,
+		"jpeg_type" => 5,
+		"q" => 6,
+		"width" => 7,
+		"height" => 8,
+	]
+);
+
+impl JpegPacket<'_> {
+	/// Returns whether a restart marker header follows this header.
+	#[must_use]
+	pub fn has_restart_marker(&self) -> bool {
+		(64..128).contains(&self.get_jpeg_type())
+	}
+
+	/// Returns whether the first fragment of this frame begins with a
+	/// quantisation table header.
+	#[must_use]
+	pub fn has_quantization_tables(&self) -> bool {
+		self.get_q() >= Q_IN_BAND
+	}
+
+	/// Returns the width of the image in pixels.
+	#[must_use]
+	pub fn frame_width(&self) -> u16 {
+		u16::from(self.get_width()) * 8
+	}
+
+	/// Returns the height of the image in pixels.
+	#[must_use]
+	pub fn frame_height(&self) -> u16 {
+		u16::from(self.get_height()) * 8
+	}
+}
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Restart marker header, following the main JPEG header when the image contains
+/// restart markers.
+///
+/// A description of fields:
+///
+/// ## `restart_interval`
+/// Number of MCUs between restart markers.
+///
+/// ## `first`, `last`
+/// Whether this fragment begins and ends a contiguous run of restart intervals,
+/// allowing a receiver to decode intervals despite losses elsewhere in the frame.
+/// Both are set if the frame is not fragmented at interval boundaries.
+///
+/// ## `restart_count`
+/// Index of the first restart interval in this fragment, or `0x3fff` if `first`
+/// and `last` are both set.
+///
+/// ## `payload`
+/// Any quantisation table header, followed by the fragment's scan data.
+pub struct RestartMarker {
+	pub restart_interval: u16be,
+
+	pub first: u1,
+
+	pub last: u1,
+
+	pub restart_count: u14be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	RestartMarkerPacket,
+	MutableRestartMarkerPacket,
+	["restart_interval" => 2, "restart_count" => 4]
+);
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Quantisation table header, present in the first fragment of a frame if `Q` is
+/// `128` or above.
+///
+/// A description of fields:
+///
+/// ## `mbz`
+/// Must be `0`.
+///
+/// ## `precision`
+/// Bit `i` is set if table `i` holds 16-bit rather than 8-bit values.
+///
+/// ## `length`
+/// Size of `tables` in bytes. This may be `0` if the tables for this `Q` value
+/// were sent in an earlier frame.
+///
+/// ## `tables`
+/// Concatenated quantisation tables in zigzag order: the first is used by the
+/// luminance component, and the second by both chrominance components.
+///
+/// ## `payload`
+/// Scan data of the fragment.
+pub struct QuantizationTable {
+	pub mbz: u8,
+
+	pub precision: u8,
+
+	pub length: u16be,
+
+	#[length = "length"]
+	pub tables: Vec<u8>,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	QuantizationTablePacket,
+	MutableQuantizationTablePacket,
+	["mbz" => 1, "precision" => 2, "length" => 4],
+	|pkt: &[u8]| {
+		let length = usize::from(u16::from_be_bytes([pkt[2], pkt[3]]));
+		ParseError::require(pkt, "tables", 4 + length)
+	}
+);
+
+/// Luminance quantisation table of the JPEG standard (Annex K), in zigzag order.
+const LUMA_QUANTIZER: [u8; 64] = [
+	16, 11, 12, 14, 12, 10, 16, 14, 13, 14, 18, 17, 16, 19, 24, 40, 26, 24, 22, 22, 24, 49, 35, 37,
+	29, 40, 58, 51, 61, 60, 57, 51, 56, 55, 64, 72, 92, 78, 64, 68, 87, 69, 55, 56, 80, 109, 81,
+	87, 95, 98, 103, 104, 103, 62, 77, 113, 121, 112, 100, 120, 92, 101, 103, 99,
+];
+
+/// Chrominance quantisation table of the JPEG standard (Annex K), in zigzag order.
+const CHROMA_QUANTIZER: [u8; 64] = [
+	17, 18, 18, 24, 21, 24, 47, 26, 26, 47, 99, 66, 56, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+	99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+	99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+const DC_LUMA_CODES: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_CHROMA_CODES: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const DC_SYMBOLS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+const AC_LUMA_CODES: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7d];
+const AC_LUMA_SYMBOLS: [u8; 162] = [
+	0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+	0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xa1, 0x08, 0x23, 0x42, 0xb1, 0xc1, 0x15, 0x52, 0xd1, 0xf0,
+	0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0a, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x25, 0x26, 0x27, 0x28,
+	0x29, 0x2a, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+	0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+	0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+	0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7,
+	0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3, 0xc4, 0xc5,
+	0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda, 0xe1, 0xe2,
+	0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+	0xf9, 0xfa,
+];
+
+const AC_CHROMA_CODES: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const AC_CHROMA_SYMBOLS: [u8; 162] = [
+	0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+	0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xa1, 0xb1, 0xc1, 0x09, 0x23, 0x33, 0x52, 0xf0,
+	0x15, 0x62, 0x72, 0xd1, 0x0a, 0x16, 0x24, 0x34, 0xe1, 0x25, 0xf1, 0x17, 0x18, 0x19, 0x1a, 0x26,
+	0x27, 0x28, 0x29, 0x2a, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+	0x49, 0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+	0x69, 0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+	0x88, 0x89, 0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5,
+	0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3,
+	0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda,
+	0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+	0xf9, 0xfa,
+];
+
+/// Returns the luminance and chrominance quantisation tables (in zigzag order)
+/// implied by a `Q` value from `1` to `99`, scaled from the standard tables as
+/// described in RFC 2435.
+///
+/// Values outside this range are clamped.
+#[must_use]
+pub fn default_quantization_tables(q: u8) -> [u8; 128] {
+	let factor = u32::from(q.clamp(1, 99));
+	let scale = if factor < 50 {
+		5000 / factor
+	} else {
+		200 - factor * 2
+	};
+
+	let mut out = [0u8; 128];
+	for (dst, &base) in out
+		.iter_mut()
+		.zip(LUMA_QUANTIZER.iter().chain(CHROMA_QUANTIZER.iter()))
+	{
+		#[allow(clippy::cast_possible_truncation)]
+		{
+			*dst = ((u32::from(base) * scale + 50) / 100).clamp(1, 255) as u8;
+		}
+	}
+
+	out
+}
+
+/// Returns whether an RTP payload holds (part of) a keyframe, which every
+/// well-formed JPEG payload does.
+#[must_use]
+pub fn is_keyframe(payload: &[u8]) -> bool {
+	JpegPacket::new(payload).is_some()
+}
+
+/// Depacketizer of Motion JPEG RTP payloads into JFIF images.
+///
+/// Fragments must be contiguous from offset `0`: frames with missing data are
+/// dropped. Quantisation tables sent in-band are remembered for each `Q` value,
+/// so that later frames may omit them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct JpegDepacketizer {
+	tables: Vec<(u8, u8, Vec<u8>)>,
+}
+
+impl JpegDepacketizer {
+	/// Creates a depacketizer with no remembered quantisation tables.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Forgets all quantisation tables received in-band.
+	pub fn clear_tables(&mut self) {
+		self.tables.clear();
+	}
+
+	fn remember_tables(&mut self, q: u8, precision: u8, tables: &[u8]) {
+		if let Some(entry) = self.tables.iter_mut().find(|(k, ..)| *k == q) {
+			entry.1 = precision;
+			entry.2 = tables.to_vec();
+		} else {
+			self.tables.push((q, precision, tables.to_vec()));
+		}
+	}
+}
+
+/// Frame parameters taken from the first fragment of a frame.
+struct FrameInfo {
+	jpeg_type: u8,
+	q: u8,
+	width: u16,
+	height: u16,
+	restart_interval: u16,
+}
+
+impl Depacketizer for JpegDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let mut info = None;
+		let mut scan = Vec::new();
+
+		for payload in payloads {
+			let pkt = JpegPacket::new_checked(payload).ok()?;
+			if pkt.get_fragment_offset() as usize != scan.len() {
+				return None;
+			}
+
+			let mut restart_interval = 0;
+			let mut body = pkt.payload();
+			if pkt.has_restart_marker() {
+				let rst = RestartMarkerPacket::new_checked(body).ok()?;
+				restart_interval = rst.get_restart_interval();
+				body = &body[RESTART_HEADER_LEN..];
+			}
+
+			if scan.is_empty() {
+				if pkt.has_quantization_tables() {
+					let qt = QuantizationTablePacket::new_checked(body).ok()?;
+					let len = usize::from(qt.get_length());
+					if len != 0 {
+						self.remember_tables(pkt.get_q(), qt.get_precision(), &body[4..4 + len]);
+					}
+					body = &body[4 + len..];
+				}
+
+				info = Some(FrameInfo {
+					jpeg_type: pkt.get_jpeg_type(),
+					q: pkt.get_q(),
+					width: pkt.frame_width(),
+					height: pkt.frame_height(),
+					restart_interval,
+				});
+			}
+
+			scan.extend_from_slice(body);
+		}
+
+		let info = info?;
+		let mut out = self.headers(&info)?;
+		out.extend_from_slice(&scan);
+		if !scan.ends_with(&[0xff, 0xd9]) {
+			out.extend_from_slice(&[0xff, 0xd9]);
+		}
+
+		Some(out)
+	}
+
+	fn is_keyframe(&self, payloads: &[&[u8]]) -> bool {
+		payloads.iter().any(|p| is_keyframe(p))
+	}
+}
+
+impl JpegDepacketizer {
+	/// Writes the JFIF markers preceding the scan data of a frame.
+	///
+	/// Returns `None` if the frame's type or dimensions are unsupported, or if its
+	/// quantisation tables are unknown.
+	fn headers(&self, info: &FrameInfo) -> Option<Vec<u8>> {
+		if info.width == 0 || info.height == 0 {
+			return None;
+		}
+		let sampling = match info.jpeg_type {
+			0 | 64 => 0x21,
+			1 | 65 => 0x22,
+			_ => return None,
+		};
+
+		let default_tables;
+		let (precision, tables) = match info.q {
+			1..=99 => {
+				default_tables = default_quantization_tables(info.q);
+				(0, &default_tables[..])
+			},
+			Q_IN_BAND.. => self
+				.tables
+				.iter()
+				.find(|(k, ..)| *k == info.q)
+				.map(|(_, p, t)| (*p, &t[..]))?,
+			_ => return None,
+		};
+
+		let mut out = Vec::with_capacity(1024);
+		out.extend_from_slice(&[0xff, 0xd8]);
+
+		// Quantisation tables, each prefixed by its precision and index.
+		let mut dqt = Vec::with_capacity(tables.len() + 4);
+		let mut rest = tables;
+		let mut count = 0u8;
+		while !rest.is_empty() && count < 4 {
+			let wide = (precision >> count) & 1;
+			let len = if wide == 1 { 128 } else { 64 };
+			let table = rest.get(..len)?;
+			dqt.push((wide << 4) | count);
+			dqt.extend_from_slice(table);
+			rest = &rest[len..];
+			count += 1;
+		}
+		if count == 0 {
+			return None;
+		}
+		push_segment(&mut out, 0xdb, &dqt);
+
+		if info.restart_interval != 0 {
+			push_segment(&mut out, 0xdd, &info.restart_interval.to_be_bytes());
+		}
+
+		let chroma_table = count.min(2) - 1;
+		let [h_hi, h_lo] = info.height.to_be_bytes();
+		let [w_hi, w_lo] = info.width.to_be_bytes();
+		push_segment(
+			&mut out,
+			0xc0,
+			&[
+				8,
+				h_hi,
+				h_lo,
+				w_hi,
+				w_lo,
+				3,
+				1,
+				sampling,
+				0,
+				2,
+				0x11,
+				chroma_table,
+				3,
+				0x11,
+				chroma_table,
+			],
+		);
+
+		let mut dht = Vec::with_capacity(420);
+		for (class, codes, symbols) in [
+			(0x00, &DC_LUMA_CODES, &DC_SYMBOLS[..]),
+			(0x10, &AC_LUMA_CODES, &AC_LUMA_SYMBOLS[..]),
+			(0x01, &DC_CHROMA_CODES, &DC_SYMBOLS[..]),
+			(0x11, &AC_CHROMA_CODES, &AC_CHROMA_SYMBOLS[..]),
+		] {
+			dht.push(class);
+			dht.extend_from_slice(codes);
+			dht.extend_from_slice(symbols);
+		}
+		push_segment(&mut out, 0xc4, &dht);
+
+		push_segment(&mut out, 0xda, &[3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]);
+
+		Some(out)
+	}
+}
+
+/// Appends a JPEG marker segment, whose length field covers itself and `body`.
+fn push_segment(out: &mut Vec<u8>, marker: u8, body: &[u8]) {
+	out.extend_from_slice(&[0xff, marker]);
+	#[allow(clippy::cast_possible_truncation)]
+	out.extend_from_slice(&((body.len() + 2) as u16).to_be_bytes());
+	out.extend_from_slice(body);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn fragment(offset: u32, q: u8, tables: Option<&[u8]>, scan: &[u8]) -> Vec<u8> {
+		let [_, o1, o2, o3] = offset.to_be_bytes();
+		let mut out = alloc::vec![0, o1, o2, o3, 1, q, 4, 2];
+		if let Some(tables) = tables {
+			let len = u16::try_from(tables.len()).unwrap().to_be_bytes();
+			out.extend_from_slice(&[0, 0, len[0], len[1]]);
+			out.extend_from_slice(tables);
+		}
+		out.extend_from_slice(scan);
+		out
+	}
+
+	#[test]
+	fn fragments_reassemble_with_in_band_tables() {
+		let tables: Vec<u8> = (1..=128).collect();
+		let first = fragment(0, 200, Some(&tables), &[1, 2, 3]);
+		let second = fragment(3, 200, None, &[4, 5]);
+
+		let mut depacketizer = JpegDepacketizer::new();
+		let frame = depacketizer
+			.depacketize(&[&first[..], &second[..]])
+			.unwrap();
+		assert_eq!(frame[..2], [0xff, 0xd8]);
+		assert!(frame.ends_with(&[1, 2, 3, 4, 5, 0xff, 0xd9]));
+		assert!(frame.windows(64).any(|w| w == &tables[..64]));
+		assert!(frame.windows(64).any(|w| w == &tables[64..]));
+		// 4:2:0 sampling, at 32x16 pixels.
+		assert!(frame
+			.windows(9)
+			.any(|w| w == [0xff, 0xc0, 0, 17, 8, 0, 16, 0, 32]));
+
+		// Later frames may omit the tables for the same Q.
+		let repeat = fragment(0, 200, Some(&[]), &[6]);
+		let frame = depacketizer.depacketize(&[&repeat[..]]).unwrap();
+		assert!(frame.windows(64).any(|w| w == &tables[..64]));
+
+		// Missing fragments drop the frame.
+		assert_eq!(depacketizer.depacketize(&[&second[..]]), None);
+	}
+}
@@ -0,0 +1,336 @@
+//! Uncompressed linear PCM audio, as 16-bit (`L16`) or 24-bit (`L24`) signed
+//! samples.
+//!
+//! Samples are sent in network byte order, with the samples of each channel
+//! interleaved. Each packet carries a whole number of sample frames (one sample
+//! per channel), so that its timestamp advances by the number of frames carried.
+//!
+//! See [RFC 3551](https://tools.ietf.org/html/rfc3551#section-4.5.11) and
+//! [RFC 3190](https://tools.ietf.org/html/rfc3190).
+
+use crate::rtp::{
+	depacketizer::Depacketizer,
+	packetizer::{duration_samples, Packetizer},
+	PayloadFormat,
+	RtpType,
+};
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// Largest value of a 24-bit sample.
+const L24_MAX: i32 = (1 << 23) - 1;
+
+/// Smallest value of a 24-bit sample.
+const L24_MIN: i32 = -(1 << 23);
+
+/// Sample width of a linear PCM stream.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LinearFormat {
+	/// 16-bit samples.
+	L16,
+	/// 24-bit samples.
+	L24,
+}
+
+impl LinearFormat {
+	/// Returns the format named by an SDP encoding name, matched
+	/// case-insensitively.
+	#[must_use]
+	pub fn from_encoding(name: &str) -> Option<Self> {
+		if name.eq_ignore_ascii_case("L16") {
+			Some(Self::L16)
+		} else if name.eq_ignore_ascii_case("L24") {
+			Some(Self::L24)
+		} else {
+			None
+		}
+	}
+
+	/// Returns the size of one sample, in bytes.
+	#[must_use]
+	pub fn sample_len(self) -> usize {
+		match self {
+			Self::L16 => 2,
+			Self::L24 => 3,
+		}
+	}
+}
+
+/// Parameters of a linear PCM stream.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LinearConfig {
+	/// Width of each sample.
+	pub format: LinearFormat,
+	/// Sampling rate, which is also the RTP clock rate.
+	pub clock_rate: u32,
+	/// Number of interleaved channels.
+	pub channels: u8,
+}
+
+impl LinearConfig {
+	/// Creates a description of a stream of `channels` at `clock_rate`.
+	#[must_use]
+	pub fn new(format: LinearFormat, clock_rate: u32, channels: u8) -> Self {
+		Self {
+			format,
+			clock_rate,
+			channels,
+		}
+	}
+
+	/// Returns the parameters of a static linear PCM payload type: `10` (`L16`
+	/// stereo) or `11` (`L16` mono), both at 44.1 kHz.
+	#[must_use]
+	pub fn from_payload_type(payload_type: RtpType) -> Option<Self> {
+		Some(Self::new(
+			LinearFormat::L16,
+			payload_type.clock_rate()?,
+			match payload_type {
+				RtpType::L16Stereo => 2,
+				RtpType::L16Mono => 1,
+				_ => return None,
+			},
+		))
+	}
+
+	/// Returns the parameters of a negotiated linear PCM format, such as a dynamic
+	/// `L24` payload type.
+	///
+	/// Streams are mono unless a channel count is given.
+	#[must_use]
+	pub fn from_payload_format(format: &PayloadFormat) -> Option<Self> {
+		Some(Self::new(
+			LinearFormat::from_encoding(&format.encoding)?,
+			format.clock_rate,
+			format.channels.unwrap_or(1),
+		))
+	}
+
+	/// Returns the size of one sample frame (one sample for every channel), in
+	/// bytes.
+	#[must_use]
+	pub fn frame_len(self) -> usize {
+		self.format.sample_len() * usize::from(self.channels)
+	}
+
+	/// Returns the number of sample frames in `ptime`, which is the amount by which
+	/// the timestamp advances between packets of this duration.
+	#[must_use]
+	pub fn samples(self, ptime: Duration) -> u32 {
+		let samples = duration_samples(self.clock_rate, ptime);
+
+		u32::try_from(samples).unwrap_or(u32::MAX)
+	}
+
+	/// Returns the size in bytes of a payload lasting `ptime`.
+	#[must_use]
+	pub fn payload_len(self, ptime: Duration) -> usize {
+		self.samples(ptime) as usize * self.frame_len()
+	}
+
+	/// Returns the number of sample frames held in `payload_len` bytes, which is the
+	/// amount by which the timestamp advances after a payload of this size.
+	#[must_use]
+	pub fn duration(self, payload_len: usize) -> u32 {
+		let frames = payload_len.checked_div(self.frame_len()).unwrap_or(0);
+
+		u32::try_from(frames).unwrap_or(u32::MAX)
+	}
+}
+
+/// Serialises 16-bit samples in network byte order.
+#[must_use]
+pub fn to_l16(samples: &[i16]) -> Vec<u8> {
+	samples.iter().flat_map(|s| s.to_be_bytes()).collect()
+}
+
+/// Reads 16-bit samples in network byte order, ignoring any trailing odd byte.
+#[must_use]
+pub fn from_l16(payload: &[u8]) -> Vec<i16> {
+	payload
+		.chunks_exact(2)
+		.map(|b| i16::from_be_bytes([b[0], b[1]]))
+		.collect()
+}
+
+/// Serialises 24-bit samples in network byte order.
+///
+/// Samples are clamped to the 24-bit range.
+#[must_use]
+pub fn to_l24(samples: &[i32]) -> Vec<u8> {
+	samples
+		.iter()
+		.flat_map(|s| {
+			let [_, b0, b1, b2] = s.clamp(&L24_MIN, &L24_MAX).to_be_bytes();
+			[b0, b1, b2]
+		})
+		.collect()
+}
+
+/// Reads 24-bit samples in network byte order, sign-extending each to 32 bits and
+/// ignoring any trailing partial sample.
+#[must_use]
+pub fn from_l24(payload: &[u8]) -> Vec<i32> {
+	payload
+		.chunks_exact(3)
+		.map(|b| i32::from_be_bytes([b[0], b[1], b[2], 0]) >> 8)
+		.collect()
+}
+
+/// Interleaves the samples of several channels, in channel order.
+///
+/// The output is as long as the shortest channel allows.
+#[must_use]
+pub fn interleave<T: Copy>(channels: &[&[T]]) -> Vec<T> {
+	let len = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+	let mut out = Vec::with_capacity(len * channels.len());
+	for i in 0..len {
+		out.extend(channels.iter().map(|c| c[i]));
+	}
+
+	out
+}
+
+/// Splits interleaved samples into `channels` separate channels, ignoring any
+/// trailing partial sample frame.
+#[must_use]
+pub fn deinterleave<T: Copy>(samples: &[T], channels: usize) -> Vec<Vec<T>> {
+	let mut out: Vec<Vec<T>> = (0..channels)
+		.map(|_| Vec::with_capacity(samples.len() / channels))
+		.collect();
+	if channels == 0 {
+		return out;
+	}
+
+	for frame in samples.chunks_exact(channels) {
+		for (dst, &sample) in out.iter_mut().zip(frame) {
+			dst.push(sample);
+		}
+	}
+
+	out
+}
+
+/// Packetizer of interleaved, network-order linear PCM into RTP payloads.
+///
+/// Each frame passed in becomes a single packet, trimmed to a whole number of
+/// sample frames. [`frames`] splits a longer buffer into packet-sized pieces, and
+/// [`LinearConfig::samples`] gives the matching timestamp advance.
+///
+/// [`frames`]: #method.frames
+/// [`LinearConfig::samples`]: struct.LinearConfig.html#method.samples
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LinearPacketizer {
+	/// Parameters of the stream sent.
+	pub config: LinearConfig,
+}
+
+impl LinearPacketizer {
+	/// Creates a packetizer for streams described by `config`.
+	#[must_use]
+	pub fn new(config: LinearConfig) -> Self {
+		Self { config }
+	}
+
+	/// Splits `pcm` into pieces lasting `ptime`, the last of which may be shorter.
+	pub fn frames<'a>(&self, pcm: &'a [u8], ptime: Duration) -> core::slice::Chunks<'a, u8> {
+		pcm.chunks(
+			self.config
+				.payload_len(ptime)
+				.max(self.config.frame_len())
+				.max(1),
+		)
+	}
+}
+
+impl Packetizer for LinearPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		let frame_len = self.config.frame_len().max(1);
+		let len = frame.len() - frame.len() % frame_len;
+
+		if len == 0 || len > max_payload {
+			return Vec::new();
+		}
+
+		alloc::vec![frame[..len].to_vec()]
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		false
+	}
+}
+
+/// Depacketizer of linear PCM RTP payloads, which returns each payload's
+/// interleaved, network-order samples.
+///
+/// Trailing partial sample frames are dropped.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LinearDepacketizer {
+	/// Parameters of the stream received.
+	pub config: LinearConfig,
+}
+
+impl LinearDepacketizer {
+	/// Creates a depacketizer for streams described by `config`.
+	#[must_use]
+	pub fn new(config: LinearConfig) -> Self {
+		Self { config }
+	}
+}
+
+impl Depacketizer for LinearDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let frame_len = self.config.frame_len().max(1);
+		let mut out = Vec::new();
+		for payload in payloads {
+			out.extend_from_slice(&payload[..payload.len() - payload.len() % frame_len]);
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn l24_stereo_round_trip() {
+		let config = LinearConfig::new(LinearFormat::L24, 48_000, 2);
+		let left = [0, 1, -1, L24_MAX, L24_MIN];
+		let right = [5, -5, 1 << 20, -(1 << 20), L24_MAX + 1];
+		let pcm = to_l24(&interleave(&[&left[..], &right[..]]));
+
+		let ptime = Duration::from_micros(62);
+		assert_eq!(config.samples(ptime), 2);
+		let mut packetizer = LinearPacketizer::new(config);
+		let payloads: Vec<Vec<u8>> = packetizer
+			.frames(&pcm, ptime)
+			.flat_map(|frame| packetizer.packetize(frame, 1500))
+			.collect();
+		assert_eq!(payloads.len(), 3);
+		assert_eq!(config.duration(payloads[2].len()), 1);
+
+		let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+		let out = LinearDepacketizer::new(config)
+			.depacketize(&payloads)
+			.unwrap();
+		let channels = deinterleave(&from_l24(&out), 2);
+		assert_eq!(channels[0], left);
+		assert_eq!(channels[1][..4], right[..4]);
+		assert_eq!(channels[1][4], L24_MAX);
+	}
+
+	#[test]
+	fn l16_trims_partial_frames() {
+		let config = LinearConfig::from_payload_type(RtpType::L16Stereo).unwrap();
+		let pcm = to_l16(&[1, -2, 3, -4]);
+		let payloads = LinearPacketizer::new(config).packetize(&pcm[..7], 1500);
+		assert_eq!(payloads, [pcm[..4].to_vec()]);
+		assert_eq!(from_l16(&payloads[0]), [1, -2]);
+	}
+}
@@ -0,0 +1,171 @@
+//! Payload formats of individual media codecs.
+
+pub mod aac;
+pub mod amr;
+pub mod av1;
+pub mod evs;
+pub mod g711;
+pub mod g722;
+pub mod g726;
+pub mod h264;
+pub mod h265;
+pub mod ilbc;
+pub mod jpeg;
+pub mod linear;
+pub mod mp2t;
+pub mod mpeg;
+pub mod opus;
+pub mod speex;
+pub mod vp8;
+pub mod vp9;
+pub mod xiph;
+
+use super::{fragment::FragmentFlags, RtpType};
+
+/// Video codecs whose RTP payloads can be inspected for keyframes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum VideoCodec {
+	/// H.264 (AVC).
+	H264,
+	/// H.265 (HEVC).
+	H265,
+	/// Motion JPEG.
+	Jpeg,
+	/// MPEG-1 or MPEG-2 video.
+	Mpv,
+	/// VP8.
+	Vp8,
+	/// VP9.
+	Vp9,
+	/// AV1.
+	Av1,
+}
+
+impl VideoCodec {
+	/// Returns the codec named by an SDP encoding name, matched
+	/// case-insensitively.
+	#[must_use]
+	pub fn from_encoding(name: &str) -> Option<Self> {
+		[
+			("H264", Self::H264),
+			("H265", Self::H265),
+			("JPEG", Self::Jpeg),
+			("MPV", Self::Mpv),
+			("VP8", Self::Vp8),
+			("VP9", Self::Vp9),
+			("AV1", Self::Av1),
+		]
+		.into_iter()
+		.find(|(n, _)| n.eq_ignore_ascii_case(name))
+		.map(|(_, codec)| codec)
+	}
+
+	/// Returns the codec of a static video payload type.
+	#[must_use]
+	pub fn from_payload_type(payload_type: RtpType) -> Option<Self> {
+		match payload_type {
+			RtpType::Jpeg => Some(Self::Jpeg),
+			RtpType::Mpv => Some(Self::Mpv),
+			_ => None,
+		}
+	}
+}
+
+/// Returns whether an RTP payload of `codec` holds (the start of) a keyframe,
+/// from which a decoder can begin without reference to earlier frames.
+///
+/// This allows a receiver or forwarding unit to switch between streams or
+/// layers only once a keyframe arrives, without codec-specific logic of its own.
+#[must_use]
+pub fn is_keyframe(payload: &[u8], codec: VideoCodec) -> bool {
+	match codec {
+		VideoCodec::H264 => h264::is_keyframe(payload),
+		VideoCodec::H265 => h265::is_keyframe(payload),
+		VideoCodec::Jpeg => jpeg::is_keyframe(payload),
+		VideoCodec::Mpv => mpeg::is_keyframe(payload),
+		VideoCodec::Vp8 => vp8::is_keyframe(payload),
+		VideoCodec::Vp9 => vp9::is_keyframe(payload),
+		VideoCodec::Av1 => av1::is_keyframe(payload),
+	}
+}
+
+/// Iterator over the NAL units of an H.264 or H.265 Annex B byte stream, without
+/// their start codes.
+///
+/// Leading bytes before the first start code are treated as a NAL unit, so that
+/// a single unit without a start code is returned unchanged.
+#[derive(Clone, Debug)]
+pub struct AnnexBUnits<'a> {
+	rest: &'a [u8],
+}
+
+impl<'a> AnnexBUnits<'a> {
+	/// Creates an iterator over the NAL units of `data`.
+	#[must_use]
+	pub fn new(data: &'a [u8]) -> Self {
+		let rest = match find_start_code(data) {
+			Some((0, len)) => &data[len..],
+			_ => data,
+		};
+
+		Self { rest }
+	}
+}
+
+impl<'a> Iterator for AnnexBUnits<'a> {
+	type Item = &'a [u8];
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			if self.rest.is_empty() {
+				return None;
+			}
+
+			let unit = match find_start_code(self.rest) {
+				Some((start, len)) => {
+					let unit = &self.rest[..start];
+					self.rest = &self.rest[start + len..];
+					unit
+				},
+				None => core::mem::take(&mut self.rest),
+			};
+
+			// Trailing zero bytes belong to the next unit's 4-byte start code.
+			let end = unit.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+			if end != 0 {
+				return Some(&unit[..end]);
+			}
+		}
+	}
+}
+
+/// Returns the offset and length of the first 3-byte start code in `data`.
+fn find_start_code(data: &[u8]) -> Option<(usize, usize)> {
+	data.windows(3)
+		.position(|w| w == [0, 0, 1])
+		.map(|pos| (pos, 3))
+}
+
+/// Appends `unit` to `out` with a 4-byte Annex B start code.
+pub(crate) fn push_annex_b(out: &mut alloc::vec::Vec<u8>, unit: &[u8]) {
+	out.extend_from_slice(&[0, 0, 0, 1]);
+	out.extend_from_slice(unit);
+}
+
+/// Iterates over the `key=value` pairs of an `a=fmtp` line's parameters, with
+/// surrounding whitespace removed. Parameters without a value are skipped.
+pub(crate) fn fmtp_params(params: &str) -> impl Iterator<Item = (&str, &str)> {
+	params
+		.split(';')
+		.filter_map(|p| p.split_once('='))
+		.map(|(k, v)| (k.trim(), v.trim()))
+}
+
+/// Reads the start (`S`) and end (`E`) bits of an H.264 or H.265 FU header.
+pub(crate) fn fu_flags(fu_header: u8) -> FragmentFlags {
+	FragmentFlags {
+		start: fu_header & 0x80 != 0,
+		end: fu_header & 0x40 != 0,
+	}
+}
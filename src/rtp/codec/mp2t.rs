@@ -0,0 +1,383 @@
+//! MPEG-2 transport streams (type `33`).
+//!
+//! Each payload holds a whole number of 188-byte transport stream (TS) packets,
+//! typically seven to fit a 1500-byte MTU. Since TS packets carry their own
+//! timing and sequencing, receivers should check each packet's sync byte and
+//! continuity counter to find corruption or loss within the stream.
+//!
+//! See [RFC 2250](https://tools.ietf.org/html/rfc2250#section-2).
+
+use crate::{
+	error::impl_new_checked,
+	rtp::{depacketizer::Depacketizer, packetizer::Packetizer},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u13be, u2, u4},
+};
+
+/// Size of every transport stream packet, in bytes.
+pub const TS_PACKET_LEN: usize = 188;
+
+/// Value of the first byte of every transport stream packet.
+pub const SYNC_BYTE: u8 = 0x47;
+
+/// Packet identifier of null (stuffing) packets, which are exempt from
+/// continuity checks.
+pub const NULL_PID: u16 = 0x1fff;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Header of a single transport stream packet.
+///
+/// A description of fields:
+///
+/// ## `sync_byte`
+/// Must be [`SYNC_BYTE`].
+///
+/// ## `transport_error`
+/// Set if the packet is known to be corrupt.
+///
+/// ## `payload_unit_start`
+/// Set if the payload begins a PES packet or PSI section.
+///
+/// ## `transport_priority`
+/// Set if the packet has higher priority than others of the same PID.
+///
+/// ## `pid`
+/// Packet identifier, naming the elementary stream or table carried.
+///
+/// ## `scrambling`
+/// Scrambling mode of the payload, or `0` if unscrambled.
+///
+/// ## `adaptation_field_control`
+/// Bit `1` is set if an adaptation field follows the header, and bit `0` if a
+/// payload is present.
+///
+/// ## `continuity_counter`
+/// Counter incremented (modulo 16) with each packet of this PID which carries a
+/// payload.
+///
+/// ## `payload`
+/// Any adaptation field, followed by the packet's payload.
+///
+/// [`SYNC_BYTE`]: constant.SYNC_BYTE.html
+pub struct TransportStream {
+	pub sync_byte: u8,
+
+	pub transport_error: u1,
+
+	pub payload_unit_start: u1,
+
+	pub transport_priority: u1,
+
+	pub pid: u13be,
+
+	pub scrambling: u2,
+
+	pub adaptation_field_control: u2,
+
+	pub continuity_counter: u4,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	TransportStreamPacket,
+	MutableTransportStreamPacket,
+	["sync_byte" => 1, "pid" => 3, "continuity_counter" => 4]
+);
+
+impl TransportStreamPacket<'_> {
+	/// Returns whether an adaptation field follows the header.
+	#[must_use]
+	pub fn has_adaptation_field(&self) -> bool {
+		self.get_adaptation_field_control() & 0b10 != 0
+	}
+
+	/// Returns whether the packet carries a payload.
+	#[must_use]
+	pub fn has_payload(&self) -> bool {
+		self.get_adaptation_field_control() & 0b01 != 0
+	}
+
+	/// Returns whether the adaptation field flags a discontinuity, after which the
+	/// continuity counter may take any value.
+	#[must_use]
+	pub fn discontinuity_indicator(&self) -> bool {
+		let data = self.payload();
+
+		self.has_adaptation_field()
+			&& data.first().map_or(false, |&len| len > 0)
+			&& data.get(1).map_or(false, |&flags| flags & 0x80 != 0)
+	}
+}
+
+/// Splits a payload into its transport stream packets.
+///
+/// Returns `None` if the payload is empty, or not a whole number of packets.
+#[must_use]
+pub fn ts_packets(payload: &[u8]) -> Option<core::slice::ChunksExact<'_, u8>> {
+	(!payload.is_empty() && payload.len() % TS_PACKET_LEN == 0)
+		.then(|| payload.chunks_exact(TS_PACKET_LEN))
+}
+
+/// A problem found in a transport stream.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TsIssue {
+	/// The payload ends with a partial packet of `len` bytes.
+	PartialPacket { len: usize },
+	/// The packet at `index` within the payload does not begin with the sync byte.
+	BadSync { index: usize, byte: u8 },
+	/// The packet at `index` within the payload has an unexpected continuity
+	/// counter, indicating that packets of `pid` were lost or reordered.
+	Discontinuity {
+		index: usize,
+		pid: u16,
+		expected: u8,
+		found: u8,
+	},
+}
+
+/// Per-PID tracker of transport stream continuity counters.
+///
+/// Each packet carrying a payload should increment its PID's counter, though a
+/// single duplicate is permitted. Null packets, and packets flagged as corrupt
+/// or discontinuous, are not checked.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ContinuityChecker {
+	counters: Vec<(u16, u8, bool)>,
+	discontinuities: u64,
+}
+
+impl ContinuityChecker {
+	/// Creates a tracker which has seen no packets.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Checks each transport stream packet of `payload` in turn, returning every
+	/// [`TsIssue`] found (or an empty list if the payload is intact).
+	///
+	/// [`TsIssue`]: enum.TsIssue.html
+	pub fn check(&mut self, payload: &[u8]) -> Vec<TsIssue> {
+		let mut out = Vec::new();
+
+		let chunks = payload.chunks_exact(TS_PACKET_LEN);
+		let partial = chunks.remainder().len();
+		for (index, packet) in chunks.enumerate() {
+			let Some(pkt) = TransportStreamPacket::new(packet) else {
+				continue;
+			};
+			if pkt.get_sync_byte() != SYNC_BYTE {
+				out.push(TsIssue::BadSync {
+					index,
+					byte: pkt.get_sync_byte(),
+				});
+				continue;
+			}
+
+			if let Some((expected, found)) = self.update(&pkt) {
+				self.discontinuities += 1;
+				out.push(TsIssue::Discontinuity {
+					index,
+					pid: pkt.get_pid(),
+					expected,
+					found,
+				});
+			}
+		}
+
+		if partial != 0 {
+			out.push(TsIssue::PartialPacket { len: partial });
+		}
+
+		out
+	}
+
+	/// Records the counter of `pkt`, returning the expected and found counters if
+	/// these differ.
+	fn update(&mut self, pkt: &TransportStreamPacket<'_>) -> Option<(u8, u8)> {
+		let pid = pkt.get_pid();
+		if pid == NULL_PID || pkt.get_transport_error() != 0 {
+			return None;
+		}
+
+		let found = pkt.get_continuity_counter();
+		let Some(entry) = self.counters.iter_mut().find(|(p, ..)| *p == pid) else {
+			self.counters.push((pid, found, false));
+			return None;
+		};
+		let (_, last, duplicated) = *entry;
+
+		let expected = if pkt.discontinuity_indicator() {
+			found
+		} else if !pkt.has_payload() {
+			last
+		} else if found == last && !duplicated {
+			*entry = (pid, found, true);
+			return None;
+		} else {
+			(last + 1) & 0x0f
+		};
+
+		*entry = (pid, found, false);
+
+		(found != expected).then_some((expected, found))
+	}
+
+	/// Returns the total number of discontinuities found.
+	#[must_use]
+	pub fn discontinuities(&self) -> u64 {
+		self.discontinuities
+	}
+
+	/// Forgets all counters, such as after seeking.
+	pub fn reset(&mut self) {
+		self.counters.clear();
+	}
+}
+
+/// Packetizer of a transport stream into RTP payloads.
+///
+/// Each frame passed in is split into payloads of as many whole TS packets as
+/// fit. Any trailing partial packet is not sent.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Mp2tPacketizer;
+
+impl Packetizer for Mp2tPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		let chunk_len = max_payload - max_payload % TS_PACKET_LEN;
+		let whole = frame.len() - frame.len() % TS_PACKET_LEN;
+		if chunk_len == 0 {
+			return Vec::new();
+		}
+
+		frame[..whole]
+			.chunks(chunk_len)
+			.map(<[u8]>::to_vec)
+			.collect()
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		false
+	}
+}
+
+/// Depacketizer of MP2T RTP payloads, which merges them back into a transport
+/// stream.
+///
+/// Every payload is checked for continuity, and any issues kept until taken by
+/// [`take_issues`]. Packets without a valid sync byte are dropped.
+///
+/// [`take_issues`]: #method.take_issues
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Mp2tDepacketizer {
+	checker: ContinuityChecker,
+	issues: Vec<TsIssue>,
+}
+
+impl Mp2tDepacketizer {
+	/// Creates a depacketizer which has seen no packets.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the continuity tracker of the received stream.
+	#[must_use]
+	pub fn checker(&self) -> &ContinuityChecker {
+		&self.checker
+	}
+
+	/// Removes and returns the issues found since this was last called.
+	pub fn take_issues(&mut self) -> Vec<TsIssue> {
+		core::mem::take(&mut self.issues)
+	}
+}
+
+impl Depacketizer for Mp2tDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+
+		for payload in payloads {
+			self.issues.extend(self.checker.check(payload));
+			for packet in payload.chunks_exact(TS_PACKET_LEN) {
+				if packet[0] == SYNC_BYTE {
+					out.extend_from_slice(packet);
+				}
+			}
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn stream(pid: u16, count: u8) -> Vec<u8> {
+		let mut out = alloc::vec![0xff; TS_PACKET_LEN * usize::from(count)];
+		for (counter, packet) in (0..count).zip(out.chunks_exact_mut(TS_PACKET_LEN)) {
+			let mut pkt = MutableTransportStreamPacket::new(packet).unwrap();
+			pkt.set_sync_byte(SYNC_BYTE);
+			pkt.set_transport_error(0);
+			pkt.set_payload_unit_start(u8::from(counter == 0));
+			pkt.set_transport_priority(0);
+			pkt.set_pid(pid);
+			pkt.set_scrambling(0);
+			pkt.set_adaptation_field_control(0b01);
+			pkt.set_continuity_counter(counter & 0x0f);
+		}
+
+		out
+	}
+
+	#[test]
+	fn stream_round_trip() {
+		let ts = stream(0x100, 20);
+		let mut frame = ts.clone();
+		frame.extend_from_slice(&[SYNC_BYTE; 10]);
+
+		let payloads = Mp2tPacketizer.packetize(&frame, 1400);
+		let lens: Vec<usize> = payloads.iter().map(Vec::len).collect();
+		assert_eq!(lens, [1316, 1316, 1128]);
+		assert!(payloads.iter().all(|p| ts_packets(p).is_some()));
+
+		let payloads: Vec<&[u8]> = payloads.iter().map(Vec::as_slice).collect();
+		let mut depack = Mp2tDepacketizer::new();
+		assert_eq!(depack.depacketize(&payloads).unwrap(), ts);
+		assert!(depack.take_issues().is_empty());
+		assert_eq!(depack.checker().discontinuities(), 0);
+	}
+
+	#[test]
+	fn lost_payload_is_reported() {
+		let ts = stream(0x100, 20);
+		let payloads = Mp2tPacketizer.packetize(&ts, 1400);
+
+		let mut depack = Mp2tDepacketizer::new();
+		let out = depack.depacketize(&[&payloads[0], &payloads[2]]).unwrap();
+		assert_eq!(out.len(), 13 * TS_PACKET_LEN);
+		assert_eq!(
+			depack.take_issues(),
+			[TsIssue::Discontinuity {
+				index: 0,
+				pid: 0x100,
+				expected: 7,
+				found: 14,
+			}]
+		);
+		assert_eq!(depack.checker().discontinuities(), 1);
+	}
+}
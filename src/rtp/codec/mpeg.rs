@@ -0,0 +1,222 @@
+//! MPEG-1 and MPEG-2 elementary streams, carried under the static payload types
+//! `14` (audio) and `32` (video).
+//!
+//! See [RFC 2250](https://tools.ietf.org/html/rfc2250).
+
+use crate::error::impl_new_checked;
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::types::{u1, u10be, u16be, u3, u5};
+
+/// Size of both the audio- and video-specific headers, in bytes.
+pub const HEADER_LEN: usize = 4;
+
+/// Picture type of an intra-coded (I) picture.
+pub const PICTURE_I: u8 = 1;
+
+/// Picture type of a predictive-coded (P) picture.
+pub const PICTURE_P: u8 = 2;
+
+/// Picture type of a bidirectionally predictive-coded (B) picture.
+pub const PICTURE_B: u8 = 3;
+
+/// Picture type of a DC-coded (D) picture.
+pub const PICTURE_D: u8 = 4;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// MPEG audio-specific header, present at the start of every payload of type `14`.
+///
+/// A description of fields:
+///
+/// ## `mbz`
+/// Must be `0`.
+///
+/// ## `fragment_offset`
+/// Offset in bytes of this payload's data within the audio frame. Frames larger
+/// than a packet are fragmented, and each fragment sent with the same timestamp.
+///
+/// ## `payload`
+/// One or more whole audio frames, or a fragment of one frame.
+pub struct MpegAudio {
+	pub mbz: u16be,
+
+	pub fragment_offset: u16be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	MpegAudioPacket,
+	MutableMpegAudioPacket,
+	["mbz" => 2, "fragment_offset" => 4]
+);
+
+impl MpegAudioPacket<'_> {
+	/// Returns whether this payload continues a fragmented audio frame.
+	#[must_use]
+	pub fn is_continuation(&self) -> bool {
+		self.get_fragment_offset() != 0
+	}
+}
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// MPEG video-specific header, present at the start of every payload of type `32`.
+///
+/// A description of fields:
+///
+/// ## `mbz`
+/// Must be `0`.
+///
+/// ## `two`
+/// Set if an MPEG-2 video-specific header extension follows this header, at the
+/// start of `payload`.
+///
+/// ## `temporal_reference`
+/// Temporal reference of the current picture, within its group of pictures.
+///
+/// ## `active_n`, `new_picture_header`
+/// Used only in MPEG-2 to signal whether the `N` bit is active, and whether the
+/// picture header may have changed from the previous picture.
+///
+/// ## `sequence_header`
+/// Set if the payload contains a sequence header.
+///
+/// ## `begin_slice`
+/// Set if the payload begins at the start of a slice, or of a header preceding
+/// a slice. Unset on later fragments of a slice.
+///
+/// ## `end_slice`
+/// Set if the payload ends at the end of a slice. Unset on earlier fragments of
+/// a slice.
+///
+/// ## `picture_type`
+/// Type of the current picture: see [`PICTURE_I`] and friends.
+///
+/// ## `full_pel_backward`, `backward_code`, `full_pel_forward`, `forward_code`
+/// Motion vector parameters of the current picture, from its picture header.
+/// These are `0` where unused by the picture type.
+///
+/// ## `payload`
+/// Any header extension, followed by the slices (or slice fragment) carried.
+///
+/// [`PICTURE_I`]: constant.PICTURE_I.html
+pub struct MpegVideo {
+	pub mbz: u5,
+
+	pub two: u1,
+
+	pub temporal_reference: u10be,
+
+	pub active_n: u1,
+
+	pub new_picture_header: u1,
+
+	pub sequence_header: u1,
+
+	pub begin_slice: u1,
+
+	pub end_slice: u1,
+
+	pub picture_type: u3,
+
+	pub full_pel_backward: u1,
+
+	pub backward_code: u3,
+
+	pub full_pel_forward: u1,
+
+	pub forward_code: u3,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	MpegVideoPacket,
+	MutableMpegVideoPacket,
+	[
+		"two" => 1,
+		"temporal_reference" => 3,
+		"picture_type" => 3,
+		"forward_code" => 4,
+	]
+);
+
+impl MpegVideoPacket<'_> {
+	/// Returns whether this payload holds a fragment of a slice, rather than whole
+	/// slices.
+	#[must_use]
+	pub fn is_fragment(&self) -> bool {
+		self.get_begin_slice() == 0 || self.get_end_slice() == 0
+	}
+
+	/// Returns whether this payload is part of an intra-coded picture.
+	#[must_use]
+	pub fn is_intra(&self) -> bool {
+		self.get_picture_type() == PICTURE_I
+	}
+}
+
+/// Returns whether an MPEG video (`MPV`) RTP payload is part of an intra-coded
+/// picture, which begins a keyframe.
+#[must_use]
+pub fn is_keyframe(payload: &[u8]) -> bool {
+	MpegVideoPacket::new(payload).map_or(false, |pkt| pkt.is_intra())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pnet_macros_support::packet::{FromPacket, Packet};
+
+	#[test]
+	fn video_header_round_trip() {
+		let header = MpegVideo {
+			mbz: 0,
+			two: 0,
+			temporal_reference: 0x2a5,
+			active_n: 0,
+			new_picture_header: 0,
+			sequence_header: 1,
+			begin_slice: 1,
+			end_slice: 0,
+			picture_type: PICTURE_I,
+			full_pel_backward: 0,
+			backward_code: 0,
+			full_pel_forward: 1,
+			forward_code: 5,
+			payload: alloc::vec![0xaa, 0xbb],
+		};
+		let mut buf = [0u8; HEADER_LEN + 2];
+		MutableMpegVideoPacket::new(&mut buf)
+			.unwrap()
+			.populate(&header);
+
+		let pkt = MpegVideoPacket::new_checked(&buf).unwrap();
+		assert_eq!(pkt.get_temporal_reference(), 0x2a5);
+		assert_eq!(pkt.get_sequence_header(), 1);
+		assert_eq!(pkt.get_full_pel_forward(), 1);
+		assert_eq!(pkt.get_forward_code(), 5);
+		assert!(pkt.is_fragment());
+		assert!(pkt.is_intra());
+		assert!(is_keyframe(&buf));
+		assert_eq!(pkt.payload(), [0xaa, 0xbb]);
+		assert_eq!(pkt.from_packet(), header);
+	}
+
+	#[test]
+	fn audio_header_round_trip() {
+		let mut buf = [0u8; HEADER_LEN + 1];
+		MutableMpegAudioPacket::new(&mut buf)
+			.unwrap()
+			.set_fragment_offset(1200);
+
+		let pkt = MpegAudioPacket::new_checked(&buf).unwrap();
+		assert!(pkt.is_continuation());
+		assert_eq!(pkt.get_fragment_offset(), 1200);
+		assert_eq!(pkt.payload().len(), 1);
+	}
+}
@@ -0,0 +1,495 @@
+//! Opus packets, including the multistream packets used for surround sound and
+//! ambisonics.
+//!
+//! See [RFC 6716](https://tools.ietf.org/html/rfc6716) for the packet structure,
+//! [RFC 7587](https://tools.ietf.org/html/rfc7587) for the RTP payload format,
+//! and [RFC 7845](https://tools.ietf.org/html/rfc7845#section-5.1.1) for channel
+//! mapping families.
+
+use alloc::{vec, vec::Vec};
+
+/// Largest length of a single Opus frame, in bytes.
+pub const MAX_FRAME_LEN: usize = 1275;
+
+/// Largest number of frames in a single Opus packet.
+pub const MAX_FRAMES: usize = 48;
+
+/// Coding mode of an Opus packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Mode {
+	/// Linear prediction, suited to speech.
+	Silk,
+	/// Linear prediction for low frequencies, and MDCT for high frequencies.
+	Hybrid,
+	/// MDCT, suited to music and low latency.
+	Celt,
+}
+
+/// Audio bandwidth of an Opus packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Bandwidth {
+	/// 4kHz.
+	Narrowband,
+	/// 6kHz.
+	Mediumband,
+	/// 8kHz.
+	Wideband,
+	/// 12kHz.
+	SuperWideband,
+	/// 20kHz.
+	Fullband,
+}
+
+/// Table-of-contents byte beginning every Opus packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Toc(pub u8);
+
+impl Toc {
+	/// Configuration number, from `0` to `31`, selecting the mode, bandwidth and
+	/// frame size.
+	#[must_use]
+	pub fn config(self) -> u8 {
+		self.0 >> 3
+	}
+
+	/// Returns whether the packet is coded as stereo.
+	#[must_use]
+	pub fn stereo(self) -> bool {
+		self.0 & 0b100 != 0
+	}
+
+	/// Frame count code, from `0` to `3`.
+	#[must_use]
+	pub fn code(self) -> u8 {
+		self.0 & 0b11
+	}
+
+	/// Coding mode of the packet.
+	#[must_use]
+	pub fn mode(self) -> Mode {
+		match self.config() {
+			0..=11 => Mode::Silk,
+			12..=15 => Mode::Hybrid,
+			_ => Mode::Celt,
+		}
+	}
+
+	/// Audio bandwidth of the packet.
+	#[must_use]
+	pub fn bandwidth(self) -> Bandwidth {
+		match self.config() {
+			0..=3 | 16..=19 => Bandwidth::Narrowband,
+			4..=7 => Bandwidth::Mediumband,
+			8..=11 | 20..=23 => Bandwidth::Wideband,
+			12 | 13 | 24..=27 => Bandwidth::SuperWideband,
+			_ => Bandwidth::Fullband,
+		}
+	}
+
+	/// Duration of each frame, in samples at 48kHz.
+	#[must_use]
+	pub fn frame_samples(self) -> u32 {
+		let config = self.config();
+		match self.mode() {
+			Mode::Silk => [480, 960, 1920, 2880][usize::from(config % 4)],
+			Mode::Hybrid => [480, 960][usize::from(config % 2)],
+			Mode::Celt => [120, 240, 480, 960][usize::from(config % 4)],
+		}
+	}
+}
+
+/// The frames of a single Opus packet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpusPacket<'a> {
+	/// Table-of-contents byte of the packet.
+	pub toc: Toc,
+	/// Compressed frames, each of which may be empty (*e.g.*, during DTX).
+	pub frames: Vec<&'a [u8]>,
+	/// Padding following the frames.
+	pub padding: &'a [u8],
+}
+
+impl<'a> OpusPacket<'a> {
+	/// Parses a standalone Opus packet, filling the whole of `data`.
+	///
+	/// Returns `None` if the packet is malformed.
+	#[must_use]
+	pub fn parse(data: &'a [u8]) -> Option<Self> {
+		let (pkt, len) = Self::parse_framed(data, false)?;
+		(len == data.len()).then_some(pkt)
+	}
+
+	/// Parses a packet using self-delimiting framing from the start of `data`,
+	/// returning it along with the number of bytes it occupies.
+	///
+	/// Self-delimiting framing is used by every stream but the last of a
+	/// multistream packet.
+	///
+	/// See [RFC 6716](https://tools.ietf.org/html/rfc6716#appendix-B).
+	#[must_use]
+	pub fn parse_self_delimited(data: &'a [u8]) -> Option<(Self, usize)> {
+		Self::parse_framed(data, true)
+	}
+
+	/// Total duration of the packet, in samples at 48kHz.
+	#[must_use]
+	pub fn samples(&self) -> u32 {
+		#[allow(clippy::cast_possible_truncation)]
+		let frames = self.frames.len() as u32;
+		frames * self.toc.frame_samples()
+	}
+
+	fn parse_framed(data: &'a [u8], self_delimited: bool) -> Option<(Self, usize)> {
+		let toc = Toc(*data.first()?);
+		let mut pos = 1;
+		let mut lens = Vec::new();
+		let mut padding = 0;
+
+		match toc.code() {
+			0 =>
+				if self_delimited {
+					lens.push(read_len(data, &mut pos)?);
+				} else {
+					lens.push(data.len() - pos);
+				},
+			1 => {
+				let len = if self_delimited {
+					read_len(data, &mut pos)?
+				} else {
+					let rest = data.len() - pos;
+					if rest % 2 != 0 {
+						return None;
+					}
+					rest / 2
+				};
+				lens.extend([len, len]);
+			},
+			2 => {
+				let first = read_len(data, &mut pos)?;
+				let second = if self_delimited {
+					read_len(data, &mut pos)?
+				} else {
+					data.len().checked_sub(pos + first)?
+				};
+				lens.extend([first, second]);
+			},
+			_ => {
+				let count_byte = *data.get(pos)?;
+				pos += 1;
+
+				let vbr = count_byte & 0x80 != 0;
+				let count = usize::from(count_byte & 0x3f);
+				if count == 0 || count > MAX_FRAMES {
+					return None;
+				}
+
+				if count_byte & 0x40 != 0 {
+					loop {
+						let b = *data.get(pos)?;
+						pos += 1;
+						padding += usize::from(b.min(254));
+						if b != 255 {
+							break;
+						}
+					}
+				}
+
+				if vbr {
+					for _ in 1..count {
+						lens.push(read_len(data, &mut pos)?);
+					}
+					let last = if self_delimited {
+						read_len(data, &mut pos)?
+					} else {
+						data.len()
+							.checked_sub(pos + padding + lens.iter().sum::<usize>())?
+					};
+					lens.push(last);
+				} else {
+					let len = if self_delimited {
+						read_len(data, &mut pos)?
+					} else {
+						let rest = data.len().checked_sub(pos + padding)?;
+						if rest % count != 0 {
+							return None;
+						}
+						rest / count
+					};
+					lens.resize(count, len);
+				}
+			},
+		}
+
+		let mut frames = Vec::with_capacity(lens.len());
+		for len in lens {
+			if len > MAX_FRAME_LEN {
+				return None;
+			}
+			frames.push(data.get(pos..pos + len)?);
+			pos += len;
+		}
+
+		let padding = data.get(pos..pos + padding)?;
+		pos += padding.len();
+
+		Some((
+			Self {
+				toc,
+				frames,
+				padding,
+			},
+			pos,
+		))
+	}
+}
+
+/// Reads a frame length of one or two bytes at `pos`, advancing past it.
+fn read_len(data: &[u8], pos: &mut usize) -> Option<usize> {
+	let first = usize::from(*data.get(*pos)?);
+	if first < 252 {
+		*pos += 1;
+		return Some(first);
+	}
+
+	let second = usize::from(*data.get(*pos + 1)?);
+	*pos += 2;
+
+	Some(first + 4 * second)
+}
+
+/// Splits a multistream Opus packet into the packets of each of its `streams`.
+///
+/// Every stream but the last uses self-delimiting framing.
+///
+/// Returns `None` if any stream is malformed, or bytes remain after the last.
+#[must_use]
+pub fn split_multistream(data: &[u8], streams: u8) -> Option<Vec<OpusPacket<'_>>> {
+	let mut out = Vec::with_capacity(usize::from(streams));
+	let mut rest = data;
+
+	for _ in 1..streams {
+		let (pkt, len) = OpusPacket::parse_self_delimited(rest)?;
+		rest = &rest[len..];
+		out.push(pkt);
+	}
+
+	if streams > 0 {
+		out.push(OpusPacket::parse(rest)?);
+	}
+
+	Some(out)
+}
+
+/// Source of an output channel of a multistream decoder.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ChannelSource {
+	/// The channel is silent.
+	Silence,
+	/// The channel is the left (`0`) or right (`1`) channel of a coupled (stereo)
+	/// stream.
+	Coupled {
+		/// Index of the stream.
+		stream: u8,
+		/// Channel within the stream.
+		channel: u8,
+	},
+	/// The channel is the whole of an uncoupled (mono) stream.
+	Mono {
+		/// Index of the stream.
+		stream: u8,
+	},
+}
+
+/// Assignment of a multistream packet's streams to output channels.
+///
+/// Coupled (stereo) streams come first, followed by mono streams.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ChannelMapping {
+	/// Channel mapping family.
+	///
+	/// Family `0` is mono or stereo, `1` is surround sound using Vorbis channel
+	/// order, `2` is ambisonics, `3` is ambisonics with a demixing matrix, and
+	/// `255` is application-defined.
+	pub family: u8,
+	/// Number of output channels.
+	pub channels: u8,
+	/// Number of streams in each packet.
+	pub streams: u8,
+	/// Number of streams which are coupled (stereo).
+	pub coupled_streams: u8,
+	/// Decoded channel index of each output channel, where `255` is silence.
+	///
+	/// This is empty for family `3`, whose channels are mixed by a demixing matrix.
+	pub mapping: Vec<u8>,
+}
+
+impl ChannelMapping {
+	/// Creates the default mapping of `channels` channels for `family`, as chosen
+	/// by the reference encoder.
+	///
+	/// Returns `None` if `family` is not `0`--`3`, or does not support this many
+	/// channels.
+	#[must_use]
+	pub fn new(family: u8, channels: u8) -> Option<Self> {
+		let (streams, coupled_streams, mapping): (u8, u8, Vec<u8>) = match family {
+			0 => match channels {
+				1 => (1, 0, vec![0]),
+				2 => (1, 1, vec![0, 1]),
+				_ => return None,
+			},
+			1 => {
+				const VORBIS: [(u8, u8, &[u8]); 8] = [
+					(1, 0, &[0]),
+					(1, 1, &[0, 1]),
+					(2, 1, &[0, 2, 1]),
+					(2, 2, &[0, 1, 2, 3]),
+					(3, 2, &[0, 4, 1, 2, 3]),
+					(4, 2, &[0, 4, 1, 2, 3, 5]),
+					(4, 3, &[0, 4, 1, 2, 3, 5, 6]),
+					(5, 3, &[0, 6, 1, 2, 3, 4, 5, 7]),
+				];
+				let (s, c, m) = VORBIS.get(usize::from(channels).checked_sub(1)?)?;
+				(*s, *c, m.to_vec())
+			},
+			2 => {
+				let (ambisonic, non_diegetic) = ambisonic_channels(channels)?;
+				let coupled = non_diegetic / 2;
+				let mapping = (0..ambisonic)
+					.map(|i| i + 2 * coupled)
+					.chain(0..non_diegetic)
+					.collect();
+				(ambisonic + coupled, coupled, mapping)
+			},
+			3 => {
+				ambisonic_channels(channels)?;
+				((channels + 1) / 2, channels / 2, Vec::new())
+			},
+			_ => return None,
+		};
+
+		Some(Self {
+			family,
+			channels,
+			streams,
+			coupled_streams,
+			mapping,
+		})
+	}
+
+	/// Parses the channel mapping of an `OpusHead` identification header.
+	///
+	/// See [RFC 7845](https://tools.ietf.org/html/rfc7845#section-5.1).
+	#[must_use]
+	pub fn from_id_header(header: &[u8]) -> Option<Self> {
+		if header.get(..8)? != b"OpusHead" {
+			return None;
+		}
+
+		let channels = *header.get(9)?;
+		let family = *header.get(18)?;
+		if family == 0 {
+			return Self::new(0, channels);
+		}
+
+		let streams = *header.get(19)?;
+		let coupled_streams = *header.get(20)?;
+		let mapping = if family == 3 {
+			Vec::new()
+		} else {
+			header.get(21..21 + usize::from(channels))?.to_vec()
+		};
+
+		let out = Self {
+			family,
+			channels,
+			streams,
+			coupled_streams,
+			mapping,
+		};
+
+		out.is_valid().then_some(out)
+	}
+
+	/// Returns whether the stream counts and mapping table are consistent.
+	#[must_use]
+	pub fn is_valid(&self) -> bool {
+		let decoded = u16::from(self.streams) + u16::from(self.coupled_streams);
+
+		self.streams > 0
+			&& self.coupled_streams <= self.streams
+			&& decoded <= 255
+			&& (self.family == 3 || self.mapping.len() == usize::from(self.channels))
+			&& self
+				.mapping
+				.iter()
+				.all(|&m| m == 255 || u16::from(m) < decoded)
+	}
+
+	/// Returns which stream (and channel within it) feeds output channel `channel`.
+	///
+	/// Returns `None` if `channel` is out of range, or is mixed by a demixing matrix.
+	#[must_use]
+	pub fn source(&self, channel: u8) -> Option<ChannelSource> {
+		let idx = *self.mapping.get(usize::from(channel))?;
+
+		Some(if idx == 255 {
+			ChannelSource::Silence
+		} else if idx < 2 * self.coupled_streams {
+			ChannelSource::Coupled {
+				stream: idx / 2,
+				channel: idx % 2,
+			}
+		} else {
+			ChannelSource::Mono {
+				stream: idx - self.coupled_streams,
+			}
+		})
+	}
+
+	/// Splits a multistream packet using this mapping's stream count.
+	///
+	/// See [`split_multistream`] for more information.
+	///
+	/// [`split_multistream`]: fn.split_multistream.html
+	#[must_use]
+	pub fn split<'a>(&self, data: &'a [u8]) -> Option<Vec<OpusPacket<'a>>> {
+		split_multistream(data, self.streams)
+	}
+}
+
+/// Splits an ambisonics channel count into its full-sphere and non-diegetic
+/// (head-locked stereo) channels.
+fn ambisonic_channels(channels: u8) -> Option<(u8, u8)> {
+	(0u8..=14).find_map(|order| {
+		let ambisonic = (order + 1) * (order + 1);
+		match channels.checked_sub(ambisonic)? {
+			extra @ (0 | 2) => Some((ambisonic, extra)),
+			_ => None,
+		}
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn projection_mapping_stream_counts() {
+		let mapping = ChannelMapping::new(3, 4).unwrap();
+		assert_eq!((mapping.streams, mapping.coupled_streams), (2, 2));
+		assert!(mapping.mapping.is_empty());
+
+		let mapping = ChannelMapping::new(3, 11).unwrap();
+		assert_eq!((mapping.streams, mapping.coupled_streams), (6, 5));
+		assert!(mapping.is_valid());
+
+		assert!(ChannelMapping::new(3, 5).is_none());
+	}
+
+	#[test]
+	fn ambisonic_mapping_stream_counts() {
+		let mapping = ChannelMapping::new(2, 6).unwrap();
+		assert_eq!((mapping.streams, mapping.coupled_streams), (5, 1));
+		assert_eq!(mapping.mapping, [2, 3, 4, 5, 0, 1]);
+	}
+}
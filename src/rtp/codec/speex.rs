@@ -0,0 +1,265 @@
+//! Speex speech, as used by older voice over IP software.
+//!
+//! A payload holds one or more 20 ms Speex frames, bit-packed back to back with
+//! no padding between them. The end of the payload is padded to a whole byte with
+//! a `0` bit followed by `1` bits, which a decoder reads as a terminator. Frames
+//! are never split across packets.
+//!
+//! Frame boundaries are found by reading each frame's mode, as the reference
+//! decoder does, without decoding its speech.
+//!
+//! See [RFC 5574](https://tools.ietf.org/html/rfc5574).
+
+use crate::rtp::{
+	bits::{BitReader, BitWriter},
+	depacketizer::Depacketizer,
+	packetizer::Packetizer,
+};
+use alloc::vec::Vec;
+
+/// Size in bits of a narrowband frame of each mode, including its 5-bit header.
+const NB_FRAME_BITS: [usize; 9] = [5, 43, 119, 160, 220, 300, 364, 492, 79];
+
+/// Size in bits of a wideband layer of each submode, including its 4-bit header.
+const WB_LAYER_BITS: [usize; 5] = [4, 36, 112, 192, 352];
+
+/// Narrowband mode marking a user in-band message.
+const MODE_USER_INBAND: u32 = 13;
+
+/// Narrowband mode marking a Speex in-band request.
+const MODE_SPEEX_INBAND: u32 = 14;
+
+/// Narrowband mode marking the end of the frames in a payload.
+const MODE_TERMINATOR: u32 = 15;
+
+/// Sampling band of a Speex stream.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SpeexBand {
+	/// Narrowband, at 8 kHz.
+	Narrowband,
+	/// Wideband, at 16 kHz.
+	Wideband,
+	/// Ultra-wideband, at 32 kHz.
+	UltraWideband,
+}
+
+impl SpeexBand {
+	/// Returns the band whose sampling rate is `clock_rate`, as negotiated in an
+	/// `a=rtpmap` line.
+	#[must_use]
+	pub fn from_clock_rate(clock_rate: u32) -> Option<Self> {
+		match clock_rate {
+			8_000 => Some(Self::Narrowband),
+			16_000 => Some(Self::Wideband),
+			32_000 => Some(Self::UltraWideband),
+			_ => None,
+		}
+	}
+
+	/// Returns the RTP clock rate of this band, in Hz.
+	#[must_use]
+	pub fn clock_rate(self) -> u32 {
+		match self {
+			Self::Narrowband => 8_000,
+			Self::Wideband => 16_000,
+			Self::UltraWideband => 32_000,
+		}
+	}
+
+	/// Returns the amount by which the timestamp advances for each frame.
+	#[must_use]
+	pub fn frame_samples(self) -> u32 {
+		self.clock_rate() / 50
+	}
+}
+
+/// Reads the frames of `payload` up to its terminator or padding, returning the
+/// number of frames and the number of bits they occupy.
+///
+/// In-band messages are skipped over without being counted as frames.
+fn walk(payload: &[u8]) -> Option<(usize, usize)> {
+	let mut reader = BitReader::new(payload);
+	let mut frames = 0;
+	let mut end = 0;
+
+	while reader.remaining() >= 5 {
+		if reader.flag()? {
+			let submode = reader.read(3)? as usize;
+			reader.skip(WB_LAYER_BITS.get(submode)? - 4)?;
+		} else {
+			match reader.read(4)? {
+				mode @ 0..=8 => {
+					reader.skip(NB_FRAME_BITS[mode as usize] - 5)?;
+					frames += 1;
+				},
+				MODE_USER_INBAND => {
+					let len = reader.read(4)? as usize;
+					reader.skip(5 + 8 * len)?;
+				},
+				MODE_SPEEX_INBAND => {
+					let len = match reader.read(4)? {
+						0..=1 => 1,
+						2..=7 => 4,
+						8..=9 => 8,
+						10..=11 => 16,
+						12..=13 => 32,
+						_ => 64,
+					};
+					reader.skip(len)?;
+				},
+				MODE_TERMINATOR => break,
+				_ => return None,
+			}
+		}
+
+		end = reader.position();
+	}
+
+	Some((frames, end))
+}
+
+/// Returns the number of Speex frames in `payload`.
+///
+/// Returns `None` if a frame is truncated or has an invalid mode.
+#[must_use]
+pub fn count_frames(payload: &[u8]) -> Option<usize> {
+	walk(payload).map(|(frames, _)| frames)
+}
+
+/// Returns the amount by which the timestamp advances after `payload`, a stream
+/// in `band`.
+#[must_use]
+pub fn duration(payload: &[u8], band: SpeexBand) -> Option<u32> {
+	let frames = u32::try_from(count_frames(payload)?).ok()?;
+
+	frames.checked_mul(band.frame_samples())
+}
+
+/// Combines separately encoded (and padded) groups of Speex frames into a single
+/// payload, packing their bits back to back and padding only the end.
+///
+/// Returns `None` if any input is malformed, or if no frames are present.
+#[must_use]
+pub fn concat(encoded: &[&[u8]]) -> Option<Vec<u8>> {
+	let mut writer = BitWriter::default();
+	let mut frames = 0;
+
+	for data in encoded {
+		let (count, bits) = walk(data)?;
+		frames += count;
+
+		let mut reader = BitReader::new(data);
+		for _ in 0..bits / 8 {
+			writer.write(reader.read(8)?, 8);
+		}
+		#[allow(clippy::cast_possible_truncation)]
+		let tail = (bits % 8) as u8;
+		writer.write(reader.read(tail)?, tail);
+	}
+
+	if frames == 0 {
+		return None;
+	}
+
+	let pad = (8 - writer.len() % 8) % 8;
+	if pad != 0 {
+		writer.write(0, 1);
+		#[allow(clippy::cast_possible_truncation)]
+		writer.write(u32::MAX, pad as u8 - 1);
+	}
+
+	Some(writer.finish())
+}
+
+/// Packetizer of encoded Speex frames into RTP payloads.
+///
+/// Each frame passed in holds one or more frames as written by the encoder (or
+/// combined by [`concat`]), and is sent whole: the timestamp should then advance
+/// by [`SpeexBand::frame_samples`] for each frame.
+///
+/// [`concat`]: fn.concat.html
+/// [`SpeexBand::frame_samples`]: enum.SpeexBand.html#method.frame_samples
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SpeexPacketizer;
+
+impl Packetizer for SpeexPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		if frame.len() > max_payload || count_frames(frame).unwrap_or(0) == 0 {
+			return Vec::new();
+		}
+
+		alloc::vec![frame.to_vec()]
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		false
+	}
+}
+
+/// Depacketizer of Speex RTP payloads, which returns each payload's bit-packed
+/// frames for the decoder.
+///
+/// Malformed payloads are dropped.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SpeexDepacketizer;
+
+impl Depacketizer for SpeexDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let valid = payloads
+			.iter()
+			.filter(|p| count_frames(p).unwrap_or(0) != 0)
+			.copied()
+			.collect::<Vec<_>>();
+
+		concat(&valid)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Encodes a narrowband frame of `mode` with all-zero speech bits, padded as
+	/// an encoder would.
+	fn nb_frame(mode: u32) -> Vec<u8> {
+		let mut writer = BitWriter::default();
+		writer.write(mode, 5);
+		for _ in 5..NB_FRAME_BITS[mode as usize] {
+			writer.write(0, 1);
+		}
+
+		let pad = u8::try_from((8 - writer.len() % 8) % 8).unwrap();
+		if pad != 0 {
+			writer.write(0, 1);
+			writer.write(u32::MAX, pad - 1);
+		}
+
+		writer.finish()
+	}
+
+	#[test]
+	fn concatenated_frames_round_trip() {
+		let first = nb_frame(1);
+		let second = nb_frame(8);
+		assert_eq!(first.len(), 6);
+		assert_eq!(second.len(), 10);
+
+		let combined = concat(&[&first, &second]).unwrap();
+		assert_eq!(combined.len(), 16);
+		assert_eq!(count_frames(&combined), Some(2));
+		assert_eq!(duration(&combined, SpeexBand::Wideband), Some(640));
+
+		let payloads = SpeexPacketizer.packetize(&combined, 1200);
+		assert_eq!(payloads, [combined.as_slice()]);
+		assert!(SpeexPacketizer.packetize(&combined, 15).is_empty());
+
+		let out = SpeexDepacketizer
+			.depacketize(&[&first, &[0xff], &second])
+			.unwrap();
+		assert_eq!(out, combined);
+	}
+}
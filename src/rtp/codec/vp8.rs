@@ -0,0 +1,102 @@
+//! VP8 video.
+//!
+//! Each payload begins with a payload descriptor of one or more bytes, carrying
+//! the partition index and optional picture ID and temporal layer fields. The
+//! first packet of a frame is followed by the VP8 payload header, which marks
+//! whether the frame is a keyframe.
+//!
+//! See [RFC 7741](https://tools.ietf.org/html/rfc7741).
+
+/// Payload descriptor at the start of every VP8 RTP payload.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Vp8Descriptor {
+	/// Whether this frame is not used as a reference by any other frame, and so
+	/// may be discarded.
+	pub non_reference: bool,
+	/// Whether this packet begins a partition.
+	pub start_of_partition: bool,
+	/// Index of the partition this packet belongs to.
+	pub partition_id: u8,
+	/// Running picture ID, of 7 or 15 bits.
+	pub picture_id: Option<u16>,
+	/// Running index of base temporal layer frames.
+	pub tl0_pic_idx: Option<u8>,
+	/// Temporal layer index, if temporal scalability is in use.
+	pub temporal_id: Option<u8>,
+	/// Whether this frame depends only on base temporal layer frames since the
+	/// last base layer frame, allowing a switch up to its temporal layer.
+	pub layer_sync: bool,
+	/// Running index of keyframes, used to detect lost keyframes.
+	pub key_idx: Option<u8>,
+}
+
+impl Vp8Descriptor {
+	/// Parses the payload descriptor at the start of `payload`, returning it along
+	/// with its size in bytes.
+	///
+	/// Returns `None` if the descriptor is truncated.
+	#[must_use]
+	pub fn parse(payload: &[u8]) -> Option<(Self, usize)> {
+		let first = *payload.first()?;
+		let mut out = Self {
+			non_reference: first & 0x20 != 0,
+			start_of_partition: first & 0x10 != 0,
+			partition_id: first & 0x07,
+			..Self::default()
+		};
+		if first & 0x80 == 0 {
+			return Some((out, 1));
+		}
+
+		let extension = *payload.get(1)?;
+		let mut len = 2;
+		if extension & 0x80 != 0 {
+			let high = *payload.get(len)?;
+			if high & 0x80 == 0 {
+				out.picture_id = Some(u16::from(high));
+				len += 1;
+			} else {
+				let low = *payload.get(len + 1)?;
+				out.picture_id = Some(u16::from_be_bytes([high & 0x7f, low]));
+				len += 2;
+			}
+		}
+		if extension & 0x40 != 0 {
+			out.tl0_pic_idx = Some(*payload.get(len)?);
+			len += 1;
+		}
+		if extension & 0x30 != 0 {
+			let byte = *payload.get(len)?;
+			if extension & 0x20 != 0 {
+				out.temporal_id = Some(byte >> 6);
+				out.layer_sync = byte & 0x20 != 0;
+			}
+			if extension & 0x10 != 0 {
+				out.key_idx = Some(byte & 0x1f);
+			}
+			len += 1;
+		}
+
+		Some((out, len))
+	}
+
+	/// Returns whether this packet begins a frame, and so carries the VP8 payload
+	/// header.
+	#[must_use]
+	pub fn is_start_of_frame(&self) -> bool {
+		self.start_of_partition && self.partition_id == 0
+	}
+}
+
+/// Returns whether an RTP payload begins a keyframe.
+///
+/// This is only true of the first packet of the frame, which starts partition
+/// `0` and so carries the VP8 payload header.
+#[must_use]
+pub fn is_keyframe(payload: &[u8]) -> bool {
+	let Some((descriptor, len)) = Vp8Descriptor::parse(payload) else {
+		return false;
+	};
+
+	descriptor.is_start_of_frame() && payload.get(len).map_or(false, |h| h & 0x01 == 0)
+}
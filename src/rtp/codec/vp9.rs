@@ -0,0 +1,120 @@
+//! VP9 video.
+//!
+//! Each payload begins with a payload descriptor, flagging whether the frame is
+//! predicted from earlier frames and (optionally) carrying its picture ID and
+//! spatial and temporal layer indices.
+//!
+//! See [RFC 9628](https://tools.ietf.org/html/rfc9628).
+
+/// Layer indices of a VP9 payload descriptor.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Vp9Layer {
+	/// Temporal layer index.
+	pub temporal_id: u8,
+	/// Whether a switch up to a higher temporal layer may occur at this frame.
+	pub switching_up: bool,
+	/// Spatial layer index.
+	pub spatial_id: u8,
+	/// Whether this frame depends on the frame of the next lower spatial layer in
+	/// the same picture.
+	pub inter_layer_dependency: bool,
+	/// Running index of base temporal layer frames, sent only in non-flexible
+	/// mode.
+	pub tl0_pic_idx: Option<u8>,
+}
+
+/// Payload descriptor at the start of every VP9 RTP payload.
+///
+/// Reference indices and the scalability structure which may follow are not
+/// parsed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Vp9Descriptor {
+	/// Whether this frame is predicted from earlier frames of its spatial layer.
+	pub inter_predicted: bool,
+	/// Whether references are signalled per frame (flexible mode), rather than by
+	/// a scalability structure.
+	pub flexible: bool,
+	/// Whether this packet begins a frame.
+	pub start_of_frame: bool,
+	/// Whether this packet ends a frame.
+	pub end_of_frame: bool,
+	/// Whether a scalability structure follows the descriptor.
+	pub has_scalability_structure: bool,
+	/// Whether this frame is not used as a reference by higher spatial layers.
+	pub not_upper_reference: bool,
+	/// Running picture ID, of 7 or 15 bits.
+	pub picture_id: Option<u16>,
+	/// Layer indices, if spatial or temporal scalability is in use.
+	pub layer: Option<Vp9Layer>,
+}
+
+impl Vp9Descriptor {
+	/// Parses the payload descriptor at the start of `payload`.
+	///
+	/// Returns `None` if the descriptor is truncated.
+	#[must_use]
+	pub fn parse(payload: &[u8]) -> Option<Self> {
+		let first = *payload.first()?;
+		let mut out = Self {
+			inter_predicted: first & 0x40 != 0,
+			flexible: first & 0x10 != 0,
+			start_of_frame: first & 0x08 != 0,
+			end_of_frame: first & 0x04 != 0,
+			has_scalability_structure: first & 0x02 != 0,
+			not_upper_reference: first & 0x01 != 0,
+			..Self::default()
+		};
+
+		let mut pos = 1;
+		if first & 0x80 != 0 {
+			let high = *payload.get(pos)?;
+			if high & 0x80 == 0 {
+				out.picture_id = Some(u16::from(high));
+				pos += 1;
+			} else {
+				let low = *payload.get(pos + 1)?;
+				out.picture_id = Some(u16::from_be_bytes([high & 0x7f, low]));
+				pos += 2;
+			}
+		}
+
+		if first & 0x20 != 0 {
+			let layers = *payload.get(pos)?;
+			let tl0_pic_idx = if out.flexible {
+				None
+			} else {
+				Some(*payload.get(pos + 1)?)
+			};
+
+			out.layer = Some(Vp9Layer {
+				temporal_id: layers >> 5,
+				switching_up: layers & 0x10 != 0,
+				spatial_id: (layers >> 1) & 0x07,
+				inter_layer_dependency: layers & 0x01 != 0,
+				tl0_pic_idx,
+			});
+		}
+
+		Some(out)
+	}
+
+	/// Returns whether this packet begins a keyframe: the first packet of a frame
+	/// in the base spatial layer which is not predicted from earlier frames.
+	#[must_use]
+	pub fn is_keyframe(&self) -> bool {
+		self.start_of_frame
+			&& !self.inter_predicted
+			&& self.layer.map_or(true, |layer| layer.spatial_id == 0)
+	}
+}
+
+/// Returns whether an RTP payload begins a keyframe.
+///
+/// See [`Vp9Descriptor::is_keyframe`] for more information.
+///
+/// [`Vp9Descriptor::is_keyframe`]: struct.Vp9Descriptor.html#method.is_keyframe
+#[must_use]
+pub fn is_keyframe(payload: &[u8]) -> bool {
+	Vp9Descriptor::parse(payload).map_or(false, |d| d.is_keyframe())
+}
@@ -0,0 +1,527 @@
+//! Vorbis audio and Theora video, which share the Xiph payload header.
+//!
+//! Each payload begins with a header naming the codec configuration in use, and
+//! carries either several whole codec packets (each prefixed by its length) or a
+//! fragment of a single large packet. Codec setup headers are delivered as a
+//! packed configuration, either in-band or out of band (*e.g.*, in SDP).
+//!
+//! See [RFC 5215](https://tools.ietf.org/html/rfc5215).
+
+use crate::{
+	error::impl_new_checked,
+	rtp::{depacketizer::Depacketizer, packetizer::Packetizer},
+	wrap::Wrap16,
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u2, u24be, u4},
+};
+
+/// Size of the Xiph payload header, in bytes.
+pub const HEADER_LEN: usize = 4;
+
+/// Fragment type of a payload holding whole packets.
+pub const NOT_FRAGMENTED: u8 = 0;
+
+/// Fragment type of a payload holding the first fragment of a packet.
+pub const START_FRAGMENT: u8 = 1;
+
+/// Fragment type of a payload holding a middle fragment of a packet.
+pub const CONTINUATION_FRAGMENT: u8 = 2;
+
+/// Fragment type of a payload holding the last fragment of a packet.
+pub const END_FRAGMENT: u8 = 3;
+
+/// Data type of a payload holding raw codec data.
+pub const RAW_DATA: u8 = 0;
+
+/// Data type of a payload holding a packed configuration.
+pub const CONFIGURATION: u8 = 1;
+
+/// Data type of a payload holding a legacy comment header.
+pub const COMMENT: u8 = 2;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Xiph payload header, present at the start of every Vorbis or Theora payload.
+///
+/// A description of fields:
+///
+/// ## `ident`
+/// Identifier of the codec configuration needed to decode the payload.
+///
+/// ## `fragment_type`
+/// Whether the payload holds whole packets, or which fragment of a packet it
+/// holds: see [`NOT_FRAGMENTED`] and friends.
+///
+/// ## `data_type`
+/// Kind of data carried: see [`RAW_DATA`], [`CONFIGURATION`] and [`COMMENT`].
+///
+/// ## `packet_count`
+/// Number of whole packets in the payload, or `0` for a fragment.
+///
+/// ## `payload`
+/// Packets or a fragment, each preceded by its length as a 16-bit integer.
+///
+/// [`NOT_FRAGMENTED`]: constant.NOT_FRAGMENTED.html
+/// [`RAW_DATA`]: constant.RAW_DATA.html
+/// [`CONFIGURATION`]: constant.CONFIGURATION.html
+/// [`COMMENT`]: constant.COMMENT.html
+pub struct Xiph {
+	pub ident: u24be,
+
+	pub fragment_type: u2,
+
+	pub data_type: u2,
+
+	pub packet_count: u4,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	XiphPacket,
+	MutableXiphPacket,
+	["ident" => 3, "packet_count" => 4]
+);
+
+impl XiphPacket<'_> {
+	/// Iterates over the packets or fragment in this payload, without their length
+	/// prefixes.
+	///
+	/// Iteration ends early if a length is truncated, or if more packets are
+	/// present than declared.
+	pub fn packets(&self) -> impl Iterator<Item = &[u8]> {
+		let count = usize::from(self.get_packet_count()).max(1);
+
+		length_prefixed(self.payload(), 2).take(count)
+	}
+}
+
+/// Iterates over data prefixed by big-endian lengths of `len_size` bytes.
+fn length_prefixed(mut data: &[u8], len_size: usize) -> impl Iterator<Item = &[u8]> {
+	core::iter::from_fn(move || {
+		let len = data
+			.get(..len_size)?
+			.iter()
+			.fold(0usize, |acc, &b| (acc << 8) | usize::from(b));
+		let packet = data.get(len_size..len_size + len)?;
+		data = &data[len_size + len..];
+
+		Some(packet)
+	})
+}
+
+/// Splits the output of an [`XiphDepacketizer`] into codec packets, each of
+/// which is preceded by its length as a 32-bit integer.
+///
+/// [`XiphDepacketizer`]: struct.XiphDepacketizer.html
+pub fn split_packets(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+	length_prefixed(data, 4)
+}
+
+/// Reads a variable-length integer of 7 bits per byte, where the top bit of each
+/// byte marks that another follows.
+fn read_b128(data: &mut &[u8]) -> Option<usize> {
+	let mut out = 0usize;
+	loop {
+		let (&byte, rest) = data.split_first()?;
+		*data = rest;
+		out = out.checked_mul(128)? | usize::from(byte & 0x7f);
+		if byte & 0x80 == 0 {
+			return Some(out);
+		}
+	}
+}
+
+/// Writes a variable-length integer of 7 bits per byte.
+fn write_b128(out: &mut Vec<u8>, mut val: usize) {
+	let mut bytes = Vec::new();
+	loop {
+		#[allow(clippy::cast_possible_truncation)]
+		bytes.push((val & 0x7f) as u8);
+		val >>= 7;
+		if val == 0 {
+			break;
+		}
+	}
+
+	for (i, byte) in bytes.iter().enumerate().rev() {
+		out.push(byte | if i == 0 { 0 } else { 0x80 });
+	}
+}
+
+/// Codec setup headers (*e.g.*, the Vorbis identification, comment and setup
+/// headers) bound to a configuration ident.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct XiphConfig {
+	/// Configuration identifier, as carried in payload headers. Only the low 24
+	/// bits are used.
+	pub ident: u32,
+	/// Codec headers, in order.
+	pub headers: Vec<Vec<u8>>,
+}
+
+impl XiphConfig {
+	/// Parses a packed headers structure, as delivered out of band in the base64
+	/// `configuration` format parameter (once decoded).
+	///
+	/// Returns `None` if the structure is truncated or malformed.
+	#[must_use]
+	pub fn parse_packed(mut data: &[u8]) -> Option<Vec<Self>> {
+		let count = u32::from_be_bytes(data.get(..4)?.try_into().ok()?);
+		data = &data[4..];
+
+		let mut out = Vec::new();
+		for _ in 0..count {
+			let ident = u32::from_be_bytes([0, *data.first()?, *data.get(1)?, *data.get(2)?]);
+			data = &data[3..];
+			let (config, used) = Self::parse_body(ident, data)?;
+			data = &data[used..];
+			out.push(config);
+		}
+
+		Some(out)
+	}
+
+	/// Serialises `configs` as a packed headers structure, for out of band delivery.
+	///
+	/// Returns `None` if a configuration has no headers, or is too large.
+	#[must_use]
+	pub fn to_packed(configs: &[Self]) -> Option<Vec<u8>> {
+		let mut out = u32::try_from(configs.len()).ok()?.to_be_bytes().to_vec();
+		for config in configs {
+			out.extend_from_slice(&config.ident.to_be_bytes()[1..]);
+			config.write_body(&mut out)?;
+		}
+
+		Some(out)
+	}
+
+	/// Parses a packed configuration delivered in-band, as the reassembled body of
+	/// a payload whose data type is [`CONFIGURATION`].
+	///
+	/// [`CONFIGURATION`]: constant.CONFIGURATION.html
+	#[must_use]
+	pub fn from_in_band(ident: u32, body: &[u8]) -> Option<Self> {
+		Self::parse_body(ident, body).map(|(config, _)| config)
+	}
+
+	/// Serialises this configuration for in-band delivery, as the body of a payload
+	/// whose data type is [`CONFIGURATION`].
+	///
+	/// [`CONFIGURATION`]: constant.CONFIGURATION.html
+	#[must_use]
+	pub fn to_in_band(&self) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		self.write_body(&mut out)?;
+
+		Some(out)
+	}
+
+	/// Parses the length, header count, header lengths and headers following an
+	/// ident, returning the configuration and the number of bytes used.
+	fn parse_body(ident: u32, data: &[u8]) -> Option<(Self, usize)> {
+		let length = usize::from(u16::from_be_bytes([*data.first()?, *data.get(1)?]));
+		let mut rest = &data[2..];
+
+		let count = read_b128(&mut rest)?.checked_add(1)?;
+		let mut lengths = Vec::new();
+		let mut remaining = length;
+		for _ in 1..count {
+			let len = read_b128(&mut rest)?;
+			remaining = remaining.checked_sub(len)?;
+			lengths.push(len);
+		}
+		lengths.push(remaining);
+
+		let used = data.len() - rest.len() + length;
+		let mut headers = Vec::with_capacity(lengths.len());
+		for len in lengths {
+			let (header, tail) = (rest.get(..len)?, &rest[len..]);
+			headers.push(header.to_vec());
+			rest = tail;
+		}
+
+		Some((Self { ident, headers }, used))
+	}
+
+	/// Writes the length, header count, header lengths and headers of this
+	/// configuration.
+	fn write_body(&self, out: &mut Vec<u8>) -> Option<()> {
+		let (_, explicit) = self.headers.split_last()?;
+		let length = u16::try_from(self.headers.iter().map(Vec::len).sum::<usize>()).ok()?;
+
+		out.extend_from_slice(&length.to_be_bytes());
+		write_b128(out, self.headers.len() - 1);
+		for header in explicit {
+			write_b128(out, header.len());
+		}
+		for header in &self.headers {
+			out.extend_from_slice(header);
+		}
+
+		Some(())
+	}
+}
+
+/// Splits a codec packet (or in-band configuration) into payloads of at most
+/// `max_payload` bytes, fragmenting it if needed.
+fn payloads(ident: u32, data_type: u8, packet: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+	let Some(chunk_len) = max_payload
+		.checked_sub(HEADER_LEN + 2)
+		.map(|l| l.min(usize::from(u16::MAX)))
+		.filter(|&l| l > 0)
+	else {
+		return Vec::new();
+	};
+
+	let chunks = packet.chunks(chunk_len);
+	let last = chunks.len().saturating_sub(1);
+	chunks
+		.enumerate()
+		.map(|(i, chunk)| {
+			let (fragment_type, count) = match (i, last) {
+				(0, 0) => (NOT_FRAGMENTED, 1),
+				(0, _) => (START_FRAGMENT, 0),
+				(i, last) if i == last => (END_FRAGMENT, 0),
+				_ => (CONTINUATION_FRAGMENT, 0),
+			};
+
+			let [_, id0, id1, id2] = ident.to_be_bytes();
+			let mut pkt = Vec::with_capacity(HEADER_LEN + 2 + chunk.len());
+			pkt.extend_from_slice(&[
+				id0,
+				id1,
+				id2,
+				(fragment_type << 6) | (data_type << 4) | count,
+			]);
+			#[allow(clippy::cast_possible_truncation)]
+			pkt.extend_from_slice(&(chunk.len() as u16).to_be_bytes());
+			pkt.extend_from_slice(chunk);
+			pkt
+		})
+		.collect()
+}
+
+/// Packetizer of Vorbis or Theora packets into RTP payloads.
+///
+/// Each frame passed in is a single codec packet, sent whole or as fragments.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct XiphPacketizer {
+	/// Identifier of the configuration in use.
+	pub ident: u32,
+}
+
+impl XiphPacketizer {
+	/// Creates a packetizer of packets using the configuration `ident`.
+	#[must_use]
+	pub fn new(ident: u32) -> Self {
+		Self { ident }
+	}
+
+	/// Returns the payloads delivering `config` in-band.
+	///
+	/// Returns an empty list if the configuration cannot be serialised.
+	#[must_use]
+	pub fn configuration(&self, config: &XiphConfig, max_payload: usize) -> Vec<Vec<u8>> {
+		config
+			.to_in_band()
+			.map(|body| payloads(config.ident, CONFIGURATION, &body, max_payload))
+			.unwrap_or_default()
+	}
+}
+
+impl Packetizer for XiphPacketizer {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		payloads(self.ident, RAW_DATA, frame, max_payload)
+	}
+}
+
+/// Depacketizer of Vorbis or Theora RTP payloads into codec packets.
+///
+/// Every packet reassembled is written with its length as a 32-bit prefix, to be
+/// split apart by [`split_packets`]. Configurations received in-band are kept
+/// rather than returned, and comment headers are ignored. Fragmented packets
+/// with missing pieces are dropped, which requires sequence numbers to be given
+/// via [`depacketize_sequenced`] (as by an [`RtpDepacketizer`]).
+///
+/// [`split_packets`]: fn.split_packets.html
+/// [`depacketize_sequenced`]: ../../depacketizer/trait.Depacketizer.html#method.depacketize_sequenced
+/// [`RtpDepacketizer`]: ../../depacketizer/struct.RtpDepacketizer.html
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct XiphDepacketizer {
+	config: Option<XiphConfig>,
+	fragment: Option<(u32, u8, Vec<u8>)>,
+	last_sequence: Option<Wrap16>,
+}
+
+impl XiphDepacketizer {
+	/// Creates a depacketizer without any known configuration.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Returns the most recent configuration received in-band.
+	#[must_use]
+	pub fn config(&self) -> Option<&XiphConfig> {
+		self.config.as_ref()
+	}
+
+	/// Handles a single payload, appending any completed codec data to `out`.
+	fn push(&mut self, payload: &[u8], out: &mut Vec<u8>) {
+		let Ok(pkt) = XiphPacket::new_checked(payload) else {
+			return;
+		};
+		let ident = pkt.get_ident();
+		let data_type = pkt.get_data_type();
+
+		match pkt.get_fragment_type() {
+			NOT_FRAGMENTED => {
+				self.fragment = None;
+				for packet in pkt.packets() {
+					self.complete(ident, data_type, packet, out);
+				}
+			},
+			START_FRAGMENT => {
+				let data = pkt.packets().next().unwrap_or_default().to_vec();
+				self.fragment = Some((ident, data_type, data));
+			},
+			fragment_type => {
+				let Some((id, dt, mut data)) = self.fragment.take() else {
+					return;
+				};
+				if id != ident || dt != data_type {
+					return;
+				}
+
+				data.extend_from_slice(pkt.packets().next().unwrap_or_default());
+				if fragment_type == END_FRAGMENT {
+					self.complete(ident, data_type, &data, out);
+				} else {
+					self.fragment = Some((id, dt, data));
+				}
+			},
+		}
+	}
+
+	/// Handles a whole packet of `data_type`, appending any codec data to `out`.
+	fn complete(&mut self, ident: u32, data_type: u8, packet: &[u8], out: &mut Vec<u8>) {
+		match data_type {
+			RAW_DATA =>
+				if let Ok(len) = u32::try_from(packet.len()) {
+					out.extend_from_slice(&len.to_be_bytes());
+					out.extend_from_slice(packet);
+				},
+			CONFIGURATION =>
+				if let Some(config) = XiphConfig::from_in_band(ident, packet) {
+					self.config = Some(config);
+				},
+			_ => {},
+		}
+	}
+}
+
+impl Depacketizer for XiphDepacketizer {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		for payload in payloads {
+			self.push(payload, &mut out);
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn depacketize_sequenced(&mut self, packets: &[(Wrap16, &[u8])]) -> Option<Vec<u8>> {
+		let mut out = Vec::new();
+		for &(sequence, payload) in packets {
+			if self
+				.last_sequence
+				.map_or(false, |last| last + 1 != sequence)
+			{
+				self.fragment = None;
+			}
+			self.last_sequence = Some(sequence);
+
+			self.push(payload, &mut out);
+		}
+
+		(!out.is_empty()).then_some(out)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		true
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn depacketize(depack: &mut XiphDepacketizer, payloads: &[Vec<u8>], seqs: &[u16]) -> Vec<u8> {
+		seqs.iter()
+			.filter_map(|&seq| {
+				depack.depacketize_sequenced(&[(seq.into(), &payloads[usize::from(seq)][..])])
+			})
+			.flatten()
+			.collect()
+	}
+
+	#[test]
+	fn lost_continuation_drops_fragmented_packet() {
+		let packet: Vec<u8> = (0..=255).collect();
+		let payloads = XiphPacketizer::new(1).packetize(&packet, 100);
+		assert_eq!(payloads.len(), 3);
+
+		let mut depack = XiphDepacketizer::new();
+		let out = depacketize(&mut depack, &payloads, &[0, 1, 2]);
+		assert_eq!(split_packets(&out).collect::<Vec<_>>(), [&packet[..]]);
+
+		let mut depack = XiphDepacketizer::new();
+		assert!(depacketize(&mut depack, &payloads, &[0, 2]).is_empty());
+	}
+
+	#[test]
+	fn configuration_round_trip() {
+		let config = XiphConfig {
+			ident: 0x00ab_cdef,
+			headers: alloc::vec![alloc::vec![1; 30], alloc::vec![2; 200], alloc::vec![3; 300]],
+		};
+
+		let packed = XiphConfig::to_packed(&[config.clone(), config.clone()]).unwrap();
+		assert_eq!(
+			XiphConfig::parse_packed(&packed).unwrap(),
+			[config.clone(), config.clone()]
+		);
+
+		let packetizer = XiphPacketizer::new(config.ident);
+		let payloads = packetizer.configuration(&config, 200);
+		assert_eq!(payloads.len(), 3);
+
+		let mut depack = XiphDepacketizer::new();
+		let seqs: Vec<u16> = (0..3).collect();
+		assert!(depacketize(&mut depack, &payloads, &seqs).is_empty());
+		assert_eq!(depack.config(), Some(&config));
+	}
+
+	#[test]
+	fn whole_packet_round_trip() {
+		let packet: Vec<u8> = (0..50).collect();
+		let payloads = XiphPacketizer::new(7).packetize(&packet, 100);
+		assert_eq!(payloads.len(), 1);
+
+		let pkt = XiphPacket::new_checked(&payloads[0]).unwrap();
+		assert_eq!(pkt.get_ident(), 7);
+		assert_eq!(pkt.get_fragment_type(), NOT_FRAGMENTED);
+		assert_eq!(pkt.packets().collect::<Vec<_>>(), [&packet[..]]);
+
+		let out = XiphDepacketizer::new()
+			.depacketize(&[&payloads[0]])
+			.unwrap();
+		assert_eq!(split_packets(&out).collect::<Vec<_>>(), [&packet[..]]);
+	}
+}
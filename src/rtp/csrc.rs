@@ -0,0 +1,123 @@
+use super::{ext, MutableRtpPacket, Rtp};
+use pnet_macros_support::packet::{MutablePacket, Packet};
+
+/// Largest number of CSRCs an RTP header can carry.
+pub const MAX_CSRCS: usize = 15;
+
+impl Rtp {
+	/// Replaces the CSRC list of this packet, updating `csrc_count` to match.
+	///
+	/// Returns `false` (leaving the packet untouched) if more than [`MAX_CSRCS`]
+	/// are given.
+	///
+	/// [`MAX_CSRCS`]: constant.MAX_CSRCS.html
+	pub fn set_csrcs(&mut self, csrcs: &[u32]) -> bool {
+		if csrcs.len() > MAX_CSRCS {
+			return false;
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		{
+			self.csrc_count = csrcs.len() as u8;
+		}
+		self.csrc_list = csrcs.to_vec();
+
+		true
+	}
+}
+
+impl MutableRtpPacket<'_> {
+	/// Replaces the CSRC list within the first `len` bytes of this packet,
+	/// returning the new packet length.
+	///
+	/// Any header extension and payload are shifted within the underlying buffer
+	/// to follow the new list, and `csrc_count` is updated to match.
+	///
+	/// Returns `None` (leaving the packet untouched) if the current CSRC list is
+	/// truncated, more than [`MAX_CSRCS`] are given, or the buffer lacks the space.
+	///
+	/// [`MAX_CSRCS`]: constant.MAX_CSRCS.html
+	pub fn set_csrcs(&mut self, len: usize, csrcs: &[u32]) -> Option<usize> {
+		let old_end = ext::csrc_end(self.packet().get(..len)?)?;
+		if csrcs.len() > MAX_CSRCS {
+			return None;
+		}
+
+		let new_end = ext::FIXED_HEADER_LEN + 4 * csrcs.len();
+		let new_len = len - old_end + new_end;
+		let buf = self.packet_mut();
+		if new_len > buf.len() {
+			return None;
+		}
+
+		buf.copy_within(old_end..len, new_end);
+		for (chunk, csrc) in buf[ext::FIXED_HEADER_LEN..new_end]
+			.chunks_exact_mut(4)
+			.zip(csrcs)
+		{
+			chunk.copy_from_slice(&csrc.to_be_bytes());
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		self.set_csrc_count(csrcs.len() as u8);
+
+		Some(new_len)
+	}
+
+	/// Inserts `csrc` at position `index` of the CSRC list within the first `len`
+	/// bytes of this packet, returning the new packet length.
+	///
+	/// Any header extension and payload are shifted back by 4 bytes within the
+	/// underlying buffer, and `csrc_count` is updated.
+	///
+	/// Returns `None` (leaving the packet untouched) if the CSRC list is truncated
+	/// or already full, `index` lies past its end, or the buffer lacks the space.
+	pub fn insert_csrc(&mut self, len: usize, index: usize, csrc: u32) -> Option<usize> {
+		ext::csrc_end(self.packet().get(..len)?)?;
+		let count = usize::from(self.get_csrc_count());
+		if count >= MAX_CSRCS || index > count {
+			return None;
+		}
+
+		let new_len = len + 4;
+		let buf = self.packet_mut();
+		if new_len > buf.len() {
+			return None;
+		}
+
+		let at = ext::FIXED_HEADER_LEN + 4 * index;
+		buf.copy_within(at..len, at + 4);
+		buf[at..at + 4].copy_from_slice(&csrc.to_be_bytes());
+
+		#[allow(clippy::cast_possible_truncation)]
+		self.set_csrc_count(count as u8 + 1);
+
+		Some(new_len)
+	}
+
+	/// Removes the CSRC at position `index` of the CSRC list within the first `len`
+	/// bytes of this packet, returning the new packet length and the removed CSRC.
+	///
+	/// Any header extension and payload are shifted forward by 4 bytes within the
+	/// underlying buffer, and `csrc_count` is updated.
+	///
+	/// Returns `None` (leaving the packet untouched) if the CSRC list is truncated,
+	/// or `index` lies past its end.
+	pub fn remove_csrc(&mut self, len: usize, index: usize) -> Option<(usize, u32)> {
+		ext::csrc_end(self.packet().get(..len)?)?;
+		let count = usize::from(self.get_csrc_count());
+		if index >= count {
+			return None;
+		}
+
+		let at = ext::FIXED_HEADER_LEN + 4 * index;
+		let buf = self.packet_mut();
+		let csrc = u32::from_be_bytes([buf[at], buf[at + 1], buf[at + 2], buf[at + 3]]);
+		buf.copy_within(at + 4..len, at);
+
+		#[allow(clippy::cast_possible_truncation)]
+		self.set_csrc_count(count as u8 - 1);
+
+		Some((len - 4, csrc))
+	}
+}
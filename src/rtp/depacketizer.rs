@@ -0,0 +1,275 @@
+//! Reassembly of media frames from received RTP packets.
+
+use super::{
+	frame::{AssembledFrame, FrameAssembler},
+	Rtp,
+};
+use crate::wrap::{Wrap16, Wrap32};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
+use core::time::Duration;
+
+/// Codec-specific reassembly of a frame from its RTP payloads.
+///
+/// Implementors handle only the payload format: grouping packets into frames is
+/// managed by an [`RtpDepacketizer`].
+///
+/// [`RtpDepacketizer`]: struct.RtpDepacketizer.html
+pub trait Depacketizer {
+	/// Reassembles a frame from the payloads of its packets, in sequence order.
+	///
+	/// Payloads may be missing from incomplete frames, in which case as much of the
+	/// frame as possible should be recovered. Returns `None` if nothing usable
+	/// remains.
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>>;
+
+	/// Reassembles a frame as by [`depacketize`], given the sequence number of
+	/// each packet alongside its payload.
+	///
+	/// Depacketizers which keep state between packets (such as a fragment in
+	/// progress) should override this to discard it when packets are missing.
+	/// Defaults to calling [`depacketize`] with the payloads alone.
+	///
+	/// [`depacketize`]: #tymethod.depacketize
+	fn depacketize_sequenced(&mut self, packets: &[(Wrap16, &[u8])]) -> Option<Vec<u8>> {
+		let payloads: Vec<&[u8]> = packets.iter().map(|(_, p)| *p).collect();
+		self.depacketize(&payloads)
+	}
+
+	/// Returns whether the frame made up of `payloads` can be decoded without
+	/// reference to earlier frames.
+	///
+	/// Defaults to `false`.
+	fn is_keyframe(&self, payloads: &[&[u8]]) -> bool {
+		let _ = payloads;
+		false
+	}
+
+	/// Returns whether every packet holds exactly one frame, as is typical for
+	/// audio, so that frames need not wait for the marker bit or a new timestamp.
+	///
+	/// Defaults to `false`.
+	fn frame_per_packet(&self) -> bool {
+		false
+	}
+}
+
+impl<D: Depacketizer + ?Sized> Depacketizer for Box<D> {
+	fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+		(**self).depacketize(payloads)
+	}
+
+	fn depacketize_sequenced(&mut self, packets: &[(Wrap16, &[u8])]) -> Option<Vec<u8>> {
+		(**self).depacketize_sequenced(packets)
+	}
+
+	fn is_keyframe(&self, payloads: &[&[u8]]) -> bool {
+		(**self).is_keyframe(payloads)
+	}
+
+	fn frame_per_packet(&self) -> bool {
+		(**self).frame_per_packet()
+	}
+}
+
+/// A frame reassembled by an [`RtpDepacketizer`].
+///
+/// [`RtpDepacketizer`]: struct.RtpDepacketizer.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DepacketizedFrame {
+	/// RTP timestamp of the frame.
+	pub timestamp: Wrap32,
+	/// Reassembled frame data.
+	pub data: Vec<u8>,
+	/// Whether the frame can be decoded without reference to earlier frames.
+	pub keyframe: bool,
+	/// Whether every packet of the frame was received.
+	///
+	/// Incomplete frames may be corrupt, or missing data.
+	pub complete: bool,
+}
+
+/// Driver turning in-order RTP packets into frames using a [`Depacketizer`].
+///
+/// Packets are grouped into frames by a [`FrameAssembler`], unless the
+/// depacketizer carries one frame per packet. In either case, a gap in sequence
+/// numbers marks the following frame as incomplete.
+///
+/// [`Depacketizer`]: trait.Depacketizer.html
+/// [`FrameAssembler`]: ../frame/struct.FrameAssembler.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RtpDepacketizer<D> {
+	depacketizer: D,
+	assembler: FrameAssembler,
+	ready: VecDeque<DepacketizedFrame>,
+	last_seq: Option<Wrap16>,
+	gap: bool,
+}
+
+impl<D: Depacketizer> RtpDepacketizer<D> {
+	/// Creates a driver which gives up on unfinished frames after `timeout`.
+	#[must_use]
+	pub fn new(depacketizer: D, timeout: Duration) -> Self {
+		Self {
+			depacketizer,
+			assembler: FrameAssembler::new(timeout),
+			ready: VecDeque::new(),
+			last_seq: None,
+			gap: false,
+		}
+	}
+
+	/// Returns the underlying depacketizer.
+	#[must_use]
+	pub fn depacketizer(&self) -> &D {
+		&self.depacketizer
+	}
+
+	/// Returns the underlying depacketizer, mutably.
+	pub fn depacketizer_mut(&mut self) -> &mut D {
+		&mut self.depacketizer
+	}
+
+	/// Adds `packet`, which arrived (or was released for playout) at `now`.
+	pub fn push(&mut self, packet: Rtp, now: Duration) {
+		if self
+			.last_seq
+			.map_or(false, |last| packet.sequence != last + 1)
+		{
+			self.lost();
+		}
+
+		self.push_unsequenced(packet, now);
+	}
+
+	/// Adds `packet` as by [`push`], without checking its sequence number for gaps.
+	///
+	/// This suits callers which detect gaps themselves and report them via
+	/// [`lost`], such as when several payload types share a sequence number space.
+	///
+	/// [`push`]: #method.push
+	/// [`lost`]: #method.lost
+	pub fn push_unsequenced(&mut self, packet: Rtp, now: Duration) {
+		self.last_seq = Some(packet.sequence);
+
+		if self.depacketizer.frame_per_packet() {
+			let complete = !core::mem::take(&mut self.gap);
+			self.emit(&AssembledFrame {
+				timestamp: packet.timestamp,
+				packets: alloc::vec![packet],
+				complete,
+			});
+		} else {
+			self.assembler.push_unsequenced(packet, now);
+			self.drain();
+		}
+	}
+
+	/// Records that a packet is missing.
+	pub fn lost(&mut self) {
+		self.gap = true;
+		self.assembler.lost();
+	}
+
+	/// Gives up on the frame in progress if it began more than the timeout
+	/// before `now`.
+	pub fn expire(&mut self, now: Duration) {
+		self.assembler.expire(now);
+		self.drain();
+	}
+
+	/// Releases the frame in progress, if any, as incomplete.
+	pub fn flush(&mut self) {
+		self.assembler.flush();
+		self.drain();
+	}
+
+	/// Returns the next reassembled frame, if any.
+	pub fn pop(&mut self) -> Option<DepacketizedFrame> {
+		self.ready.pop_front()
+	}
+
+	fn drain(&mut self) {
+		while let Some(frame) = self.assembler.pop() {
+			self.emit(&frame);
+		}
+	}
+
+	fn emit(&mut self, frame: &AssembledFrame) {
+		let payloads: Vec<&[u8]> = frame.payloads().collect();
+		let keyframe = self.depacketizer.is_keyframe(&payloads);
+		let packets: Vec<(Wrap16, &[u8])> = frame
+			.packets
+			.iter()
+			.map(|p| (p.sequence, &p.payload[..]))
+			.collect();
+
+		if let Some(data) = self.depacketizer.depacketize_sequenced(&packets) {
+			self.ready.push_back(DepacketizedFrame {
+				timestamp: frame.timestamp,
+				data,
+				keyframe,
+				complete: frame.complete,
+			});
+		}
+	}
+}
+
+#[cfg(feature = "jitter")]
+impl<D: Depacketizer> RtpDepacketizer<D> {
+	/// Adds an item released by a [`JitterBuffer`] at `now`.
+	///
+	/// [`JitterBuffer`]: ../../jitter/struct.JitterBuffer.html
+	pub fn push_playout(&mut self, item: crate::jitter::Playout, now: Duration) {
+		match item {
+			crate::jitter::Playout::Packet(packet) => self.push(packet, now),
+			crate::jitter::Playout::Missing(_) => self.lost(),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtp::RtpType;
+
+	struct PerPacket;
+
+	impl Depacketizer for PerPacket {
+		fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+			Some(payloads.concat())
+		}
+
+		fn frame_per_packet(&self) -> bool {
+			true
+		}
+	}
+
+	fn packet(sequence: u16) -> Rtp {
+		Rtp {
+			version: 2,
+			padding: 0,
+			extension: 0,
+			csrc_count: 0,
+			marker: 0,
+			payload_type: RtpType::Dynamic(96),
+			sequence: sequence.into(),
+			timestamp: (u32::from(sequence) * 960).into(),
+			ssrc: 1,
+			csrc_list: Vec::new(),
+			payload: alloc::vec![0; 4],
+		}
+	}
+
+	#[test]
+	fn frame_per_packet_detects_sequence_gap() {
+		let mut depack = RtpDepacketizer::new(PerPacket, Duration::from_millis(100));
+		for seq in [65_534, 65_535, 0, 2, 3] {
+			depack.push(packet(seq), Duration::ZERO);
+		}
+
+		let complete: Vec<bool> = core::iter::from_fn(|| depack.pop())
+			.map(|f| f.complete)
+			.collect();
+		assert_eq!(complete, [true, true, true, false, true]);
+	}
+}
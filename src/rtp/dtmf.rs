@@ -0,0 +1,254 @@
+//! Telephone events, such as DTMF digits, sent in place of their audio.
+//!
+//! See [RFC 4733](https://tools.ietf.org/html/rfc4733).
+
+use crate::{error::impl_new_checked, wrap::Wrap32};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::types::{u1, u16be, u6};
+
+/// Number of times the final packet of an event is sent, to survive loss.
+pub const END_REPEATS: u8 = 3;
+
+/// Event code of the DTMF hook flash.
+pub const FLASH: u8 = 16;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Payload of a `telephone-event` RTP packet.
+///
+/// A description of fields:
+///
+/// ## `event`
+/// Event code. DTMF digits `0`--`9` are codes `0`--`9`, `*` and `#` are `10` and
+/// `11`, `A`--`D` are `12`--`15`, and the hook flash is `16`. See
+/// [`event_code`] and [`event_char`].
+///
+/// ## `end`
+/// Set in the final packet(s) of an event.
+///
+/// ## `reserved`
+/// Must be `0`.
+///
+/// ## `volume`
+/// Power level of the tone, as a magnitude in dBm0 from `0` (loudest) to `63`.
+///
+/// ## `duration`
+/// Length of the event so far, in timestamp units, measured from the packet's
+/// RTP timestamp.
+///
+/// ## `payload`
+/// Remainder of the payload, such as further events combined by RED.
+///
+/// [`event_code`]: fn.event_code.html
+/// [`event_char`]: fn.event_char.html
+pub struct TelephoneEvent {
+	pub event: u8,
+
+	pub end: u1,
+
+	pub reserved: u1,
+
+	pub volume: u6,
+
+	pub duration: u16be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	TelephoneEventPacket,
+	MutableTelephoneEventPacket,
+	[
+		"event" => 1,
+		"end" => 2,
+		"reserved" => 2,
+		"volume" => 2,
+		"duration" => 4,
+	]
+);
+
+impl TelephoneEvent {
+	/// Creates a telephone event of code `event` at `volume`, which has lasted for
+	/// `duration` timestamp units.
+	///
+	/// `volume` is clamped to `63`.
+	#[must_use]
+	pub fn new(event: u8, volume: u8, duration: u16, end: bool) -> Self {
+		Self {
+			event,
+			end: u8::from(end),
+			reserved: 0,
+			volume: volume.min(63),
+			duration,
+			payload: Vec::new(),
+		}
+	}
+
+	/// Serialises this event into its 4-byte wire format.
+	#[must_use]
+	pub fn to_bytes(&self) -> [u8; 4] {
+		let duration = self.duration.to_be_bytes();
+
+		[
+			self.event,
+			(self.end << 7) | (self.reserved << 6) | (self.volume & 0x3f),
+			duration[0],
+			duration[1],
+		]
+	}
+}
+
+/// Returns the event code of a DTMF digit (`0`--`9`, `*`, `#`, or `A`--`D`).
+#[must_use]
+pub fn event_code(digit: char) -> Option<u8> {
+	match digit.to_ascii_uppercase() {
+		#[allow(clippy::cast_possible_truncation)]
+		c @ '0'..='9' => Some(c as u8 - b'0'),
+		'*' => Some(10),
+		'#' => Some(11),
+		#[allow(clippy::cast_possible_truncation)]
+		c @ 'A'..='D' => Some(c as u8 - b'A' + 12),
+		_ => None,
+	}
+}
+
+/// Returns the DTMF digit of an event code, if it is one.
+#[must_use]
+pub fn event_char(event: u8) -> Option<char> {
+	match event {
+		0..=9 => Some(char::from(b'0' + event)),
+		10 => Some('*'),
+		11 => Some('#'),
+		12..=15 => Some(char::from(b'A' + event - 12)),
+		_ => None,
+	}
+}
+
+/// A telephone event packet produced by a [`DtmfSender`], to be sent with the
+/// given RTP marker bit and timestamp.
+///
+/// [`DtmfSender`]: struct.DtmfSender.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DtmfOutput {
+	/// Whether the RTP marker bit should be set, as it is on the first packet of
+	/// an event.
+	pub marker: bool,
+	/// RTP timestamp of the packet, which is the start of the event (or of the
+	/// current segment of a long event).
+	pub timestamp: Wrap32,
+	/// Payload of the packet.
+	pub event: TelephoneEvent,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum State {
+	Idle,
+	Starting,
+	Playing,
+	Ending(u8),
+}
+
+/// State machine producing the packet pattern of a telephone event.
+///
+/// A sender emits one packet with the marker bit set when an event starts,
+/// interim packets with growing durations while it continues, and
+/// [`END_REPEATS`] identical packets with the `end` bit set once it stops. Each
+/// packet of an event carries the same timestamp. Events longer than the
+/// duration field allows are split into segments, each with a new timestamp.
+///
+/// Call [`next_packet`] once every packet interval to drive the sender.
+///
+/// [`END_REPEATS`]: constant.END_REPEATS.html
+/// [`next_packet`]: #method.next_packet
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DtmfSender {
+	interval: u16,
+	state: State,
+	event: u8,
+	volume: u8,
+	timestamp: Wrap32,
+	duration: u16,
+}
+
+impl DtmfSender {
+	/// Creates an idle sender, emitting one packet every `interval` timestamp units
+	/// (*e.g.*, `400` for 50ms at 8kHz).
+	#[must_use]
+	pub fn new(interval: u16) -> Self {
+		Self {
+			interval: interval.max(1),
+			state: State::Idle,
+			event: 0,
+			volume: 0,
+			timestamp: Wrap32::new(0),
+			duration: 0,
+		}
+	}
+
+	/// Begins sending `event` at `volume`, starting at RTP timestamp `timestamp`.
+	///
+	/// Any event in progress is abandoned without its end packets.
+	pub fn start(&mut self, event: u8, volume: u8, timestamp: Wrap32) {
+		self.state = State::Starting;
+		self.event = event;
+		self.volume = volume.min(63);
+		self.timestamp = timestamp;
+		self.duration = 0;
+	}
+
+	/// Ends the current event, so that the following packets carry the `end` bit.
+	///
+	/// Has no effect if no event is in progress.
+	pub fn stop(&mut self) {
+		if matches!(self.state, State::Starting | State::Playing) {
+			self.state = State::Ending(END_REPEATS);
+		}
+	}
+
+	/// Returns whether the sender has packets left to send.
+	#[must_use]
+	pub fn is_active(&self) -> bool {
+		self.state != State::Idle
+	}
+
+	/// Returns the packet to be sent for the current packet interval, if any.
+	pub fn next_packet(&mut self) -> Option<DtmfOutput> {
+		let marker = match self.state {
+			State::Idle => return None,
+			State::Starting => {
+				self.state = State::Playing;
+				true
+			},
+			State::Playing => false,
+			State::Ending(left) => {
+				self.state = if left > 1 {
+					State::Ending(left - 1)
+				} else {
+					State::Idle
+				};
+
+				return Some(self.output(false, true));
+			},
+		};
+
+		let Some(duration) = self.duration.checked_add(self.interval) else {
+			// Begin a new segment of a long-lasting event.
+			self.timestamp += u32::from(self.duration);
+			self.duration = self.interval;
+			return Some(self.output(marker, false));
+		};
+		self.duration = duration;
+
+		Some(self.output(marker, false))
+	}
+
+	fn output(&self, marker: bool, end: bool) -> DtmfOutput {
+		DtmfOutput {
+			marker,
+			timestamp: self.timestamp,
+			event: TelephoneEvent::new(self.event, self.volume, self.duration, end),
+		}
+	}
+}
@@ -0,0 +1,71 @@
+use super::{ExtensionUri, HeaderExtension};
+
+/// Client-to-mixer audio level of an RTP packet's payload.
+///
+/// See [RFC 6464](https://tools.ietf.org/html/rfc6464).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AudioLevel {
+	/// Whether the sender believes this packet contains voice activity.
+	///
+	/// Senders which do not perform voice activity detection may leave this unset.
+	pub voice: bool,
+	/// Audio level in -dBov, from `0` (loudest) to `127` (silence or quieter).
+	pub level: u8,
+}
+
+impl AudioLevel {
+	/// Level used to signal digital silence.
+	pub const SILENCE: u8 = 127;
+
+	/// Creates an audio level from its voice activity flag and -dBov level.
+	///
+	/// `level` is clamped to [`SILENCE`].
+	///
+	/// [`SILENCE`]: #associatedconstant.SILENCE
+	#[must_use]
+	pub fn new(voice: bool, level: u8) -> Self {
+		Self {
+			voice,
+			level: level.min(Self::SILENCE),
+		}
+	}
+
+	/// Returns the audio level in dBov, from `0` down to `-127`.
+	#[must_use]
+	pub fn dbov(self) -> i8 {
+		#[allow(clippy::cast_possible_wrap)]
+		-(self.level.min(Self::SILENCE) as i8)
+	}
+
+	/// Decodes an audio level from its single-byte encoding.
+	#[must_use]
+	pub fn from_byte(byte: u8) -> Self {
+		Self {
+			voice: byte & 0x80 != 0,
+			level: byte & 0x7f,
+		}
+	}
+
+	/// Returns the single-byte encoding of this audio level.
+	#[must_use]
+	pub fn to_byte(self) -> u8 {
+		(u8::from(self.voice) << 7) | self.level.min(Self::SILENCE)
+	}
+}
+
+impl HeaderExtension for AudioLevel {
+	const URI: ExtensionUri = ExtensionUri::AudioLevel;
+
+	fn parse(data: &[u8]) -> Option<Self> {
+		data.first().copied().map(Self::from_byte)
+	}
+
+	fn wire_size(&self) -> usize {
+		1
+	}
+
+	fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		*buf.first_mut()? = self.to_byte();
+		Some(1)
+	}
+}
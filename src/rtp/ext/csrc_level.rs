@@ -0,0 +1,86 @@
+use super::{audio_level::AudioLevel, ExtensionUri, HeaderExtension};
+use crate::rtp::RtpPacket;
+use alloc::vec::Vec;
+
+/// Mixer-to-client audio levels of each contributing source in an RTP packet.
+///
+/// See [RFC 6465](https://tools.ietf.org/html/rfc6465).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct CsrcAudioLevels {
+	/// Audio level of each contributing source in -dBov, from `0` (loudest) to
+	/// `127` (silence or quieter).
+	///
+	/// These are listed in the same order as the packet's `csrc_list`.
+	pub levels: Vec<u8>,
+}
+
+impl CsrcAudioLevels {
+	/// Creates a set of audio levels from -dBov values in CSRC order.
+	///
+	/// Each level is clamped to [`AudioLevel::SILENCE`].
+	///
+	/// [`AudioLevel::SILENCE`]: struct.AudioLevel.html#associatedconstant.SILENCE
+	#[must_use]
+	pub fn new(levels: &[u8]) -> Self {
+		Self {
+			levels: levels
+				.iter()
+				.map(|l| (*l).min(AudioLevel::SILENCE))
+				.collect(),
+		}
+	}
+
+	/// Pairs each entry of `pkt`'s `csrc_list` with its audio level.
+	///
+	/// Iteration stops at the shorter of the two lists.
+	pub fn zip_csrcs<'a>(&'a self, pkt: &RtpPacket<'_>) -> impl Iterator<Item = (u32, u8)> + 'a {
+		pkt.get_csrc_list()
+			.into_iter()
+			.zip(self.levels.iter().copied())
+	}
+}
+
+impl HeaderExtension for CsrcAudioLevels {
+	const URI: ExtensionUri = ExtensionUri::CsrcAudioLevel;
+
+	fn parse(data: &[u8]) -> Option<Self> {
+		Some(Self {
+			levels: data.iter().map(|l| l & 0x7f).collect(),
+		})
+	}
+
+	fn wire_size(&self) -> usize {
+		self.levels.len()
+	}
+
+	fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let out = buf.get_mut(..self.levels.len())?;
+		for (o, l) in out.iter_mut().zip(&self.levels) {
+			*o = l & 0x7f;
+		}
+
+		Some(out.len())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtp::{RtpPacketBuilder, RtpType};
+	use alloc::vec;
+
+	#[test]
+	fn zip_csrcs_pairs_levels() {
+		let buf = RtpPacketBuilder::new(RtpType::Dynamic(96), 1)
+			.csrcs(&[11, 22, 33])
+			.payload(&[0; 4])
+			.to_vec();
+		let pkt = RtpPacket::new(&buf).unwrap();
+
+		let levels = CsrcAudioLevels::new(&[5, 200]);
+		assert_eq!(
+			levels.zip_csrcs(&pkt).collect::<Vec<_>>(),
+			vec![(11, 5), (22, AudioLevel::SILENCE)]
+		);
+	}
+}
@@ -0,0 +1,589 @@
+use super::{ExtensionUri, HeaderExtension};
+use crate::rtp::bits::{BitReader, BitWriter};
+use alloc::vec::Vec;
+
+/// Frame dependency information of an AV1 (or other scalable codec) RTP packet,
+/// allowing an SFU to forward selected layers without parsing the payload.
+///
+/// Most descriptors refer to a frame template from a [`DependencyStructure`] sent
+/// in an earlier key frame's descriptor, so decoding requires that receivers keep
+/// hold of the latest structure seen on each stream. See
+/// [`parse_with`](#method.parse_with) and [`resolve`](#method.resolve).
+///
+/// See the [AV1 RTP specification](https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DependencyDescriptor {
+	/// Whether this packet holds the first byte of a frame.
+	pub start_of_frame: bool,
+	/// Whether this packet holds the last byte of a frame.
+	pub end_of_frame: bool,
+	/// ID of the frame template describing this frame, from `0` to `63`.
+	pub template_id: u8,
+	/// Frame number, incrementing by `1` for each frame.
+	pub frame_number: u16,
+	/// New dependency structure, typically sent alongside key frames.
+	pub structure: Option<DependencyStructure>,
+	/// Bitmask of decode targets still being produced by the sender, where bit `n`
+	/// corresponds to decode target `n`.
+	///
+	/// If absent, the previous mask (or all targets of a new `structure`) remains in effect.
+	pub active_decode_targets: Option<u32>,
+	/// Per-decode-target indications overriding those of the frame template.
+	pub custom_dtis: Option<Vec<DecodeTargetIndication>>,
+	/// Frame number differences to referenced frames, overriding those of the frame template.
+	pub custom_fdiffs: Option<Vec<u16>>,
+	/// Frame number differences to the previous frame in each chain, overriding
+	/// those of the frame template.
+	pub custom_chain_fdiffs: Option<Vec<u8>>,
+}
+
+/// Set of frame templates and decode targets shared by all frames of a stream
+/// until the next key frame.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DependencyStructure {
+	/// Template ID of the first entry in `templates`, from `0` to `63`.
+	pub template_id_offset: u8,
+	/// Number of decode targets, from `1` to `32`.
+	pub decode_target_count: u8,
+	/// Frame templates, in order of template ID.
+	pub templates: Vec<FrameTemplate>,
+	/// Chain protecting each decode target.
+	///
+	/// This is empty if the stream uses no chains, and otherwise holds one entry
+	/// per decode target.
+	pub decode_target_protected_by: Vec<u8>,
+	/// Number of chains, from `0` to `decode_target_count`.
+	pub chain_count: u8,
+	/// Render resolution of each spatial layer, if signalled.
+	pub resolutions: Option<Vec<RenderResolution>>,
+}
+
+/// Frame dependency information shared by many frames of a stream.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FrameTemplate {
+	/// Spatial layer ID of frames using this template.
+	pub spatial_id: u8,
+	/// Temporal layer ID of frames using this template.
+	pub temporal_id: u8,
+	/// Indication for each decode target.
+	pub dtis: Vec<DecodeTargetIndication>,
+	/// Frame number differences to referenced frames, from `1` to `16`.
+	pub fdiffs: Vec<u8>,
+	/// Frame number differences to the previous frame in each chain, from `0` to `15`.
+	pub chain_fdiffs: Vec<u8>,
+}
+
+/// Display resolution of a spatial layer.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RenderResolution {
+	/// Width in pixels, from `1` to `65536`.
+	pub width: u32,
+	/// Height in pixels, from `1` to `65536`.
+	pub height: u32,
+}
+
+/// Relationship between a frame and a decode target.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum DecodeTargetIndication {
+	/// The frame is not part of the decode target.
+	///
+	/// Code 0.
+	#[default]
+	NotPresent,
+	/// The frame is part of the decode target, but no later frame in it depends on this one.
+	///
+	/// Code 1.
+	Discardable,
+	/// The frame is part of the decode target, and forwarding can switch to the
+	/// decode target from this frame.
+	///
+	/// Code 2.
+	Switch,
+	/// The frame is part of the decode target, and later frames may depend on it.
+	///
+	/// Code 3.
+	Required,
+}
+
+/// Fully-resolved dependency information of a single frame, combining a
+/// descriptor with its frame template.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FrameDependency {
+	/// Spatial layer ID of this frame.
+	pub spatial_id: u8,
+	/// Temporal layer ID of this frame.
+	pub temporal_id: u8,
+	/// Indication for each decode target.
+	pub dtis: Vec<DecodeTargetIndication>,
+	/// Frame number differences to referenced frames.
+	pub fdiffs: Vec<u16>,
+	/// Frame number differences to the previous frame in each chain.
+	pub chain_fdiffs: Vec<u8>,
+}
+
+impl DecodeTargetIndication {
+	fn new(val: u32) -> Self {
+		match val & 0b11 {
+			0 => Self::NotPresent,
+			1 => Self::Discardable,
+			2 => Self::Switch,
+			_ => Self::Required,
+		}
+	}
+
+	fn code(self) -> u32 {
+		match self {
+			Self::NotPresent => 0,
+			Self::Discardable => 1,
+			Self::Switch => 2,
+			Self::Required => 3,
+		}
+	}
+}
+
+impl DependencyDescriptor {
+	/// Parses a dependency descriptor from the body of an element, using `latest`
+	/// as the most recent structure seen on this stream.
+	///
+	/// A structure carried by the descriptor itself takes precedence over `latest`.
+	/// Returns `None` if the descriptor is truncated, or needs a structure which is
+	/// unavailable or does not contain its frame template.
+	#[must_use]
+	pub fn parse_with(data: &[u8], latest: Option<&DependencyStructure>) -> Option<Self> {
+		let mut r = BitReader::new(data);
+
+		let mut out = Self {
+			start_of_frame: r.flag()?,
+			end_of_frame: r.flag()?,
+			template_id: r.read_u8(6)?,
+			#[allow(clippy::cast_possible_truncation)]
+			frame_number: r.read(16)? as u16,
+			..Default::default()
+		};
+
+		if data.len() <= 3 {
+			return Some(out);
+		}
+
+		let structure_present = r.flag()?;
+		let active_present = r.flag()?;
+		let custom_dtis = r.flag()?;
+		let custom_fdiffs = r.flag()?;
+		let custom_chains = r.flag()?;
+
+		if structure_present {
+			out.structure = Some(DependencyStructure::read(&mut r)?);
+		}
+
+		let structure = out.structure.as_ref().or(latest)?;
+		let dt_count = structure.decode_target_count;
+
+		structure.template(out.template_id)?;
+
+		if active_present {
+			out.active_decode_targets = Some(r.read(dt_count)?);
+		}
+
+		if custom_dtis {
+			out.custom_dtis = Some(
+				(0..dt_count)
+					.map(|_| r.read(2).map(DecodeTargetIndication::new))
+					.collect::<Option<_>>()?,
+			);
+		}
+
+		if custom_fdiffs {
+			let mut fdiffs = Vec::new();
+			loop {
+				let size = r.read_u8(2)?;
+				if size == 0 {
+					break;
+				}
+				#[allow(clippy::cast_possible_truncation)]
+				fdiffs.push(r.read(4 * size)? as u16 + 1);
+			}
+			out.custom_fdiffs = Some(fdiffs);
+		}
+
+		if custom_chains {
+			out.custom_chain_fdiffs = Some(
+				(0..structure.chain_count)
+					.map(|_| r.read_u8(8))
+					.collect::<Option<_>>()?,
+			);
+		}
+
+		Some(out)
+	}
+
+	/// Combines this descriptor with its frame template from `latest`, the most
+	/// recent structure seen on this stream.
+	///
+	/// A structure carried by the descriptor itself takes precedence over `latest`.
+	/// Returns `None` if no structure is available, or it does not contain this
+	/// frame's template.
+	#[must_use]
+	pub fn resolve(&self, latest: Option<&DependencyStructure>) -> Option<FrameDependency> {
+		let template = self
+			.structure
+			.as_ref()
+			.or(latest)?
+			.template(self.template_id)?;
+
+		Some(FrameDependency {
+			spatial_id: template.spatial_id,
+			temporal_id: template.temporal_id,
+			dtis: self
+				.custom_dtis
+				.clone()
+				.unwrap_or_else(|| template.dtis.clone()),
+			fdiffs: self
+				.custom_fdiffs
+				.clone()
+				.unwrap_or_else(|| template.fdiffs.iter().map(|&f| u16::from(f)).collect()),
+			chain_fdiffs: self
+				.custom_chain_fdiffs
+				.clone()
+				.unwrap_or_else(|| template.chain_fdiffs.clone()),
+		})
+	}
+
+	/// Returns the body of this descriptor's element, using `latest` as the most
+	/// recent structure on this stream.
+	///
+	/// Returns `None` if a structure is needed to encode the active decode target
+	/// mask or custom fields, but none is available.
+	#[must_use]
+	pub fn encode_with(&self, latest: Option<&DependencyStructure>) -> Option<Vec<u8>> {
+		let mut w = BitWriter::default();
+
+		w.flag(self.start_of_frame);
+		w.flag(self.end_of_frame);
+		w.write(u32::from(self.template_id), 6);
+		w.write(u32::from(self.frame_number), 16);
+
+		let extended = self.structure.is_some()
+			|| self.active_decode_targets.is_some()
+			|| self.custom_dtis.is_some()
+			|| self.custom_fdiffs.is_some()
+			|| self.custom_chain_fdiffs.is_some();
+
+		if !extended {
+			return Some(w.finish());
+		}
+
+		w.flag(self.structure.is_some());
+		w.flag(self.active_decode_targets.is_some());
+		w.flag(self.custom_dtis.is_some());
+		w.flag(self.custom_fdiffs.is_some());
+		w.flag(self.custom_chain_fdiffs.is_some());
+
+		if let Some(structure) = &self.structure {
+			structure.write(&mut w)?;
+		}
+
+		let structure = self.structure.as_ref().or(latest)?;
+
+		if let Some(mask) = self.active_decode_targets {
+			w.write(mask, structure.decode_target_count);
+		}
+
+		if let Some(dtis) = &self.custom_dtis {
+			if dtis.len() != usize::from(structure.decode_target_count) {
+				return None;
+			}
+			for dti in dtis {
+				w.write(dti.code(), 2);
+			}
+		}
+
+		if let Some(fdiffs) = &self.custom_fdiffs {
+			for &fdiff in fdiffs {
+				let val = u32::from(fdiff.checked_sub(1)?);
+				let size = match val {
+					0..=0xf => 1,
+					0x10..=0xff => 2,
+					0x100..=0xfff => 3,
+					_ => return None,
+				};
+				w.write(size, 2);
+				#[allow(clippy::cast_possible_truncation)]
+				w.write(val, 4 * size as u8);
+			}
+			w.write(0, 2);
+		}
+
+		if let Some(chains) = &self.custom_chain_fdiffs {
+			if chains.len() != usize::from(structure.chain_count) {
+				return None;
+			}
+			for &fdiff in chains {
+				w.write(u32::from(fdiff), 8);
+			}
+		}
+
+		Some(w.finish())
+	}
+
+	/// Returns whether decode target `target` is active, using `latest_mask` as the
+	/// most recent active decode target mask seen on this stream.
+	///
+	/// All targets are considered active if no mask has been seen.
+	#[must_use]
+	pub fn is_target_active(&self, target: u8, latest_mask: Option<u32>) -> bool {
+		let mask = self.active_decode_targets.or(latest_mask).or_else(|| {
+			self.structure
+				.as_ref()
+				.map(DependencyStructure::all_targets)
+		});
+
+		target < 32 && mask.map_or(true, |m| m & (1 << target) != 0)
+	}
+}
+
+impl HeaderExtension for DependencyDescriptor {
+	const URI: ExtensionUri = ExtensionUri::DependencyDescriptor;
+
+	/// Parses a descriptor which is either mandatory fields only, or carries its
+	/// own structure.
+	///
+	/// Use [`DependencyDescriptor::parse_with`] for descriptors referring to an
+	/// earlier structure.
+	///
+	/// [`DependencyDescriptor::parse_with`]: struct.DependencyDescriptor.html#method.parse_with
+	fn parse(data: &[u8]) -> Option<Self> {
+		Self::parse_with(data, None)
+	}
+
+	fn wire_size(&self) -> usize {
+		self.encode_with(None).map_or(0, |v| v.len())
+	}
+
+	fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let data = self.encode_with(None)?;
+		buf.get_mut(..data.len())?.copy_from_slice(&data);
+
+		Some(data.len())
+	}
+}
+
+impl DependencyStructure {
+	/// Returns the frame template with the given `template_id`.
+	#[must_use]
+	pub fn template(&self, template_id: u8) -> Option<&FrameTemplate> {
+		let idx = (usize::from(template_id) + 64 - usize::from(self.template_id_offset)) % 64;
+		self.templates.get(idx)
+	}
+
+	/// Returns the highest spatial and temporal layer IDs which make up decode
+	/// target `target`.
+	#[must_use]
+	pub fn decode_target_layers(&self, target: u8) -> Option<(u8, u8)> {
+		if target >= self.decode_target_count {
+			return None;
+		}
+
+		Some(
+			self.templates
+				.iter()
+				.filter(|t| {
+					t.dtis
+						.get(usize::from(target))
+						.map_or(false, |d| *d != DecodeTargetIndication::NotPresent)
+				})
+				.fold((0, 0), |(s, t), tmpl| {
+					(s.max(tmpl.spatial_id), t.max(tmpl.temporal_id))
+				}),
+		)
+	}
+
+	/// Returns the active decode target mask with every target of this structure set.
+	#[must_use]
+	pub fn all_targets(&self) -> u32 {
+		u32::MAX >> (32 - u32::from(self.decode_target_count.clamp(1, 32)))
+	}
+
+	fn read(r: &mut BitReader<'_>) -> Option<Self> {
+		let mut out = Self {
+			template_id_offset: r.read_u8(6)?,
+			decode_target_count: r.read_u8(5)? + 1,
+			..Default::default()
+		};
+		let dt_count = out.decode_target_count;
+
+		let (mut spatial_id, mut temporal_id) = (0, 0);
+		loop {
+			out.templates.push(FrameTemplate {
+				spatial_id,
+				temporal_id,
+				..Default::default()
+			});
+
+			match r.read(2)? {
+				1 => temporal_id += 1,
+				2 => {
+					temporal_id = 0;
+					spatial_id += 1;
+				},
+				3 => break,
+				_ => {},
+			}
+
+			if out.templates.len() >= 64 {
+				return None;
+			}
+		}
+
+		for t in &mut out.templates {
+			t.dtis = (0..dt_count)
+				.map(|_| r.read(2).map(DecodeTargetIndication::new))
+				.collect::<Option<_>>()?;
+		}
+
+		for t in &mut out.templates {
+			while r.flag()? {
+				t.fdiffs.push(r.read_u8(4)? + 1);
+			}
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		let chain_count = r.ns(u32::from(dt_count) + 1)? as u8;
+		out.chain_count = chain_count;
+		if chain_count != 0 {
+			out.decode_target_protected_by = (0..dt_count)
+				.map(|_| {
+					r.ns(u32::from(chain_count))
+						.and_then(|v| u8::try_from(v).ok())
+				})
+				.collect::<Option<_>>()?;
+
+			for t in &mut out.templates {
+				t.chain_fdiffs = (0..chain_count)
+					.map(|_| r.read_u8(4))
+					.collect::<Option<_>>()?;
+			}
+		}
+
+		if r.flag()? {
+			let layers = out
+				.templates
+				.iter()
+				.map(|t| t.spatial_id)
+				.max()
+				.unwrap_or(0);
+			out.resolutions = Some(
+				(0..=layers)
+					.map(|_| {
+						Some(RenderResolution {
+							width: r.read(16)? + 1,
+							height: r.read(16)? + 1,
+						})
+					})
+					.collect::<Option<_>>()?,
+			);
+		}
+
+		Some(out)
+	}
+
+	fn write(&self, w: &mut BitWriter) -> Option<()> {
+		let dt_count = self.decode_target_count;
+		if !(1..=32).contains(&dt_count) || self.templates.is_empty() || self.templates.len() > 64 {
+			return None;
+		}
+
+		w.write(u32::from(self.template_id_offset), 6);
+		w.write(u32::from(dt_count - 1), 5);
+
+		for pair in self.templates.windows(2) {
+			let (prev, next) = (&pair[0], &pair[1]);
+			let idc = if next.spatial_id == prev.spatial_id && next.temporal_id == prev.temporal_id
+			{
+				0
+			} else if next.spatial_id == prev.spatial_id && next.temporal_id == prev.temporal_id + 1
+			{
+				1
+			} else if next.spatial_id == prev.spatial_id + 1 && next.temporal_id == 0 {
+				2
+			} else {
+				return None;
+			};
+			w.write(idc, 2);
+		}
+		w.write(3, 2);
+
+		for t in &self.templates {
+			if t.dtis.len() != usize::from(dt_count) {
+				return None;
+			}
+			for dti in &t.dtis {
+				w.write(dti.code(), 2);
+			}
+		}
+
+		for t in &self.templates {
+			for &fdiff in &t.fdiffs {
+				if !(1..=16).contains(&fdiff) {
+					return None;
+				}
+				w.flag(true);
+				w.write(u32::from(fdiff - 1), 4);
+			}
+			w.flag(false);
+		}
+
+		if self.chain_count > dt_count {
+			return None;
+		}
+		w.ns(u32::from(dt_count) + 1, u32::from(self.chain_count));
+		if self.chain_count != 0 {
+			if self.decode_target_protected_by.len() != usize::from(dt_count) {
+				return None;
+			}
+			for &chain in &self.decode_target_protected_by {
+				if chain >= self.chain_count {
+					return None;
+				}
+				w.ns(u32::from(self.chain_count), u32::from(chain));
+			}
+
+			for t in &self.templates {
+				if t.chain_fdiffs.len() != usize::from(self.chain_count) {
+					return None;
+				}
+				for &fdiff in &t.chain_fdiffs {
+					w.write(u32::from(fdiff), 4);
+				}
+			}
+		}
+
+		w.flag(self.resolutions.is_some());
+		if let Some(resolutions) = &self.resolutions {
+			let layers = self
+				.templates
+				.iter()
+				.map(|t| t.spatial_id)
+				.max()
+				.unwrap_or(0);
+			if resolutions.len() != usize::from(layers) + 1 {
+				return None;
+			}
+			for res in resolutions {
+				w.write(res.width.checked_sub(1).filter(|v| *v <= 0xffff)?, 16);
+				w.write(res.height.checked_sub(1).filter(|v| *v <= 0xffff)?, 16);
+			}
+		}
+
+		Some(())
+	}
+}
+
+impl FrameDependency {
+	/// Returns whether this frame is part of decode target `target`.
+	#[must_use]
+	pub fn is_part_of(&self, target: u8) -> bool {
+		self.dtis
+			.get(usize::from(target))
+			.map_or(false, |d| *d != DecodeTargetIndication::NotPresent)
+	}
+}
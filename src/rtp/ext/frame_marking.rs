@@ -0,0 +1,103 @@
+use super::{ExtensionUri, HeaderExtension};
+
+/// Codec-independent frame boundary and dependency information of an RTP packet,
+/// allowing forwarding decisions without parsing the payload.
+///
+/// See [RFC 9626](https://tools.ietf.org/html/rfc9626).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct FrameMarking {
+	/// Whether this packet begins a frame.
+	pub start: bool,
+	/// Whether this packet ends a frame.
+	pub end: bool,
+	/// Whether this frame can be decoded without reference to any previous frame.
+	pub independent: bool,
+	/// Whether this frame can be dropped without affecting any other frame.
+	pub discardable: bool,
+	/// Layer information, present for scalable streams.
+	pub layer: Option<FrameLayer>,
+}
+
+/// Scalability layer information of a [`FrameMarking`].
+///
+/// [`FrameMarking`]: struct.FrameMarking.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FrameLayer {
+	/// Whether this frame depends only on the base temporal layer, allowing a
+	/// switch up to this frame's temporal layer.
+	pub base_layer_sync: bool,
+	/// Temporal layer ID, from `0` to `7`.
+	pub temporal_id: u8,
+	/// Codec-specific spatial and quality layer ID.
+	pub layer_id: u8,
+	/// Running index of base temporal layer frames, as sent by implementations of
+	/// earlier drafts.
+	pub tl0_pic_idx: Option<u8>,
+}
+
+impl FrameMarking {
+	/// Returns whether this packet may be dropped when forwarding to a receiver
+	/// which only wants temporal layers up to and including `max_temporal_id`.
+	///
+	/// Non-scalable streams are never dropped on this basis.
+	#[must_use]
+	pub fn exceeds_temporal_layer(&self, max_temporal_id: u8) -> bool {
+		self.layer
+			.map_or(false, |layer| layer.temporal_id > max_temporal_id)
+	}
+}
+
+impl HeaderExtension for FrameMarking {
+	const URI: ExtensionUri = ExtensionUri::FrameMarking;
+
+	fn parse(data: &[u8]) -> Option<Self> {
+		let (&flags, rest) = data.split_first()?;
+
+		let layer = rest.first().map(|&layer_id| FrameLayer {
+			base_layer_sync: flags & 0x08 != 0,
+			temporal_id: flags & 0x07,
+			layer_id,
+			tl0_pic_idx: rest.get(1).copied(),
+		});
+
+		Some(Self {
+			start: flags & 0x80 != 0,
+			end: flags & 0x40 != 0,
+			independent: flags & 0x20 != 0,
+			discardable: flags & 0x10 != 0,
+			layer,
+		})
+	}
+
+	fn wire_size(&self) -> usize {
+		match self.layer {
+			None => 1,
+			Some(FrameLayer {
+				tl0_pic_idx: None, ..
+			}) => 2,
+			Some(_) => 3,
+		}
+	}
+
+	fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let len = self.wire_size();
+		let out = buf.get_mut(..len)?;
+
+		out[0] = (u8::from(self.start) << 7)
+			| (u8::from(self.end) << 6)
+			| (u8::from(self.independent) << 5)
+			| (u8::from(self.discardable) << 4);
+
+		if let Some(layer) = self.layer {
+			out[0] |= (u8::from(layer.base_layer_sync) << 3) | (layer.temporal_id & 0x07);
+			out[1] = layer.layer_id;
+
+			if let Some(idx) = layer.tl0_pic_idx {
+				out[2] = idx;
+			}
+		}
+
+		Some(len)
+	}
+}
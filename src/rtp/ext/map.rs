@@ -0,0 +1,192 @@
+use super::{elements, HeaderExtension};
+use alloc::vec::Vec;
+
+/// Well-known RTP header extensions, as named by their SDP `a=extmap` URI.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ExtensionUri {
+	/// Client-to-mixer audio level.
+	///
+	/// See [RFC 6464](https://tools.ietf.org/html/rfc6464).
+	AudioLevel,
+	/// Mixer-to-client audio levels.
+	///
+	/// See [RFC 6465](https://tools.ietf.org/html/rfc6465).
+	CsrcAudioLevel,
+	/// Transmission time offset.
+	///
+	/// See [RFC 5450](https://tools.ietf.org/html/rfc5450).
+	TransmissionOffset,
+	/// Absolute send time, as used by WebRTC congestion control.
+	AbsoluteSendTime,
+	/// Absolute capture time, as used by WebRTC for A/V sync across mixers.
+	AbsoluteCaptureTime,
+	/// Transport-wide sequence number, for transport-wide congestion control.
+	TransportWideCc,
+	/// Media identification, for BUNDLE.
+	///
+	/// See [RFC 8843](https://tools.ietf.org/html/rfc8843#section-15).
+	Mid,
+	/// RTP stream identifier.
+	///
+	/// See [RFC 8852](https://tools.ietf.org/html/rfc8852).
+	RtpStreamId,
+	/// Repaired RTP stream identifier.
+	///
+	/// See [RFC 8852](https://tools.ietf.org/html/rfc8852).
+	RepairedRtpStreamId,
+	/// Video frame marking.
+	///
+	/// See [RFC 9626](https://tools.ietf.org/html/rfc9626).
+	FrameMarking,
+	/// AV1 dependency descriptor.
+	///
+	/// See the [AV1 RTP specification](https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension).
+	DependencyDescriptor,
+	/// Coordination of video orientation.
+	///
+	/// See [3GPP TS 26.114](https://www.3gpp.org/DynaReport/26114.htm).
+	VideoOrientation,
+	/// Playout delay limits, as used by WebRTC.
+	PlayoutDelay,
+}
+
+impl ExtensionUri {
+	/// Returns the canonical URI of this extension.
+	#[must_use]
+	pub fn uri(self) -> &'static str {
+		match self {
+			Self::AudioLevel => "urn:ietf:params:rtp-hdrext:ssrc-audio-level",
+			Self::CsrcAudioLevel => "urn:ietf:params:rtp-hdrext:csrc-audio-level",
+			Self::TransmissionOffset => "urn:ietf:params:rtp-hdrext:toffset",
+			Self::AbsoluteSendTime =>
+				"http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time",
+			Self::AbsoluteCaptureTime =>
+				"http://www.webrtc.org/experiments/rtp-hdrext/abs-capture-time",
+			Self::TransportWideCc =>
+				"http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01",
+			Self::Mid => "urn:ietf:params:rtp-hdrext:sdes:mid",
+			Self::RtpStreamId => "urn:ietf:params:rtp-hdrext:sdes:rtp-stream-id",
+			Self::RepairedRtpStreamId =>
+				"urn:ietf:params:rtp-hdrext:sdes:repaired-rtp-stream-id",
+			Self::FrameMarking => "urn:ietf:params:rtp-hdrext:framemarking",
+			Self::DependencyDescriptor =>
+				"https://aomediacodec.github.io/av1-rtp-spec/#dependency-descriptor-rtp-header-extension",
+			Self::VideoOrientation => "urn:3gpp:video-orientation",
+			Self::PlayoutDelay => "http://www.webrtc.org/experiments/rtp-hdrext/playout-delay",
+		}
+	}
+
+	/// Looks up a well-known extension by its URI.
+	///
+	/// Older draft URIs still in common use (*e.g.*, for frame marking) are also
+	/// recognised.
+	#[must_use]
+	pub fn from_uri(uri: &str) -> Option<Self> {
+		const ALL: [ExtensionUri; 13] = [
+			ExtensionUri::AudioLevel,
+			ExtensionUri::CsrcAudioLevel,
+			ExtensionUri::TransmissionOffset,
+			ExtensionUri::AbsoluteSendTime,
+			ExtensionUri::AbsoluteCaptureTime,
+			ExtensionUri::TransportWideCc,
+			ExtensionUri::Mid,
+			ExtensionUri::RtpStreamId,
+			ExtensionUri::RepairedRtpStreamId,
+			ExtensionUri::FrameMarking,
+			ExtensionUri::DependencyDescriptor,
+			ExtensionUri::VideoOrientation,
+			ExtensionUri::PlayoutDelay,
+		];
+
+		match uri.trim() {
+			"http://tools.ietf.org/html/draft-ietf-avtext-framemarking-07" =>
+				Some(Self::FrameMarking),
+			uri => ALL.into_iter().find(|ext| ext.uri() == uri),
+		}
+	}
+}
+
+/// Mapping between negotiated RTP header extension IDs and the extensions they
+/// carry, as agreed by SDP `a=extmap` lines.
+///
+/// Each ID and each extension appear at most once in the map.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExtensionMap {
+	entries: Vec<(u8, ExtensionUri)>,
+}
+
+impl ExtensionMap {
+	/// Creates an empty extension map.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Binds `id` to the extension `uri`, replacing any existing binding for
+	/// either.
+	///
+	/// Returns `false` (leaving the map unchanged) if `id` is `0`, which is
+	/// reserved for padding.
+	pub fn insert(&mut self, id: u8, uri: ExtensionUri) -> bool {
+		if id == 0 {
+			return false;
+		}
+
+		self.entries
+			.retain(|&(e_id, e_uri)| e_id != id && e_uri != uri);
+		self.entries.push((id, uri));
+
+		true
+	}
+
+	/// Removes the binding for `id`, returning the extension it carried.
+	pub fn remove(&mut self, id: u8) -> Option<ExtensionUri> {
+		let idx = self.entries.iter().position(|&(e_id, _)| e_id == id)?;
+		Some(self.entries.swap_remove(idx).1)
+	}
+
+	/// Returns the ID negotiated for the extension `uri`.
+	#[must_use]
+	pub fn id(&self, uri: ExtensionUri) -> Option<u8> {
+		self.entries
+			.iter()
+			.find_map(|&(id, e_uri)| (e_uri == uri).then_some(id))
+	}
+
+	/// Returns the extension bound to `id`.
+	#[must_use]
+	pub fn uri(&self, id: u8) -> Option<ExtensionUri> {
+		self.entries
+			.iter()
+			.find_map(|&(e_id, uri)| (e_id == id).then_some(uri))
+	}
+
+	/// Returns an iterator over all `(id, extension)` bindings.
+	pub fn iter(&self) -> impl Iterator<Item = (u8, ExtensionUri)> + '_ {
+		self.entries.iter().copied()
+	}
+
+	/// Returns the raw element body of the extension `uri` in the RTP packet `pkt`.
+	#[must_use]
+	pub fn element<'a>(&self, uri: ExtensionUri, pkt: &'a [u8]) -> Option<&'a [u8]> {
+		elements(pkt)?.find(self.id(uri)?)
+	}
+
+	/// Parses the typed extension `E` from the RTP packet `pkt`.
+	#[must_use]
+	pub fn get<E: HeaderExtension>(&self, pkt: &[u8]) -> Option<E> {
+		E::parse(self.element(E::URI, pkt)?)
+	}
+
+	/// Serialises the typed extension `ext` into an `(id, data)` element, for use
+	/// with [`encode_elements`].
+	///
+	/// Returns `None` if `E` has no negotiated ID.
+	///
+	/// [`encode_elements`]: fn.encode_elements.html
+	#[must_use]
+	pub fn encode<E: HeaderExtension>(&self, ext: &E) -> Option<(u8, Vec<u8>)> {
+		Some((self.id(E::URI)?, ext.to_vec()))
+	}
+}
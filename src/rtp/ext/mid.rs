@@ -0,0 +1,51 @@
+use super::{ExtensionUri, HeaderExtension};
+use alloc::string::String;
+
+/// Media identification tag of an RTP packet, used to route packets sharing a
+/// BUNDLE transport to the correct media description.
+///
+/// See [RFC 8843](https://tools.ietf.org/html/rfc8843#section-15).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Mid {
+	/// Value of the SDP `a=mid` attribute identifying this packet's media.
+	pub id: String,
+}
+
+impl Mid {
+	/// Creates a media identification tag.
+	///
+	/// Returns `None` if `id` is empty, or longer than the 255 bytes an element can
+	/// hold. Tags of up to 16 bytes fit into the one-byte element format.
+	#[must_use]
+	pub fn new(id: &str) -> Option<Self> {
+		(1..=255)
+			.contains(&id.len())
+			.then(|| Self { id: id.into() })
+	}
+
+	/// Returns the media identification tag as a string slice.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		&self.id
+	}
+}
+
+impl HeaderExtension for Mid {
+	const URI: ExtensionUri = ExtensionUri::Mid;
+
+	fn parse(data: &[u8]) -> Option<Self> {
+		let id = core::str::from_utf8(data).ok()?.trim_end_matches('\0');
+		Self::new(id)
+	}
+
+	fn wire_size(&self) -> usize {
+		self.id.len()
+	}
+
+	fn write(&self, buf: &mut [u8]) -> Option<usize> {
+		let out = buf.get_mut(..self.id.len())?;
+		out.copy_from_slice(self.id.as_bytes());
+
+		Some(out.len())
+	}
+}
@@ -0,0 +1,305 @@
+//! RTP header extensions, and in-place editing of the extension block.
+//!
+//! The extension block itself is described by [RFC 3550], while the one- and
+//! two-byte element formats used to carry several extensions at once are defined
+//! by [RFC 8285]. Individual extensions are identified by a negotiated ID, which
+//! an [`ExtensionMap`] resolves to a well-known [`ExtensionUri`].
+//!
+//! [`ExtensionMap`]: struct.ExtensionMap.html
+//! [`ExtensionUri`]: enum.ExtensionUri.html
+//! [RFC 3550]: https://tools.ietf.org/html/rfc3550#section-5.3.1
+//! [RFC 8285]: https://tools.ietf.org/html/rfc8285
+
+mod audio_level;
+mod csrc_level;
+mod dependency;
+mod frame_marking;
+mod map;
+mod mid;
+mod rid;
+
+pub use self::{
+	audio_level::AudioLevel,
+	csrc_level::CsrcAudioLevels,
+	dependency::{
+		DecodeTargetIndication,
+		DependencyDescriptor,
+		DependencyStructure,
+		FrameDependency,
+		FrameTemplate,
+		RenderResolution,
+	},
+	frame_marking::{FrameLayer, FrameMarking},
+	map::{ExtensionMap, ExtensionUri},
+	mid::Mid,
+	rid::{RepairedRtpStreamId, RtpStreamId},
+};
+
+use super::{MutableRtpPacket, RtpPacket};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+use pnet_macros_support::packet::{MutablePacket, Packet};
+
+/// Extension profile identifier for one-byte header elements.
+pub const ONE_BYTE_PROFILE: u16 = 0xBEDE;
+
+/// Extension profile identifier for two-byte header elements, with all
+/// `appbits` unset.
+pub const TWO_BYTE_PROFILE: u16 = 0x1000;
+
+/// A typed RTP header extension, carried as a single [RFC 8285] element.
+///
+/// [RFC 8285]: https://tools.ietf.org/html/rfc8285
+pub trait HeaderExtension: Sized {
+	/// URI identifying this extension in SDP `a=extmap` lines.
+	const URI: ExtensionUri;
+
+	/// Parses this extension from the body of an element.
+	fn parse(data: &[u8]) -> Option<Self>;
+
+	/// Size of this extension's element body, in bytes.
+	fn wire_size(&self) -> usize;
+
+	/// Writes this extension's element body into `buf`, returning the number of
+	/// bytes written.
+	fn write(&self, buf: &mut [u8]) -> Option<usize>;
+
+	/// Serialises this extension's element body into a new `Vec`.
+	#[must_use]
+	fn to_vec(&self) -> Vec<u8> {
+		let mut out = vec![0u8; self.wire_size()];
+		self.write(&mut out);
+		out
+	}
+}
+
+/// Length of the fixed RTP header, excluding CSRCs and extensions.
+pub(crate) const FIXED_HEADER_LEN: usize = 12;
+
+/// Returns the offset just past the CSRC list of the RTP packet `pkt`, or `None`
+/// if the CSRC list is truncated.
+pub(crate) fn csrc_end(pkt: &[u8]) -> Option<usize> {
+	let end = FIXED_HEADER_LEN + 4 * usize::from(pkt.first()? & 0x0f);
+	(end <= pkt.len()).then_some(end)
+}
+
+/// Returns the byte range of the extension block of the RTP packet `pkt`,
+/// including its 4-byte header.
+///
+/// This is an empty range at the end of the CSRC list if the `extension` bit
+/// is unset, and `None` if the CSRC list or extension block are truncated.
+pub(crate) fn extension_range(pkt: &[u8]) -> Option<Range<usize>> {
+	let start = csrc_end(pkt)?;
+	if pkt[0] & 0x10 == 0 {
+		return Some(start..start);
+	}
+
+	let words = u16::from_be_bytes([*pkt.get(start + 2)?, *pkt.get(start + 3)?]);
+	let end = start + 4 + 4 * usize::from(words);
+
+	(end <= pkt.len()).then_some(start..end)
+}
+
+impl MutableRtpPacket<'_> {
+	/// Adds or replaces the header extension block of the first `len` bytes of this
+	/// packet, returning the new packet length.
+	///
+	/// The extension is given as its 16-bit `profile` identifier (*e.g.*, `0xBEDE`
+	/// for one-byte elements) and `data`, which is zero-padded to a 32-bit boundary.
+	/// Any payload is shifted within the underlying buffer to make room, and the
+	/// `extension` bit and block length are filled in.
+	///
+	/// Returns `None` (leaving the packet untouched) if the header is truncated,
+	/// `data` is too long for the length field, or the buffer lacks the space.
+	pub fn insert_extension(&mut self, len: usize, profile: u16, data: &[u8]) -> Option<usize> {
+		let buf = self.packet_mut();
+		let old = extension_range(buf.get(..len)?)?;
+
+		let words = u16::try_from((data.len() + 3) / 4).ok()?;
+		let new_ext_len = 4 + 4 * usize::from(words);
+		let new_len = len - old.len() + new_ext_len;
+		if new_len > buf.len() {
+			return None;
+		}
+
+		buf.copy_within(old.end..len, old.start + new_ext_len);
+
+		let ext = &mut buf[old.start..old.start + new_ext_len];
+		ext[..2].copy_from_slice(&profile.to_be_bytes());
+		ext[2..4].copy_from_slice(&words.to_be_bytes());
+		ext[4..4 + data.len()].copy_from_slice(data);
+		ext[4 + data.len()..].fill(0);
+
+		buf[0] |= 0x10;
+
+		Some(new_len)
+	}
+
+	/// Removes the header extension block from the first `len` bytes of this packet,
+	/// returning the new packet length.
+	///
+	/// Any payload is shifted back to follow the CSRC list, and the `extension` bit
+	/// is cleared. Packets without an extension are left unchanged.
+	///
+	/// Returns `None` if the header is truncated.
+	pub fn remove_extension(&mut self, len: usize) -> Option<usize> {
+		let buf = self.packet_mut();
+		let old = extension_range(buf.get(..len)?)?;
+
+		buf.copy_within(old.end..len, old.start);
+		buf[0] &= !0x10;
+
+		Some(len - old.len())
+	}
+
+	/// Returns the profile identifier and data of the header extension block within
+	/// the first `len` bytes of this packet, for rewriting in place.
+	///
+	/// Returns `None` if the `extension` bit is unset, or the header is truncated.
+	pub fn extension_data_mut(&mut self, len: usize) -> Option<(u16, &mut [u8])> {
+		let range = extension_range(self.packet().get(..len)?)?;
+		if range.is_empty() {
+			return None;
+		}
+
+		let block = &mut self.packet_mut()[range];
+		let profile = u16::from_be_bytes([block[0], block[1]]);
+
+		Some((profile, &mut block[4..]))
+	}
+}
+
+/// Iterator over the `(id, data)` pairs of an [RFC 8285] extension block.
+///
+/// Padding bytes are skipped, and iteration ends early on a malformed or
+/// truncated element.
+///
+/// [RFC 8285]: https://tools.ietf.org/html/rfc8285
+#[derive(Clone, Debug)]
+pub struct Elements<'a> {
+	data: &'a [u8],
+	two_byte: bool,
+}
+
+impl<'a> Elements<'a> {
+	/// Iterates over the elements of an extension block with the given `profile`
+	/// identifier and body.
+	///
+	/// Returns `None` if `profile` is not one of the RFC 8285 formats.
+	#[must_use]
+	pub fn new(profile: u16, data: &'a [u8]) -> Option<Self> {
+		let two_byte = match profile {
+			ONE_BYTE_PROFILE => false,
+			p if p & 0xfff0 == TWO_BYTE_PROFILE => true,
+			_ => return None,
+		};
+
+		Some(Self { data, two_byte })
+	}
+
+	/// Returns the body of the first element with the given `id`.
+	#[must_use]
+	pub fn find(mut self, id: u8) -> Option<&'a [u8]> {
+		self.find_map(|(el_id, data)| (el_id == id).then_some(data))
+	}
+}
+
+impl<'a> Iterator for Elements<'a> {
+	type Item = (u8, &'a [u8]);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		loop {
+			let (&first, rest) = self.data.split_first()?;
+
+			if first == 0 {
+				self.data = rest;
+				continue;
+			}
+
+			let (id, len, rest) = if self.two_byte {
+				let (&len, rest) = rest.split_first()?;
+				(first, usize::from(len), rest)
+			} else if first >> 4 == 15 {
+				self.data = &[];
+				return None;
+			} else {
+				(first >> 4, usize::from(first & 0x0f) + 1, rest)
+			};
+
+			if len > rest.len() {
+				self.data = &[];
+				return None;
+			}
+
+			let (body, rest) = rest.split_at(len);
+			self.data = rest;
+
+			return Some((id, body));
+		}
+	}
+}
+
+/// Encodes a set of `(id, data)` elements as an RTP extension block, returning
+/// its profile identifier and (padded) body for use with
+/// [`MutableRtpPacket::insert_extension`].
+///
+/// The one-byte format is used if every ID lies in `1..=14` and every body holds
+/// between 1 and 16 bytes, falling back to the two-byte format otherwise.
+///
+/// Returns `None` if any element cannot be represented in either format.
+///
+/// [`MutableRtpPacket::insert_extension`]: ../struct.MutableRtpPacket.html#method.insert_extension
+#[must_use]
+pub fn encode_elements(elements: &[(u8, &[u8])]) -> Option<(u16, Vec<u8>)> {
+	let one_byte = elements
+		.iter()
+		.all(|(id, data)| (1..=14).contains(id) && (1..=16).contains(&data.len()));
+
+	let mut out = Vec::new();
+	for (id, data) in elements {
+		if one_byte {
+			#[allow(clippy::cast_possible_truncation)]
+			out.push((id << 4) | (data.len() - 1) as u8);
+		} else {
+			if *id == 0 {
+				return None;
+			}
+			out.push(*id);
+			out.push(u8::try_from(data.len()).ok()?);
+		}
+		out.extend_from_slice(data);
+	}
+
+	out.resize((out.len() + 3) / 4 * 4, 0);
+
+	Some((
+		if one_byte {
+			ONE_BYTE_PROFILE
+		} else {
+			TWO_BYTE_PROFILE
+		},
+		out,
+	))
+}
+
+impl RtpPacket<'_> {
+	/// Returns an iterator over the [RFC 8285] elements of this packet's header
+	/// extension.
+	///
+	/// Returns `None` if the `extension` bit is unset, the header is truncated, or
+	/// the extension block does not use the one- or two-byte element format.
+	///
+	/// [RFC 8285]: https://tools.ietf.org/html/rfc8285
+	#[must_use]
+	pub fn extension_elements(&self) -> Option<Elements<'_>> {
+		elements(self.packet())
+	}
+}
+
+/// Returns the RFC 8285 elements of the RTP packet `pkt`, if any.
+pub(crate) fn elements(pkt: &[u8]) -> Option<Elements<'_>> {
+	let range = extension_range(pkt)?;
+	let block = pkt.get(range).filter(|b| !b.is_empty())?;
+
+	Elements::new(u16::from_be_bytes([block[0], block[1]]), &block[4..])
+}
@@ -0,0 +1,76 @@
+use super::{ExtensionUri, HeaderExtension};
+use alloc::string::String;
+
+/// RTP stream identifier of an RTP packet, naming the simulcast or layered
+/// encoding it belongs to.
+///
+/// See [RFC 8852](https://tools.ietf.org/html/rfc8852#section-3.1).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RtpStreamId {
+	/// Value of the `rid` identifying this packet's stream, as used in SDP
+	/// `a=rid` and `a=simulcast` attributes.
+	pub id: String,
+}
+
+/// RTP stream identifier of the stream an RTP packet repairs, as sent on
+/// retransmission or FEC streams.
+///
+/// See [RFC 8852](https://tools.ietf.org/html/rfc8852#section-3.2).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RepairedRtpStreamId {
+	/// Value of the `rid` identifying the repaired stream.
+	pub id: String,
+}
+
+/// Returns whether `id` is a valid `rid`: between 1 and 255 alphanumeric
+/// characters, hyphens or underscores.
+fn is_valid(id: &str) -> bool {
+	(1..=255).contains(&id.len())
+		&& id
+			.bytes()
+			.all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+macro_rules! impl_rid {
+	($name:ident, $uri:ident) => {
+		impl $name {
+			/// Creates a stream identifier.
+			///
+			/// Returns `None` if `id` is empty, longer than 255 bytes, or contains
+			/// characters other than letters, digits, `-` and `_`.
+			#[must_use]
+			pub fn new(id: &str) -> Option<Self> {
+				is_valid(id).then(|| Self { id: id.into() })
+			}
+
+			/// Returns the stream identifier as a string slice.
+			#[must_use]
+			pub fn as_str(&self) -> &str {
+				&self.id
+			}
+		}
+
+		impl HeaderExtension for $name {
+			const URI: ExtensionUri = ExtensionUri::$uri;
+
+			fn parse(data: &[u8]) -> Option<Self> {
+				let id = core::str::from_utf8(data).ok()?.trim_end_matches('\0');
+				Self::new(id)
+			}
+
+			fn wire_size(&self) -> usize {
+				self.id.len()
+			}
+
+			fn write(&self, buf: &mut [u8]) -> Option<usize> {
+				let out = buf.get_mut(..self.id.len())?;
+				out.copy_from_slice(self.id.as_bytes());
+
+				Some(out.len())
+			}
+		}
+	};
+}
+
+impl_rid!(RtpStreamId, RtpStreamId);
+impl_rid!(RepairedRtpStreamId, RepairedRtpStreamId);
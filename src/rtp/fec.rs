@@ -0,0 +1,326 @@
+//! Generic forward error correction, allowing receivers to rebuild lost packets
+//! from the XOR parity of a protected group.
+//!
+//! See [RFC 5109](https://tools.ietf.org/html/rfc5109).
+
+use super::ext::FIXED_HEADER_LEN;
+use crate::{error::impl_new_checked, wrap::Wrap16};
+use alloc::{vec, vec::Vec};
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::Packet,
+	types::{u1, u16be, u32be, u4, u7},
+};
+
+/// Largest number of consecutive packets which a single FEC packet can protect.
+pub const MAX_PROTECTED: u16 = 48;
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Header of a ULPFEC packet, carried as the payload of an RTP packet (or a
+/// RED block) with the negotiated FEC payload type.
+///
+/// Fields named `*_recovery` hold the XOR of the corresponding fields in each
+/// protected packet.
+///
+/// A description of fields:
+///
+/// ## `extension`
+/// Reserved for future header extensions. Must be `0`.
+///
+/// ## `long_mask`
+/// If set, each level header carries a 48-bit mask rather than a 16-bit mask.
+///
+/// ## `padding_recovery`
+/// XOR of the padding bits of the protected packets.
+///
+/// ## `extension_recovery`
+/// XOR of the extension bits of the protected packets.
+///
+/// ## `csrc_count_recovery`
+/// XOR of the CSRC counts of the protected packets.
+///
+/// ## `marker_recovery`
+/// XOR of the marker bits of the protected packets.
+///
+/// ## `payload_type_recovery`
+/// XOR of the payload types of the protected packets.
+///
+/// ## `sn_base`
+/// Lowest sequence number of the protected packets: bit `i` of each level's
+/// mask (MSB first) refers to the packet with sequence number `sn_base + i`.
+///
+/// ## `timestamp_recovery`
+/// XOR of the timestamps of the protected packets.
+///
+/// ## `length_recovery`
+/// XOR of the lengths of the protected packets, excluding their 12-byte fixed
+/// headers.
+///
+/// ## `payload`
+/// Level headers and level payloads. See [`UlpFecPacket::levels`].
+///
+/// [`UlpFecPacket::levels`]: struct.UlpFecPacket.html#method.levels
+pub struct UlpFec {
+	pub extension: u1,
+
+	pub long_mask: u1,
+
+	pub padding_recovery: u1,
+
+	pub extension_recovery: u1,
+
+	pub csrc_count_recovery: u4,
+
+	pub marker_recovery: u1,
+
+	pub payload_type_recovery: u7,
+
+	#[construct_with(u16be)]
+	pub sn_base: Wrap16,
+
+	pub timestamp_recovery: u32be,
+
+	pub length_recovery: u16be,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	UlpFecPacket,
+	MutableUlpFecPacket,
+	[
+		"extension" => 1,
+		"long_mask" => 1,
+		"padding_recovery" => 1,
+		"extension_recovery" => 1,
+		"csrc_count_recovery" => 1,
+		"marker_recovery" => 2,
+		"payload_type_recovery" => 2,
+		"sn_base" => 4,
+		"timestamp_recovery" => 8,
+		"length_recovery" => 10,
+	]
+);
+
+const FEC_HEADER_LEN: usize = UlpFecPacket::minimum_packet_size();
+
+/// A single protection level of a ULPFEC packet.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FecLevel<'a> {
+	/// Number of payload bytes of each protected packet covered by this level.
+	pub protection_length: u16,
+	/// Mask of protected packets, with the bit for `sn_base + i` at position
+	/// `47 - i`.
+	///
+	/// Short (16-bit) masks occupy only the upper 16 of these 48 bits.
+	pub mask: u64,
+	/// XOR of the covered payload bytes of each protected packet.
+	pub data: &'a [u8],
+}
+
+impl FecLevel<'_> {
+	/// Returns whether the packet with sequence number `seq` is protected by this
+	/// level, given the FEC packet's `sn_base`.
+	#[must_use]
+	pub fn protects(&self, sn_base: Wrap16, seq: Wrap16) -> bool {
+		let offset = u16::from(seq - u16::from(sn_base));
+		offset < MAX_PROTECTED && self.mask & (1 << (47 - offset)) != 0
+	}
+
+	/// Iterates over the sequence numbers protected by this level, given the FEC
+	/// packet's `sn_base`.
+	pub fn protected(&self, sn_base: Wrap16) -> impl Iterator<Item = Wrap16> {
+		let mask = self.mask;
+		(0..MAX_PROTECTED)
+			.filter(move |i| mask & (1 << (47 - i)) != 0)
+			.map(move |i| sn_base + i)
+	}
+}
+
+/// Iterator over the protection levels of a ULPFEC packet, from level 0 upwards.
+///
+/// Iteration ends early if a level header or payload is truncated.
+#[derive(Clone, Debug)]
+pub struct FecLevels<'a> {
+	data: &'a [u8],
+	long_mask: bool,
+}
+
+impl<'a> Iterator for FecLevels<'a> {
+	type Item = FecLevel<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let header_len = if self.long_mask { 8 } else { 4 };
+		let Some(header) = self.data.get(..header_len) else {
+			self.data = &[];
+			return None;
+		};
+
+		let protection_length = u16::from_be_bytes([header[0], header[1]]);
+		let mask = header[2..]
+			.iter()
+			.fold(0u64, |acc, &b| (acc << 8) | u64::from(b))
+			<< (8 * (8 - header_len));
+
+		let end = header_len + usize::from(protection_length);
+		let Some(data) = self.data.get(header_len..end) else {
+			self.data = &[];
+			return None;
+		};
+		self.data = &self.data[end..];
+
+		Some(FecLevel {
+			protection_length,
+			mask,
+			data,
+		})
+	}
+}
+
+impl UlpFecPacket<'_> {
+	/// Iterates over each protection level in this packet.
+	#[must_use]
+	pub fn levels(&self) -> FecLevels<'_> {
+		FecLevels {
+			data: self.payload(),
+			long_mask: self.get_long_mask() != 0,
+		}
+	}
+
+	/// Rebuilds the single packet of this FEC packet's level 0 group which is absent
+	/// from `received`, sent by the media source `ssrc`.
+	///
+	/// `received` may contain packets outside of the protected group, which are
+	/// ignored.
+	///
+	/// Returns `None` if no packet or more than one packet is missing, or if the
+	/// lost packet was longer than the level 0 protection length.
+	#[must_use]
+	pub fn recover(&self, ssrc: u32, received: &[&[u8]]) -> Option<Vec<u8>> {
+		let level = self.levels().next()?;
+		let sn_base = self.get_sn_base();
+
+		let mut missing = None;
+		for seq in level.protected(sn_base) {
+			let found = received
+				.iter()
+				.any(|pkt| rtp_sequence(pkt) == Some(u16::from(seq)));
+			if !found {
+				if missing.is_some() {
+					return None;
+				}
+				missing = Some(seq);
+			}
+		}
+		let missing = missing?;
+
+		let header = self.packet();
+		let mut bits = [
+			header[0], header[1], header[4], header[5], header[6], header[7], header[8], header[9],
+		];
+		let mut data = level.data.to_vec();
+
+		for pkt in received.iter().filter(|pkt| {
+			rtp_sequence(pkt).map_or(false, |seq| level.protects(sn_base, Wrap16::new(seq)))
+		}) {
+			xor(&mut bits, &bit_string(pkt));
+			xor(&mut data, &pkt[FIXED_HEADER_LEN..]);
+		}
+
+		let len = usize::from(u16::from_be_bytes([bits[6], bits[7]]));
+		if len > data.len() {
+			return None;
+		}
+
+		let mut out = vec![0u8; FIXED_HEADER_LEN + len];
+		out[0] = 0x80 | (bits[0] & 0x3f);
+		out[1] = bits[1];
+		out[2..4].copy_from_slice(&u16::from(missing).to_be_bytes());
+		out[4..8].copy_from_slice(&bits[2..6]);
+		out[8..12].copy_from_slice(&ssrc.to_be_bytes());
+		out[FIXED_HEADER_LEN..].copy_from_slice(&data[..len]);
+
+		Some(out)
+	}
+}
+
+impl UlpFec {
+	/// Builds the ULPFEC payload protecting every RTP packet in `packets` with a
+	/// single level covering their full length.
+	///
+	/// Returns `None` if `packets` is empty, any packet is shorter than an RTP
+	/// header, or the packets span more than [`MAX_PROTECTED`] sequence numbers.
+	///
+	/// [`MAX_PROTECTED`]: constant.MAX_PROTECTED.html
+	#[must_use]
+	pub fn protect(packets: &[&[u8]]) -> Option<Vec<u8>> {
+		let first = Wrap16::new(rtp_sequence(packets.first()?)?);
+		let mut sn_base = first;
+		for pkt in packets {
+			let seq = Wrap16::new(rtp_sequence(pkt)?);
+			if seq.wrapping_lt(sn_base) {
+				sn_base = seq;
+			}
+		}
+
+		let mut mask = 0u64;
+		let mut bits = [0u8; 8];
+		let mut data = Vec::new();
+		for pkt in packets {
+			let offset = u16::from(Wrap16::new(rtp_sequence(pkt)?) - u16::from(sn_base));
+			if offset >= MAX_PROTECTED {
+				return None;
+			}
+			mask |= 1 << (47 - offset);
+
+			let body = &pkt[FIXED_HEADER_LEN..];
+			if body.len() > data.len() {
+				data.resize(body.len(), 0);
+			}
+			xor(&mut bits, &bit_string(pkt));
+			xor(&mut data, body);
+		}
+
+		let long_mask = mask & 0xffff_ffff != 0;
+		let mask_len = if long_mask { 6 } else { 2 };
+
+		let mut out = Vec::with_capacity(FEC_HEADER_LEN + 2 + mask_len + data.len());
+		out.push((u8::from(long_mask) << 6) | (bits[0] & 0x3f));
+		out.push(bits[1]);
+		out.extend_from_slice(&u16::from(sn_base).to_be_bytes());
+		out.extend_from_slice(&bits[2..]);
+
+		#[allow(clippy::cast_possible_truncation)]
+		out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+		out.extend_from_slice(&mask.to_be_bytes()[2..2 + mask_len]);
+		out.extend_from_slice(&data);
+
+		Some(out)
+	}
+}
+
+/// Returns the sequence number of the RTP packet `pkt`, if it holds a full header.
+fn rtp_sequence(pkt: &[u8]) -> Option<u16> {
+	(pkt.len() >= FIXED_HEADER_LEN).then(|| u16::from_be_bytes([pkt[2], pkt[3]]))
+}
+
+/// Returns the FEC bit string of the RTP packet `pkt`, which must hold a full
+/// header: its first two bytes, its timestamp, and its length after the fixed
+/// header.
+fn bit_string(pkt: &[u8]) -> [u8; 8] {
+	#[allow(clippy::cast_possible_truncation)]
+	let len = ((pkt.len() - FIXED_HEADER_LEN) as u16).to_be_bytes();
+
+	[
+		pkt[0], pkt[1], pkt[4], pkt[5], pkt[6], pkt[7], len[0], len[1],
+	]
+}
+
+/// XORs `src` into the start of `dst`, ignoring any excess in `src`.
+fn xor(dst: &mut [u8], src: &[u8]) {
+	for (d, s) in dst.iter_mut().zip(src) {
+		*d ^= s;
+	}
+}
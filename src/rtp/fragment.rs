@@ -0,0 +1,161 @@
+//! Fragmentation of large units (such as NAL units) across several RTP payloads.
+//!
+//! Many video payload formats split a unit too large for one packet into
+//! fragments, each carrying a small header with start and end flags. Only the
+//! layout of that header differs between formats, so [`fragment`] and
+//! [`Defragmenter`] take closures to write or rebuild it.
+//!
+//! [`fragment`]: fn.fragment.html
+//! [`Defragmenter`]: struct.Defragmenter.html
+
+use crate::wrap::Wrap16;
+use alloc::vec::Vec;
+
+/// Position of a fragment within the unit it was split from.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FragmentFlags {
+	/// Whether this fragment begins the unit.
+	pub start: bool,
+	/// Whether this fragment ends the unit.
+	pub end: bool,
+}
+
+/// Splits `body` into payloads of at most `max_payload` bytes, appending them to
+/// `out`.
+///
+/// Each payload begins with `header_len` bytes written by `write_header`, which
+/// is given the position of the fragment, followed by the next chunk of `body`.
+///
+/// Returns `false` if `max_payload` leaves no room for data after the header.
+pub fn fragment(
+	body: &[u8],
+	max_payload: usize,
+	header_len: usize,
+	out: &mut Vec<Vec<u8>>,
+	mut write_header: impl FnMut(FragmentFlags, &mut Vec<u8>),
+) -> bool {
+	let Some(chunk_len) = max_payload.checked_sub(header_len).filter(|&l| l > 0) else {
+		return false;
+	};
+
+	let chunks = body.chunks(chunk_len);
+	let last = chunks.len().saturating_sub(1);
+	for (i, chunk) in chunks.enumerate() {
+		let flags = FragmentFlags {
+			start: i == 0,
+			end: i == last,
+		};
+
+		let mut pkt = Vec::with_capacity(header_len + chunk.len());
+		write_header(flags, &mut pkt);
+		pkt.extend_from_slice(chunk);
+		out.push(pkt);
+	}
+
+	true
+}
+
+/// Reassembler of units split by [`fragment`] (or an equivalent sender).
+///
+/// A unit is released once its end fragment arrives. Units are dropped if their
+/// start is missing, if a new unit starts before they end, or if a packet is
+/// reported lost while they are in progress.
+///
+/// [`fragment`]: fn.fragment.html
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Defragmenter {
+	unit: Option<Vec<u8>>,
+	last_sequence: Option<Wrap16>,
+	dropped: u64,
+}
+
+impl Defragmenter {
+	/// Creates a reassembler with no unit in progress.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds the data of a fragment, after its header, returning the whole unit if
+	/// this fragment completes it.
+	///
+	/// On a start fragment, `write_header` is called to write the beginning of the
+	/// unit (such as a NAL unit header rebuilt from the fragment's header).
+	pub fn push(
+		&mut self,
+		flags: FragmentFlags,
+		body: &[u8],
+		write_header: impl FnOnce(&mut Vec<u8>),
+	) -> Option<Vec<u8>> {
+		if flags.start {
+			if self.unit.is_some() {
+				self.dropped += 1;
+			}
+
+			let mut unit = Vec::new();
+			write_header(&mut unit);
+			self.unit = Some(unit);
+		} else if self.unit.is_none() {
+			self.dropped += 1;
+			return None;
+		}
+
+		if let Some(unit) = self.unit.as_mut() {
+			unit.extend_from_slice(body);
+		}
+
+		if flags.end {
+			self.unit.take()
+		} else {
+			None
+		}
+	}
+
+	/// Adds a fragment as by [`push`], treating any gap in `sequence` since the
+	/// previous fragment as a loss.
+	///
+	/// [`push`]: #method.push
+	pub fn push_sequenced(
+		&mut self,
+		sequence: Wrap16,
+		flags: FragmentFlags,
+		body: &[u8],
+		write_header: impl FnOnce(&mut Vec<u8>),
+	) -> Option<Vec<u8>> {
+		if self
+			.last_sequence
+			.map_or(false, |last| last + 1 != sequence)
+		{
+			self.lost();
+		}
+		self.last_sequence = Some(sequence);
+
+		self.push(flags, body, write_header)
+	}
+
+	/// Records that a packet is missing, dropping any unit in progress.
+	pub fn lost(&mut self) {
+		if self.unit.take().is_some() {
+			self.dropped += 1;
+		}
+	}
+
+	/// Returns whether a unit has been started but not yet finished.
+	#[must_use]
+	pub fn in_progress(&self) -> bool {
+		self.unit.is_some()
+	}
+
+	/// Returns the number of units (or orphaned fragments) dropped as incomplete.
+	#[must_use]
+	pub fn dropped(&self) -> u64 {
+		self.dropped
+	}
+
+	/// Drops any unit in progress without counting it, and forgets the last
+	/// sequence number seen.
+	pub fn reset(&mut self) {
+		self.unit = None;
+		self.last_sequence = None;
+	}
+}
@@ -0,0 +1,167 @@
+//! Grouping of received RTP packets into the media frames they carry.
+
+use super::Rtp;
+use crate::wrap::{Wrap16, Wrap32};
+use alloc::{collections::VecDeque, vec::Vec};
+use core::time::Duration;
+
+/// A group of consecutive RTP packets sharing a timestamp.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssembledFrame {
+	/// RTP timestamp shared by every packet of the frame.
+	pub timestamp: Wrap32,
+	/// Packets of the frame, in sequence order.
+	pub packets: Vec<Rtp>,
+	/// Whether the frame ended with the marker bit, and no packets are known to be
+	/// missing from it.
+	pub complete: bool,
+}
+
+impl AssembledFrame {
+	/// Iterates over the payloads of each packet in the frame, in order.
+	pub fn payloads(&self) -> impl Iterator<Item = &[u8]> + '_ {
+		self.packets.iter().map(|p| &p.payload[..])
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Pending {
+	timestamp: Wrap32,
+	packets: Vec<Rtp>,
+	started: Duration,
+	intact: bool,
+}
+
+/// Assembler of in-order RTP packets into frames, using timestamp changes and
+/// the marker bit as frame boundaries.
+///
+/// A frame is released once a packet with the marker bit set arrives, or when a
+/// packet with a new timestamp begins the next frame. Frames whose final packet
+/// never arrives are released as incomplete after a timeout.
+///
+/// Packets should be given in sequence order, such as from the output of a
+/// [`JitterBuffer`]. A missing packet marks as incomplete both the frame in
+/// progress and the next frame to begin, as it may have held either's boundary.
+///
+/// [`JitterBuffer`]: ../../jitter/struct.JitterBuffer.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FrameAssembler {
+	timeout: Duration,
+	current: Option<Pending>,
+	ready: VecDeque<AssembledFrame>,
+	last_seq: Option<Wrap16>,
+	gap: bool,
+}
+
+impl FrameAssembler {
+	/// Creates an assembler which releases unfinished frames after `timeout`.
+	#[must_use]
+	pub fn new(timeout: Duration) -> Self {
+		Self {
+			timeout,
+			current: None,
+			ready: VecDeque::new(),
+			last_seq: None,
+			gap: false,
+		}
+	}
+
+	/// Adds `packet`, which arrived (or was released for playout) at `now`.
+	pub fn push(&mut self, packet: Rtp, now: Duration) {
+		if let Some(last) = self.last_seq {
+			if packet.sequence != last + 1 {
+				self.lost();
+			}
+		}
+
+		self.push_unsequenced(packet, now);
+	}
+
+	/// Adds `packet` as by [`push`], without checking its sequence number for gaps.
+	///
+	/// This suits callers which detect gaps themselves and report them via
+	/// [`lost`], such as when several payload types share a sequence number space.
+	///
+	/// [`push`]: #method.push
+	/// [`lost`]: #method.lost
+	pub fn push_unsequenced(&mut self, packet: Rtp, now: Duration) {
+		self.last_seq = Some(packet.sequence);
+
+		if self
+			.current
+			.as_ref()
+			.map_or(false, |c| c.timestamp != packet.timestamp)
+		{
+			self.finish(false);
+		}
+
+		let gap = core::mem::take(&mut self.gap);
+		let current = self.current.get_or_insert_with(|| Pending {
+			timestamp: packet.timestamp,
+			packets: Vec::new(),
+			started: now,
+			intact: !gap,
+		});
+
+		let marker = packet.marker != 0;
+		current.packets.push(packet);
+
+		if marker {
+			self.finish(true);
+		}
+	}
+
+	/// Records that a packet is missing (*e.g.*, [`Playout::Missing`]).
+	///
+	/// [`Playout::Missing`]: ../../jitter/enum.Playout.html#variant.Missing
+	pub fn lost(&mut self) {
+		if let Some(current) = self.current.as_mut() {
+			current.intact = false;
+		}
+		self.gap = true;
+	}
+
+	/// Releases the frame in progress as incomplete if it began more than the
+	/// timeout before `now`.
+	pub fn expire(&mut self, now: Duration) {
+		if self
+			.current
+			.as_ref()
+			.map_or(false, |c| now.saturating_sub(c.started) > self.timeout)
+		{
+			self.finish(false);
+		}
+	}
+
+	/// Releases the frame in progress, if any, as incomplete.
+	pub fn flush(&mut self) {
+		self.finish(false);
+	}
+
+	/// Returns the next finished frame, if any.
+	pub fn pop(&mut self) -> Option<AssembledFrame> {
+		self.ready.pop_front()
+	}
+
+	/// Number of finished frames waiting to be taken.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.ready.len()
+	}
+
+	/// Returns whether no finished frames are waiting to be taken.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.ready.is_empty()
+	}
+
+	fn finish(&mut self, marker: bool) {
+		if let Some(frame) = self.current.take() {
+			self.ready.push_back(AssembledFrame {
+				timestamp: frame.timestamp,
+				packets: frame.packets,
+				complete: marker && frame.intact,
+			});
+		}
+	}
+}
@@ -0,0 +1,71 @@
+use super::{RtpPacketBuilder, RtpType};
+use crate::wrap::{Wrap16, Wrap32};
+
+/// Number of SSRCs drawn by [`RtpInit::random_avoiding`] before giving up.
+///
+/// [`RtpInit::random_avoiding`]: struct.RtpInit.html#method.random_avoiding
+const MAX_SSRC_ATTEMPTS: usize = 32;
+
+/// Initial identifiers for a new RTP stream.
+///
+/// RFC 3550 recommends that the SSRC, initial sequence number and initial
+/// timestamp of each stream be chosen at random, so that streams are unlikely to
+/// collide and known-plaintext attacks on encrypted streams are frustrated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RtpInit {
+	/// Initial sequence number.
+	pub sequence: Wrap16,
+	/// Initial timestamp.
+	pub timestamp: Wrap32,
+	/// Synchronisation source identifier.
+	pub ssrc: u32,
+}
+
+impl RtpInit {
+	/// Draws each identifier from the operating system's cryptographically secure
+	/// random number generator.
+	///
+	/// Returns `None` if no random numbers are available.
+	#[must_use]
+	pub fn random() -> Option<Self> {
+		Self::random_avoiding(|_| false)
+	}
+
+	/// Draws each identifier at random, as with [`random`], redrawing the SSRC
+	/// while `is_taken` reports that it is already in use (*e.g.*, by another
+	/// member of the session).
+	///
+	/// Returns `None` if no random numbers are available, or no free SSRC was found
+	/// after many attempts.
+	///
+	/// [`random`]: #method.random
+	#[must_use]
+	pub fn random_avoiding(mut is_taken: impl FnMut(u32) -> bool) -> Option<Self> {
+		let mut buf = [0u8; 6];
+		getrandom::getrandom(&mut buf).ok()?;
+
+		let ssrc = (0..MAX_SSRC_ATTEMPTS).find_map(|_| {
+			let mut ssrc = [0u8; 4];
+			getrandom::getrandom(&mut ssrc).ok()?;
+			let ssrc = u32::from_be_bytes(ssrc);
+
+			(!is_taken(ssrc)).then_some(ssrc)
+		})?;
+
+		Some(Self {
+			sequence: Wrap16::new(u16::from_be_bytes([buf[0], buf[1]])),
+			timestamp: Wrap32::new(u32::from_be_bytes([buf[2], buf[3], buf[4], buf[5]])),
+			ssrc,
+		})
+	}
+
+	/// Creates a packet builder for `payload_type`, starting from these
+	/// identifiers.
+	#[must_use]
+	pub fn builder(&self, payload_type: RtpType) -> RtpPacketBuilder {
+		let mut out = RtpPacketBuilder::new(payload_type, self.ssrc);
+		out.sequence(self.sequence).timestamp(self.timestamp);
+
+		out
+	}
+}
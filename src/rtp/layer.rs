@@ -0,0 +1,150 @@
+//! Codec-independent simulcast and scalable video layer information.
+//!
+//! Forwarding units choose which layers of a stream to send each receiver, and
+//! may only switch layers at certain frames. The same information is signalled
+//! by several header extensions and payload descriptors, each of which can be
+//! converted into a [`LayerInfo`].
+//!
+//! [`LayerInfo`]: struct.LayerInfo.html
+
+use super::{
+	codec::{vp8, vp9::Vp9Descriptor},
+	ext::{
+		DecodeTargetIndication,
+		DependencyDescriptor,
+		DependencyStructure,
+		FrameMarking,
+		RtpStreamId,
+	},
+};
+use alloc::string::String;
+
+/// Layer membership and switching points of a single video packet.
+///
+/// Fields are `None` (or unset) where a source does not signal them.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct LayerInfo {
+	/// RTP stream identifier of the simulcast encoding carrying this packet.
+	pub rid: Option<String>,
+	/// Spatial (or quality) layer index.
+	pub spatial_id: Option<u8>,
+	/// Temporal layer index.
+	pub temporal_id: Option<u8>,
+	/// Whether this packet begins a frame.
+	pub start_of_frame: bool,
+	/// Whether this packet ends a frame.
+	pub end_of_frame: bool,
+	/// Whether this frame can be decoded without reference to earlier frames, so
+	/// that forwarding may switch to this stream (or spatial layer) here.
+	pub keyframe: bool,
+	/// Whether forwarding may switch up to this frame's temporal layer here.
+	pub temporal_switch: bool,
+	/// Whether no other frame depends on this one, so that it may be dropped.
+	pub discardable: bool,
+}
+
+impl LayerInfo {
+	/// Returns the layer information of a packet identified only by its RTP stream
+	/// identifier.
+	#[must_use]
+	pub fn from_rid(rid: &RtpStreamId) -> Self {
+		Self::default().with_rid(rid)
+	}
+
+	/// Sets the RTP stream identifier, such as from the header extension of a
+	/// packet whose layers were read from its payload.
+	#[must_use]
+	pub fn with_rid(mut self, rid: &RtpStreamId) -> Self {
+		self.rid = Some(rid.id.clone());
+		self
+	}
+
+	/// Returns the layer information signalled by a frame marking extension.
+	///
+	/// The codec-specific layer ID is taken as the spatial layer index.
+	#[must_use]
+	pub fn from_frame_marking(marking: &FrameMarking) -> Self {
+		Self {
+			rid: None,
+			spatial_id: marking.layer.map(|l| l.layer_id),
+			temporal_id: marking.layer.map(|l| l.temporal_id),
+			start_of_frame: marking.start,
+			end_of_frame: marking.end,
+			keyframe: marking.independent,
+			temporal_switch: marking.layer.map_or(false, |l| l.base_layer_sync),
+			discardable: marking.discardable,
+		}
+	}
+
+	/// Returns the layer information of a VP8 payload, from its payload descriptor
+	/// and header.
+	///
+	/// Returns `None` if the descriptor is truncated.
+	#[must_use]
+	pub fn from_vp8(payload: &[u8]) -> Option<Self> {
+		let (descriptor, _) = vp8::Vp8Descriptor::parse(payload)?;
+
+		Some(Self {
+			temporal_id: descriptor.temporal_id,
+			start_of_frame: descriptor.is_start_of_frame(),
+			keyframe: vp8::is_keyframe(payload),
+			temporal_switch: descriptor.layer_sync,
+			discardable: descriptor.non_reference,
+			..Self::default()
+		})
+	}
+
+	/// Returns the layer information of a VP9 payload, from its payload
+	/// descriptor.
+	///
+	/// Returns `None` if the descriptor is truncated.
+	#[must_use]
+	pub fn from_vp9(payload: &[u8]) -> Option<Self> {
+		let descriptor = Vp9Descriptor::parse(payload)?;
+
+		Some(Self {
+			spatial_id: descriptor.layer.map(|l| l.spatial_id),
+			temporal_id: descriptor.layer.map(|l| l.temporal_id),
+			start_of_frame: descriptor.start_of_frame,
+			end_of_frame: descriptor.end_of_frame,
+			keyframe: descriptor.is_keyframe(),
+			temporal_switch: descriptor.layer.map_or(false, |l| l.switching_up),
+			..Self::default()
+		})
+	}
+
+	/// Returns the layer information of a dependency descriptor, as used by AV1,
+	/// using `latest` as the most recent structure seen on this stream.
+	///
+	/// A frame is a temporal switch point if it is a switch point of any decode
+	/// target, and discardable if it is discardable in every decode target it is
+	/// part of.
+	///
+	/// Returns `None` if the descriptor's frame template cannot be resolved.
+	#[must_use]
+	pub fn from_dependency_descriptor(
+		descriptor: &DependencyDescriptor,
+		latest: Option<&DependencyStructure>,
+	) -> Option<Self> {
+		let frame = descriptor.resolve(latest)?;
+		let present = || {
+			frame
+				.dtis
+				.iter()
+				.filter(|&&dti| dti != DecodeTargetIndication::NotPresent)
+		};
+
+		Some(Self {
+			rid: None,
+			spatial_id: Some(frame.spatial_id),
+			temporal_id: Some(frame.temporal_id),
+			start_of_frame: descriptor.start_of_frame,
+			end_of_frame: descriptor.end_of_frame,
+			keyframe: frame.fdiffs.is_empty(),
+			temporal_switch: present().any(|&dti| dti == DecodeTargetIndication::Switch),
+			discardable: present().count() != 0
+				&& present().all(|&dti| dti == DecodeTargetIndication::Discardable),
+		})
+	}
+}
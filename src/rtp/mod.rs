@@ -0,0 +1,558 @@
+//! Readers and writers for the [Real-time Transport Protocol].
+//!
+//! *These are included when using the `"rtp"` feature.*
+//!
+//! [Real-time Transport Protocol]: https://tools.ietf.org/html/rfc3550
+
+mod bits;
+mod builder;
+pub mod cn;
+pub mod codec;
+mod csrc;
+pub mod depacketizer;
+pub mod dtmf;
+pub mod ext;
+pub mod fec;
+pub mod fragment;
+pub mod frame;
+#[cfg(feature = "rand")]
+mod init;
+pub mod layer;
+pub mod packetizer;
+mod padding;
+pub mod red;
+mod registry;
+pub mod rtx;
+pub mod validate;
+
+#[cfg(feature = "rand")]
+pub use self::init::RtpInit;
+pub use self::{
+	builder::RtpPacketBuilder,
+	csrc::MAX_CSRCS,
+	registry::{CodecRegistry, MediaKind, PayloadFormat, PayloadRegistry, StreamFrame},
+};
+
+use crate::{
+	error::{impl_new_checked, ParseError},
+	wrap::{Wrap16, Wrap32},
+};
+use alloc::vec::Vec;
+use pnet_macros::packet;
+use pnet_macros_support::{
+	packet::{MutablePacket, Packet, PrimitiveValues},
+	types::{u1, u16be, u2, u32be, u4, u7},
+};
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Packet header for the [Real-time Transport Protocol].
+///
+/// A description of fields:
+///
+/// ## `version`
+/// RTP version. Should be `2`.
+///
+/// ## `padding`
+/// Packet contains padding octets which are not part of the payload.
+/// The last byte of the payload contains the
+/// count of bytes to be ignored from the end (including itself).
+///
+/// ## `extension`
+/// If set, the fixed header must be followed by exactly one [`RtpExtension`].
+///
+/// ## `csrc_count`
+/// Number of CSRC identifiers following the fixed header.
+///
+/// ## `marker`
+/// Bitflag with application/profile-specific meaning, *e.g.*, frame boundaries.
+///
+/// ## `payload_type`
+/// Describes the format of the RTP [`payload`].
+///
+/// ## `sequence`
+/// Increments by `1` for every packet sent, and used to detect packet loss
+/// or enable reordering.
+///
+/// Should be initialised to a random number.
+///
+/// ## `timestamp`
+/// Sampling instant for the first octet of the payload: must be derived from a
+/// monotonically and linearly increasing clock. If data are generated periodically,
+/// then the measure should be derived from the sample clock (*e.g.*, increment by
+/// one for every sampling period).
+///
+/// Should be initialised to a random number.
+///
+/// ## `ssrc`
+/// Synchronisation Source, identifying the sender.
+///
+/// Should be initialised to a random number.
+///
+/// ## `csrc_list`
+/// Used to identify contributing SSRCs in the packet (*i.e.*, to allow speaker
+/// detection if packets are mixed and re-encoded along the path).
+///
+/// This must hold exactly `csrc_count` entries: [`Rtp::set_csrcs`] and
+/// [`MutableRtpPacket::set_csrcs`] keep both in sync.
+///
+/// ## `payload`
+/// Bytes of the RTP body, including any [`RtpExtension`]. See
+/// [`RtpPacket::media_payload`] to skip the extension.
+///
+/// [`payload`]: #structfield.payload
+/// [`RtpExtension`]: struct.RtpExtension.html
+/// [`MutableRtpPacket::set_csrcs`]: struct.MutableRtpPacket.html#method.set_csrcs
+/// [`Rtp::set_csrcs`]: struct.Rtp.html#method.set_csrcs
+/// [`RtpPacket::media_payload`]: struct.RtpPacket.html#method.media_payload
+/// [Real-time Transport Protocol]: https://tools.ietf.org/html/rfc3550
+pub struct Rtp {
+	pub version: u2,
+
+	pub padding: u1,
+
+	pub extension: u1,
+
+	pub csrc_count: u4,
+
+	pub marker: u1,
+
+	#[construct_with(u7)]
+	pub payload_type: RtpType,
+
+	#[construct_with(u16be)]
+	pub sequence: Wrap16,
+
+	#[construct_with(u32be)]
+	pub timestamp: Wrap32,
+
+	pub ssrc: u32be,
+
+	#[length = "4 * csrc_count"]
+	pub csrc_list: Vec<u32be>,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+#[packet]
+#[derive(Eq, PartialEq)]
+/// Extension data for an [`Rtp`] packet.
+///
+/// A description of fields:
+///
+/// ## `info`
+/// Profile-defined, usually an extension type.
+///
+/// ## `length`
+/// Number of 32-bit words in `ext_data`. `0` is valid.
+///
+/// ## `ext_data`
+/// Application-defined extension data.
+///
+/// ## `payload`
+/// Remainder of packet data.
+///
+/// [`Rtp`]: struct.Rtp.html
+pub struct RtpExtension {
+	pub info: u16be,
+
+	pub length: u16be,
+
+	#[length = "4 * length"]
+	pub ext_data: Vec<u8>,
+
+	#[payload]
+	pub payload: Vec<u8>,
+}
+
+impl_new_checked!(
+	RtpPacket,
+	MutableRtpPacket,
+	[
+		"version" => 1,
+		"padding" => 1,
+		"extension" => 1,
+		"csrc_count" => 1,
+		"marker" => 2,
+		"payload_type" => 2,
+		"sequence" => 4,
+		"timestamp" => 8,
+		"ssrc" => 12,
+	],
+	|pkt: &[u8]| {
+		let csrc_end = ext::FIXED_HEADER_LEN + 4 * usize::from(pkt[0] & 0x0f);
+		ParseError::require(pkt, "csrc_list", csrc_end)?;
+
+		if pkt[0] & 0x10 != 0 {
+			RtpExtensionPacket::new_checked(&pkt[csrc_end..]).map_err(|e| ParseError {
+				needed: csrc_end + e.needed,
+				provided: pkt.len(),
+				..e
+			})?;
+		}
+
+		Ok(())
+	}
+);
+
+impl_new_checked!(
+	RtpExtensionPacket,
+	MutableRtpExtensionPacket,
+	["info" => 2, "length" => 4],
+	|pkt: &[u8]| {
+		let words = u16::from_be_bytes([pkt[2], pkt[3]]);
+		ParseError::require(pkt, "ext_data", 4 + 4 * usize::from(words))
+	}
+);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+/// RTP message types. These define the packet format used for the payload.
+///
+/// See the [IANA page] on the matter for an up-to-date-list.
+///
+/// [IANA page]: https://www.iana.org/assignments/rtp-parameters/rtp-parameters.xhtml#rtp-parameters-1
+pub enum RtpType {
+	/// Code 0.
+	Pcmu,
+
+	/// Code 3.
+	Gsm,
+
+	/// Code 4.
+	G723,
+
+	/// Code 5--6, 16--17.
+	Dvi4(u8),
+
+	/// Code 7.
+	Lpc,
+
+	/// Code 8.
+	Pcma,
+
+	/// Code 9.
+	G722,
+
+	/// Code 10.
+	L16Stereo,
+
+	/// Code 11.
+	L16Mono,
+
+	/// Code 12.
+	Qcelp,
+
+	/// Code 13.
+	Cn,
+
+	/// Code 14.
+	Mpa,
+
+	/// Code 15.
+	G728,
+
+	/// Code 18.
+	G729,
+
+	/// Code 25.
+	CelB,
+
+	/// Code 26.
+	Jpeg,
+
+	/// Code 28.
+	Nv,
+
+	/// Code 31.
+	H261,
+
+	/// Code 32.
+	Mpv,
+
+	/// Code 33.
+	Mp2t,
+
+	/// Code 34.
+	H263,
+
+	/// Dynamically assigned payload type (codes >= 96).
+	Dynamic(u8),
+
+	/// Reserved payload types, typically to mitigate RTCP packet type collisions (1--2, 19, 72--76).
+	Reserved(u8),
+
+	/// Unassigned payload type (all remaining < 128).
+	Unassigned(u8),
+
+	/// Code point too high for u7: application error?
+	Illegal(u8),
+}
+
+impl RtpType {
+	#[must_use]
+	pub fn new(val: u7) -> Self {
+		match val {
+			0 => Self::Pcmu,
+			3 => Self::Gsm,
+			4 => Self::G723,
+			5 | 6 | 16 | 17 => Self::Dvi4(val),
+			7 => Self::Lpc,
+			8 => Self::Pcma,
+			9 => Self::G722,
+			10 => Self::L16Stereo,
+			11 => Self::L16Mono,
+			12 => Self::Qcelp,
+			13 => Self::Cn,
+			14 => Self::Mpa,
+			15 => Self::G728,
+			18 => Self::G729,
+			25 => Self::CelB,
+			26 => Self::Jpeg,
+			28 => Self::Nv,
+			31 => Self::H261,
+			32 => Self::Mpv,
+			33 => Self::Mp2t,
+			34 => Self::H263,
+			1..=2 | 19 | 72..=76 => Self::Reserved(val),
+			96..=127 => Self::Dynamic(val),
+			128..=255 => Self::Illegal(val),
+			_ => Self::Unassigned(val),
+		}
+	}
+
+	/// Returns the RTP timestamp clock rate of a statically assigned payload type,
+	/// in Hz.
+	///
+	/// Returns `None` for dynamic, reserved, unassigned and illegal payload types,
+	/// whose clock rate must be negotiated (*e.g.*, by SDP `a=rtpmap`).
+	#[must_use]
+	pub fn clock_rate(self) -> Option<u32> {
+		match self {
+			Self::Pcmu
+			| Self::Gsm
+			| Self::G723
+			| Self::Dvi4(5)
+			| Self::Lpc
+			| Self::Pcma
+			| Self::G722
+			| Self::Qcelp
+			| Self::Cn
+			| Self::G728
+			| Self::G729 => Some(8_000),
+			Self::Dvi4(6) => Some(16_000),
+			Self::Dvi4(16) => Some(11_025),
+			Self::Dvi4(17) => Some(22_050),
+			Self::L16Stereo | Self::L16Mono => Some(44_100),
+			Self::Mpa
+			| Self::CelB
+			| Self::Jpeg
+			| Self::Nv
+			| Self::H261
+			| Self::Mpv
+			| Self::Mp2t
+			| Self::H263 => Some(90_000),
+			_ => None,
+		}
+	}
+
+	/// Returns the audio sampling rate of a statically assigned payload type, in Hz.
+	///
+	/// This matches the [`clock_rate`] except where the two differ for historical
+	/// reasons (see [`has_legacy_clock_rate`]).
+	///
+	/// [`clock_rate`]: #method.clock_rate
+	/// [`has_legacy_clock_rate`]: #method.has_legacy_clock_rate
+	#[must_use]
+	pub fn sample_rate(self) -> Option<u32> {
+		match self {
+			Self::G722 => Some(16_000),
+			_ => self.clock_rate(),
+		}
+	}
+
+	/// Returns whether the RTP clock of this payload type runs slower than its audio
+	/// is sampled.
+	///
+	/// This is true only of G.722, which is sampled at 16 kHz but whose timestamps
+	/// advance at 8 kHz due to an error in RFC 1890 preserved for compatibility.
+	/// Durations must then be computed from timestamps using the [`clock_rate`],
+	/// and sample counts halved to find timestamp increments.
+	///
+	/// [`clock_rate`]: #method.clock_rate
+	#[must_use]
+	pub fn has_legacy_clock_rate(self) -> bool {
+		self == Self::G722
+	}
+
+	/// Returns the number of audio channels of a statically assigned audio payload
+	/// type.
+	///
+	/// Returns `None` for video types, for MPEG audio (whose channel count is carried
+	/// in-band), and for dynamic, reserved, unassigned and illegal payload types.
+	#[must_use]
+	pub fn channels(self) -> Option<u8> {
+		match self {
+			Self::L16Stereo => Some(2),
+			Self::Pcmu
+			| Self::Gsm
+			| Self::G723
+			| Self::Dvi4(_)
+			| Self::Lpc
+			| Self::Pcma
+			| Self::G722
+			| Self::L16Mono
+			| Self::Qcelp
+			| Self::Cn
+			| Self::G728
+			| Self::G729 => Some(1),
+			_ => None,
+		}
+	}
+}
+
+impl PrimitiveValues for RtpType {
+	type T = (u7,);
+
+	fn to_primitive_values(&self) -> Self::T {
+		match self {
+			Self::Pcmu => (0,),
+			Self::Gsm => (3,),
+			Self::G723 => (4,),
+			Self::Lpc => (7,),
+			Self::Pcma => (8,),
+			Self::G722 => (9,),
+			Self::L16Stereo => (10,),
+			Self::L16Mono => (11,),
+			Self::Qcelp => (12,),
+			Self::Cn => (13,),
+			Self::Mpa => (14,),
+			Self::G728 => (15,),
+			Self::G729 => (18,),
+			Self::CelB => (25,),
+			Self::Jpeg => (26,),
+			Self::Nv => (28,),
+			Self::H261 => (31,),
+			Self::Mpv => (32,),
+			Self::Mp2t => (33,),
+			Self::H263 => (34,),
+
+			Self::Dvi4(val)
+			| Self::Dynamic(val)
+			| Self::Unassigned(val)
+			| Self::Reserved(val)
+			| Self::Illegal(val) => (*val,),
+		}
+	}
+}
+
+impl RtpPacket<'_> {
+	/// Parses an RTP packet of `len` bytes, starting at `offset` bytes into `buf`.
+	///
+	/// RTP has no inherent length field, so the datagram length must be known
+	/// (*e.g.*, from `recvmmsg` or an `io_uring` completion). The second element
+	/// is the number of bytes consumed, allowing a cursor into a larger receive
+	/// buffer to be advanced.
+	///
+	/// Returns `None` if the requested range lies outside `buf`, or is too short.
+	#[must_use]
+	pub fn parse_at(buf: &[u8], offset: usize, len: usize) -> Option<(RtpPacket<'_>, usize)> {
+		let pkt = buf.get(offset..offset.checked_add(len)?)?;
+		RtpPacket::new(pkt).map(|p| (p, len))
+	}
+
+	/// Returns a view of this packet's header extension, positioned after the CSRC list.
+	///
+	/// The extension's own [`payload`] is the remainder of the packet.
+	///
+	/// Returns `None` if the `extension` bit is unset, or the CSRC list or extension
+	/// are truncated.
+	///
+	/// [`payload`]: struct.RtpExtensionPacket.html#method.payload
+	#[must_use]
+	pub fn extension_view(&self) -> Option<RtpExtensionPacket<'_>> {
+		let range = ext::extension_range(self.packet())?;
+		if range.is_empty() {
+			return None;
+		}
+
+		RtpExtensionPacket::new(&self.packet()[range.start..])
+	}
+
+	/// Returns the bytes following the CSRC list and any header extension.
+	///
+	/// Unlike [`payload`], this never includes extension data. Padding is not removed.
+	///
+	/// Returns `None` if the CSRC list or extension are truncated.
+	///
+	/// [`payload`]: #method.payload
+	#[must_use]
+	pub fn media_payload(&self) -> Option<&[u8]> {
+		let range = ext::extension_range(self.packet())?;
+		Some(&self.packet()[range.end..])
+	}
+}
+
+impl MutableRtpPacket<'_> {
+	/// Parses a mutable RTP packet of `len` bytes, starting at `offset` bytes into `buf`.
+	///
+	/// See [`RtpPacket::parse_at`] for more information.
+	///
+	/// [`RtpPacket::parse_at`]: struct.RtpPacket.html#method.parse_at
+	pub fn parse_at(
+		buf: &mut [u8],
+		offset: usize,
+		len: usize,
+	) -> Option<(MutableRtpPacket<'_>, usize)> {
+		let pkt = buf.get_mut(offset..offset.checked_add(len)?)?;
+		MutableRtpPacket::new(pkt).map(|p| (p, len))
+	}
+
+	/// Returns the bytes following the CSRC list and any header extension.
+	///
+	/// See [`RtpPacket::media_payload`] for more information.
+	///
+	/// [`RtpPacket::media_payload`]: struct.RtpPacket.html#method.media_payload
+	#[must_use]
+	pub fn media_payload(&self) -> Option<&[u8]> {
+		let range = ext::extension_range(self.packet())?;
+		Some(&self.packet()[range.end..])
+	}
+
+	/// Returns the bytes following the CSRC list and any header extension, mutably.
+	///
+	/// Unlike [`payload_mut`], this never includes extension data, making it safe
+	/// to encrypt or rewrite in place. Padding is not removed.
+	///
+	/// Returns `None` if the CSRC list or extension are truncated.
+	///
+	/// [`payload_mut`]: #method.payload_mut
+	pub fn media_payload_mut(&mut self) -> Option<&mut [u8]> {
+		let range = ext::extension_range(self.packet())?;
+		Some(&mut self.packet_mut()[range.end..])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pnet_macros_support::packet::FromPacket;
+
+	#[test]
+	fn csrc_list_uses_four_bytes_per_entry() {
+		let buf = RtpPacketBuilder::new(RtpType::Dynamic(96), 1)
+			.csrcs(&[0x0102_0304, 0x0506_0708])
+			.payload(&[9, 10, 11])
+			.to_vec();
+		let pkt = RtpPacket::new(&buf).unwrap();
+
+		assert_eq!(pkt.get_csrc_count(), 2);
+		assert_eq!(pkt.get_csrc_list(), [0x0102_0304, 0x0506_0708]);
+		assert_eq!(pkt.payload(), [9, 10, 11]);
+
+		let owned = pkt.from_packet();
+		assert_eq!(owned.csrc_list, [0x0102_0304, 0x0506_0708]);
+		assert_eq!(owned.payload, [9, 10, 11]);
+	}
+}
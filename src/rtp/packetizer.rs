@@ -0,0 +1,305 @@
+//! Splitting of media frames into RTP packets.
+
+use super::{ext::FIXED_HEADER_LEN, Rtp, RtpType};
+use crate::wrap::{Wrap16, Wrap32};
+use alloc::{boxed::Box, vec::Vec};
+use core::time::Duration;
+
+/// Codec-specific splitting of a frame into RTP payloads.
+///
+/// Implementors handle only the payload format: sequence numbers, timestamps and
+/// the marker bit are managed by an [`RtpPacketizer`].
+///
+/// [`RtpPacketizer`]: struct.RtpPacketizer.html
+pub trait Packetizer {
+	/// Splits `frame` into payloads of at most `max_payload` bytes, in sending order.
+	///
+	/// Returns an empty list if the frame cannot be carried in payloads of this size.
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>>;
+
+	/// Returns whether the marker bit should be set on the final packet of each
+	/// frame, as is conventional for video.
+	///
+	/// Defaults to `true`.
+	fn marks_frame_end(&self) -> bool {
+		true
+	}
+}
+
+impl<P: Packetizer + ?Sized> Packetizer for Box<P> {
+	fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+		(**self).packetize(frame, max_payload)
+	}
+
+	fn marks_frame_end(&self) -> bool {
+		(**self).marks_frame_end()
+	}
+}
+
+/// Converts `duration` into a number of samples (or timestamp units) at
+/// `clock_rate`, rounding down.
+pub(crate) fn duration_samples(clock_rate: u32, duration: Duration) -> u128 {
+	u128::from(clock_rate) * duration.as_nanos() / 1_000_000_000
+}
+
+/// Driver turning frames into complete RTP packets using a [`Packetizer`],
+/// assigning sequence numbers, timestamps and marker bits.
+///
+/// Unless otherwise set, packets begin at sequence number and timestamp `0`.
+///
+/// [`Packetizer`]: trait.Packetizer.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RtpPacketizer<P> {
+	packetizer: P,
+	payload_type: RtpType,
+	ssrc: u32,
+	mtu: usize,
+	sequence: Wrap16,
+	timestamp: Wrap32,
+}
+
+impl<P: Packetizer> RtpPacketizer<P> {
+	/// Creates a driver sending packets of `payload_type` from `ssrc`, where each
+	/// packet (including its RTP header) is at most `mtu` bytes.
+	#[must_use]
+	pub fn new(packetizer: P, payload_type: RtpType, ssrc: u32, mtu: usize) -> Self {
+		Self {
+			packetizer,
+			payload_type,
+			ssrc,
+			mtu,
+			sequence: Wrap16::new(0),
+			timestamp: Wrap32::new(0),
+		}
+	}
+
+	/// Sets the sequence number of the next packet.
+	pub fn set_sequence(&mut self, sequence: Wrap16) {
+		self.sequence = sequence;
+	}
+
+	/// Sets the timestamp of the next frame.
+	pub fn set_timestamp(&mut self, timestamp: Wrap32) {
+		self.timestamp = timestamp;
+	}
+
+	/// Sequence number of the next packet.
+	#[must_use]
+	pub fn sequence(&self) -> Wrap16 {
+		self.sequence
+	}
+
+	/// Timestamp of the next frame.
+	#[must_use]
+	pub fn timestamp(&self) -> Wrap32 {
+		self.timestamp
+	}
+
+	/// Returns the underlying packetizer.
+	#[must_use]
+	pub fn packetizer(&self) -> &P {
+		&self.packetizer
+	}
+
+	/// Returns the underlying packetizer, mutably.
+	pub fn packetizer_mut(&mut self) -> &mut P {
+		&mut self.packetizer
+	}
+
+	/// Splits `frame` into packets sharing the current timestamp, and then advances
+	/// the timestamp by `duration` timestamp units.
+	///
+	/// Returns an empty list if the packetizer cannot fit the frame within the
+	/// MTU. The timestamp is advanced regardless, as the frame's duration has
+	/// still passed.
+	pub fn packetize(&mut self, frame: &[u8], duration: u32) -> Vec<Rtp> {
+		let payloads = self
+			.packetizer
+			.packetize(frame, self.mtu.saturating_sub(FIXED_HEADER_LEN));
+		let last = payloads.len().saturating_sub(1);
+		let marks_end = self.packetizer.marks_frame_end();
+
+		let out = payloads
+			.into_iter()
+			.enumerate()
+			.map(|(i, payload)| {
+				let pkt = Rtp {
+					version: 2,
+					padding: 0,
+					extension: 0,
+					csrc_count: 0,
+					marker: u8::from(marks_end && i == last),
+					payload_type: self.payload_type,
+					sequence: self.sequence,
+					timestamp: self.timestamp,
+					ssrc: self.ssrc,
+					csrc_list: Vec::new(),
+					payload,
+				};
+				self.sequence += 1;
+
+				pkt
+			})
+			.collect();
+
+		self.timestamp += duration;
+
+		out
+	}
+}
+
+/// Driver for audio codecs with fixed-duration frames, which advances the
+/// timestamp by a whole frame for each frame sent or skipped.
+///
+/// The marker bit is set on the first packet of each talkspurt: that is, the
+/// first packet sent, and the first sent after frames are skipped for
+/// discontinuous transmission (DTX), and cleared on all others regardless of
+/// [`Packetizer::marks_frame_end`]. Packets are otherwise built as by an
+/// [`RtpPacketizer`], which remains accessible.
+///
+/// [`Packetizer::marks_frame_end`]: trait.Packetizer.html#method.marks_frame_end
+/// [`RtpPacketizer`]: struct.RtpPacketizer.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AudioPacketizer<P> {
+	rtp: RtpPacketizer<P>,
+	clock_rate: u32,
+	frame_samples: u32,
+	talkspurt_start: bool,
+}
+
+impl<P: Packetizer> AudioPacketizer<P> {
+	/// Creates a driver sending packets of `payload_type` from `ssrc`, where each
+	/// frame lasts `frame_duration` at `clock_rate`.
+	#[must_use]
+	pub fn new(
+		packetizer: P,
+		payload_type: RtpType,
+		ssrc: u32,
+		mtu: usize,
+		clock_rate: u32,
+		frame_duration: Duration,
+	) -> Self {
+		let samples = duration_samples(clock_rate, frame_duration);
+
+		Self {
+			rtp: RtpPacketizer::new(packetizer, payload_type, ssrc, mtu),
+			clock_rate,
+			frame_samples: u32::try_from(samples).unwrap_or(u32::MAX),
+			talkspurt_start: true,
+		}
+	}
+
+	/// Returns the RTP clock rate of the stream, in Hz.
+	#[must_use]
+	pub fn clock_rate(&self) -> u32 {
+		self.clock_rate
+	}
+
+	/// Returns the amount by which the timestamp advances for each frame.
+	#[must_use]
+	pub fn frame_samples(&self) -> u32 {
+		self.frame_samples
+	}
+
+	/// Returns whether the next packet sent will begin a talkspurt.
+	#[must_use]
+	pub fn is_talkspurt_start(&self) -> bool {
+		self.talkspurt_start
+	}
+
+	/// Returns the underlying [`RtpPacketizer`], for inspecting or setting its
+	/// sequence number and timestamp.
+	///
+	/// [`RtpPacketizer`]: struct.RtpPacketizer.html
+	#[must_use]
+	pub fn rtp(&self) -> &RtpPacketizer<P> {
+		&self.rtp
+	}
+
+	/// Returns the underlying [`RtpPacketizer`], mutably.
+	///
+	/// [`RtpPacketizer`]: struct.RtpPacketizer.html
+	pub fn rtp_mut(&mut self) -> &mut RtpPacketizer<P> {
+		&mut self.rtp
+	}
+
+	/// Splits a single encoded frame into packets, and then advances the
+	/// timestamp by one frame.
+	pub fn push(&mut self, frame: &[u8]) -> Vec<Rtp> {
+		self.push_frames(frame, 1)
+	}
+
+	/// Splits `frame`, holding `frames` consecutive encoded frames, into packets,
+	/// and then advances the timestamp by that many frames.
+	///
+	/// If the frame cannot fit within the MTU, no packets are sent and the next
+	/// packet still begins the talkspurt.
+	pub fn push_frames(&mut self, frame: &[u8], frames: u32) -> Vec<Rtp> {
+		let mut out = self
+			.rtp
+			.packetize(frame, self.frame_samples.wrapping_mul(frames));
+
+		for (i, pkt) in out.iter_mut().enumerate() {
+			pkt.marker = u8::from(i == 0 && self.talkspurt_start);
+		}
+		if !out.is_empty() {
+			self.talkspurt_start = false;
+		}
+
+		out
+	}
+
+	/// Skips `frames` frames which are not sent during silence, advancing the
+	/// timestamp past them. The next packet sent begins a new talkspurt.
+	pub fn skip(&mut self, frames: u32) {
+		if frames == 0 {
+			return;
+		}
+
+		let timestamp = self.rtp.timestamp() + self.frame_samples.wrapping_mul(frames);
+		self.rtp.set_timestamp(timestamp);
+		self.talkspurt_start = true;
+	}
+
+	/// Skips a period of silence lasting `duration`, rounded down to a whole number
+	/// of frames.
+	pub fn skip_duration(&mut self, duration: Duration) {
+		let samples = duration_samples(self.clock_rate, duration);
+		let frames = samples / u128::from(self.frame_samples.max(1));
+
+		self.skip(u32::try_from(frames).unwrap_or(u32::MAX));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct Chunks;
+
+	impl Packetizer for Chunks {
+		fn packetize(&mut self, frame: &[u8], max_payload: usize) -> Vec<Vec<u8>> {
+			frame.chunks(max_payload).map(<[u8]>::to_vec).collect()
+		}
+	}
+
+	#[test]
+	fn audio_marker_only_on_talkspurt_start() {
+		let mut audio = AudioPacketizer::new(
+			Chunks,
+			RtpType::Dynamic(111),
+			1,
+			FIXED_HEADER_LEN + 10,
+			48_000,
+			Duration::from_millis(20),
+		);
+
+		let markers = |pkts: Vec<Rtp>| pkts.iter().map(|p| p.marker).collect::<Vec<_>>();
+		assert_eq!(markers(audio.push(&[0; 25])), [1, 0, 0]);
+		assert_eq!(markers(audio.push(&[0; 5])), [0]);
+
+		audio.skip(2);
+		assert_eq!(markers(audio.push(&[0; 15])), [1, 0]);
+		assert_eq!(audio.rtp().timestamp(), Wrap32::new(960 * 5));
+	}
+}
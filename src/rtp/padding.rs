@@ -0,0 +1,160 @@
+use super::{ext, MutableRtpPacket, RtpPacket};
+use pnet_macros_support::packet::{MutablePacket, Packet};
+
+/// Returns the number of padding bytes at the end of the RTP packet `pkt`.
+///
+/// This is `0` if the padding bit is unset, the count octet is zero, or the header
+/// is truncated, and is clamped so that padding never extends into the CSRC list
+/// or header extension.
+pub(crate) fn padding_len(pkt: &[u8]) -> usize {
+	match (pkt.first(), pkt.last()) {
+		(Some(b), Some(&count)) if b & 0b0010_0000 != 0 =>
+			ext::extension_range(pkt).map_or(0, |ext| usize::from(count).min(pkt.len() - ext.end)),
+		_ => 0,
+	}
+}
+
+impl RtpPacket<'_> {
+	/// Returns the number of padding bytes at the end of this packet.
+	///
+	/// When the padding bit is set, the final byte of the packet counts the padding
+	/// bytes to be ignored, including itself. This is `0` if the padding bit is unset,
+	/// and malformed counts are clamped to the bytes following the header.
+	#[must_use]
+	pub fn padding_len(&self) -> usize {
+		padding_len(self.packet())
+	}
+
+	/// Returns this packet's [`payload`], excluding any padding.
+	///
+	/// [`payload`]: #method.payload
+	#[must_use]
+	pub fn payload_without_padding(&self) -> &[u8] {
+		let pad = self.padding_len();
+		let payload = self.payload();
+
+		&payload[..payload.len().saturating_sub(pad)]
+	}
+}
+
+impl MutableRtpPacket<'_> {
+	/// Returns the number of padding bytes at the end of this packet.
+	///
+	/// See [`RtpPacket::padding_len`] for more information.
+	///
+	/// [`RtpPacket::padding_len`]: struct.RtpPacket.html#method.padding_len
+	#[must_use]
+	pub fn padding_len(&self) -> usize {
+		padding_len(self.packet())
+	}
+
+	/// Returns this packet's [`payload`], excluding any padding.
+	///
+	/// [`payload`]: #method.payload
+	#[must_use]
+	pub fn payload_without_padding(&self) -> &[u8] {
+		let pad = self.padding_len();
+		let payload = self.payload();
+
+		&payload[..payload.len().saturating_sub(pad)]
+	}
+
+	/// Returns this packet's [`payload`] mutably, excluding any padding.
+	///
+	/// [`payload`]: #method.payload
+	pub fn payload_without_padding_mut(&mut self) -> &mut [u8] {
+		let pad = self.padding_len();
+		let payload = self.payload_mut();
+		let end = payload.len().saturating_sub(pad);
+
+		&mut payload[..end]
+	}
+
+	/// Replaces any padding at the end of the first `len` bytes of this packet with
+	/// `pad` bytes, returning the new packet length.
+	///
+	/// Pad bytes are zeroed, save for the final count octet, and the padding bit is
+	/// set. A `pad` of `0` removes any existing padding and clears the padding bit.
+	///
+	/// Returns `None` if the header is truncated, or the buffer lacks the space.
+	pub fn write_padding(&mut self, len: usize, pad: u8) -> Option<usize> {
+		let pkt = self.packet().get(..len)?;
+		ext::extension_range(pkt)?;
+
+		let unpadded = len - padding_len(pkt);
+		let new_len = unpadded + usize::from(pad);
+
+		let buf = self.packet_mut();
+		let out = buf.get_mut(unpadded..new_len)?;
+		if let Some((count, rest)) = out.split_last_mut() {
+			rest.fill(0);
+			*count = pad;
+			buf[0] |= 0b0010_0000;
+		} else {
+			buf[0] &= !0b0010_0000;
+		}
+
+		Some(new_len)
+	}
+
+	/// Pads the first `len` bytes of this packet to a multiple of `block` bytes,
+	/// returning the new packet length.
+	///
+	/// This is useful for cipher alignment or disguising payload sizes. Any existing
+	/// padding is replaced, and no padding is added if the unpadded packet is already
+	/// aligned.
+	///
+	/// Returns `None` if `block` is `0` or larger than the 256 bytes which padding
+	/// can align to, the header is truncated, or the buffer lacks the space.
+	pub fn pad_to_block(&mut self, len: usize, block: usize) -> Option<usize> {
+		if !(1..=256).contains(&block) {
+			return None;
+		}
+
+		let unpadded = len.checked_sub(padding_len(self.packet().get(..len)?))?;
+		let pad = u8::try_from((block - unpadded % block) % block).ok()?;
+
+		self.write_padding(len, pad)
+	}
+
+	/// Removes any padding from the first `len` bytes of this packet in place,
+	/// clearing the padding bit.
+	///
+	/// Padding bytes are zeroed. Returns the new length of the packet, to which
+	/// the caller may shorten the datagram (this is `len` if the packet is
+	/// unpadded).
+	///
+	/// Returns `None` if `len` exceeds the buffer.
+	pub fn strip_padding(&mut self, len: usize) -> Option<usize> {
+		let pad = padding_len(self.packet().get(..len)?);
+		if pad == 0 {
+			return Some(len);
+		}
+
+		let buf = self.packet_mut();
+		buf[len - pad..len].fill(0);
+		buf[0] &= !0b0010_0000;
+
+		Some(len - pad)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn strip_padding_within_larger_buffer() {
+		let mut buf = [0u8; 64];
+		buf[0] = 0x80;
+		let mut pkt = MutableRtpPacket::new(&mut buf).unwrap();
+		assert_eq!(pkt.write_padding(20, 4), Some(24));
+		assert_eq!(pkt.strip_padding(24), Some(20));
+		assert_eq!(buf[0], 0x80);
+		assert!(buf[20..24].iter().all(|&b| b == 0));
+
+		let mut pkt = MutableRtpPacket::new(&mut buf).unwrap();
+		assert_eq!(pkt.strip_padding(20), Some(20));
+		assert_eq!(pkt.strip_padding(65), None);
+	}
+}
@@ -0,0 +1,223 @@
+//! Redundant audio data, carrying earlier encodings of media alongside the
+//! current one.
+//!
+//! See [RFC 2198](https://tools.ietf.org/html/rfc2198).
+
+use super::RtpType;
+use alloc::{vec, vec::Vec};
+use pnet_macros_support::packet::PrimitiveValues;
+
+/// Largest timestamp offset of a redundant block.
+pub const MAX_TIMESTAMP_OFFSET: u16 = 0x3fff;
+
+/// Largest length of a redundant block, in bytes.
+pub const MAX_BLOCK_LEN: usize = 0x3ff;
+
+/// Length of the header of each redundant block.
+const REDUNDANT_HEADER_LEN: usize = 4;
+
+/// Length of the header of the primary block.
+const PRIMARY_HEADER_LEN: usize = 1;
+
+/// A single encoding within a RED payload.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RedBlock<'a> {
+	/// Payload type of this encoding.
+	pub payload_type: RtpType,
+	/// Number of timestamp units by which this encoding precedes the packet's
+	/// timestamp.
+	///
+	/// This is always `0` for the primary encoding.
+	pub timestamp_offset: u16,
+	/// Whether this is the primary (*i.e.*, latest) encoding, which is always last.
+	pub primary: bool,
+	/// Encoded media.
+	pub data: &'a [u8],
+}
+
+/// Iterator over the blocks of a RED payload, from oldest to the primary encoding.
+///
+/// Iteration ends early if the headers or block lengths are truncated.
+#[derive(Clone, Debug)]
+pub struct RedBlocks<'a> {
+	headers: &'a [u8],
+	data: &'a [u8],
+}
+
+impl<'a> RedBlocks<'a> {
+	/// Creates an iterator over the blocks of the RED payload `payload`.
+	#[must_use]
+	pub fn new(payload: &'a [u8]) -> Self {
+		let mut header_len = 0;
+		while let Some(&b) = payload.get(header_len) {
+			if b & 0x80 == 0 {
+				header_len += PRIMARY_HEADER_LEN;
+				break;
+			}
+			header_len += REDUNDANT_HEADER_LEN;
+		}
+
+		let header_len = header_len.min(payload.len());
+
+		Self {
+			headers: &payload[..header_len],
+			data: &payload[header_len..],
+		}
+	}
+}
+
+impl<'a> Iterator for RedBlocks<'a> {
+	type Item = RedBlock<'a>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let &first = self.headers.first()?;
+		let payload_type = RtpType::new(first & 0x7f);
+
+		if first & 0x80 == 0 {
+			let data = self.data;
+			self.headers = &[];
+			self.data = &[];
+
+			return Some(RedBlock {
+				payload_type,
+				timestamp_offset: 0,
+				primary: true,
+				data,
+			});
+		}
+
+		let Some(header) = self.headers.get(..REDUNDANT_HEADER_LEN) else {
+			self.headers = &[];
+			return None;
+		};
+		let timestamp_offset = (u16::from(header[1]) << 6) | (u16::from(header[2]) >> 2);
+		let len = (usize::from(header[2] & 0b11) << 8) | usize::from(header[3]);
+
+		let Some(data) = self.data.get(..len) else {
+			self.headers = &[];
+			return None;
+		};
+		self.headers = &self.headers[REDUNDANT_HEADER_LEN..];
+		self.data = &self.data[len..];
+
+		Some(RedBlock {
+			payload_type,
+			timestamp_offset,
+			primary: false,
+			data,
+		})
+	}
+}
+
+/// Builder for RED payloads, packing redundant encodings ahead of a primary
+/// encoding.
+///
+/// Redundant blocks should be added from oldest to newest.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RedPayloadBuilder {
+	redundant: Vec<(RtpType, u16, Vec<u8>)>,
+	primary_type: RtpType,
+	primary: Vec<u8>,
+}
+
+impl RedPayloadBuilder {
+	/// Creates a builder whose primary encoding has `payload_type`.
+	#[must_use]
+	pub fn new(payload_type: RtpType) -> Self {
+		Self {
+			redundant: Vec::new(),
+			primary_type: payload_type,
+			primary: Vec::new(),
+		}
+	}
+
+	/// Adds a redundant encoding of `payload_type`, sampled `timestamp_offset` units
+	/// before the primary encoding.
+	///
+	/// Blocks whose offset exceeds [`MAX_TIMESTAMP_OFFSET`] or whose length exceeds
+	/// [`MAX_BLOCK_LEN`] cannot be described by a RED header, and are ignored.
+	///
+	/// [`MAX_TIMESTAMP_OFFSET`]: constant.MAX_TIMESTAMP_OFFSET.html
+	/// [`MAX_BLOCK_LEN`]: constant.MAX_BLOCK_LEN.html
+	pub fn redundant(
+		&mut self,
+		payload_type: RtpType,
+		timestamp_offset: u16,
+		data: &[u8],
+	) -> &mut Self {
+		if timestamp_offset <= MAX_TIMESTAMP_OFFSET && data.len() <= MAX_BLOCK_LEN {
+			self.redundant
+				.push((payload_type, timestamp_offset, data.to_vec()));
+		}
+		self
+	}
+
+	/// Sets the primary encoding.
+	pub fn primary(&mut self, data: &[u8]) -> &mut Self {
+		self.primary = data.to_vec();
+		self
+	}
+
+	/// Removes all redundant encodings.
+	pub fn clear_redundant(&mut self) -> &mut Self {
+		self.redundant.clear();
+		self
+	}
+
+	/// Total number of bytes needed to serialise the payload.
+	#[must_use]
+	pub fn wire_size(&self) -> usize {
+		self.redundant
+			.iter()
+			.map(|(_, _, d)| REDUNDANT_HEADER_LEN + d.len())
+			.sum::<usize>()
+			+ PRIMARY_HEADER_LEN
+			+ self.primary.len()
+	}
+
+	/// Serialises the payload into the start of `buf`, returning the number of
+	/// bytes written.
+	///
+	/// Returns `None` if `buf` is too small.
+	pub fn build_into(&self, buf: &mut [u8]) -> Option<usize> {
+		let len = self.wire_size();
+		let buf = buf.get_mut(..len)?;
+		let mut offset = 0;
+
+		for (pt, ts_offset, data) in &self.redundant {
+			#[allow(clippy::cast_possible_truncation)]
+			let header = [
+				0x80 | (pt.to_primitive_values().0 & 0x7f),
+				(ts_offset >> 6) as u8,
+				((ts_offset << 2) as u8) | (data.len() >> 8) as u8,
+				data.len() as u8,
+			];
+			buf[offset..offset + REDUNDANT_HEADER_LEN].copy_from_slice(&header);
+			offset += REDUNDANT_HEADER_LEN;
+		}
+
+		buf[offset] = self.primary_type.to_primitive_values().0 & 0x7f;
+		offset += PRIMARY_HEADER_LEN;
+
+		for data in self
+			.redundant
+			.iter()
+			.map(|(_, _, d)| d)
+			.chain(core::iter::once(&self.primary))
+		{
+			buf[offset..offset + data.len()].copy_from_slice(data);
+			offset += data.len();
+		}
+
+		Some(offset)
+	}
+
+	/// Serialises the payload into a newly allocated buffer.
+	#[must_use]
+	pub fn to_vec(&self) -> Vec<u8> {
+		let mut out = vec![0u8; self.wire_size()];
+		self.build_into(&mut out);
+
+		out
+	}
+}
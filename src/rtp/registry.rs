@@ -0,0 +1,688 @@
+use super::{
+	depacketizer::{DepacketizedFrame, Depacketizer, RtpDepacketizer},
+	packetizer::Packetizer,
+	Rtp,
+	RtpPacket,
+	RtpType,
+};
+use crate::wrap::Wrap16;
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::time::Duration;
+use pnet_macros_support::packet::FromPacket;
+
+/// Broad class of media carried by a payload type.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum MediaKind {
+	/// Audio, such as speech or music.
+	Audio,
+	/// Video.
+	Video,
+	/// Multiplexed audio and video, such as an MPEG-2 transport stream.
+	AudioVideo,
+	/// Real-time text.
+	Text,
+	/// Other application data.
+	Application,
+}
+
+impl MediaKind {
+	/// Returns the kind of media carried by a well-known encoding name, as used in
+	/// SDP `a=rtpmap` lines.
+	///
+	/// Names are matched case-insensitively.
+	#[must_use]
+	pub fn from_encoding(name: &str) -> Option<Self> {
+		const AUDIO: &[&str] = &[
+			"AMR",
+			"AMR-WB",
+			"CN",
+			"DVI4",
+			"EVS",
+			"G722",
+			"G723",
+			"G726-16",
+			"G726-24",
+			"G726-32",
+			"G726-40",
+			"G728",
+			"G729",
+			"GSM",
+			"iLBC",
+			"L16",
+			"L24",
+			"L8",
+			"LPC",
+			"MPA",
+			"MPEG4-GENERIC",
+			"MP4A-LATM",
+			"multiopus",
+			"opus",
+			"PCMA",
+			"PCMU",
+			"QCELP",
+			"speex",
+			"telephone-event",
+			"vorbis",
+		];
+		const VIDEO: &[&str] = &[
+			"AV1",
+			"CelB",
+			"H261",
+			"H263",
+			"H263-1998",
+			"H263-2000",
+			"H264",
+			"H265",
+			"JPEG",
+			"MP4V-ES",
+			"MPV",
+			"nv",
+			"theora",
+			"VP8",
+			"VP9",
+		];
+
+		let matches = |list: &[&str]| list.iter().any(|n| n.eq_ignore_ascii_case(name));
+
+		if matches(AUDIO) {
+			Some(Self::Audio)
+		} else if matches(VIDEO) {
+			Some(Self::Video)
+		} else if name.eq_ignore_ascii_case("MP2T") {
+			Some(Self::AudioVideo)
+		} else if name.eq_ignore_ascii_case("t140") {
+			Some(Self::Text)
+		} else {
+			None
+		}
+	}
+}
+
+/// Description of the format negotiated for a payload type, as agreed by SDP
+/// `a=rtpmap` and `a=fmtp` lines.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayloadFormat {
+	/// Encoding name, such as `opus` or `H264`.
+	pub encoding: String,
+	/// RTP timestamp clock rate, in Hz.
+	pub clock_rate: u32,
+	/// Number of audio channels, if signalled.
+	pub channels: Option<u8>,
+	/// Kind of media carried, if known.
+	pub kind: Option<MediaKind>,
+	/// Format-specific parameters, such as those of an `a=fmtp` line.
+	pub parameters: Option<String>,
+}
+
+impl PayloadFormat {
+	/// Creates a format with the given `encoding` name and `clock_rate`, inferring
+	/// its media kind from well-known encoding names.
+	#[must_use]
+	pub fn new(encoding: &str, clock_rate: u32) -> Self {
+		Self {
+			encoding: encoding.into(),
+			clock_rate,
+			channels: None,
+			kind: MediaKind::from_encoding(encoding),
+			parameters: None,
+		}
+	}
+
+	/// Returns whether the RTP clock of this format runs slower than its audio is
+	/// sampled, as for G.722.
+	///
+	/// See [`RtpType::has_legacy_clock_rate`] for more information.
+	///
+	/// [`RtpType::has_legacy_clock_rate`]: enum.RtpType.html#method.has_legacy_clock_rate
+	#[must_use]
+	pub fn has_legacy_clock_rate(&self) -> bool {
+		self.encoding.eq_ignore_ascii_case("G722") && self.clock_rate == 8_000
+	}
+
+	/// Returns the audio sampling rate of this format, in Hz, which differs from
+	/// its clock rate only for G.722.
+	#[must_use]
+	pub fn sample_rate(&self) -> u32 {
+		if self.has_legacy_clock_rate() {
+			16_000
+		} else {
+			self.clock_rate
+		}
+	}
+}
+
+/// Mapping between payload types and the formats they carry, for payload types
+/// whose meaning is not fixed by the static table (*i.e.*, [`RtpType::Dynamic`]).
+///
+/// Entries may also override static payload types. Each payload type appears at
+/// most once in the registry.
+///
+/// [`RtpType::Dynamic`]: enum.RtpType.html#variant.Dynamic
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PayloadRegistry {
+	entries: Vec<(RtpType, PayloadFormat)>,
+}
+
+impl PayloadRegistry {
+	/// Creates an empty payload registry.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Binds `payload_type` to `format`, returning any format it replaced.
+	pub fn insert(
+		&mut self,
+		payload_type: RtpType,
+		format: PayloadFormat,
+	) -> Option<PayloadFormat> {
+		let old = self.remove(payload_type);
+		self.entries.push((payload_type, format));
+
+		old
+	}
+
+	/// Removes the binding for `payload_type`, returning its format.
+	pub fn remove(&mut self, payload_type: RtpType) -> Option<PayloadFormat> {
+		let idx = self
+			.entries
+			.iter()
+			.position(|(pt, _)| *pt == payload_type)?;
+		Some(self.entries.swap_remove(idx).1)
+	}
+
+	/// Returns the format bound to `payload_type`.
+	#[must_use]
+	pub fn get(&self, payload_type: RtpType) -> Option<&PayloadFormat> {
+		self.entries
+			.iter()
+			.find_map(|(pt, fmt)| (*pt == payload_type).then_some(fmt))
+	}
+
+	/// Returns the format bound to `payload_type`, mutably.
+	pub fn get_mut(&mut self, payload_type: RtpType) -> Option<&mut PayloadFormat> {
+		self.entries
+			.iter_mut()
+			.find_map(|(pt, fmt)| (*pt == payload_type).then_some(fmt))
+	}
+
+	/// Returns the first payload type bound to the encoding `name`, matched
+	/// case-insensitively.
+	#[must_use]
+	pub fn payload_type(&self, name: &str) -> Option<RtpType> {
+		self.entries
+			.iter()
+			.find_map(|(pt, fmt)| fmt.encoding.eq_ignore_ascii_case(name).then_some(*pt))
+	}
+
+	/// Returns an iterator over all `(payload_type, format)` bindings.
+	pub fn iter(&self) -> impl Iterator<Item = (RtpType, &PayloadFormat)> + '_ {
+		self.entries.iter().map(|(pt, fmt)| (*pt, fmt))
+	}
+
+	/// Returns the clock rate of `payload_type`, preferring a registered format
+	/// over the static table.
+	#[must_use]
+	pub fn clock_rate(&self, payload_type: RtpType) -> Option<u32> {
+		self.get(payload_type)
+			.map(|fmt| fmt.clock_rate)
+			.or_else(|| payload_type.clock_rate())
+	}
+
+	/// Returns the audio sampling rate of `payload_type`, preferring a registered
+	/// format over the static table.
+	///
+	/// This differs from the [`clock_rate`] only for G.722.
+	///
+	/// [`clock_rate`]: #method.clock_rate
+	#[must_use]
+	pub fn sample_rate(&self, payload_type: RtpType) -> Option<u32> {
+		self.get(payload_type)
+			.map(PayloadFormat::sample_rate)
+			.or_else(|| payload_type.sample_rate())
+	}
+
+	/// Returns the channel count of `payload_type`, preferring a registered format
+	/// over the static table.
+	#[must_use]
+	pub fn channels(&self, payload_type: RtpType) -> Option<u8> {
+		match self.get(payload_type) {
+			Some(fmt) => fmt
+				.channels
+				.or_else(|| (fmt.kind == Some(MediaKind::Audio)).then_some(1)),
+			None => payload_type.channels(),
+		}
+	}
+}
+
+type DepacketizerFactory<'a> = Box<dyn FnMut() -> Box<dyn Depacketizer + 'a> + 'a>;
+
+type Stream<'a> = (u32, RtpType, RtpDepacketizer<Box<dyn Depacketizer + 'a>>);
+
+/// Sequence number state shared by every payload type of one SSRC.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct SourceSequence {
+	ssrc: u32,
+	last: Wrap16,
+	payload_type: RtpType,
+	gap: bool,
+}
+
+/// A frame reassembled by a [`CodecRegistry`], along with the stream it belongs
+/// to.
+///
+/// [`CodecRegistry`]: struct.CodecRegistry.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamFrame {
+	/// SSRC of the stream.
+	pub ssrc: u32,
+	/// Payload type of the frame's packets.
+	pub payload_type: RtpType,
+	/// The reassembled frame.
+	pub frame: DepacketizedFrame,
+}
+
+/// [`PayloadRegistry`] which also binds payload types to the codecs handling
+/// them, forming a single point where frames are packetized and received
+/// packets are turned back into frames.
+///
+/// Each payload type may be bound to a [`Packetizer`] for sending, and to a
+/// factory of [`Depacketizer`]s for receiving. As depacketizers hold the state of
+/// a single stream, a new one is made for each SSRC and payload type seen.
+///
+/// Sequence numbers are tracked per SSRC, so that packets of another payload
+/// type on the same SSRC (such as telephone events, or after a codec switch) are
+/// not mistaken for losses. A gap is reported to the streams of the packets
+/// either side of it.
+///
+/// [`PayloadRegistry`]: struct.PayloadRegistry.html
+/// [`Packetizer`]: packetizer/trait.Packetizer.html
+/// [`Depacketizer`]: depacketizer/trait.Depacketizer.html
+pub struct CodecRegistry<'a> {
+	formats: PayloadRegistry,
+	packetizers: Vec<(RtpType, Box<dyn Packetizer + 'a>)>,
+	depacketizers: Vec<(RtpType, DepacketizerFactory<'a>)>,
+	streams: Vec<Stream<'a>>,
+	sequences: Vec<SourceSequence>,
+	timeout: Duration,
+}
+
+impl<'a> CodecRegistry<'a> {
+	/// Creates a registry of the payload types in `formats`, with no codecs bound.
+	///
+	/// Streams give up on unfinished frames after `timeout`.
+	#[must_use]
+	pub fn new(formats: PayloadRegistry, timeout: Duration) -> Self {
+		Self {
+			formats,
+			packetizers: Vec::new(),
+			depacketizers: Vec::new(),
+			streams: Vec::new(),
+			sequences: Vec::new(),
+			timeout,
+		}
+	}
+
+	/// Returns the formats bound to each payload type.
+	#[must_use]
+	pub fn formats(&self) -> &PayloadRegistry {
+		&self.formats
+	}
+
+	/// Returns the formats bound to each payload type, mutably.
+	pub fn formats_mut(&mut self) -> &mut PayloadRegistry {
+		&mut self.formats
+	}
+
+	/// Binds `payload_type` to `packetizer` for sending, replacing any existing
+	/// packetizer.
+	pub fn bind_packetizer(&mut self, payload_type: RtpType, packetizer: impl Packetizer + 'a) {
+		self.packetizers.retain(|(pt, _)| *pt != payload_type);
+		self.packetizers.push((payload_type, Box::new(packetizer)));
+	}
+
+	/// Binds `payload_type` to `factory` for receiving, replacing any existing
+	/// factory. `factory` is called to create a depacketizer for each new stream.
+	///
+	/// Streams already receiving this payload type are unaffected.
+	pub fn bind_depacketizer<D: Depacketizer + 'a>(
+		&mut self,
+		payload_type: RtpType,
+		mut factory: impl FnMut() -> D + 'a,
+	) {
+		self.depacketizers.retain(|(pt, _)| *pt != payload_type);
+		self.depacketizers.push((
+			payload_type,
+			Box::new(move || Box::new(factory()) as Box<dyn Depacketizer + 'a>),
+		));
+	}
+
+	/// Removes the codecs bound to `payload_type`, along with any streams
+	/// receiving it.
+	///
+	/// Returns `true` if any codec was bound.
+	pub fn unbind(&mut self, payload_type: RtpType) -> bool {
+		let before = self.packetizers.len() + self.depacketizers.len();
+		self.packetizers.retain(|(pt, _)| *pt != payload_type);
+		self.depacketizers.retain(|(pt, _)| *pt != payload_type);
+		self.streams.retain(|(_, pt, _)| *pt != payload_type);
+
+		before != self.packetizers.len() + self.depacketizers.len()
+	}
+
+	/// Returns the packetizer bound to `payload_type`, mutably.
+	pub fn packetizer_mut(&mut self, payload_type: RtpType) -> Option<&mut (dyn Packetizer + 'a)> {
+		self.packetizers
+			.iter_mut()
+			.find_map(|(pt, p)| (*pt == payload_type).then_some(&mut **p))
+	}
+
+	/// Splits `frame` into payloads of at most `max_payload` bytes using the
+	/// packetizer bound to `payload_type`.
+	///
+	/// Returns `None` if no packetizer is bound.
+	pub fn packetize(
+		&mut self,
+		payload_type: RtpType,
+		frame: &[u8],
+		max_payload: usize,
+	) -> Option<Vec<Vec<u8>>> {
+		self.packetizer_mut(payload_type)
+			.map(|p| p.packetize(frame, max_payload))
+	}
+
+	/// Adds `packet`, which arrived (or was released for playout) at `now`, to its
+	/// stream.
+	///
+	/// Returns `false` if no depacketizer is bound to its payload type. Such
+	/// packets still advance the sequence number of their SSRC.
+	pub fn push(&mut self, packet: Rtp, now: Duration) -> bool {
+		let (ssrc, payload_type) = (packet.ssrc, packet.payload_type);
+		let gap_before = self.advance(ssrc, packet.sequence, payload_type);
+		if let Some(previous) = gap_before {
+			self.stream_lost(ssrc, previous);
+		}
+
+		let existing = self
+			.streams
+			.iter()
+			.position(|(s, pt, _)| *s == ssrc && *pt == payload_type);
+		let stream = if let Some(idx) = existing {
+			idx
+		} else {
+			let Some((_, factory)) = self
+				.depacketizers
+				.iter_mut()
+				.find(|(pt, _)| *pt == payload_type)
+			else {
+				return false;
+			};
+			let depacketizer = RtpDepacketizer::new(factory(), self.timeout);
+			self.streams.push((ssrc, payload_type, depacketizer));
+			self.streams.len() - 1
+		};
+
+		let stream = &mut self.streams[stream].2;
+		if gap_before.is_some() {
+			stream.lost();
+		}
+		stream.push_unsequenced(packet, now);
+
+		true
+	}
+
+	/// Records `sequence` as the latest of `ssrc`, returning the payload type of
+	/// the previous packet if any packets are missing between the two.
+	fn advance(&mut self, ssrc: u32, sequence: Wrap16, payload_type: RtpType) -> Option<RtpType> {
+		let next = SourceSequence {
+			ssrc,
+			last: sequence,
+			payload_type,
+			gap: false,
+		};
+
+		let Some(source) = self.sequences.iter_mut().find(|s| s.ssrc == ssrc) else {
+			self.sequences.push(next);
+			return None;
+		};
+		let gap = source.gap || sequence != source.last + 1;
+		let previous = source.payload_type;
+		*source = next;
+
+		gap.then_some(previous)
+	}
+
+	fn stream_lost(&mut self, ssrc: u32, payload_type: RtpType) {
+		for (_, _, stream) in self
+			.streams
+			.iter_mut()
+			.filter(|(s, pt, _)| *s == ssrc && *pt == payload_type)
+		{
+			stream.lost();
+		}
+	}
+
+	/// Adds a received (or demultiplexed) RTP packet to its stream.
+	///
+	/// See [`push`] for more information.
+	///
+	/// [`push`]: #method.push
+	pub fn push_packet(&mut self, packet: &RtpPacket<'_>, now: Duration) -> bool {
+		self.push(packet.from_packet(), now)
+	}
+
+	/// Records that a packet of `ssrc` is missing.
+	///
+	/// As the payload type of the missing packet is unknown, this is reported to the
+	/// streams of the packets either side of it once the next packet arrives.
+	pub fn lost(&mut self, ssrc: u32) {
+		if let Some(source) = self.sequences.iter_mut().find(|s| s.ssrc == ssrc) {
+			source.gap = true;
+		}
+	}
+
+	/// Gives up on frames in progress which began more than the timeout before
+	/// `now`.
+	pub fn expire(&mut self, now: Duration) {
+		for (_, _, stream) in &mut self.streams {
+			stream.expire(now);
+		}
+	}
+
+	/// Forgets the streams of `ssrc`, such as when it leaves the session.
+	pub fn remove_stream(&mut self, ssrc: u32) {
+		self.streams.retain(|(s, ..)| *s != ssrc);
+		self.sequences.retain(|s| s.ssrc != ssrc);
+	}
+
+	/// Returns the next reassembled frame of any stream, if any.
+	pub fn pop(&mut self) -> Option<StreamFrame> {
+		self.streams
+			.iter_mut()
+			.find_map(|(ssrc, payload_type, stream)| {
+				stream.pop().map(|frame| StreamFrame {
+					ssrc: *ssrc,
+					payload_type: *payload_type,
+					frame,
+				})
+			})
+	}
+}
+
+impl core::fmt::Debug for CodecRegistry<'_> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("CodecRegistry")
+			.field("formats", &self.formats)
+			.field(
+				"packetizers",
+				&self
+					.packetizers
+					.iter()
+					.map(|(pt, _)| *pt)
+					.collect::<Vec<_>>(),
+			)
+			.field(
+				"depacketizers",
+				&self
+					.depacketizers
+					.iter()
+					.map(|(pt, _)| *pt)
+					.collect::<Vec<_>>(),
+			)
+			.field(
+				"streams",
+				&self
+					.streams
+					.iter()
+					.map(|(ssrc, pt, _)| (*ssrc, *pt))
+					.collect::<Vec<_>>(),
+			)
+			.field("timeout", &self.timeout)
+			.finish()
+	}
+}
+
+impl RtpType {
+	/// Returns the kind of media carried by this payload type.
+	///
+	/// Static payload types are classified from the IANA table. Other payload types
+	/// are looked up in `registry`, which also overrides the static table.
+	#[must_use]
+	pub fn media_kind(self, registry: Option<&PayloadRegistry>) -> Option<MediaKind> {
+		if let Some(fmt) = registry.and_then(|r| r.get(self)) {
+			return fmt.kind;
+		}
+
+		match self {
+			Self::Pcmu
+			| Self::Gsm
+			| Self::G723
+			| Self::Dvi4(_)
+			| Self::Lpc
+			| Self::Pcma
+			| Self::G722
+			| Self::L16Stereo
+			| Self::L16Mono
+			| Self::Qcelp
+			| Self::Cn
+			| Self::Mpa
+			| Self::G728
+			| Self::G729 => Some(MediaKind::Audio),
+			Self::CelB | Self::Jpeg | Self::Nv | Self::H261 | Self::Mpv | Self::H263 =>
+				Some(MediaKind::Video),
+			Self::Mp2t => Some(MediaKind::AudioVideo),
+			_ => None,
+		}
+	}
+
+	/// Returns whether this payload type carries audio (including multiplexed
+	/// audio and video).
+	///
+	/// See [`media_kind`] for more information.
+	///
+	/// [`media_kind`]: #method.media_kind
+	#[must_use]
+	pub fn is_audio(self, registry: Option<&PayloadRegistry>) -> bool {
+		matches!(
+			self.media_kind(registry),
+			Some(MediaKind::Audio | MediaKind::AudioVideo)
+		)
+	}
+
+	/// Returns whether this payload type carries video (including multiplexed
+	/// audio and video).
+	///
+	/// See [`media_kind`] for more information.
+	///
+	/// [`media_kind`]: #method.media_kind
+	#[must_use]
+	pub fn is_video(self, registry: Option<&PayloadRegistry>) -> bool {
+		matches!(
+			self.media_kind(registry),
+			Some(MediaKind::Video | MediaKind::AudioVideo)
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use pnet_macros_support::packet::PrimitiveValues;
+
+	struct PerPacket;
+
+	impl Depacketizer for PerPacket {
+		fn depacketize(&mut self, payloads: &[&[u8]]) -> Option<Vec<u8>> {
+			Some(payloads.concat())
+		}
+
+		fn frame_per_packet(&self) -> bool {
+			true
+		}
+	}
+
+	fn packet(payload_type: u8, sequence: u16) -> Rtp {
+		Rtp {
+			version: 2,
+			padding: 0,
+			extension: 0,
+			csrc_count: 0,
+			marker: 0,
+			payload_type: RtpType::new(payload_type),
+			sequence: sequence.into(),
+			timestamp: u32::from(sequence).into(),
+			ssrc: 1,
+			csrc_list: Vec::new(),
+			payload: alloc::vec![0; 4],
+		}
+	}
+
+	fn completeness(registry: &mut CodecRegistry<'_>) -> Vec<(u8, bool)> {
+		core::iter::from_fn(|| registry.pop())
+			.map(|f| (f.payload_type.to_primitive_values().0, f.frame.complete))
+			.collect()
+	}
+
+	#[test]
+	fn other_payload_types_are_not_losses() {
+		let mut registry = CodecRegistry::new(PayloadRegistry::default(), Duration::from_secs(1));
+		registry.bind_depacketizer(RtpType::new(111), || PerPacket);
+		registry.bind_depacketizer(RtpType::new(101), || PerPacket);
+
+		for (pt, seq) in [(111, 10), (101, 11), (101, 12), (111, 13), (111, 14)] {
+			assert!(registry.push(packet(pt, seq), Duration::ZERO));
+		}
+		assert!(completeness(&mut registry).iter().all(|&(_, c)| c));
+
+		assert!(!registry.push(packet(100, 15), Duration::ZERO));
+		registry.push(packet(111, 16), Duration::ZERO);
+		assert_eq!(completeness(&mut registry), [(111, true)]);
+	}
+
+	#[test]
+	fn gaps_mark_neighbouring_payload_types() {
+		let mut registry = CodecRegistry::new(PayloadRegistry::default(), Duration::from_secs(1));
+		registry.bind_depacketizer(RtpType::new(111), || PerPacket);
+		registry.bind_depacketizer(RtpType::new(101), || PerPacket);
+
+		for (pt, seq) in [(111, 10), (101, 11), (111, 13), (101, 14)] {
+			registry.push(packet(pt, seq), Duration::ZERO);
+		}
+		registry.lost(1);
+		registry.push(packet(101, 16), Duration::ZERO);
+
+		let mut frames = completeness(&mut registry);
+		frames.sort_unstable();
+		assert_eq!(
+			frames,
+			[
+				(101, false),
+				(101, false),
+				(101, true),
+				(111, false),
+				(111, true)
+			]
+		);
+	}
+}
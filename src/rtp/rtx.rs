@@ -0,0 +1,158 @@
+//! Retransmission of RTP packets in a separate stream.
+//!
+//! See [RFC 4588](https://tools.ietf.org/html/rfc4588).
+
+use super::{ext, padding::padding_len, MutableRtpPacket, RtpPacket, RtpType};
+use crate::wrap::Wrap16;
+use alloc::vec::Vec;
+use pnet_macros_support::packet::Packet;
+
+/// Length of the original sequence number (OSN) prefixed to an RTX payload.
+pub const OSN_LEN: usize = 2;
+
+/// Mapping between RTX payload types and the associated payload types (`apt`)
+/// they retransmit, as agreed by SDP `a=fmtp:<pt> apt=<apt>` lines.
+///
+/// Each RTX payload type and each associated payload type appear at most once
+/// in the map.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RtxMap {
+	entries: Vec<(RtpType, RtpType)>,
+}
+
+impl RtxMap {
+	/// Creates an empty RTX map.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Binds `rtx` as the payload type retransmitting `apt`, replacing any
+	/// existing binding for either.
+	pub fn insert(&mut self, rtx: RtpType, apt: RtpType) {
+		self.entries
+			.retain(|&(e_rtx, e_apt)| e_rtx != rtx && e_apt != apt);
+		self.entries.push((rtx, apt));
+	}
+
+	/// Removes the binding for the RTX payload type `rtx`, returning its
+	/// associated payload type.
+	pub fn remove(&mut self, rtx: RtpType) -> Option<RtpType> {
+		let idx = self.entries.iter().position(|&(e_rtx, _)| e_rtx == rtx)?;
+		Some(self.entries.swap_remove(idx).1)
+	}
+
+	/// Returns the payload type retransmitted by the RTX payload type `rtx`.
+	#[must_use]
+	pub fn apt(&self, rtx: RtpType) -> Option<RtpType> {
+		self.entries
+			.iter()
+			.find_map(|&(e_rtx, apt)| (e_rtx == rtx).then_some(apt))
+	}
+
+	/// Returns the RTX payload type used to retransmit `apt`.
+	#[must_use]
+	pub fn rtx(&self, apt: RtpType) -> Option<RtpType> {
+		self.entries
+			.iter()
+			.find_map(|&(rtx, e_apt)| (e_apt == apt).then_some(rtx))
+	}
+
+	/// Returns an iterator over all `(rtx, apt)` bindings.
+	pub fn iter(&self) -> impl Iterator<Item = (RtpType, RtpType)> + '_ {
+		self.entries.iter().copied()
+	}
+
+	/// Encapsulates `pkt` for retransmission on the RTX stream `rtx_ssrc`, using
+	/// the RTX payload type bound to its payload type.
+	///
+	/// See [`wrap`] for more information.
+	///
+	/// [`wrap`]: fn.wrap.html
+	#[must_use]
+	pub fn wrap(&self, pkt: &RtpPacket<'_>, rtx_ssrc: u32, rtx_seq: Wrap16) -> Option<Vec<u8>> {
+		wrap(pkt, self.rtx(pkt.get_payload_type())?, rtx_ssrc, rtx_seq)
+	}
+
+	/// Recovers the original packet of the media stream `ssrc` from the RTX packet
+	/// `pkt`, using the payload type associated with its RTX payload type.
+	///
+	/// See [`unwrap`] for more information.
+	///
+	/// [`unwrap`]: fn.unwrap.html
+	#[must_use]
+	pub fn unwrap(&self, pkt: &RtpPacket<'_>, ssrc: u32) -> Option<Vec<u8>> {
+		unwrap(pkt, self.apt(pkt.get_payload_type())?, ssrc)
+	}
+}
+
+/// Encapsulates `pkt` for retransmission, as packet `rtx_seq` of the RTX stream
+/// `rtx_ssrc` with payload type `rtx_pt`.
+///
+/// The original sequence number is prefixed to the media payload, and the
+/// header (including CSRCs, extensions, marker and timestamp) is otherwise
+/// kept. Any padding is removed.
+///
+/// Returns `None` if the CSRC list or header extension of `pkt` are truncated.
+#[must_use]
+pub fn wrap(
+	pkt: &RtpPacket<'_>,
+	rtx_pt: RtpType,
+	rtx_ssrc: u32,
+	rtx_seq: Wrap16,
+) -> Option<Vec<u8>> {
+	let bytes = pkt.packet();
+	let header = ext::extension_range(bytes)?.end;
+	let media = &bytes[header..bytes.len() - padding_len(bytes)];
+
+	let mut out = Vec::with_capacity(header + OSN_LEN + media.len());
+	out.extend_from_slice(&bytes[..header]);
+	out.extend_from_slice(&u16::from(pkt.get_sequence()).to_be_bytes());
+	out.extend_from_slice(media);
+
+	let mut rtx = MutableRtpPacket::new(&mut out)?;
+	rtx.set_padding(0);
+	rtx.set_payload_type(rtx_pt);
+	rtx.set_ssrc(rtx_ssrc);
+	rtx.set_sequence(rtx_seq);
+
+	Some(out)
+}
+
+/// Recovers the original packet from the RTX packet `pkt`, restoring its
+/// sequence number, payload type `apt` and media stream `ssrc`.
+///
+/// Any padding on the RTX packet is removed.
+///
+/// Returns `None` if `pkt` is too short to hold the original sequence number, or
+/// its CSRC list or header extension are truncated.
+#[must_use]
+pub fn unwrap(pkt: &RtpPacket<'_>, apt: RtpType, ssrc: u32) -> Option<Vec<u8>> {
+	let bytes = pkt.packet();
+	let header = ext::extension_range(bytes)?.end;
+	let body = bytes.get(header..bytes.len() - padding_len(bytes))?;
+	let osn = body.get(..OSN_LEN)?;
+
+	let mut out = Vec::with_capacity(header + body.len() - OSN_LEN);
+	out.extend_from_slice(&bytes[..header]);
+	out.extend_from_slice(&body[OSN_LEN..]);
+
+	let mut orig = MutableRtpPacket::new(&mut out)?;
+	orig.set_padding(0);
+	orig.set_payload_type(apt);
+	orig.set_ssrc(ssrc);
+	orig.set_sequence(Wrap16::new(u16::from_be_bytes([osn[0], osn[1]])));
+
+	Some(out)
+}
+
+/// Returns the original sequence number carried by the RTX packet `pkt`.
+///
+/// Returns `None` if `pkt` is too short to hold it, or its CSRC list or header
+/// extension are truncated.
+#[must_use]
+pub fn original_sequence(pkt: &RtpPacket<'_>) -> Option<Wrap16> {
+	let osn = pkt.media_payload()?.get(..OSN_LEN)?;
+
+	Some(Wrap16::new(u16::from_be_bytes([osn[0], osn[1]])))
+}
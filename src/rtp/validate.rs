@@ -0,0 +1,96 @@
+//! Opt-in validation of RTP packets.
+//!
+//! Parsing is deliberately lenient: [`RtpPacket::new`] accepts any buffer large
+//! enough to hold the fixed header. These checks catch the header fields which
+//! disagree with the buffer they describe.
+//!
+//! [`RtpPacket::new`]: ../struct.RtpPacket.html#method.new
+
+use super::{ext, RtpPacket};
+use alloc::vec::Vec;
+use pnet_macros_support::packet::Packet;
+
+/// A rule broken by an RTP packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Violation {
+	/// The packet is shorter than the 12-byte fixed header.
+	Truncated { len: usize },
+	/// The version field is not `2`.
+	BadVersion { version: u8 },
+	/// The CSRC list described by `csrc_count` runs past the end of the packet.
+	CsrcOverrun { csrc_count: u8 },
+	/// The `extension` bit is set, but the extension header or data run past the
+	/// end of the packet.
+	ExtensionOverrun,
+	/// The padding bit is set, but the packet holds no bytes after its header to
+	/// carry a padding count.
+	MissingPadding,
+	/// The padding count is zero, or exceeds the bytes following the header.
+	BadPadding { count: u8 },
+}
+
+/// Checks the RTP packet `pkt`, returning each [`Violation`] found (or an empty
+/// list if the packet is valid).
+///
+/// The version must be `2`, the CSRC list and any header extension must fit
+/// within the packet, and any padding count must be nonzero and fit within the
+/// bytes following the header.
+///
+/// Checking stops at a truncated fixed header, CSRC list, or extension, as the
+/// positions of later fields cannot be trusted.
+///
+/// [`Violation`]: enum.Violation.html
+#[must_use]
+pub fn validate(pkt: &[u8]) -> Vec<Violation> {
+	let mut out = Vec::new();
+
+	if pkt.len() < ext::FIXED_HEADER_LEN {
+		out.push(Violation::Truncated { len: pkt.len() });
+		return out;
+	}
+
+	let version = pkt[0] >> 6;
+	if version != 2 {
+		out.push(Violation::BadVersion { version });
+	}
+
+	if ext::csrc_end(pkt).is_none() {
+		out.push(Violation::CsrcOverrun {
+			csrc_count: pkt[0] & 0x0f,
+		});
+		return out;
+	}
+
+	let Some(header) = ext::extension_range(pkt) else {
+		out.push(Violation::ExtensionOverrun);
+		return out;
+	};
+
+	if pkt[0] & 0b0010_0000 != 0 {
+		let body = pkt.len() - header.end;
+		let count = pkt[pkt.len() - 1];
+
+		if body == 0 {
+			out.push(Violation::MissingPadding);
+		} else if count == 0 || usize::from(count) > body {
+			out.push(Violation::BadPadding { count });
+		}
+	}
+
+	out
+}
+
+impl RtpPacket<'_> {
+	/// Checks this packet's header fields against its length, returning each
+	/// [`Violation`] found (or an empty list if the packet is valid).
+	///
+	/// See [`validate`] for more information.
+	///
+	/// [`Violation`]: validate/enum.Violation.html
+	/// [`validate`]: validate/fn.validate.html
+	#[must_use]
+	pub fn validate(&self) -> Vec<Violation> {
+		validate(self.packet())
+	}
+}
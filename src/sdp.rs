@@ -0,0 +1,143 @@
+//! Parsing of the RTP payload and header extension mappings carried in SDP.
+//!
+//! *These are included when using the `"sdp"` feature.*
+//!
+//! This handles only the `a=rtpmap`, `a=fmtp` and `a=extmap` attributes (and
+//! `m=` lines, to classify payload types), ignoring all other lines. Full
+//! offer/answer handling is left to a dedicated SDP implementation.
+
+use crate::rtp::{
+	ext::{ExtensionMap, ExtensionUri},
+	MediaKind,
+	PayloadFormat,
+	PayloadRegistry,
+	RtpType,
+};
+
+/// Payload types and header extensions negotiated in a session description.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SdpMaps {
+	/// Formats bound to each payload type by `a=rtpmap` and `a=fmtp` lines.
+	pub payloads: PayloadRegistry,
+	/// Header extensions bound to each ID by `a=extmap` lines.
+	///
+	/// Extensions with unrecognised URIs are omitted.
+	pub extensions: ExtensionMap,
+}
+
+impl SdpMaps {
+	/// Collects the payload and header extension mappings of every media section
+	/// in `sdp`.
+	///
+	/// `a=fmtp` lines may appear before or after the `a=rtpmap` line of their
+	/// payload type. Payload types which are listed under `m=audio` or `m=video`
+	/// take that media kind when their encoding name is not recognised. Malformed
+	/// lines are skipped.
+	#[must_use]
+	pub fn parse(sdp: &str) -> Self {
+		let mut out = Self::default();
+		let mut kind = None;
+
+		for line in sdp.lines().map(str::trim) {
+			if let Some(media) = line.strip_prefix("m=") {
+				kind = parse_media_kind(media);
+			} else if let Some((pt, mut fmt)) = parse_rtpmap(line) {
+				fmt.kind = fmt.kind.or(kind);
+				out.payloads.insert(pt, fmt);
+			} else if let Some((id, uri)) = parse_extmap(line) {
+				if let Some(uri) = ExtensionUri::from_uri(uri) {
+					out.extensions.insert(id, uri);
+				}
+			}
+		}
+
+		for (pt, params) in sdp.lines().map(str::trim).filter_map(parse_fmtp) {
+			if let Some(fmt) = out.payloads.get_mut(pt) {
+				fmt.parameters = Some(params.into());
+			}
+		}
+
+		out
+	}
+}
+
+/// Parses an `a=rtpmap:<pt> <encoding>/<clock rate>[/<channels>]` line.
+///
+/// The format's media kind is inferred from its encoding name.
+#[must_use]
+pub fn parse_rtpmap(line: &str) -> Option<(RtpType, PayloadFormat)> {
+	let (pt, value) = split_attribute(line, "rtpmap")?;
+	let mut parts = value.split_whitespace().next()?.split('/');
+
+	let encoding = parts.next().filter(|e| !e.is_empty())?;
+	let clock_rate = parts.next()?.parse().ok()?;
+	let channels = match parts.next() {
+		Some(c) => Some(c.parse().ok()?),
+		None => None,
+	};
+
+	let mut fmt = PayloadFormat::new(encoding, clock_rate);
+	fmt.channels = channels;
+
+	Some((parse_payload_type(pt)?, fmt))
+}
+
+/// Parses an `a=fmtp:<pt> <parameters>` line, returning the parameters unchanged.
+#[must_use]
+pub fn parse_fmtp(line: &str) -> Option<(RtpType, &str)> {
+	let (pt, params) = split_attribute(line, "fmtp")?;
+
+	Some((parse_payload_type(pt)?, params))
+}
+
+/// Parses an `a=extmap:<id>[/<direction>] <uri> [<attributes>]` line, returning
+/// the ID and URI.
+///
+/// IDs outside `1`--`255` are rejected.
+#[must_use]
+pub fn parse_extmap(line: &str) -> Option<(u8, &str)> {
+	let (id, value) = split_attribute(line, "extmap")?;
+	let id = id.split('/').next()?.parse().ok().filter(|&id| id != 0)?;
+
+	Some((id, value.split_whitespace().next()?))
+}
+
+/// Parses a payload type, which must fit in the 7-bit field of an RTP header.
+fn parse_payload_type(pt: &str) -> Option<RtpType> {
+	pt.parse().ok().filter(|&pt| pt <= 0x7f).map(RtpType::new)
+}
+
+/// Splits an `a=<name>:<key> <value>` line into its key and value.
+fn split_attribute<'a>(line: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+	let rest = line
+		.trim()
+		.strip_prefix("a=")?
+		.strip_prefix(name)?
+		.strip_prefix(':')?;
+	let (key, value) = rest.split_once(char::is_whitespace)?;
+
+	Some((key, value.trim()))
+}
+
+/// Returns the kind of an `m=<media> ...` line.
+fn parse_media_kind(media: &str) -> Option<MediaKind> {
+	match media.split_whitespace().next()? {
+		"audio" => Some(MediaKind::Audio),
+		"video" => Some(MediaKind::Video),
+		"text" => Some(MediaKind::Text),
+		"application" => Some(MediaKind::Application),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn payload_types_above_127_are_skipped() {
+		assert!(parse_rtpmap("a=rtpmap:127 opus/48000/2").is_some());
+		assert!(parse_rtpmap("a=rtpmap:128 opus/48000/2").is_none());
+		assert!(parse_fmtp("a=fmtp:200 minptime=10").is_none());
+	}
+}
@@ -0,0 +1,135 @@
+use crate::wrap::Wrap16;
+
+/// Number of sequence numbers behind the highest seen which a
+/// [`DuplicateFilter`] remembers.
+///
+/// [`DuplicateFilter`]: struct.DuplicateFilter.html
+pub const DUPLICATE_WINDOW: u16 = 128;
+
+/// Sliding-window filter flagging duplicated or replayed packets from a single
+/// remote source.
+///
+/// Sequence numbers are extended across wraparound, and a bitmap records which of
+/// the last [`DUPLICATE_WINDOW`] sequence numbers have been seen, in the style of
+/// the [SRTP replay list](https://tools.ietf.org/html/rfc3711#section-3.3.2).
+/// Packets older than the window cannot be told apart from replays, and so are
+/// rejected.
+///
+/// Receivers should keep one filter per SSRC.
+///
+/// [`DUPLICATE_WINDOW`]: constant.DUPLICATE_WINDOW.html
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DuplicateFilter {
+	highest: Option<u64>,
+	seen: u128,
+	rejected: u64,
+}
+
+impl DuplicateFilter {
+	/// Creates a filter which has not yet seen any packets.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Estimates the extended (48-bit) index of `seq`, relative to the highest
+	/// sequence number seen.
+	///
+	/// This is the packet index used by SRTP, with the rollover count in the upper
+	/// 32 bits.
+	#[must_use]
+	pub fn index(&self, seq: Wrap16) -> u64 {
+		self.highest
+			.map_or(u64::from(u16::from(seq)), |high| extend(high, seq))
+	}
+
+	/// Returns whether a packet with sequence number `seq` would be accepted,
+	/// without recording it.
+	///
+	/// SRTP receivers should check a packet before authenticating it, and only
+	/// [`insert`] it once authenticated.
+	///
+	/// [`insert`]: #method.insert
+	#[must_use]
+	pub fn check(&self, seq: Wrap16) -> bool {
+		let Some(high) = self.highest else {
+			return true;
+		};
+		let idx = self.index(seq);
+
+		match high.checked_sub(idx) {
+			None => true,
+			Some(age) if age < u64::from(DUPLICATE_WINDOW) => self.seen & (1 << age) == 0,
+			Some(_) => false,
+		}
+	}
+
+	/// Records the arrival of a packet with sequence number `seq`.
+	///
+	/// Returns `false` if the packet is a duplicate, or too old to be told apart
+	/// from one.
+	pub fn insert(&mut self, seq: Wrap16) -> bool {
+		if !self.check(seq) {
+			self.rejected = self.rejected.saturating_add(1);
+			return false;
+		}
+
+		let idx = self.index(seq);
+		match self.highest {
+			Some(high) if idx <= high => {
+				self.seen |= 1 << (high - idx);
+			},
+			Some(high) => {
+				let shift = idx - high;
+				self.seen = if shift < u64::from(DUPLICATE_WINDOW) {
+					(self.seen << shift) | 1
+				} else {
+					1
+				};
+				self.highest = Some(idx);
+			},
+			None => {
+				self.seen = 1;
+				self.highest = Some(idx);
+			},
+		}
+
+		true
+	}
+
+	/// Highest extended index seen, if any.
+	#[must_use]
+	pub fn highest_index(&self) -> Option<u64> {
+		self.highest
+	}
+
+	/// Number of packets rejected by [`insert`].
+	///
+	/// [`insert`]: #method.insert
+	#[must_use]
+	pub fn rejected(&self) -> u64 {
+		self.rejected
+	}
+
+	/// Forgets all packets seen, *e.g.*, after the source restarts its sequence
+	/// numbers.
+	///
+	/// The rejection counter is kept.
+	pub fn reset(&mut self) {
+		self.highest = None;
+		self.seen = 0;
+	}
+}
+
+/// Extends `seq` to a 64-bit index relative to `highest`, an earlier extended index.
+pub(crate) fn extend(highest: u64, seq: Wrap16) -> u64 {
+	#[allow(clippy::cast_possible_truncation)]
+	let delta = Wrap16::new(highest as u16).distance(seq);
+	let idx = if delta < 0 {
+		highest.checked_sub(u64::from(delta.unsigned_abs()))
+	} else {
+		highest.checked_add(u64::from(delta.unsigned_abs()))
+	};
+
+	idx.unwrap_or(u64::from(u16::from(seq)))
+}
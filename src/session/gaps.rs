@@ -0,0 +1,198 @@
+use super::{duplicate::extend, MAX_DROPOUT, MAX_MISORDER};
+use crate::wrap::Wrap16;
+use alloc::collections::{btree_map, BTreeMap};
+use core::{iter::Peekable, time::Duration};
+
+/// Default number of missing packets a [`GapTracker`] remembers.
+///
+/// [`GapTracker`]: struct.GapTracker.html
+pub const DEFAULT_MAX_MISSING: usize = 1000;
+
+/// Run of consecutive missing sequence numbers.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Gap {
+	/// First missing sequence number.
+	pub start: Wrap16,
+	/// Number of missing sequence numbers, from `start` onwards.
+	pub count: u16,
+	/// Time since the oldest packet in this run was found to be missing.
+	pub age: Duration,
+}
+
+impl Gap {
+	/// Iterates over every sequence number in this run, in order.
+	pub fn sequences(&self) -> impl Iterator<Item = Wrap16> {
+		let start = self.start;
+		(0..self.count).map(move |i| start + i)
+	}
+}
+
+/// Tracker of missing packets from a single remote source, for use when
+/// generating NACK feedback.
+///
+/// Packets are marked missing when a later sequence number arrives, and are
+/// forgotten once they arrive, once they are older than the tracker's maximum
+/// age (after which retransmission would arrive too late to be useful), or once
+/// too many packets are missing. Large jumps in sequence number (of more than
+/// [`MAX_DROPOUT`]) are treated as a restart rather than as loss.
+///
+/// The sequence numbers held can be passed directly to [`TransportFeedback::nack`].
+///
+/// [`MAX_DROPOUT`]: constant.MAX_DROPOUT.html
+/// [`TransportFeedback::nack`]: ../rtcp/feedback/struct.TransportFeedback.html#method.nack
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GapTracker {
+	highest: Option<u64>,
+	missing: BTreeMap<u64, Duration>,
+	max_age: Duration,
+	max_missing: usize,
+}
+
+impl GapTracker {
+	/// Creates a tracker which forgets missing packets after `max_age`.
+	#[must_use]
+	pub fn new(max_age: Duration) -> Self {
+		Self::with_limit(max_age, DEFAULT_MAX_MISSING)
+	}
+
+	/// Creates a tracker which forgets missing packets after `max_age`, and
+	/// remembers at most `max_missing` at once.
+	#[must_use]
+	pub fn with_limit(max_age: Duration, max_missing: usize) -> Self {
+		Self {
+			highest: None,
+			missing: BTreeMap::new(),
+			max_age,
+			max_missing,
+		}
+	}
+
+	/// Records the arrival at `now` of a packet with sequence number `seq`.
+	///
+	/// Returns the number of packets newly found to be missing.
+	pub fn insert(&mut self, seq: Wrap16, now: Duration) -> usize {
+		let Some(high) = self.highest else {
+			self.highest = Some(u64::from(u16::from(seq)));
+			return 0;
+		};
+
+		let idx = extend(high, seq);
+		if idx <= high {
+			if high - idx < u64::from(MAX_MISORDER) {
+				self.missing.remove(&idx);
+			}
+			return 0;
+		}
+
+		self.highest = Some(idx);
+		if idx - high >= u64::from(MAX_DROPOUT) {
+			self.missing.clear();
+			return 0;
+		}
+
+		for lost in high + 1..idx {
+			self.missing.insert(lost, now);
+		}
+		while self.missing.len() > self.max_missing {
+			if let Some(oldest) = self.missing.keys().next().copied() {
+				self.missing.remove(&oldest);
+			}
+		}
+
+		usize::try_from(idx - high - 1).unwrap_or(usize::MAX)
+	}
+
+	/// Forgets all missing packets older than the maximum age at `now`.
+	///
+	/// Returns the number of packets forgotten.
+	pub fn expire(&mut self, now: Duration) -> usize {
+		let before = self.missing.len();
+		let max_age = self.max_age;
+		self.missing
+			.retain(|_, found| now.saturating_sub(*found) <= max_age);
+
+		before - self.missing.len()
+	}
+
+	/// Expires old entries, and then iterates over each remaining run of missing
+	/// packets in sequence order.
+	pub fn gaps(&mut self, now: Duration) -> Gaps<'_> {
+		self.expire(now);
+
+		Gaps {
+			inner: self.missing.iter().peekable(),
+			now,
+		}
+	}
+
+	/// Iterates over every sequence number currently believed missing, in order.
+	///
+	/// Unlike [`gaps`], this does not expire old entries.
+	///
+	/// [`gaps`]: #method.gaps
+	pub fn lost(&self) -> impl Iterator<Item = Wrap16> + '_ {
+		#[allow(clippy::cast_possible_truncation)]
+		self.missing.keys().map(|&idx| Wrap16::new(idx as u16))
+	}
+
+	/// Marks `seq` as no longer missing, *e.g.*, once a NACK has been sent the
+	/// maximum number of times.
+	///
+	/// Returns whether it was held.
+	pub fn forget(&mut self, seq: Wrap16) -> bool {
+		self.highest.map_or(false, |high| {
+			self.missing.remove(&extend(high, seq)).is_some()
+		})
+	}
+
+	/// Number of packets currently believed missing.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.missing.len()
+	}
+
+	/// Returns whether no packets are currently believed missing.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.missing.is_empty()
+	}
+
+	/// Forgets all packets seen, *e.g.*, after the source changes its SSRC.
+	pub fn reset(&mut self) {
+		self.highest = None;
+		self.missing.clear();
+	}
+}
+
+/// Iterator over runs of missing packets, produced by [`GapTracker::gaps`].
+///
+/// [`GapTracker::gaps`]: struct.GapTracker.html#method.gaps
+pub struct Gaps<'a> {
+	inner: Peekable<btree_map::Iter<'a, u64, Duration>>,
+	now: Duration,
+}
+
+impl Iterator for Gaps<'_> {
+	type Item = Gap;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (&first, &found) = self.inner.next()?;
+		let (mut last, mut oldest) = (first, found);
+
+		while let Some((&idx, &found)) = self.inner.peek() {
+			if idx != last + 1 || idx - first >= u64::from(u16::MAX) {
+				break;
+			}
+			last = idx;
+			oldest = oldest.min(found);
+			self.inner.next();
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		Some(Gap {
+			start: Wrap16::new(first as u16),
+			count: (last - first + 1) as u16,
+			age: self.now.saturating_sub(oldest),
+		})
+	}
+}
@@ -0,0 +1,128 @@
+use crate::rtcp::{
+	bye::Goodbye,
+	compound::CompoundBuilder,
+	report::{MutableReportBlockPacket, ReceiverReport, ReportBlock},
+	sdes::SourceDescription,
+	Rtcp,
+	RtcpType,
+};
+use alloc::{string::String, vec, vec::Vec};
+
+/// RAII guard which ensures a session's RTCP teardown is sent.
+///
+/// When dropped (including during unwinding or early returns on error paths), the guard
+/// serialises a final compound RTCP packet of RR + SDES (CNAME) + BYE (with optional
+/// reason) and hands it to the caller-supplied `sink`. Without this, remote peers
+/// only notice a departed source once it times out of their member tables.
+///
+/// Use [`disarm`] to suppress the final packet, or [`finish`] to send it early.
+///
+/// [`disarm`]: #method.disarm
+/// [`finish`]: #method.finish
+pub struct ByeGuard<F: FnMut(&[u8])> {
+	ssrc: u32,
+	cname: String,
+	reason: Option<String>,
+	report_blocks: Vec<ReportBlock>,
+	sink: F,
+	armed: bool,
+}
+
+impl<F: FnMut(&[u8])> ByeGuard<F> {
+	/// Creates a guard for the local source `ssrc`, whose teardown packet will be
+	/// passed to `sink` when dropped.
+	pub fn new(ssrc: u32, cname: impl Into<String>, sink: F) -> Self {
+		Self {
+			ssrc,
+			cname: cname.into(),
+			reason: None,
+			report_blocks: Vec::new(),
+			sink,
+			armed: true,
+		}
+	}
+
+	/// Sets the reason string to be included in the BYE packet.
+	pub fn set_reason(&mut self, reason: impl Into<String>) {
+		self.reason = Some(reason.into());
+	}
+
+	/// Sets the reception report blocks to be included in the final RR.
+	///
+	/// At most 31 blocks will be included.
+	pub fn set_report_blocks(&mut self, blocks: Vec<ReportBlock>) {
+		self.report_blocks = blocks;
+	}
+
+	/// Prevents the teardown packet from being sent when this guard is dropped.
+	pub fn disarm(&mut self) {
+		self.armed = false;
+	}
+
+	/// Sends the teardown packet immediately, consuming the guard.
+	pub fn finish(mut self) {
+		self.emit();
+	}
+
+	/// Serialises the compound RR + SDES + BYE packet this guard would send.
+	#[must_use]
+	pub fn teardown_packet(&self) -> Vec<u8> {
+		let blocks = &self.report_blocks[..self.report_blocks.len().min(31)];
+		let block_len = MutableReportBlockPacket::minimum_packet_size();
+		let mut rr_body = vec![0u8; blocks.len() * block_len];
+		for (block, space) in blocks.iter().zip(rr_body.chunks_mut(block_len)) {
+			if let Some(mut pkt) = MutableReportBlockPacket::new(space) {
+				pkt.populate(block);
+			}
+		}
+
+		#[allow(clippy::cast_possible_truncation)]
+		let rr = ReceiverReport {
+			version: 2,
+			padding: 0,
+			rx_report_count: blocks.len() as u8,
+			packet_type: RtcpType::ReceiverReport,
+			pkt_length: 0,
+			ssrc: self.ssrc,
+			payload: rr_body,
+		};
+
+		let mut sdes = SourceDescription::new();
+		sdes.push_chunk(self.ssrc).push_cname(&self.cname);
+
+		CompoundBuilder::new()
+			.push(Rtcp::ReceiverReport(rr))
+			.push(Rtcp::SourceDescription(sdes))
+			.push(Rtcp::Goodbye(Goodbye::new(
+				&[self.ssrc],
+				self.reason.as_deref(),
+			)))
+			.to_vec()
+	}
+
+	fn emit(&mut self) {
+		if self.armed {
+			self.armed = false;
+			let pkt = self.teardown_packet();
+			(self.sink)(&pkt);
+		}
+	}
+}
+
+impl<F: FnMut(&[u8])> Drop for ByeGuard<F> {
+	fn drop(&mut self) {
+		self.emit();
+	}
+}
+
+impl<F: FnMut(&[u8])> core::fmt::Debug for ByeGuard<F> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.debug_struct("ByeGuard")
+			.field("ssrc", &self.ssrc)
+			.field("cname", &self.cname)
+			.field("reason", &self.reason)
+			.field("report_blocks", &self.report_blocks)
+			.field("armed", &self.armed)
+			.finish_non_exhaustive()
+	}
+}
@@ -0,0 +1,212 @@
+use super::report::{fraction_lost, SourceReception};
+use crate::wrap::Wrap16;
+
+/// Largest forward jump in sequence number treated as loss rather than a restart.
+pub const MAX_DROPOUT: u16 = 3000;
+
+/// Largest backward jump in sequence number treated as reordering rather than a restart.
+pub const MAX_MISORDER: u16 = 100;
+
+/// Number of sequential packets needed before a new source is considered valid.
+pub const MIN_SEQUENTIAL: u8 = 2;
+
+/// Tracker of packets expected and received from a single remote source,
+/// deriving the loss statistics of its report blocks.
+///
+/// This follows the sequence number validation and loss estimation algorithms of
+/// [RFC 3550](https://tools.ietf.org/html/rfc3550#appendix-A.1): a source is on
+/// probation until [`MIN_SEQUENTIAL`] packets arrive in sequence, and a large jump
+/// in sequence number is accepted as a restart only once confirmed by the
+/// following packet.
+///
+/// [`MIN_SEQUENTIAL`]: constant.MIN_SEQUENTIAL.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LossEstimator {
+	max_seq: u16,
+	cycles: u32,
+	base_seq: u32,
+	bad_seq: u32,
+	probation: u8,
+	min_sequential: u8,
+	received: u32,
+	expected_prior: u32,
+	received_prior: u32,
+}
+
+impl LossEstimator {
+	/// Creates a tracker for a source whose first packet has sequence number `seq`,
+	/// placing it on probation.
+	///
+	/// `seq` should then also be passed to [`update`].
+	///
+	/// [`update`]: #method.update
+	#[must_use]
+	pub fn new(seq: Wrap16) -> Self {
+		Self::with_probation(seq, MIN_SEQUENTIAL)
+	}
+
+	/// Creates a tracker for a source whose first packet has sequence number `seq`,
+	/// requiring `min_sequential` packets in sequence before it is considered valid.
+	///
+	/// A `min_sequential` of `0` or `1` validates the source on its first packet.
+	#[must_use]
+	pub fn with_probation(seq: Wrap16, min_sequential: u8) -> Self {
+		let seq = u16::from(seq);
+		let mut out = Self {
+			max_seq: 0,
+			cycles: 0,
+			base_seq: 0,
+			bad_seq: 0,
+			probation: min_sequential.max(1),
+			min_sequential: min_sequential.max(1),
+			received: 0,
+			expected_prior: 0,
+			received_prior: 0,
+		};
+		out.init_seq(seq);
+		out.max_seq = seq.wrapping_sub(1);
+
+		out
+	}
+
+	fn init_seq(&mut self, seq: u16) {
+		self.base_seq = u32::from(seq);
+		self.max_seq = seq;
+		self.bad_seq = u32::from(u16::MAX) + 2;
+		self.cycles = 0;
+		self.received = 0;
+		self.received_prior = 0;
+		self.expected_prior = 0;
+	}
+
+	/// Records the arrival of a packet with sequence number `seq`.
+	///
+	/// Returns `false` if the packet should not be treated as valid, either because
+	/// the source is on probation or `seq` is far from the expected value. Two
+	/// sequential packets after such a jump cause the source to be resynchronised,
+	/// resetting its statistics.
+	pub fn update(&mut self, seq: Wrap16) -> bool {
+		let seq = u16::from(seq);
+		let udelta = seq.wrapping_sub(self.max_seq);
+
+		if self.probation > 0 {
+			if seq == self.max_seq.wrapping_add(1) {
+				self.probation -= 1;
+				self.max_seq = seq;
+				if self.probation == 0 {
+					self.init_seq(seq);
+					self.received += 1;
+					return true;
+				}
+			} else {
+				self.probation = self.min_sequential - 1;
+				self.max_seq = seq;
+			}
+			return false;
+		} else if udelta < MAX_DROPOUT {
+			if seq < self.max_seq {
+				self.cycles = self.cycles.wrapping_add(1 << 16);
+			}
+			self.max_seq = seq;
+		} else if udelta <= u16::MAX - MAX_MISORDER + 1 {
+			if u32::from(seq) == self.bad_seq {
+				self.init_seq(seq);
+			} else {
+				self.bad_seq = u32::from(seq.wrapping_add(1));
+				return false;
+			}
+		}
+
+		self.received = self.received.wrapping_add(1);
+		true
+	}
+
+	/// Returns whether the source has passed probation.
+	#[must_use]
+	pub fn is_valid(&self) -> bool {
+		self.probation == 0
+	}
+
+	/// Highest sequence number received.
+	#[must_use]
+	pub fn highest_seq(&self) -> Wrap16 {
+		self.max_seq.into()
+	}
+
+	/// Number of times the sequence number has wrapped, as reported in the upper
+	/// 16 bits of the extended highest sequence number.
+	#[must_use]
+	pub fn cycles(&self) -> u16 {
+		#[allow(clippy::cast_possible_truncation)]
+		{
+			(self.cycles >> 16) as u16
+		}
+	}
+
+	/// Highest sequence number received, extended with the wrap count.
+	#[must_use]
+	pub fn extended_highest(&self) -> u32 {
+		self.cycles | u32::from(self.max_seq)
+	}
+
+	/// Number of packets expected since the source became valid.
+	#[must_use]
+	pub fn expected(&self) -> u32 {
+		self.extended_highest()
+			.wrapping_sub(self.base_seq)
+			.wrapping_add(1)
+	}
+
+	/// Number of valid packets received, including duplicates and late packets.
+	#[must_use]
+	pub fn received(&self) -> u32 {
+		self.received
+	}
+
+	/// Cumulative packets lost, which is negative if duplicates outnumber losses.
+	#[must_use]
+	pub fn cumulative_lost(&self) -> i32 {
+		let lost = i64::from(self.expected()) - i64::from(self.received);
+
+		i32::try_from(lost).unwrap_or(if lost < 0 { i32::MIN } else { i32::MAX })
+	}
+
+	/// Returns the fraction of packets lost (*i.e.*, n => n/256) since the previous
+	/// call, and begins a new reporting interval.
+	pub fn take_fraction_lost(&mut self) -> u8 {
+		let expected = self.expected();
+		let expected_interval = expected.wrapping_sub(self.expected_prior);
+		let received_interval = self.received.wrapping_sub(self.received_prior);
+
+		self.expected_prior = expected;
+		self.received_prior = self.received;
+
+		fraction_lost(expected_interval, received_interval)
+	}
+
+	/// Fills in the sequence and loss fields of `reception`, beginning a new
+	/// reporting interval.
+	pub fn fill(&mut self, reception: &mut SourceReception) {
+		reception.highest_seq = self.max_seq;
+		reception.cycles = self.cycles();
+		reception.cumulative_lost = self.cumulative_lost();
+		reception.fraction_lost = self.take_fraction_lost();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn probation_restarts_with_configured_length() {
+		let mut loss = LossEstimator::with_probation(100.into(), 4);
+		assert!(!loss.update(100.into()));
+		assert!(!loss.update(101.into()));
+		assert!(!loss.update(200.into()));
+		assert!(!loss.update(201.into()));
+		assert!(!loss.update(202.into()));
+		assert!(loss.update(203.into()));
+		assert!(loss.is_valid());
+	}
+}
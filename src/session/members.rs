@@ -0,0 +1,339 @@
+use crate::rtcp::{compound::CompoundPackets, RtcpPacket};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::time::Duration;
+use pnet_macros_support::packet::Packet;
+
+/// Minimum deterministic RTCP interval, before halving for the initial report.
+pub const RTCP_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of deterministic intervals after which a silent member times out.
+pub const MEMBER_TIMEOUT_INTERVALS: u32 = 5;
+
+/// How long a member who sent a BYE is kept, so that late or reordered packets
+/// do not re-add it.
+pub const BYE_LINGER: Duration = Duration::from_secs(2);
+
+/// State held for a single session participant.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Member {
+	/// Time at which an RTP packet was last received from this member.
+	pub last_rtp: Option<Duration>,
+	/// Time at which an RTCP packet was last received from this member.
+	pub last_rtcp: Option<Duration>,
+	/// Time at which this member sent a BYE, if it has left.
+	pub left: Option<Duration>,
+	/// Whether this member is currently counted as a sender.
+	pub sender: bool,
+}
+
+impl Member {
+	/// Time at which any packet was last received from this member.
+	#[must_use]
+	pub fn last_heard(&self) -> Option<Duration> {
+		self.last_rtp.max(self.last_rtcp)
+	}
+}
+
+/// Tracks the senders and receivers in an RTP session, as seen via RTP and RTCP.
+///
+/// This applies the member and sender timeouts and BYE handling described by
+/// [RFC 3550](https://tools.ietf.org/html/rfc3550#section-6.3), and uses the
+/// resulting member and sender counts to compute the RTCP reporting interval.
+///
+/// Times are given as [`Duration`]s since an arbitrary (monotonic) epoch chosen
+/// by the caller. The local source is always counted as a member.
+///
+/// [`Duration`]: https://doc.rust-lang.org/core/time/struct.Duration.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemberTable {
+	local_ssrc: u32,
+	members: BTreeMap<u32, Member>,
+}
+
+impl MemberTable {
+	/// Creates a table containing only the local source `local_ssrc`.
+	#[must_use]
+	pub fn new(local_ssrc: u32) -> Self {
+		let mut members = BTreeMap::new();
+		members.insert(local_ssrc, Member::default());
+
+		Self {
+			local_ssrc,
+			members,
+		}
+	}
+
+	/// SSRC of the local source.
+	#[must_use]
+	pub fn local_ssrc(&self) -> u32 {
+		self.local_ssrc
+	}
+
+	/// Records that an RTP packet was received from `ssrc` (or sent, if `ssrc` is
+	/// the local source), marking it as a sender.
+	pub fn on_rtp(&mut self, ssrc: u32, now: Duration) {
+		if let Some(member) = self.live_entry(ssrc) {
+			member.last_rtp = Some(now);
+			member.sender = true;
+		}
+	}
+
+	/// Records that an RTCP packet was received from `ssrc`.
+	pub fn on_rtcp(&mut self, ssrc: u32, now: Duration) {
+		if let Some(member) = self.live_entry(ssrc) {
+			member.last_rtcp = Some(now);
+		}
+	}
+
+	/// Records that `ssrc` has left the session via a BYE.
+	///
+	/// The member stops counting towards member and sender totals immediately,
+	/// and is removed by [`timeout`] once [`BYE_LINGER`] has passed.
+	///
+	/// [`timeout`]: #method.timeout
+	/// [`BYE_LINGER`]: constant.BYE_LINGER.html
+	pub fn on_bye(&mut self, ssrc: u32, now: Duration) {
+		if ssrc == self.local_ssrc {
+			return;
+		}
+
+		let member = self.members.entry(ssrc).or_default();
+		member.left.get_or_insert(now);
+		member.sender = false;
+	}
+
+	/// Records every source in a (compound) RTCP datagram.
+	///
+	/// The sender of each packet is marked as heard from, and each SSRC listed
+	/// in a BYE is marked as having left.
+	pub fn on_rtcp_compound(&mut self, buf: &[u8], now: Duration) {
+		for pkt in CompoundPackets::lenient(buf) {
+			if let RtcpPacket::Goodbye(bye) = &pkt {
+				for ssrc in bye.get_ssrcs() {
+					self.on_bye(ssrc, now);
+				}
+			} else if let Some(b) = pkt.packet().get(4..8) {
+				self.on_rtcp(u32::from_be_bytes([b[0], b[1], b[2], b[3]]), now);
+			}
+		}
+	}
+
+	/// Returns the state held for `ssrc`, if known.
+	#[must_use]
+	pub fn get(&self, ssrc: u32) -> Option<&Member> {
+		self.members.get(&ssrc)
+	}
+
+	/// Returns `true` if `ssrc` is a current (not departed) member.
+	#[must_use]
+	pub fn contains(&self, ssrc: u32) -> bool {
+		self.get(ssrc).map_or(false, |m| m.left.is_none())
+	}
+
+	/// Returns `true` if `ssrc` is a current sender.
+	#[must_use]
+	pub fn is_sender(&self, ssrc: u32) -> bool {
+		self.get(ssrc).map_or(false, |m| m.sender)
+	}
+
+	/// Returns `true` if the local source is a current sender.
+	#[must_use]
+	pub fn we_sent(&self) -> bool {
+		self.is_sender(self.local_ssrc)
+	}
+
+	/// Number of current members, including the local source.
+	#[must_use]
+	pub fn members(&self) -> usize {
+		self.members.values().filter(|m| m.left.is_none()).count()
+	}
+
+	/// Number of current senders, including the local source if it is sending.
+	#[must_use]
+	pub fn senders(&self) -> usize {
+		self.members.values().filter(|m| m.sender).count()
+	}
+
+	/// Iterates over the SSRCs of current members, excluding the local source.
+	pub fn remote_members(&self) -> impl Iterator<Item = u32> + '_ {
+		self.members
+			.iter()
+			.filter(move |(&ssrc, m)| ssrc != self.local_ssrc && m.left.is_none())
+			.map(|(&ssrc, _)| ssrc)
+	}
+
+	/// Applies timeouts, returning the SSRCs of any members removed.
+	///
+	/// Senders which have not sent RTP within two transmission intervals
+	/// (`interval`) revert to receivers. Members not heard from within
+	/// [`MEMBER_TIMEOUT_INTERVALS`] deterministic intervals (`td`, see
+	/// [`deterministic_interval`]) are removed, as are members which left via
+	/// BYE more than [`BYE_LINGER`] ago. The local source is never removed.
+	///
+	/// This should be called at least once per RTCP transmission interval.
+	///
+	/// [`MEMBER_TIMEOUT_INTERVALS`]: constant.MEMBER_TIMEOUT_INTERVALS.html
+	/// [`deterministic_interval`]: #method.deterministic_interval
+	/// [`BYE_LINGER`]: constant.BYE_LINGER.html
+	pub fn timeout(&mut self, now: Duration, interval: Duration, td: Duration) -> Vec<u32> {
+		let sender_deadline = now.checked_sub(interval.saturating_mul(2));
+		let member_deadline = now.checked_sub(td.saturating_mul(MEMBER_TIMEOUT_INTERVALS));
+		let bye_deadline = now.checked_sub(BYE_LINGER);
+
+		let mut removed = Vec::new();
+		for (&ssrc, member) in &mut self.members {
+			if member.sender && member.last_rtp < sender_deadline {
+				member.sender = false;
+			}
+
+			if ssrc == self.local_ssrc {
+				continue;
+			}
+
+			let expired = match member.left {
+				Some(left) => Some(left) < bye_deadline,
+				None => member.last_heard() < member_deadline,
+			};
+
+			if expired {
+				removed.push(ssrc);
+			}
+		}
+
+		for ssrc in &removed {
+			self.members.remove(ssrc);
+		}
+
+		removed
+	}
+
+	/// Computes the deterministic RTCP transmission interval (*Td*) from the current
+	/// member and sender counts, following
+	/// [RFC 3550](https://tools.ietf.org/html/rfc3550#appendix-A.7).
+	///
+	/// `rtcp_bandwidth` is the total RTCP bandwidth of the session in bytes per
+	/// second (typically 5% of the session bandwidth), and `avg_rtcp_size` is the
+	/// running average size of RTCP packets in bytes, including lower-layer headers.
+	/// `initial` should be set until the first RTCP packet has been sent, which
+	/// halves the minimum interval.
+	///
+	/// The transmission interval is then obtained from this via [`randomise_interval`].
+	///
+	/// [`randomise_interval`]: fn.randomise_interval.html
+	#[must_use]
+	pub fn deterministic_interval(
+		&self,
+		rtcp_bandwidth: f64,
+		avg_rtcp_size: f64,
+		initial: bool,
+	) -> Duration {
+		let min = if initial {
+			RTCP_MIN_INTERVAL / 2
+		} else {
+			RTCP_MIN_INTERVAL
+		};
+
+		let members = self.members();
+		let senders = self.senders();
+
+		// Dedicate a quarter of the RTCP bandwidth to senders if they are few.
+		let (n, bandwidth) = if senders * 4 <= members {
+			if self.we_sent() {
+				(senders, rtcp_bandwidth * 0.25)
+			} else {
+				(members - senders, rtcp_bandwidth * 0.75)
+			}
+		} else {
+			(members, rtcp_bandwidth)
+		};
+
+		#[allow(clippy::cast_precision_loss)]
+		let secs = n as f64 * avg_rtcp_size / bandwidth;
+
+		if secs.is_finite() && secs > min.as_secs_f64() {
+			secs_saturating(secs)
+		} else {
+			min
+		}
+	}
+
+	fn live_entry(&mut self, ssrc: u32) -> Option<&mut Member> {
+		let member = self.members.entry(ssrc).or_default();
+		member.left.is_none().then_some(member)
+	}
+}
+
+/// Randomises a deterministic interval `td` into an RTCP transmission interval,
+/// following [RFC 3550](https://tools.ietf.org/html/rfc3550#section-6.3.1).
+///
+/// `random` should be drawn uniformly from `[0, 1)`, and non-finite values are
+/// treated as `0.5`. The interval is scaled by a factor in `[0.5, 1.5)`, then
+/// divided by `e - 3/2` to compensate for timer reconsideration.
+#[must_use]
+pub fn randomise_interval(td: Duration, random: f64) -> Duration {
+	const COMPENSATION: f64 = core::f64::consts::E - 1.5;
+
+	let random = if random.is_finite() {
+		random.clamp(0.0, 1.0)
+	} else {
+		0.5
+	};
+
+	secs_saturating(td.as_secs_f64() * (random + 0.5) / COMPENSATION)
+}
+
+/// Converts a non-negative, non-NaN number of seconds into a `Duration`,
+/// saturating at `Duration::MAX`.
+fn secs_saturating(secs: f64) -> Duration {
+	if secs >= Duration::MAX.as_secs_f64() {
+		Duration::MAX
+	} else {
+		Duration::from_secs_f64(secs)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn deterministic_interval_saturates() {
+		let mut table = MemberTable::new(1);
+		for ssrc in 2..100 {
+			table.on_rtcp(ssrc, Duration::ZERO);
+		}
+
+		assert_eq!(
+			table.deterministic_interval(1e-300, 100.0, false),
+			Duration::MAX
+		);
+		assert_eq!(
+			table.deterministic_interval(0.0, 100.0, false),
+			RTCP_MIN_INTERVAL
+		);
+	}
+
+	#[test]
+	fn randomise_interval_handles_extreme_inputs() {
+		let td = Duration::from_secs(5);
+		assert_eq!(
+			randomise_interval(td, f64::NAN),
+			randomise_interval(td, 0.5)
+		);
+		assert_eq!(
+			randomise_interval(td, f64::INFINITY),
+			randomise_interval(td, 0.5)
+		);
+		assert_eq!(randomise_interval(Duration::MAX, 0.99), Duration::MAX);
+	}
+
+	#[test]
+	fn timeout_saturates_long_intervals() {
+		let mut table = MemberTable::new(1);
+		table.on_rtp(2, Duration::from_secs(1));
+
+		let removed = table.timeout(Duration::from_secs(10), Duration::MAX, Duration::MAX);
+		assert!(removed.is_empty());
+		assert!(table.is_sender(2));
+	}
+}
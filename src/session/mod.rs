@@ -0,0 +1,26 @@
+//! Utilities for managing the lifecycle and state of an RTP session.
+//!
+//! *These are included when using the `"session"` feature.*
+
+mod duplicate;
+mod gaps;
+mod guard;
+mod loss;
+mod members;
+mod report;
+mod stats;
+
+pub use duplicate::{DuplicateFilter, DUPLICATE_WINDOW};
+pub use gaps::{Gap, GapTracker, Gaps, DEFAULT_MAX_MISSING};
+pub use guard::ByeGuard;
+pub use loss::{LossEstimator, MAX_DROPOUT, MAX_MISORDER, MIN_SEQUENTIAL};
+pub use members::{
+	randomise_interval,
+	Member,
+	MemberTable,
+	BYE_LINGER,
+	MEMBER_TIMEOUT_INTERVALS,
+	RTCP_MIN_INTERVAL,
+};
+pub use report::{fraction_lost, ReportGenerator, SenderStats, SourceReception, MAX_REPORT_BLOCKS};
+pub use stats::{ReceiverStats, SourceStats, DEFAULT_BITRATE_WINDOW};
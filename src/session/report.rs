@@ -0,0 +1,202 @@
+use crate::rtcp::{
+	self,
+	report::{
+		MutableReportBlockPacket,
+		MutableSenderInfoPacket,
+		ReceiverReport,
+		ReportBlock,
+		SenderReport,
+	},
+	Rtcp,
+	RtcpType,
+};
+use alloc::{vec, vec::Vec};
+
+/// Maximum number of report blocks carried by a single SR or RR.
+pub const MAX_REPORT_BLOCKS: usize = 31;
+
+/// Reception statistics for a single remote source, as needed to fill in a
+/// report block.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SourceReception {
+	/// SSRC of the remote source.
+	pub ssrc: u32,
+	/// Highest sequence number received.
+	pub highest_seq: u16,
+	/// Number of times the sequence number has wrapped.
+	pub cycles: u16,
+	/// Interarrival jitter estimate, in RTP timestamp units.
+	pub jitter: u32,
+	/// Cumulative packets lost (negative if duplicates outnumber losses).
+	pub cumulative_lost: i32,
+	/// Fraction of packets lost since the previous report (*i.e.*, n => n/256).
+	pub fraction_lost: u8,
+	/// Middle 32 bits of the NTP timestamp of the last SR received from this
+	/// source, or `0`.
+	pub last_sr: u32,
+	/// Delay since the last SR was received, in units of 1/65536 seconds, or `0`.
+	pub delay_since_last_sr: u32,
+}
+
+impl SourceReception {
+	/// Converts these statistics into a report block.
+	///
+	/// `cumulative_lost` is clamped to the signed 24-bit range of the field.
+	#[must_use]
+	pub fn report_block(&self) -> ReportBlock {
+		let lost = self.cumulative_lost.clamp(-0x80_0000, 0x7f_ffff);
+
+		ReportBlock {
+			ssrc: self.ssrc,
+			fraction_lost: self.fraction_lost,
+			cumulative_pkts_lost: u32::from_be_bytes(lost.to_be_bytes()) & 0xff_ffff,
+			cycles: self.cycles,
+			sequence: self.highest_seq,
+			interarrival_jitter: self.jitter,
+			last_sr_timestamp: self.last_sr,
+			last_sr_delay: self.delay_since_last_sr,
+			payload: Vec::new(),
+		}
+	}
+}
+
+/// Computes the `fraction_lost` field from the packets expected and received
+/// since the previous report, following
+/// [RFC 3550](https://tools.ietf.org/html/rfc3550#appendix-A.3).
+///
+/// This is `0` if no packets were lost, or if duplicates outnumber losses.
+#[must_use]
+pub fn fraction_lost(expected_interval: u32, received_interval: u32) -> u8 {
+	let lost_interval = expected_interval.saturating_sub(received_interval);
+	if expected_interval == 0 || lost_interval == 0 {
+		0
+	} else {
+		u8::try_from((u64::from(lost_interval) << 8) / u64::from(expected_interval))
+			.unwrap_or(u8::MAX)
+	}
+}
+
+/// Statistics of the local source, used to fill in the sender info of an SR.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SenderStats {
+	/// Wallclock time at which the report is sent, as a 64-bit NTP timestamp.
+	pub ntp_timestamp: u64,
+	/// RTP timestamp corresponding to `ntp_timestamp`.
+	pub rtp_timestamp: u32,
+	/// Total packets sent.
+	pub packet_count: u32,
+	/// Total payload bytes sent.
+	pub byte_count: u32,
+}
+
+/// Builds the sender or receiver reports for a local source from reception statistics.
+///
+/// The first packet is an SR if sender statistics are given (*i.e.*, the local source
+/// has sent media since the last report), or an RR otherwise. Each packet holds at most
+/// [`MAX_REPORT_BLOCKS`] report blocks, so additional RRs are appended for any
+/// remaining sources, as allowed by
+/// [RFC 3550](https://tools.ietf.org/html/rfc3550#section-6.4.2).
+///
+/// The resulting packets should begin a compound packet, *e.g.*, via
+/// [`CompoundBuilder`].
+///
+/// [`MAX_REPORT_BLOCKS`]: constant.MAX_REPORT_BLOCKS.html
+/// [`CompoundBuilder`]: ../rtcp/compound/struct.CompoundBuilder.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ReportGenerator {
+	ssrc: u32,
+}
+
+impl ReportGenerator {
+	/// Creates a generator for reports sent by the local source `ssrc`.
+	#[must_use]
+	pub fn new(ssrc: u32) -> Self {
+		Self { ssrc }
+	}
+
+	/// SSRC of the local source.
+	#[must_use]
+	pub fn ssrc(&self) -> u32 {
+		self.ssrc
+	}
+
+	/// Produces fully populated reports from `sender` statistics and reception
+	/// statistics of each remote source.
+	///
+	/// At least one packet is always returned.
+	#[must_use]
+	pub fn generate(&self, sender: Option<&SenderStats>, sources: &[SourceReception]) -> Vec<Rtcp> {
+		let mut chunks = sources.chunks(MAX_REPORT_BLOCKS);
+		let first = chunks.next().unwrap_or_default();
+
+		let mut out = Vec::with_capacity(1 + sources.len() / MAX_REPORT_BLOCKS);
+		out.push(match sender {
+			Some(info) => Rtcp::SenderReport(self.sender_report(info, first)),
+			None => Rtcp::ReceiverReport(self.receiver_report(first)),
+		});
+		out.extend(chunks.map(|c| Rtcp::ReceiverReport(self.receiver_report(c))));
+
+		out
+	}
+
+	/// Builds a single SR carrying up to [`MAX_REPORT_BLOCKS`] report blocks.
+	///
+	/// [`MAX_REPORT_BLOCKS`]: constant.MAX_REPORT_BLOCKS.html
+	#[must_use]
+	pub fn sender_report(&self, sender: &SenderStats, sources: &[SourceReception]) -> SenderReport {
+		let sources = &sources[..sources.len().min(MAX_REPORT_BLOCKS)];
+		let mut payload = vec![0u8; MutableSenderInfoPacket::minimum_packet_size()];
+		if let Some(mut info) = MutableSenderInfoPacket::new(&mut payload) {
+			let [s0, s1, s2, s3, f0, f1, f2, f3] = sender.ntp_timestamp.to_be_bytes();
+			info.set_ntp_timestamp_second(u32::from_be_bytes([s0, s1, s2, s3]));
+			info.set_ntp_timestamp_fraction(u32::from_be_bytes([f0, f1, f2, f3]));
+			info.set_rtp_timestamp(sender.rtp_timestamp);
+			info.set_pkt_count(sender.packet_count);
+			info.set_byte_count(sender.byte_count);
+		}
+		payload.extend(report_blocks(sources));
+
+		#[allow(clippy::cast_possible_truncation)]
+		SenderReport {
+			version: 2,
+			padding: 0,
+			rx_report_count: sources.len() as u8,
+			packet_type: RtcpType::SenderReport,
+			pkt_length: rtcp::length_words(4 + 4 + payload.len()),
+			ssrc: self.ssrc,
+			payload,
+		}
+	}
+
+	/// Builds a single RR carrying up to [`MAX_REPORT_BLOCKS`] report blocks.
+	///
+	/// [`MAX_REPORT_BLOCKS`]: constant.MAX_REPORT_BLOCKS.html
+	#[must_use]
+	pub fn receiver_report(&self, sources: &[SourceReception]) -> ReceiverReport {
+		let sources = &sources[..sources.len().min(MAX_REPORT_BLOCKS)];
+		let payload = report_blocks(sources);
+
+		#[allow(clippy::cast_possible_truncation)]
+		ReceiverReport {
+			version: 2,
+			padding: 0,
+			rx_report_count: sources.len() as u8,
+			packet_type: RtcpType::ReceiverReport,
+			pkt_length: rtcp::length_words(4 + 4 + payload.len()),
+			ssrc: self.ssrc,
+			payload,
+		}
+	}
+}
+
+fn report_blocks(sources: &[SourceReception]) -> Vec<u8> {
+	let block_len = MutableReportBlockPacket::minimum_packet_size();
+	let mut out = vec![0u8; sources.len() * block_len];
+	for (source, space) in sources.iter().zip(out.chunks_mut(block_len)) {
+		if let Some(mut pkt) = MutableReportBlockPacket::new(space) {
+			pkt.populate(&source.report_block());
+		}
+	}
+
+	out
+}
@@ -0,0 +1,434 @@
+use super::{loss::LossEstimator, report::SourceReception};
+use crate::{
+	rtp::{packetizer::duration_samples, RtpPacket, RtpType},
+	wrap::Wrap16,
+};
+use alloc::{
+	collections::{BTreeMap, VecDeque},
+	vec::Vec,
+};
+use core::time::Duration;
+
+/// Default window over which [`SourceStats::bitrate`] is measured.
+///
+/// [`SourceStats::bitrate`]: struct.SourceStats.html#method.bitrate
+pub const DEFAULT_BITRATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Reception statistics of a single remote source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SourceStats {
+	ssrc: u32,
+	loss: LossEstimator,
+	clock_rate: Option<u32>,
+	last_transit: Option<u32>,
+	jitter_q4: u32,
+	fraction_lost: u8,
+	packets: u64,
+	bytes: u64,
+	last_arrival: Duration,
+	window: Duration,
+	recent: VecDeque<(Duration, usize)>,
+	recent_bytes: u64,
+}
+
+impl SourceStats {
+	fn new(ssrc: u32, seq: Wrap16, window: Duration) -> Self {
+		Self {
+			ssrc,
+			loss: LossEstimator::new(seq),
+			clock_rate: None,
+			last_transit: None,
+			jitter_q4: 0,
+			fraction_lost: 0,
+			packets: 0,
+			bytes: 0,
+			last_arrival: Duration::ZERO,
+			window,
+			recent: VecDeque::new(),
+			recent_bytes: 0,
+		}
+	}
+
+	/// SSRC of the remote source.
+	#[must_use]
+	pub fn ssrc(&self) -> u32 {
+		self.ssrc
+	}
+
+	/// Sequence number and loss tracker of the source.
+	#[must_use]
+	pub fn loss(&self) -> &LossEstimator {
+		&self.loss
+	}
+
+	/// Returns whether the source has passed probation.
+	#[must_use]
+	pub fn is_valid(&self) -> bool {
+		self.loss.is_valid()
+	}
+
+	/// Highest sequence number received.
+	#[must_use]
+	pub fn highest_seq(&self) -> Wrap16 {
+		self.loss.highest_seq()
+	}
+
+	/// Number of times the sequence number has wrapped.
+	#[must_use]
+	pub fn cycles(&self) -> u16 {
+		self.loss.cycles()
+	}
+
+	/// Cumulative packets lost, which is negative if duplicates outnumber losses.
+	#[must_use]
+	pub fn cumulative_lost(&self) -> i32 {
+		self.loss.cumulative_lost()
+	}
+
+	/// Fraction of packets lost (*i.e.*, n => n/256) over the last reporting
+	/// interval to end.
+	#[must_use]
+	pub fn fraction_lost(&self) -> u8 {
+		self.fraction_lost
+	}
+
+	/// Clock rate of the source's timestamps, once known.
+	#[must_use]
+	pub fn clock_rate(&self) -> Option<u32> {
+		self.clock_rate
+	}
+
+	/// Interarrival jitter estimate, in RTP timestamp units.
+	#[must_use]
+	pub fn jitter(&self) -> u32 {
+		self.jitter_q4 >> 4
+	}
+
+	/// Interarrival jitter estimate, or zero if the clock rate is unknown.
+	#[must_use]
+	pub fn jitter_duration(&self) -> Duration {
+		match self.clock_rate {
+			Some(rate) if rate != 0 => Duration::from_nanos(
+				u64::from(self.jitter_q4) * 1_000_000_000 / (u64::from(rate) << 4),
+			),
+			_ => Duration::ZERO,
+		}
+	}
+
+	/// Total packets received, including those sent while on probation.
+	#[must_use]
+	pub fn packets(&self) -> u64 {
+		self.packets
+	}
+
+	/// Total payload bytes received, excluding headers and padding.
+	#[must_use]
+	pub fn bytes(&self) -> u64 {
+		self.bytes
+	}
+
+	/// Time at which the latest packet arrived.
+	#[must_use]
+	pub fn last_arrival(&self) -> Duration {
+		self.last_arrival
+	}
+
+	/// Payload bitrate over the window ending at the latest arrival, in bits per
+	/// second.
+	#[must_use]
+	pub fn bitrate(&self) -> u64 {
+		let window = self.window.as_micros();
+		if window == 0 {
+			return 0;
+		}
+
+		u64::try_from(u128::from(self.recent_bytes) * 8_000_000 / window).unwrap_or(u64::MAX)
+	}
+
+	/// Fills in a report block's statistics for this source, beginning a new
+	/// reporting interval.
+	///
+	/// The SR timing fields are left as `0`.
+	pub fn reception(&mut self) -> SourceReception {
+		let mut out = SourceReception {
+			ssrc: self.ssrc,
+			jitter: self.jitter(),
+			..SourceReception::default()
+		};
+		self.loss.fill(&mut out);
+		self.fraction_lost = out.fraction_lost;
+
+		out
+	}
+
+	/// Snapshot of this source's statistics, for export to `metrics`.
+	///
+	/// Neither the media ID nor the round-trip time are known from received RTP
+	/// alone, so these are taken as `mid` (*e.g.*, negotiated via SDP) and `rtt`
+	/// (*e.g.*, from [`ReportBlock::round_trip_time`]).
+	///
+	/// *This is included when using the `"metrics"` feature.*
+	///
+	/// [`ReportBlock::round_trip_time`]: ../rtcp/report/struct.ReportBlock.html#method.round_trip_time
+	#[cfg(feature = "metrics")]
+	#[must_use]
+	pub fn metrics(
+		&self,
+		mid: Option<&str>,
+		rtt: Option<Duration>,
+	) -> crate::metrics::StreamMetrics {
+		crate::metrics::StreamMetrics {
+			ssrc: self.ssrc,
+			mid: mid.map(alloc::string::String::from),
+			packets: self.packets,
+			bytes: self.bytes,
+			lost: self.cumulative_lost().into(),
+			fraction_lost: f64::from(self.fraction_lost) / 256.0,
+			jitter: self.jitter_duration(),
+			rtt,
+		}
+	}
+
+	fn update(
+		&mut self,
+		seq: Wrap16,
+		timestamp: u32,
+		len: usize,
+		arrival: Duration,
+		clock_rate: Option<u32>,
+	) -> bool {
+		let valid = self.loss.update(seq);
+
+		self.packets += 1;
+		self.bytes += len as u64;
+		self.last_arrival = self.last_arrival.max(arrival);
+
+		self.recent.push_back((arrival, len));
+		self.recent_bytes += len as u64;
+		while let Some(&(at, old)) = self.recent.front() {
+			if self.last_arrival.saturating_sub(at) < self.window {
+				break;
+			}
+			self.recent.pop_front();
+			self.recent_bytes -= old as u64;
+		}
+
+		if clock_rate != self.clock_rate {
+			self.clock_rate = clock_rate;
+			self.last_transit = None;
+			self.jitter_q4 = 0;
+		}
+
+		if let Some(rate) = clock_rate.filter(|&r| r != 0) {
+			#[allow(clippy::cast_possible_truncation)]
+			let arrival = duration_samples(rate, arrival) as u32;
+			let transit = arrival.wrapping_sub(timestamp);
+			if let Some(last) = self.last_transit {
+				#[allow(clippy::cast_possible_wrap)]
+				let d = (transit.wrapping_sub(last) as i32).unsigned_abs();
+				let jitter_q4 = u64::from(self.jitter_q4);
+				let next = jitter_q4 + u64::from(d) - ((jitter_q4 + 8) >> 4);
+				self.jitter_q4 = u32::try_from(next).unwrap_or(u32::MAX);
+			}
+			self.last_transit = Some(transit);
+		}
+
+		valid
+	}
+}
+
+/// Per-SSRC tracker of the reception statistics of remote sources.
+///
+/// Each received RTP packet updates the sequence number validation and loss
+/// estimate of its source (via [`LossEstimator`]), its interarrival jitter as
+/// described by [RFC 3550](https://tools.ietf.org/html/rfc3550#appendix-A.8),
+/// and its packet, byte and bitrate counters. These fill the report blocks of a
+/// [`ReportGenerator`], and the jitter estimate may be used to size a jitter
+/// buffer.
+///
+/// Jitter is only measured for payload types whose clock rate has been bound with
+/// [`bind_clock_rate`].
+///
+/// Times are given as [`Duration`]s since an arbitrary (monotonic) epoch chosen
+/// by the caller.
+///
+/// [`LossEstimator`]: struct.LossEstimator.html
+/// [`ReportGenerator`]: struct.ReportGenerator.html
+/// [`bind_clock_rate`]: #method.bind_clock_rate
+/// [`Duration`]: https://doc.rust-lang.org/core/time/struct.Duration.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiverStats {
+	clock_rates: Vec<(RtpType, u32)>,
+	sources: BTreeMap<u32, SourceStats>,
+	window: Duration,
+}
+
+impl Default for ReceiverStats {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl ReceiverStats {
+	/// Creates a tracker which has seen no sources, measuring bitrate over
+	/// [`DEFAULT_BITRATE_WINDOW`].
+	///
+	/// [`DEFAULT_BITRATE_WINDOW`]: constant.DEFAULT_BITRATE_WINDOW.html
+	#[must_use]
+	pub fn new() -> Self {
+		Self::with_bitrate_window(DEFAULT_BITRATE_WINDOW)
+	}
+
+	/// Creates a tracker which has seen no sources, measuring bitrate over
+	/// `window`.
+	#[must_use]
+	pub fn with_bitrate_window(window: Duration) -> Self {
+		Self {
+			clock_rates: Vec::new(),
+			sources: BTreeMap::new(),
+			window,
+		}
+	}
+
+	/// Sets the timestamp clock rate of `payload_type`, replacing any previous rate.
+	pub fn bind_clock_rate(&mut self, payload_type: RtpType, clock_rate: u32) {
+		if let Some(entry) = self
+			.clock_rates
+			.iter_mut()
+			.find(|(pt, _)| *pt == payload_type)
+		{
+			entry.1 = clock_rate;
+		} else {
+			self.clock_rates.push((payload_type, clock_rate));
+		}
+	}
+
+	/// Returns the timestamp clock rate bound to `payload_type`.
+	#[must_use]
+	pub fn clock_rate(&self, payload_type: RtpType) -> Option<u32> {
+		self.clock_rates
+			.iter()
+			.find(|(pt, _)| *pt == payload_type)
+			.map(|(_, rate)| *rate)
+	}
+
+	/// Records the arrival of `packet` at time `arrival`.
+	///
+	/// Returns `false` if the packet should not be treated as valid, as described by
+	/// [`LossEstimator::update`]. Such packets still count towards the packet, byte
+	/// and bitrate statistics.
+	///
+	/// [`LossEstimator::update`]: struct.LossEstimator.html#method.update
+	pub fn push(&mut self, packet: &RtpPacket<'_>, arrival: Duration) -> bool {
+		let ssrc = packet.get_ssrc();
+		let seq = packet.get_sequence();
+		let len = packet
+			.media_payload()
+			.map_or(0, |p| p.len().saturating_sub(packet.padding_len()));
+		let clock_rate = self.clock_rate(packet.get_payload_type());
+		let window = self.window;
+
+		self.sources
+			.entry(ssrc)
+			.or_insert_with(|| SourceStats::new(ssrc, seq, window))
+			.update(seq, packet.get_timestamp().into(), len, arrival, clock_rate)
+	}
+
+	/// Records the arrival of a `received` packet, measuring its arrival from
+	/// `epoch`.
+	///
+	/// See [`push`] for more information.
+	///
+	/// *This is included when using the `"std"` feature.*
+	///
+	/// [`push`]: #method.push
+	#[cfg(feature = "std")]
+	pub fn push_received(
+		&mut self,
+		received: &crate::received::Received<RtpPacket<'_>>,
+		epoch: std::time::Instant,
+	) -> bool {
+		self.push(&received.packet, received.since(epoch))
+	}
+
+	/// Returns the statistics of `ssrc`, if any packets have been received from it.
+	#[must_use]
+	pub fn source(&self, ssrc: u32) -> Option<&SourceStats> {
+		self.sources.get(&ssrc)
+	}
+
+	/// Returns the statistics of every source seen, in order of SSRC.
+	pub fn sources(&self) -> impl Iterator<Item = &SourceStats> + '_ {
+		self.sources.values()
+	}
+
+	/// Fills in report block statistics for each valid source, beginning a new
+	/// reporting interval for each.
+	///
+	/// The SR timing fields are left as `0`.
+	pub fn receptions(&mut self) -> Vec<SourceReception> {
+		self.sources
+			.values_mut()
+			.filter(|s| s.is_valid())
+			.map(SourceStats::reception)
+			.collect()
+	}
+
+	/// Forgets the statistics of `ssrc`, such as when it leaves the session.
+	pub fn remove(&mut self, ssrc: u32) -> Option<SourceStats> {
+		self.sources.remove(&ssrc)
+	}
+
+	/// Forgets all sources which have sent nothing since `timeout` before `now`,
+	/// returning the number removed.
+	pub fn expire(&mut self, now: Duration, timeout: Duration) -> usize {
+		let before = self.sources.len();
+		self.sources
+			.retain(|_, s| now.saturating_sub(s.last_arrival) < timeout);
+
+		before - self.sources.len()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::rtp::RtpPacketBuilder;
+
+	#[test]
+	fn jitter_saturates_on_timestamp_jumps() {
+		let mut stats = ReceiverStats::new();
+		stats.bind_clock_rate(RtpType::Dynamic(96), 8000);
+
+		for i in 0..64u16 {
+			let timestamp = if i % 2 == 0 { 0 } else { 0x8000_0000 };
+			let buf = RtpPacketBuilder::new(RtpType::Dynamic(96), 1)
+				.sequence(i.into())
+				.timestamp(timestamp.into())
+				.payload(&[0; 4])
+				.to_vec();
+			stats.push(&RtpPacket::new(&buf).unwrap(), Duration::ZERO);
+		}
+
+		assert_eq!(stats.source(1).unwrap().jitter(), u32::MAX >> 4);
+	}
+
+	#[cfg(feature = "metrics")]
+	#[test]
+	fn metrics_carry_mid_and_rtt() {
+		let mut stats = ReceiverStats::new();
+		let buf = RtpPacketBuilder::new(RtpType::Dynamic(96), 7)
+			.payload(&[0; 10])
+			.to_vec();
+		stats.push(&RtpPacket::new(&buf).unwrap(), Duration::ZERO);
+
+		let rtt = Duration::from_millis(30);
+		let metrics = stats.source(7).unwrap().metrics(Some("audio"), Some(rtt));
+		assert_eq!(metrics.ssrc, 7);
+		assert_eq!(metrics.mid.as_deref(), Some("audio"));
+		assert_eq!(metrics.rtt, Some(rtt));
+		assert_eq!(metrics.packets, 1);
+		assert_eq!(metrics.bytes, 10);
+		assert_eq!(metrics.labels().len(), 2);
+	}
+}
@@ -0,0 +1,149 @@
+//! Tools for exercising receivers under adverse network conditions.
+//!
+//! *These are included when using the `"testing"` feature.*
+
+use crate::rng::SplitMix64;
+use alloc::vec::Vec;
+use core::time::Duration;
+
+/// Applies seeded, reproducible network impairments to a stream of packets.
+///
+/// Each packet is assigned an arrival time from its send time plus a fixed
+/// base delay and a uniformly-distributed jitter component. Packets may
+/// additionally be dropped, duplicated, or held back to force reordering.
+/// Output is sorted by arrival time, ready to be fed into a jitter buffer or
+/// recovery mechanism under test.
+///
+/// The same seed and configuration always produce the same output.
+#[derive(Clone, Debug)]
+pub struct Impairment {
+	rng: SplitMix64,
+	loss: f64,
+	duplication: f64,
+	reorder: f64,
+	reorder_delay: Duration,
+	base_delay: Duration,
+	jitter: Duration,
+}
+
+/// A packet emitted by an [`Impairment`], alongside its simulated arrival time.
+///
+/// [`Impairment`]: struct.Impairment.html
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Impaired<T> {
+	/// The (possibly duplicated) packet.
+	pub packet: T,
+
+	/// Simulated arrival time, on the same clock as the provided send times.
+	pub arrival: Duration,
+}
+
+impl Impairment {
+	/// Creates an impairment which (until configured) delivers every packet
+	/// unchanged and in order.
+	#[must_use]
+	pub fn new(seed: u64) -> Self {
+		Self {
+			rng: SplitMix64::new(seed),
+			loss: 0.0,
+			duplication: 0.0,
+			reorder: 0.0,
+			reorder_delay: Duration::ZERO,
+			base_delay: Duration::ZERO,
+			jitter: Duration::ZERO,
+		}
+	}
+
+	/// Sets the probability (`0.0`--`1.0`) that any packet is dropped.
+	#[must_use]
+	pub fn loss(mut self, probability: f64) -> Self {
+		self.loss = probability;
+		self
+	}
+
+	/// Sets the probability (`0.0`--`1.0`) that any delivered packet arrives twice.
+	#[must_use]
+	pub fn duplication(mut self, probability: f64) -> Self {
+		self.duplication = probability;
+		self
+	}
+
+	/// Sets the probability (`0.0`--`1.0`) that a packet is held back by an extra
+	/// `delay`, causing it to arrive after its successors.
+	#[must_use]
+	pub fn reorder(mut self, probability: f64, delay: Duration) -> Self {
+		self.reorder = probability;
+		self.reorder_delay = delay;
+		self
+	}
+
+	/// Sets the fixed one-way delay applied to all packets.
+	#[must_use]
+	pub fn delay(mut self, delay: Duration) -> Self {
+		self.base_delay = delay;
+		self
+	}
+
+	/// Sets the maximum additional delay, sampled uniformly per packet.
+	#[must_use]
+	pub fn jitter(mut self, max: Duration) -> Self {
+		self.jitter = max;
+		self
+	}
+
+	/// Impairs a sequence of `(send_time, packet)` pairs, returning the delivered
+	/// packets in order of arrival.
+	pub fn apply<T, I>(&mut self, packets: I) -> Vec<Impaired<T>>
+	where
+		T: Clone,
+		I: IntoIterator<Item = (Duration, T)>,
+	{
+		let mut out = Vec::new();
+
+		for (sent, packet) in packets {
+			if self.rng.chance(self.loss) {
+				continue;
+			}
+
+			if self.rng.chance(self.duplication) {
+				let arrival = self.arrival(sent);
+				out.push(Impaired {
+					packet: packet.clone(),
+					arrival,
+				});
+			}
+
+			let arrival = self.arrival(sent);
+			out.push(Impaired { packet, arrival });
+		}
+
+		// Stable sort keeps equal-arrival packets in send order.
+		out.sort_by_key(|p| p.arrival);
+		out
+	}
+
+	/// Impairs packets sent at a fixed `interval` (*e.g.*, 20ms audio frames),
+	/// returning the delivered packets in order of arrival.
+	pub fn apply_periodic<T, I>(&mut self, packets: I, interval: Duration) -> Vec<Impaired<T>>
+	where
+		T: Clone,
+		I: IntoIterator<Item = T>,
+	{
+		let mut sent = Duration::ZERO;
+		self.apply(packets.into_iter().map(|p| {
+			let out = (sent, p);
+			sent += interval;
+			out
+		}))
+	}
+
+	fn arrival(&mut self, sent: Duration) -> Duration {
+		let mut arrival = sent + self.base_delay + self.jitter.mul_f64(self.rng.next_f64());
+
+		if self.rng.chance(self.reorder) {
+			arrival += self.reorder_delay;
+		}
+
+		arrival
+	}
+}
@@ -1,6 +1,7 @@
 //! Utility types for wrapping arithmetic, compatible with pnet.
 
 use core::{
+	cmp::Ordering,
 	num::Wrapping,
 	ops::{Add, AddAssign, Sub, SubAssign},
 };
@@ -20,6 +21,36 @@ impl Wrap16 {
 	pub fn new(v: u16be) -> Self {
 		Self(Wrapping(v))
 	}
+
+	/// Signed number of steps from `self` forward to `other`, using
+	/// [RFC 1982](https://tools.ietf.org/html/rfc1982) half-range semantics.
+	///
+	/// This is positive if `other` is newer than `self`, and negative if older.
+	/// Values exactly half the range apart are treated as `other` being older.
+	#[must_use]
+	pub fn distance(self, other: Self) -> i16 {
+		#[allow(clippy::cast_possible_wrap)]
+		{
+			u16::from(other).wrapping_sub(u16::from(self)) as i16
+		}
+	}
+
+	/// Orders `self` relative to `other`, treating values less than half the
+	/// range ahead as newer (*i.e.*, greater).
+	///
+	/// See [`distance`](#method.distance) for more information.
+	#[must_use]
+	pub fn wrapping_cmp(self, other: Self) -> Ordering {
+		0.cmp(&self.distance(other))
+	}
+
+	/// Returns whether `self` is older than `other`, accounting for wraparound.
+	///
+	/// See [`distance`](#method.distance) for more information.
+	#[must_use]
+	pub fn wrapping_lt(self, other: Self) -> bool {
+		self.distance(other) > 0
+	}
 }
 
 impl From<Wrap16> for u16 {
@@ -75,6 +106,36 @@ impl Wrap32 {
 	pub fn new(v: u32be) -> Self {
 		Self(Wrapping(v))
 	}
+
+	/// Signed number of steps from `self` forward to `other`, using
+	/// [RFC 1982](https://tools.ietf.org/html/rfc1982) half-range semantics.
+	///
+	/// This is positive if `other` is newer than `self`, and negative if older.
+	/// Values exactly half the range apart are treated as `other` being older.
+	#[must_use]
+	pub fn distance(self, other: Self) -> i32 {
+		#[allow(clippy::cast_possible_wrap)]
+		{
+			u32::from(other).wrapping_sub(u32::from(self)) as i32
+		}
+	}
+
+	/// Orders `self` relative to `other`, treating values less than half the
+	/// range ahead as newer (*i.e.*, greater).
+	///
+	/// See [`distance`](#method.distance) for more information.
+	#[must_use]
+	pub fn wrapping_cmp(self, other: Self) -> Ordering {
+		0.cmp(&self.distance(other))
+	}
+
+	/// Returns whether `self` is older than `other`, accounting for wraparound.
+	///
+	/// See [`distance`](#method.distance) for more information.
+	#[must_use]
+	pub fn wrapping_lt(self, other: Self) -> bool {
+		self.distance(other) > 0
+	}
 }
 
 impl From<Wrap32> for u32 {